@@ -61,6 +61,7 @@ pub(crate) mod transform;
 mod video;
 pub(crate) mod xml;
 mod xml_node;
+pub(crate) mod xml_socket;
 
 const GLOBAL_DECLS: &[Declaration] = declare_properties! {
     "trace" => method(trace; DONT_ENUM);
@@ -664,6 +665,7 @@ pub fn create_globals<'gc>(
         xmlnode_proto,
     );
     let xml = xml::create_constructor(context, xmlnode_proto, function_proto);
+    let xml_socket = xml_socket::create_constructor(context, object_proto, function_proto);
     let string = string::create_string_object(context, string_proto, function_proto);
     let number = number::create_number_object(context, number_proto, function_proto);
     let boolean = boolean::create_boolean_object(context, boolean_proto, function_proto);
@@ -918,6 +920,12 @@ pub fn create_globals<'gc>(
     );
     globals.define_value(gc_context, "XMLNode", xmlnode.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "XML", xml.into(), Attribute::DONT_ENUM);
+    globals.define_value(
+        gc_context,
+        "XMLSocket",
+        xml_socket.into(),
+        Attribute::DONT_ENUM,
+    );
     globals.define_value(gc_context, "String", string.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "Number", number.into(), Attribute::DONT_ENUM);
     globals.define_value(gc_context, "Boolean", boolean.into(), Attribute::DONT_ENUM);
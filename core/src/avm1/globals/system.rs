@@ -7,6 +7,7 @@ use crate::avm1::runtime::Avm1;
 use crate::avm1::{ScriptObject, TObject, Value};
 use crate::avm1_stub;
 use crate::context::{GcContext, UpdateContext};
+use crate::socket_policy::parse_xmlsocket_url;
 use bitflags::bitflags;
 use core::fmt;
 
@@ -290,6 +291,8 @@ pub struct SystemProperties {
     pub cpu_architecture: CpuArchitecture,
     /// The highest supported h264 decoder level
     pub idc_level: String,
+    /// The socket policy file locations registered with `loadPolicyFile`
+    pub socket_policy_files: Vec<(String, u16)>,
 }
 
 impl SystemProperties {
@@ -313,6 +316,7 @@ impl SystemProperties {
             sandbox_type,
             cpu_architecture: CpuArchitecture::X86,
             idc_level: "5.1".into(),
+            socket_policy_files: Vec::new(),
         }
     }
     pub fn get_version_string(&self, avm: &mut Avm1) -> String {
@@ -323,6 +327,20 @@ impl SystemProperties {
         )
     }
 
+    /// Registers the location of a socket policy file, as given to `loadPolicyFile`.
+    ///
+    /// Returns `false` if the URL isn't an `xmlsocket://host:port` URL.
+    pub fn add_socket_policy_file(&mut self, url: &str) -> bool {
+        let Some(location) = parse_xmlsocket_url(url) else {
+            return false;
+        };
+
+        if !self.socket_policy_files.contains(&location) {
+            self.socket_policy_files.push(location);
+        }
+        true
+    }
+
     pub fn has_capability(&self, cap: SystemCapabilities) -> bool {
         self.capabilities.contains(cap)
     }
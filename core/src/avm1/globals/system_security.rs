@@ -38,9 +38,25 @@ fn allow_insecure_domain<'gc>(
 fn load_policy_file<'gc>(
     activation: &mut Activation<'_, 'gc>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm1_stub!(activation, "System.security", "loadPolicyFile");
+    let url = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+
+    if !activation
+        .context
+        .system
+        .add_socket_policy_file(&url.to_utf8_lossy())
+    {
+        avm1_stub!(
+            activation,
+            "System.security",
+            "loadPolicyFile",
+            "with non-xmlsocket URL"
+        );
+    }
     Ok(Value::Undefined)
 }
 
@@ -0,0 +1,196 @@
+//! XMLSocket class
+
+use crate::avm1::function::{Executable, ExecutionReason, FunctionObject};
+use crate::avm1::property_decl::{define_properties_on, Declaration};
+use crate::avm1::{Activation, Error, NativeObject, Object, ScriptObject, TObject, Value};
+use crate::backend::navigator::OutgoingSocketAction;
+use crate::context::GcContext;
+use crate::loader::{default_socket_host, SocketTarget};
+use crate::socket_stats::SocketSender;
+use gc_arena::{Collect, GcCell, MutationContext};
+use std::time::Duration;
+
+/// How long an AVM1 `XMLSocket` waits for a connection, which isn't configurable.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Copy, Clone, Collect)]
+#[collect(no_drop)]
+pub struct XmlSocket<'gc>(GcCell<'gc, XmlSocketData>);
+
+#[derive(Collect)]
+#[collect(require_static)]
+pub struct XmlSocketData {
+    /// The queue of actions for the IO task, present while connected.
+    outgoing_queue: Option<SocketSender>,
+}
+
+impl<'gc> XmlSocket<'gc> {
+    /// Replace the queue of actions for the IO task.
+    ///
+    /// The socket loader installs it once connected, and removes it once the
+    /// connection is closed.
+    pub fn set_outgoing_queue(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        outgoing_queue: Option<SocketSender>,
+    ) {
+        self.0.write(gc_context).outgoing_queue = outgoing_queue;
+    }
+
+    /// How many actions the IO task hasn't picked up yet, if connected.
+    pub fn queue_depth(self) -> Option<usize> {
+        self.0
+            .read()
+            .outgoing_queue
+            .as_ref()
+            .map(SocketSender::queue_depth)
+    }
+
+    /// Hand an action over to the IO task.
+    ///
+    /// Returns `false` if the socket isn't connected.
+    fn send(self, action: OutgoingSocketAction) -> bool {
+        match &self.0.read().outgoing_queue {
+            Some(queue) => queue.send(action).is_ok(),
+            None => false,
+        }
+    }
+
+    fn close(self, gc_context: MutationContext<'gc, '_>) {
+        self.send(OutgoingSocketAction::Close);
+        self.set_outgoing_queue(gc_context, None);
+    }
+}
+
+const PROTO_DECLS: &[Declaration] = declare_properties! {
+    "connect" => method(connect; DONT_ENUM | DONT_DELETE);
+    "send" => method(send; DONT_ENUM | DONT_DELETE);
+    "close" => method(close; DONT_ENUM | DONT_DELETE);
+    "onData" => method(on_data; DONT_ENUM | DONT_DELETE);
+};
+
+pub fn constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let xml_socket = XmlSocket(GcCell::new(
+        activation.context.gc_context,
+        XmlSocketData {
+            outgoing_queue: None,
+        },
+    ));
+    this.set_native(
+        activation.context.gc_context,
+        NativeObject::XmlSocket(xml_socket),
+    );
+
+    Ok(this.into())
+}
+
+fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let NativeObject::XmlSocket(xml_socket) = this.native() else {
+        return Ok(Value::Undefined);
+    };
+
+    let host = match args.get(0).unwrap_or(&Value::Undefined) {
+        Value::Undefined | Value::Null => default_socket_host(activation.context.swf.url()),
+        host => host.coerce_to_string(activation)?.to_string(),
+    };
+    let port = args
+        .get(1)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_i32(activation)?;
+    let Ok(port) = u16::try_from(port) else {
+        return Ok(false.into());
+    };
+
+    // Connecting again drops the previous connection.
+    xml_socket.close(activation.context.gc_context);
+
+    let future = activation.context.load_manager.load_socket(
+        activation.context.player.clone(),
+        SocketTarget::Avm1Xml(this),
+        (host, port),
+        CONNECT_TIMEOUT,
+    );
+    activation.context.navigator.spawn_future(future);
+
+    Ok(true.into())
+}
+
+fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let NativeObject::XmlSocket(xml_socket) = this.native() else {
+        return Ok(Value::Undefined);
+    };
+
+    // Every message is terminated by a zero byte.
+    let data = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_string(activation)?;
+    let mut bytes = data.to_utf8_lossy().into_owned().into_bytes();
+    bytes.push(0);
+
+    if xml_socket.send(OutgoingSocketAction::Write(bytes)) {
+        xml_socket.send(OutgoingSocketAction::Flush);
+    }
+
+    Ok(Value::Undefined)
+}
+
+fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let NativeObject::XmlSocket(xml_socket) = this.native() {
+        xml_socket.close(activation.context.gc_context);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// The default `onData` handler, which parses each message and passes it to `onXML`.
+fn on_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let src = args.get(0).cloned().unwrap_or(Value::Undefined);
+
+    let constructor = activation.context.avm1.prototypes().xml_constructor;
+    let xml = constructor.construct(activation, &[src])?;
+    this.call_method(
+        "onXML".into(),
+        &[xml],
+        activation,
+        ExecutionReason::FunctionCall,
+    )?;
+
+    Ok(Value::Undefined)
+}
+
+pub fn create_constructor<'gc>(
+    context: &mut GcContext<'_, 'gc>,
+    proto: Object<'gc>,
+    fn_proto: Object<'gc>,
+) -> Object<'gc> {
+    let xml_socket_proto = ScriptObject::new(context.gc_context, Some(proto));
+    define_properties_on(PROTO_DECLS, context, xml_socket_proto, fn_proto);
+    FunctionObject::constructor(
+        context.gc_context,
+        Executable::Native(constructor),
+        constructor_to_fn!(constructor),
+        fn_proto,
+        xml_socket_proto.into(),
+    )
+}
@@ -14,6 +14,7 @@ use crate::avm1::globals::gradient_filter::GradientFilter;
 use crate::avm1::globals::shared_object::SharedObject;
 use crate::avm1::globals::transform::TransformObject;
 use crate::avm1::globals::xml::Xml;
+use crate::avm1::globals::xml_socket::XmlSocket;
 use crate::avm1::object::array_object::ArrayObject;
 use crate::avm1::object::super_object::SuperObject;
 use crate::avm1::object::value_object::ValueObject;
@@ -58,6 +59,7 @@ pub enum NativeObject<'gc> {
     BitmapData(BitmapDataWrapper<'gc>),
     Xml(Xml<'gc>),
     XmlNode(XmlNode<'gc>),
+    XmlSocket(XmlSocket<'gc>),
     SharedObject(GcCell<'gc, SharedObject>),
 }
 
@@ -1,10 +1,12 @@
-use crate::avm2::bytearray::ByteArrayStorage;
+use crate::avm2::bytearray::{ByteArrayStorage, ObjectEncoding};
 use crate::avm2::object::{ByteArrayObject, TObject};
 use crate::avm2::ArrayObject;
 use crate::avm2::ArrayStorage;
 use crate::avm2::{Activation, Error, Object, Value};
 use crate::string::AvmString;
 use enumset::EnumSet;
+use flash_lso::amf0::read::AMF0Decoder;
+use flash_lso::amf3::read::AMF3Decoder;
 use flash_lso::types::{AMFVersion, Element, Lso};
 use flash_lso::types::{Attribute, ClassDefinition, Value as AmfValue};
 
@@ -223,6 +225,64 @@ pub fn deserialize_value<'gc>(
     })
 }
 
+/// Serialize a single Value into its AMF representation, as written by `writeObject`.
+///
+/// Returns `None` if the value cannot be represented in the given encoding.
+pub fn serialize_to_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    value: Value<'gc>,
+    object_encoding: ObjectEncoding,
+) -> Result<Option<Vec<u8>>, Error<'gc>> {
+    let amf_version = match object_encoding {
+        ObjectEncoding::Amf0 => AMFVersion::AMF0,
+        ObjectEncoding::Amf3 => AMFVersion::AMF3,
+    };
+    if let Some(amf) = serialize_value(activation, value, amf_version) {
+        let element = Element::new("", amf);
+        let mut lso = Lso::new(vec![element], "", amf_version);
+        let bytes =
+            flash_lso::write::write_to_bytes(&mut lso).map_err(|_| "Failed to serialize object")?;
+        // This is kind of hacky: We need to strip out the header and any padding so that we only write
+        // the value. In the future, there should be a method to do this in the flash_lso crate.
+        let element_padding = match amf_version {
+            AMFVersion::AMF0 => 8,
+            AMFVersion::AMF3 => 7,
+        };
+        return Ok(Some(
+            bytes[flash_lso::write::header_length(&lso.header) + element_padding..bytes.len() - 1]
+                .to_vec(),
+        ));
+    }
+
+    Ok(None)
+}
+
+/// Deserialize a single AMF value from the start of `bytes`, as read by `readObject`.
+///
+/// Returns the number of bytes left over after the value, along with the value itself.
+pub fn deserialize_from_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    bytes: &[u8],
+    object_encoding: ObjectEncoding,
+) -> Result<(usize, Value<'gc>), Error<'gc>> {
+    Ok(match object_encoding {
+        ObjectEncoding::Amf0 => {
+            let mut decoder = AMF0Decoder::default();
+            let (extra, amf) = decoder
+                .parse_single_element(bytes)
+                .map_err(|_| "Error: Invalid object")?;
+            (extra.len(), deserialize_value(activation, &amf)?)
+        }
+        ObjectEncoding::Amf3 => {
+            let mut decoder = AMF3Decoder::default();
+            let (extra, amf) = decoder
+                .parse_single_element(bytes)
+                .map_err(|_| "Error: Invalid object")?;
+            (extra.len(), deserialize_value(activation, &amf)?)
+        }
+    })
+}
+
 /// Deserializes a Lso into an object containing the properties stored
 pub fn deserialize_lso<'gc>(
     activation: &mut Activation<'_, 'gc>,
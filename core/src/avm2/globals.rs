@@ -94,12 +94,16 @@ pub struct SystemClasses<'gc> {
     pub progressevent: ClassObject<'gc>,
     pub httpstatusevent: ClassObject<'gc>,
     pub textevent: ClassObject<'gc>,
+    pub dataevent: ClassObject<'gc>,
     pub errorevent: ClassObject<'gc>,
     pub ioerrorevent: ClassObject<'gc>,
     pub securityerrorevent: ClassObject<'gc>,
     pub transform: ClassObject<'gc>,
     pub colortransform: ClassObject<'gc>,
     pub matrix: ClassObject<'gc>,
+    pub matrix3d: ClassObject<'gc>,
+    pub vector3d: ClassObject<'gc>,
+    pub perspectiveprojection: ClassObject<'gc>,
     pub illegaloperationerror: ClassObject<'gc>,
     pub eventdispatcher: ClassObject<'gc>,
     pub rectangle: ClassObject<'gc>,
@@ -138,12 +142,16 @@ pub struct SystemClasses<'gc> {
     pub texture: ClassObject<'gc>,
     pub cubetexture: ClassObject<'gc>,
     pub rectangletexture: ClassObject<'gc>,
+    pub videotexture: ClassObject<'gc>,
     pub morphshape: ClassObject<'gc>,
     pub shader: ClassObject<'gc>,
     pub shaderinput: ClassObject<'gc>,
     pub shaderparameter: ClassObject<'gc>,
     pub netstatusevent: ClassObject<'gc>,
     pub shaderfilter: ClassObject<'gc>,
+    pub pngencoderoptions: ClassObject<'gc>,
+    pub jpegencoderoptions: ClassObject<'gc>,
+    pub jpegxrencoderoptions: ClassObject<'gc>,
 }
 
 impl<'gc> SystemClasses<'gc> {
@@ -213,11 +221,15 @@ impl<'gc> SystemClasses<'gc> {
             progressevent: object,
             httpstatusevent: object,
             textevent: object,
+            dataevent: object,
             errorevent: object,
             ioerrorevent: object,
             securityerrorevent: object,
             transform: object,
             colortransform: object,
+            matrix3d: object,
+            vector3d: object,
+            perspectiveprojection: object,
             matrix: object,
             illegaloperationerror: object,
             eventdispatcher: object,
@@ -257,12 +269,16 @@ impl<'gc> SystemClasses<'gc> {
             texture: object,
             cubetexture: object,
             rectangletexture: object,
+            videotexture: object,
             morphshape: object,
             shader: object,
             shaderinput: object,
             shaderparameter: object,
             netstatusevent: object,
             shaderfilter: object,
+            pngencoderoptions: object,
+            jpegencoderoptions: object,
+            jpegxrencoderoptions: object,
         }
     }
 }
@@ -681,10 +697,17 @@ fn load_playerglobal<'gc>(
             ("flash.display", "GraphicsSolidFill", graphicssolidfill),
             ("flash.display", "GraphicsStroke", graphicsstroke),
             ("flash.display", "Graphics", graphics),
+            ("flash.display", "JPEGEncoderOptions", jpegencoderoptions),
+            (
+                "flash.display",
+                "JPEGXREncoderOptions",
+                jpegxrencoderoptions
+            ),
             ("flash.display", "Loader", loader),
             ("flash.display", "LoaderInfo", loaderinfo),
             ("flash.display", "MorphShape", morphshape),
             ("flash.display", "MovieClip", movieclip),
+            ("flash.display", "PNGEncoderOptions", pngencoderoptions),
             ("flash.display", "ShaderInput", shaderinput),
             ("flash.display", "ShaderParameter", shaderparameter),
             ("flash.display", "Shape", shape),
@@ -702,6 +725,7 @@ fn load_playerglobal<'gc>(
                 "RectangleTexture",
                 rectangletexture
             ),
+            ("flash.display3D.textures", "VideoTexture", videotexture),
             ("flash.display3D", "VertexBuffer3D", vertexbuffer3d),
             (
                 "flash.errors",
@@ -713,6 +737,7 @@ fn load_playerglobal<'gc>(
             ("flash.events", "Event", event),
             ("flash.events", "EventDispatcher", eventdispatcher),
             ("flash.events", "TextEvent", textevent),
+            ("flash.events", "DataEvent", dataevent),
             ("flash.events", "ErrorEvent", errorevent),
             ("flash.events", "KeyboardEvent", keyboardevent),
             ("flash.events", "ProgressEvent", progressevent),
@@ -728,6 +753,9 @@ fn load_playerglobal<'gc>(
             ("flash.geom", "Rectangle", rectangle),
             ("flash.geom", "Transform", transform),
             ("flash.geom", "ColorTransform", colortransform),
+            ("flash.geom", "Matrix3D", matrix3d),
+            ("flash.geom", "Vector3D", vector3d),
+            ("flash.geom", "PerspectiveProjection", perspectiveprojection),
             ("flash.media", "SoundChannel", soundchannel),
             ("flash.media", "SoundTransform", soundtransform),
             ("flash.media", "Video", video),
@@ -4,9 +4,7 @@ package flash.display {
     import flash.geom.Point;
     import flash.geom.Matrix;
     import flash.filters.BitmapFilter;
-    import flash.filters.ShaderFilter;
     import flash.utils.ByteArray;
-    import __ruffle__.stub_method;
 
     [Ruffle(InstanceAllocator)]
     public class BitmapData implements IBitmapDrawable {
@@ -24,6 +22,7 @@ package flash.display {
         public native function get transparent():Boolean;
 
         public native function getPixels(rect:Rectangle):ByteArray;
+        public native function copyPixelsToByteArray(rect:Rectangle, data:ByteArray):void;
         public native function getVector(rect:Rectangle):Vector.<uint>;
         public native function getPixel(x:int, y:int):uint;
         public native function getPixel32(x:int, y:int):uint;
@@ -63,18 +62,15 @@ package flash.display {
             sourceBitmapData:BitmapData, sourceRect:Rectangle, destPoint:Point, operation:String, threshold:uint, color:uint = 0, mask:uint = 0xFFFFFFFF, copySource:Boolean = false
         ):uint;
         public native function compare(otherBitmapData:BitmapData):Object;
+        public native function histogram(hRect:Rectangle = null):Vector.<Vector.<Number>>;
+        public native function merge(
+            sourceBitmapData:BitmapData, sourceRect:Rectangle, destPoint:Point, redMultiplier:uint, greenMultiplier:uint, blueMultiplier:uint, alphaMultiplier:uint
+        ):void;
         public native function pixelDissolve(
             sourceBitmapData:BitmapData, sourceRect:Rectangle, destPoint:Point, randomSeed:int = 0, numPixels:int = 0,
             fillColor:uint = 0
         ):int;
-
-        public function generateFilterRect(sourceRect:Rectangle, filter:BitmapFilter):Rectangle {
-            // Flash always reports that a ShaderFilter affects the entire BitampData, ignoring SourceRect.
-            if (filter is ShaderFilter) {
-                return this.rect.clone();
-            }
-            stub_method("flash.display.BitmapData", "generateFilterRect");
-            return sourceRect.clone();
-        }
+        public native function encode(rect:Rectangle, compressor:Object, byteArray:ByteArray = null):ByteArray;
+        public native function generateFilterRect(sourceRect:Rectangle, filter:BitmapFilter):Rectangle;
     }
 }
@@ -8,6 +8,7 @@ package flash.display {
     import flash.display.LoaderInfo;
     import flash.display.Stage;
     import flash.geom.Point;
+    import flash.geom.Vector3D;
     import flash.events.EventDispatcher;
     
     import __ruffle__.stub_getter;
@@ -123,6 +124,10 @@ package flash.display {
 
         public native function globalToLocal(point:Point):Point;
 
+        public native function local3DToGlobal(point3d:Vector3D):Point;
+
+        public native function globalToLocal3D(point:Point):Vector3D;
+
         public native function getBounds(targetCoordinateSpace:DisplayObject):Rectangle;
 
         public native function getRect(targetCoordinateSpace:DisplayObject):Rectangle;
@@ -10,8 +10,12 @@ package flash.display {
 
         public native function get context3D():Context3D;
         private native function requestContext3D_internal(context3DRenderMode:String, profile:String):void;
+        private native function checkProfile(profile:String):void;
 
         public function requestContext3D(context3DRenderMode:String = "auto", profile:String = "baseline"):void {
+            // Unknown profiles are reported right away, even though the context isn't.
+            checkProfile(profile);
+
             // Several SWFS (the examples from the Context3D documentation, and the Starling framework)
             // rely on the `context3DCreate` being fired asynchronously - they initialize variables
             // after the call to `requestContext3D`, and then use those variables in the event handler.
@@ -1,6 +1,7 @@
 //! `flash.display.BitmapData` builtin/prototype
 
 use crate::avm2::activation::Activation;
+use crate::avm2::bytearray::ByteArrayStorage;
 use crate::avm2::error::{argument_error, make_error_2008, range_error};
 use crate::avm2::filters::FilterAvm2Ext;
 pub use crate::avm2::object::bitmap_data_allocator;
@@ -31,7 +32,7 @@ use swf::{Rectangle, Twips};
 // and `y + height` as floating point operations before
 // `round_to_even`, which is needed to match Flash Player's
 // rounding behavior.
-fn get_rectangle_x_y_width_height<'gc>(
+pub(crate) fn get_rectangle_x_y_width_height<'gc>(
     activation: &mut Activation<'_, 'gc>,
     rectangle: Object<'gc>,
 ) -> Result<(i32, i32, i32, i32), Error<'gc>> {
@@ -307,6 +308,32 @@ pub fn get_pixels<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `BitmapData.copyPixelsToByteArray`.
+pub fn copy_pixels_to_byte_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.as_bitmap_data() {
+        bitmap_data.check_valid(activation)?;
+        let rectangle = args.get_object(activation, 0, "rect")?;
+        let (x, y, width, height) = get_rectangle_x_y_width_height(activation, rectangle)?;
+        let data = args.get_object(activation, 1, "data")?;
+        if let Some(mut bytearray) = data.as_bytearray_mut(activation.context.gc_context) {
+            operations::copy_pixels_to_byte_array(
+                bitmap_data,
+                x,
+                y,
+                width,
+                height,
+                &mut bytearray,
+            )?;
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn get_vector<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -1053,8 +1080,17 @@ pub fn apply_filter<'gc>(
             })?;
         let source_rect = args.get_object(activation, 1, "sourceRect")?;
         let mut source_rect = super::display_object::object_to_rectangle(activation, source_rect)?;
-        let filter = args.get_object(activation, 3, "filter")?;
-        let filter = Filter::from_avm2_object(activation, filter)?;
+        let filter_object = args.get_object(activation, 3, "filter")?;
+        let filter = Filter::from_avm2_object(activation, filter_object)?;
+        // The software filters need to read the map's pixels, which the renderer's handle doesn't give us
+        let map_bitmap = if matches!(filter, Filter::DisplacementMapFilter(_)) {
+            filter_object
+                .get_public_property("mapBitmap", activation)?
+                .as_object()
+                .and_then(|map| map.as_bitmap_data())
+        } else {
+            None
+        };
 
         if matches!(filter, Filter::ShaderFilter(_)) {
             let source_bitmap_rect = Rectangle {
@@ -1112,11 +1148,60 @@ pub fn apply_filter<'gc>(
             source_size,
             dest_point,
             filter,
+            map_bitmap,
         );
     }
     Ok(Value::Undefined)
 }
 
+/// Implement `BitmapData.generateFilterRect`
+pub fn generate_filter_rect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.as_bitmap_data() {
+        bitmap_data.check_valid(activation)?;
+        let source_rect = args.get_object(activation, 0, "sourceRect")?;
+        let filter_object = args.get_object(activation, 1, "filter")?;
+
+        // Flash always reports that a ShaderFilter affects the entire BitmapData, ignoring sourceRect.
+        let shader_filter = activation
+            .avm2()
+            .classes()
+            .shaderfilter
+            .inner_class_definition();
+        if filter_object.is_of_type(shader_filter, &mut activation.context) {
+            return get_rect(activation, this, &[]);
+        }
+
+        let filter = Filter::from_avm2_object(activation, filter_object)?;
+        let (x, y, width, height) = get_rectangle_x_y_width_height(activation, source_rect)?;
+        // The rectangle comes from user code, so it can be anywhere.
+        let dest_rect = filter.calculate_dest_rect(Rectangle {
+            x_min: x,
+            y_min: y,
+            x_max: x.saturating_add(width),
+            y_max: y.saturating_add(height),
+        });
+        return Ok(activation
+            .avm2()
+            .classes()
+            .rectangle
+            .construct(
+                activation,
+                &[
+                    dest_rect.x_min.into(),
+                    dest_rect.y_min.into(),
+                    dest_rect.x_max.saturating_sub(dest_rect.x_min).into(),
+                    dest_rect.y_max.saturating_sub(dest_rect.y_min).into(),
+                ],
+            )?
+            .into());
+    }
+    Ok(Value::Undefined)
+}
+
 /// Implement `BitmapData.clone`
 pub fn clone<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -1401,6 +1486,94 @@ pub fn compare<'gc>(
     }
 }
 
+/// Implement `BitmapData.histogram`
+pub fn histogram<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.as_bitmap_data() {
+        bitmap_data.check_valid(activation)?;
+        let (x, y, width, height) = match args.try_get_object(activation, 0) {
+            Some(rectangle) => get_rectangle_x_y_width_height(activation, rectangle)?,
+            None => (
+                0,
+                0,
+                bitmap_data.width() as i32,
+                bitmap_data.height() as i32,
+            ),
+        };
+
+        let histogram = operations::histogram(bitmap_data, x, y, width, height);
+
+        let number_class = activation.avm2().classes().number;
+        let number_vector_class = activation
+            .avm2()
+            .classes()
+            .vector
+            .apply(activation, number_class.into())?;
+        let mut channels = Vec::with_capacity(histogram.len());
+        for channel in histogram {
+            let counts = channel
+                .iter()
+                .map(|count| f64::from(*count).into())
+                .collect();
+            let storage = VectorStorage::from_values(counts, false, number_class);
+            channels.push(VectorObject::from_vector(storage, activation)?.into());
+        }
+        let storage = VectorStorage::from_values(channels, false, number_vector_class);
+        return Ok(VectorObject::from_vector(storage, activation)?.into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implement `BitmapData.merge`
+pub fn merge<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.as_bitmap_data() {
+        bitmap_data.check_valid(activation)?;
+        let source_bitmap = args.get_object(activation, 0, "sourceBitmapData")?;
+
+        let source_rect = args.get_object(activation, 1, "sourceRect")?;
+        let source_rect = get_rectangle_x_y_width_height(activation, source_rect)?;
+
+        let dest_point = args.get_object(activation, 2, "destPoint")?;
+        let dest_point = (
+            dest_point
+                .get_public_property("x", activation)?
+                .coerce_to_i32(activation)?,
+            dest_point
+                .get_public_property("y", activation)?
+                .coerce_to_i32(activation)?,
+        );
+
+        // Multipliers above 256 behave just like 256, so clamp them before they can wrap around
+        let mut multipliers = [0; 4];
+        for (index, multiplier) in multipliers.iter_mut().enumerate() {
+            *multiplier = args.get_u32(activation, 3 + index)?.min(256) as i32;
+        }
+        let [red_mult, green_mult, blue_mult, alpha_mult] = multipliers;
+
+        if let Some(source_bitmap) = source_bitmap.as_bitmap_data() {
+            source_bitmap.check_valid(activation)?;
+            operations::merge(
+                activation.context.gc_context,
+                bitmap_data,
+                source_bitmap,
+                source_rect,
+                dest_point,
+                (red_mult, green_mult, blue_mult, alpha_mult),
+            );
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 /// Implements `BitmapData.pixelDissolve`.
 pub fn pixel_dissolve<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -1460,3 +1633,72 @@ pub fn pixel_dissolve<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// Implements `BitmapData.encode`.
+pub fn encode<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.as_bitmap_data() {
+        bitmap_data.check_valid(activation)?;
+        let rectangle = args.get_object(activation, 0, "rect")?;
+        let compressor = args.get_object(activation, 1, "compressor")?;
+        let byte_array = match args.try_get_object(activation, 2) {
+            Some(byte_array) => byte_array,
+            None => ByteArrayObject::from_storage(activation, ByteArrayStorage::new())?,
+        };
+
+        let (x, y, width, height) = get_rectangle_x_y_width_height(activation, rectangle)?;
+        let bitmap = operations::get_region_as_bitmap(bitmap_data, x, y, width, height);
+        // An empty rectangle has nothing to encode, and leaves the ByteArray as it was
+        if bitmap.width() == 0 || bitmap.height() == 0 {
+            return Ok(byte_array.into());
+        }
+
+        let classes = activation.avm2().classes();
+        let png_class = classes.pngencoderoptions.inner_class_definition();
+        let jpeg_class = classes.jpegencoderoptions.inner_class_definition();
+        let jpeg_xr_class = classes.jpegxrencoderoptions.inner_class_definition();
+        let encoded = if compressor.is_of_type(png_class, &mut activation.context) {
+            let fast_compression = compressor
+                .get_public_property("fastCompression", activation)?
+                .coerce_to_boolean();
+            ruffle_render::utils::encode_png(bitmap, fast_compression)
+        } else if compressor.is_of_type(jpeg_class, &mut activation.context) {
+            let quality = compressor
+                .get_public_property("quality", activation)?
+                .coerce_to_u32(activation)?;
+            ruffle_render::utils::encode_jpeg(bitmap, quality.clamp(1, 100) as u8)
+        } else if compressor.is_of_type(jpeg_xr_class, &mut activation.context) {
+            // We can only decode JPEG XR, so nothing gets written to the ByteArray.
+            avm2_stub_method!(
+                activation,
+                "flash.display.BitmapData",
+                "encode",
+                "with JPEGXREncoderOptions, which writes nothing"
+            );
+            return Ok(byte_array.into());
+        } else {
+            return Err(Error::AvmError(argument_error(
+                activation,
+                "Error #2004: One of the parameters is invalid.",
+                2004,
+            )?));
+        };
+
+        match encoded {
+            Ok(encoded) => {
+                if let Some(mut storage) =
+                    byte_array.as_bytearray_mut(activation.context.gc_context)
+                {
+                    storage.write_bytes(&encoded)?;
+                }
+            }
+            Err(e) => tracing::error!("BitmapData.encode: couldn't encode image: {}", e),
+        }
+        return Ok(byte_array.into());
+    }
+
+    Ok(Value::Undefined)
+}
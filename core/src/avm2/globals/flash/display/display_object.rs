@@ -15,7 +15,6 @@ use crate::prelude::*;
 use crate::string::AvmString;
 use crate::types::{Degrees, Percent};
 use crate::vminterface::Instantiator;
-use crate::{avm2_stub_getter, avm2_stub_setter};
 use ruffle_render::filters::Filter;
 use std::str::FromStr;
 use swf::Twips;
@@ -342,96 +341,160 @@ pub fn set_y<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `z`'s getter.
 pub fn get_z<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "z");
-    Ok(0.into())
+    if let Some(dobj) = this.as_display_object() {
+        return Ok(dobj.transform_3d().unwrap_or_default().z.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `z`'s setter.
 pub fn set_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "z");
+    if let Some(dobj) = this.as_display_object() {
+        let z = args.get_f64(activation, 0)?;
+        let mut transform_3d = dobj.transform_3d().unwrap_or_default();
+        transform_3d.z = z;
+        dobj.set_transform_3d(activation.context.gc_context, Some(transform_3d));
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationX`'s getter.
 pub fn get_rotation_x<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationX");
-    Ok(0.into())
+    if let Some(dobj) = this.as_display_object() {
+        let rotation_x = dobj.transform_3d().unwrap_or_default().rotation_x;
+        return Ok(normalize_rotation(rotation_x.into()).into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `rotationX`'s setter.
 pub fn set_rotation_x<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationX");
+    if let Some(dobj) = this.as_display_object() {
+        let rotation_x = args.get_f64(activation, 0)?;
+        let mut transform_3d = dobj.transform_3d().unwrap_or_default();
+        transform_3d.rotation_x = Degrees::from(rotation_x);
+        dobj.set_transform_3d(activation.context.gc_context, Some(transform_3d));
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationY`'s getter.
 pub fn get_rotation_y<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationY");
-    Ok(0.into())
+    if let Some(dobj) = this.as_display_object() {
+        let rotation_y = dobj.transform_3d().unwrap_or_default().rotation_y;
+        return Ok(normalize_rotation(rotation_y.into()).into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `rotationY`'s setter.
 pub fn set_rotation_y<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationY");
+    if let Some(dobj) = this.as_display_object() {
+        let rotation_y = args.get_f64(activation, 0)?;
+        let mut transform_3d = dobj.transform_3d().unwrap_or_default();
+        transform_3d.rotation_y = Degrees::from(rotation_y);
+        dobj.set_transform_3d(activation.context.gc_context, Some(transform_3d));
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Implements `rotationZ`'s getter.
 pub fn get_rotation_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "rotationZ");
-    Ok(0.into())
+    // `rotationZ` is the same as `rotation`, which is kept in the 2D matrix.
+    get_rotation(activation, this, args)
 }
 
+/// Implements `rotationZ`'s setter.
 pub fn set_rotation_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "rotationZ");
-    Ok(Value::Undefined)
+    if let Some(dobj) = this.as_display_object() {
+        // Unlike `rotation`, this turns the object into a 3D one.
+        let transform_3d = dobj.transform_3d().unwrap_or_default();
+        dobj.set_transform_3d(activation.context.gc_context, Some(transform_3d));
+    }
+
+    set_rotation(activation, this, args)
 }
 
+/// Implements `scaleZ`'s getter.
 pub fn get_scale_z<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.DisplayObject", "scaleZ");
-    Ok(1.into())
+    if let Some(dobj) = this.as_display_object() {
+        return Ok(dobj.transform_3d().unwrap_or_default().scale_z.into());
+    }
+
+    Ok(Value::Undefined)
 }
 
+/// Implements `scaleZ`'s setter.
 pub fn set_scale_z<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    this: Object<'gc>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.DisplayObject", "scaleZ");
+    if let Some(dobj) = this.as_display_object() {
+        let scale_z = args.get_f64(activation, 0)?;
+        let mut transform_3d = dobj.transform_3d().unwrap_or_default();
+        transform_3d.scale_z = scale_z;
+        dobj.set_transform_3d(activation.context.gc_context, Some(transform_3d));
+    }
+
     Ok(Value::Undefined)
 }
 
+/// Wraps a rotation in degrees into the range that Flash reports it in.
+fn normalize_rotation(degrees: f64) -> f64 {
+    let rem = degrees % 360.0;
+
+    if rem <= 180.0 {
+        rem
+    } else {
+        rem - 360.0
+    }
+}
+
 /// Implements `rotation`'s getter.
 pub fn get_rotation<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -440,13 +503,7 @@ pub fn get_rotation<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(dobj) = this.as_display_object() {
         let rot: f64 = dobj.rotation(activation.context.gc_context).into();
-        let rem = rot % 360.0;
-
-        if rem <= 180.0 {
-            return Ok(rem.into());
-        } else {
-            return Ok((rem - 360.0).into());
-        }
+        return Ok(normalize_rotation(rot).into());
     }
 
     Ok(Value::Undefined)
@@ -699,26 +756,42 @@ pub fn set_transform<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     let transform = args.get_object(activation, 0, "transform")?;
 
-    // FIXME - consider 3D matrix and pixel bounds
-    let matrix = transform
-        .get_public_property("matrix", activation)?
-        .coerce_to_object(activation)?;
+    // FIXME - consider pixel bounds
+    let matrix_3d = transform.get_public_property("matrix3D", activation)?;
+    let matrix_3d = match matrix_3d {
+        Value::Null | Value::Undefined => None,
+        matrix_3d => Some(
+            crate::avm2::globals::flash::geom::transform::object_to_matrix_3d(
+                matrix_3d.coerce_to_object(activation)?,
+                activation,
+            )?,
+        ),
+    };
+    // Objects with a 3D transform don't have a 2D matrix.
+    let matrix = if matrix_3d.is_none() {
+        let matrix = transform
+            .get_public_property("matrix", activation)?
+            .coerce_to_object(activation)?;
+        Some(crate::avm2::globals::flash::geom::transform::object_to_matrix(matrix, activation)?)
+    } else {
+        None
+    };
     let color_transform = transform
         .get_public_property("colorTransform", activation)?
         .coerce_to_object(activation)?;
-
-    let matrix =
-        crate::avm2::globals::flash::geom::transform::object_to_matrix(matrix, activation)?;
     let color_transform = crate::avm2::globals::flash::geom::transform::object_to_color_transform(
         color_transform,
         activation,
     )?;
 
     let dobj = this.as_display_object().unwrap();
-    let mut write = dobj.base_mut(activation.context.gc_context);
-    write.set_matrix(matrix);
-    write.set_color_transform(color_transform);
-    drop(write);
+    if let Some(matrix) = matrix {
+        dobj.set_matrix(activation.context.gc_context, matrix);
+    }
+    if matrix_3d.is_some() || dobj.transform_3d().is_some() {
+        dobj.set_matrix_3d(activation.context.gc_context, matrix_3d.as_ref());
+    }
+    dobj.set_color_transform(activation.context.gc_context, color_transform);
     if let Some(parent) = dobj.parent() {
         // Self-transform changes are automatically handled,
         // we only want to inform ancestors to avoid unnecessary invalidations for tx/ty
@@ -903,6 +976,78 @@ pub fn global_to_local<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn local3d_to_global<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(dobj) = this.as_display_object() {
+        let point = args.get_object(activation, 0, "point3d")?;
+        let x = point
+            .get_public_property("x", activation)?
+            .coerce_to_number(activation)?;
+        let y = point
+            .get_public_property("y", activation)?
+            .coerce_to_number(activation)?;
+        let z = point
+            .get_public_property("z", activation)?
+            .coerce_to_number(activation)?;
+
+        let global = dobj.local_3d_to_global(
+            activation.context.gc_context,
+            activation.context.stage,
+            (x, y, z),
+        );
+        return Ok(activation
+            .avm2()
+            .classes()
+            .point
+            .construct(
+                activation,
+                &[global.x.to_pixels().into(), global.y.to_pixels().into()],
+            )?
+            .into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn global_to_local_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(dobj) = this.as_display_object() {
+        let point = args.get_object(activation, 0, "point")?;
+        let x = point
+            .get_public_property("x", activation)?
+            .coerce_to_number(activation)?;
+        let y = point
+            .get_public_property("y", activation)?
+            .coerce_to_number(activation)?;
+
+        let global = Point::from_pixels(x, y);
+        let (x, y) = dobj
+            .global_to_local_3d(
+                activation.context.gc_context,
+                activation.context.stage,
+                global,
+            )
+            .unwrap_or_else(|| {
+                let local = dobj.global_to_local(global).unwrap_or(global);
+                (local.x.to_pixels(), local.y.to_pixels())
+            });
+        return Ok(activation
+            .avm2()
+            .classes()
+            .vector3d
+            .construct(activation, &[x.into(), y.into(), 0.into()])?
+            .into());
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn get_bounds<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -1,36 +1,87 @@
-use crate::avm2::object::Context3DObject;
+use crate::avm2::error::make_error_2008;
 use crate::avm2::object::TObject;
+use crate::avm2::object::{Context3DObject, Stage3DObject};
 
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::{Activation, Error, Object, Value};
+use ruffle_render::backend::Context3DProfile;
 
 pub use crate::avm2::object::stage_3d_allocator;
 
 pub fn request_context3d_internal<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let this_stage3d = this.as_stage_3d().unwrap();
-    if this_stage3d.context3d().is_none() {
-        let context = activation.context.renderer.create_context3d()?;
-        let context3d_obj = Context3DObject::from_context(activation, context)?;
-        this_stage3d.set_context3d(context3d_obj, activation.context.gc_context);
+    // A context disposed with `recreate` set to false can be requested again.
+    let has_context = this_stage3d
+        .context3d()
+        .and_then(|context| context.as_context_3d())
+        .map_or(false, |context| !context.disposed());
+    if !has_context {
+        let profile = args.get_string(activation, 1)?;
+        let profile = Context3DProfile::from_wstr(&profile)
+            .ok_or_else(|| make_error_2008(activation, "profile"))?;
 
-        let event = activation
-            .avm2()
-            .classes()
-            .event
-            .construct(activation, &["context3DCreate".into()])?;
+        recreate_context3d(activation, this_stage3d, profile)?;
+    }
 
-        // FIXME - fire this at least one frame later,
-        // since some seems to expect this (e.g. the adobe triangle example)
-        this.call_public_property("dispatchEvent", &[event.into()], activation)?;
+    Ok(Value::Undefined)
+}
+
+/// Throws the error of `Stage3D.requestContext3D` for an unknown profile.
+pub fn check_profile<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let profile = args.get_string(activation, 0)?;
+    if Context3DProfile::from_wstr(&profile).is_none() {
+        return Err(make_error_2008(activation, "profile"));
     }
 
     Ok(Value::Undefined)
 }
 
+/// Gives `stage3d` a new context, and dispatches `context3DCreate`.
+pub fn recreate_context3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    stage3d: Stage3DObject<'gc>,
+    profile: Context3DProfile,
+) -> Result<(), Error<'gc>> {
+    let context = activation.context.renderer.create_context3d()?;
+    let context3d_obj = Context3DObject::from_context(activation, context, profile)?;
+    stage3d.set_context3d(context3d_obj, activation.context.gc_context);
+
+    let event = activation
+        .avm2()
+        .classes()
+        .event
+        .construct(activation, &["context3DCreate".into()])?;
+
+    // FIXME - fire this at least one frame later,
+    // since some seems to expect this (e.g. the adobe triangle example)
+    Object::from(stage3d).call_public_property("dispatchEvent", &[event.into()], activation)?;
+    Ok(())
+}
+
+/// Simulates losing the graphics device: the context of `stage3d` is disposed, and
+/// it gets a new one along with another `context3DCreate` event.
+pub fn lose_context3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    stage3d: Stage3DObject<'gc>,
+) -> Result<(), Error<'gc>> {
+    let Some(context) = stage3d
+        .context3d()
+        .and_then(|context| context.as_context_3d())
+    else {
+        return Ok(());
+    };
+    context.dispose(activation);
+    recreate_context3d(activation, stage3d, context.profile())
+}
+
 pub fn get_context_3d<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -5,3 +5,33 @@ pub mod index_buffer_3d;
 pub mod program_3d;
 pub mod textures;
 pub mod vertex_buffer_3d;
+
+use crate::avm2::error::{error, range_error};
+use crate::avm2::{Activation, Error};
+
+/// The error thrown when an upload doesn't fit in its destination, or isn't covered by
+/// its source data.
+pub fn make_error_3669<'gc>(activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
+    match range_error(activation, "Error #3669: Bad input size.", 3669) {
+        Ok(err) => Error::AvmError(err),
+        Err(err) => err,
+    }
+}
+
+/// The error thrown when using a context, buffer, texture or program after disposing it.
+pub fn make_error_3694<'gc>(activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
+    match error(
+        activation,
+        "Error #3694: The object was disposed by an earlier call of dispose() on it.",
+        3694,
+    ) {
+        Ok(err) => Error::AvmError(err),
+        Err(err) => err,
+    }
+}
+
+/// The range of `len` bytes at `offset` in a ByteArray of `available` bytes, if it's in bounds.
+pub fn byte_range(offset: usize, len: usize, available: usize) -> Option<std::ops::Range<usize>> {
+    let end = offset.checked_add(len)?;
+    (end <= available).then_some(offset..end)
+}
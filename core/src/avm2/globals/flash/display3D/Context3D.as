@@ -1,12 +1,14 @@
 package flash.display3D {
+    import flash.display.BitmapData;
     import flash.events.EventDispatcher;
     import flash.geom.Matrix3D;
+    import flash.geom.Point;
     import flash.geom.Rectangle;
     import flash.display3D.textures.CubeTexture;
     import flash.display3D.textures.TextureBase;
     import flash.display3D.textures.RectangleTexture;
     import flash.display3D.textures.Texture;
-    import __ruffle__.stub_method;
+    import flash.display3D.textures.VideoTexture;
     import __ruffle__.stub_getter;
 
     public final class Context3D extends EventDispatcher {
@@ -22,16 +24,19 @@ package flash.display3D {
         public native function setProgram(program:Program3D):void;
         public native function drawTriangles(indexBuffer:IndexBuffer3D, firstIndex:int = 0, numTriangles:int = -1):void;
         public native function present():void;
+        public native function drawToBitmapData(destination:BitmapData, srcRect:Rectangle = null, destPoint:Point = null):void;
         public native function setCulling(triangleFaceToCull:String):void;
         public native function createTexture(width:int, height:int, format:String, optimizeForRenderToTexture:Boolean, streamingLevels:int = 0):Texture;
         public native function createCubeTexture(size:int, format:String, optimizeForRenderToTexture:Boolean, streamingLevels:int = 0):CubeTexture;
         public native function createRectangleTexture(width:int, height:int, format:String, optimizeForRenderToTexture:Boolean):RectangleTexture;
+        public native function createVideoTexture():VideoTexture;
 
-        public function get driverInfo():String {
-            stub_getter("flash.display3D.Context3D", "driverInfo");
-            return "Dummy Ruffle software driver";
+        public static function get supportsVideoTexture():Boolean {
+            return true;
         }
 
+        public native function get driverInfo():String;
+
         public var enableErrorChecking:Boolean = true;
 
         public native function setProgramConstantsFromMatrix(programType:String, firstRegister:int, matrix:Matrix3D, transposedMatrix:Boolean = false):void;
@@ -47,10 +52,7 @@ package flash.display3D {
 
         public native function setTextureAt(sampler:int, texture:TextureBase):void;
 
-        public function get profile():String {
-            stub_getter("flash.display3D.Context3D", "profile");
-            return "baseline";
-        }
+        public native function get profile():String;
 
         public function get maxBackBufferWidth():int {
             stub_getter("flash.display3D.Context3D", "maxBackBufferWidth");
@@ -62,26 +64,20 @@ package flash.display3D {
             return 2048;
         }
 
-        public function setStencilReferenceValue(referenceValue:uint, readMask:uint = 255, writeMask:uint = 255):void {
-            stub_method("flash.display3D.Context3D", "setStencilReferenceValue");
-        }
+        public native function setStencilReferenceValue(referenceValue:uint, readMask:uint = 255, writeMask:uint = 255):void;
 
         public native function setSamplerStateAt(sampler:int, wrap:String, filter:String, mipfilter:String):void;
 
         public native function setRenderToTexture(texture:TextureBase, enableDepthAndStencil:Boolean = false, antiAlias:int = 0, surfaceSelector:int = 0, colorOutputIndex:int = 0):void;
 
-        public function setStencilActions(
+        public native function setStencilActions(
             triangleFace:String = "frontAndBack",
             compareMode:String = "always",
             actionOnBothPass:String = "keep",
             actionOnDepthFail:String = "keep",
             actionOnDepthPassStencilFail:String = "keep"
-        ):void {
-            stub_method("flash.display3D.Context3D", "setStencilActions");
-        }
+        ):void;
 
-        public function dispose(recreate: Boolean = true):void {
-            stub_method("flash.display3D.Context3D", "dispose");
-        }
+        public native function dispose(recreate:Boolean = true):void;
     }
 }
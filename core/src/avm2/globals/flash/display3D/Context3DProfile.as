@@ -26,5 +26,8 @@ package flash.display3D
         // Use standard extended profile to target GPUs which support AGAL3 and instanced drawing feature.
         public static const STANDARD_EXTENDED:String = "standardExtended";
 
+        // Use an enhanced profile to target GPUs which support AGAL4 and vertex texture fetch.
+        public static const ENHANCED:String = "enhanced";
+
     }
 }
@@ -1,24 +1,41 @@
 use crate::avm2::error::{argument_error, error, make_error_2008};
+use crate::avm2::globals::flash::display::bitmap_data::get_rectangle_x_y_width_height;
+use crate::avm2::globals::flash::display::stage_3d::recreate_context3d;
+use crate::avm2::globals::flash::display3D::make_error_3694;
+use crate::avm2::object::{Context3DObject, RenderTarget};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::Activation;
 use crate::avm2::TObject;
 use crate::avm2::Value;
 use crate::avm2::{Error, Object};
+use crate::avm2_stub_getter;
 use crate::avm2_stub_method;
-use ruffle_render::backend::Context3DTextureFilter;
+use crate::string::AvmString;
 use ruffle_render::backend::Context3DWrapMode;
 use ruffle_render::backend::{
-    BufferUsage, Context3DBlendFactor, Context3DCompareMode, Context3DTextureFormat,
-    Context3DTriangleFace, Context3DVertexBufferFormat, ProgramType,
+    BufferUsage, Context3DBlendFactor, Context3DCompareMode, Context3DStencilAction,
+    Context3DTextureFormat, Context3DTriangleFace, Context3DVertexBufferFormat, ProgramType,
 };
+use ruffle_render::backend::{Context3DMipFilter, Context3DTextureFilter};
 use swf::{Rectangle, Twips};
 
+/// The context a method was called on, which can't be used anymore once disposed.
+fn context_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+) -> Result<Option<Context3DObject<'gc>>, Error<'gc>> {
+    match this.as_context_3d() {
+        Some(context) if context.disposed() => Err(make_error_3694(activation)),
+        context => Ok(context),
+    }
+}
+
 pub fn create_index_buffer<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // FIXME - get bufferUsage and pass it through
         let num_indices = args.get_u32(activation, 0)?;
         return context.create_index_buffer(num_indices, activation);
@@ -31,7 +48,7 @@ pub fn create_vertex_buffer<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // FIXME - get bufferUsage and pass it through
         let num_vertices = args.get_u32(activation, 0)?;
         let data_32_per_vertex = args.get_u32(activation, 1)?;
@@ -55,7 +72,7 @@ pub fn configure_back_buffer<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(mut context) = this.as_context_3d() {
+    if let Some(mut context) = context_3d(activation, this)? {
         let width = args.get_u32(activation, 0)?;
         let height = args.get_u32(activation, 1)?;
         let anti_alias = args.get_u32(activation, 2)?;
@@ -129,7 +146,7 @@ pub fn set_vertex_buffer_at<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let index = args.get_u32(activation, 0)?;
         let buffer = if matches!(args[1], Value::Null) {
             None
@@ -155,14 +172,17 @@ pub fn set_vertex_buffer_at<'gc>(
                 )?));
             };
 
-            Some((
-                args.get(1)
-                    .unwrap_or(&Value::Undefined)
-                    .coerce_to_object(activation)?
-                    .as_vertex_buffer()
-                    .unwrap(),
-                format,
-            ))
+            let buffer = args
+                .get(1)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation)?
+                .as_vertex_buffer()
+                .unwrap();
+            if buffer.disposed() {
+                return Err(make_error_3694(activation));
+            }
+
+            Some((buffer, format))
         };
 
         let buffer_offset = args.get_u32(activation, 2)?;
@@ -177,7 +197,7 @@ pub fn create_program<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         return context.create_program(activation);
     }
     Ok(Value::Undefined)
@@ -188,10 +208,13 @@ pub fn set_program<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let program = args
             .try_get_object(activation, 0)
             .map(|p| p.as_program_3d().unwrap());
+        if program.map_or(false, |p| p.disposed()) {
+            return Err(make_error_3694(activation));
+        }
         context.set_program(program);
     }
     Ok(Value::Undefined)
@@ -202,13 +225,16 @@ pub fn draw_triangles<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let index_buffer = args
             .get(0)
             .unwrap_or(&Value::Undefined)
             .coerce_to_object(activation)?
             .as_index_buffer()
             .unwrap();
+        if index_buffer.disposed() {
+            return Err(make_error_3694(activation));
+        }
 
         let first_index = args.get_u32(activation, 1)?;
         let num_triangles = args.get_u32(activation, 2)? as i32;
@@ -223,32 +249,59 @@ pub fn present<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         context.present(activation)?;
     }
     Ok(Value::Undefined)
 }
 
+pub fn draw_to_bitmap_data<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(context) = context_3d(activation, this)? {
+        let destination = args.get_object(activation, 0, "destination")?;
+        let Some(destination) = destination.as_bitmap_data() else {
+            return Ok(Value::Undefined);
+        };
+        destination.check_valid(activation)?;
+
+        let source_rect = match args.try_get_object(activation, 1) {
+            Some(source_rect) => Some(get_rectangle_x_y_width_height(activation, source_rect)?),
+            None => None,
+        };
+
+        // A null `destPoint` is the same as `new Point(0, 0)`.
+        let mut dest_point = (0, 0);
+        if let Some(point) = args.try_get_object(activation, 2) {
+            dest_point = (
+                point
+                    .get_public_property("x", activation)?
+                    .coerce_to_i32(activation)?,
+                point
+                    .get_public_property("y", activation)?
+                    .coerce_to_i32(activation)?,
+            );
+        }
+
+        context.draw_to_bitmap_data(activation, destination, source_rect, dest_point);
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn set_culling<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let culling = args.get_string(activation, 0)?;
 
-        let culling = if &*culling == b"none" {
-            Context3DTriangleFace::None
-        } else if &*culling == b"back" {
-            Context3DTriangleFace::Back
-        } else if &*culling == b"front" {
-            Context3DTriangleFace::Front
-        } else if &*culling == b"front_and_back" {
-            Context3DTriangleFace::FrontAndBack
-        } else {
+        let culling = Context3DTriangleFace::from_wstr(&culling).unwrap_or_else(|| {
             tracing::error!("Unknown culling {:?}", culling);
             Context3DTriangleFace::None
-        };
+        });
 
         context.set_culling(culling);
     }
@@ -260,7 +313,7 @@ pub fn set_program_constants_from_matrix<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let program_type = args.get_string(activation, 0)?;
 
         let is_vertex = if &*program_type == b"vertex" {
@@ -315,7 +368,7 @@ pub fn set_program_constants_from_vector<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         let program_type = args.get_string(activation, 0)?;
 
         let program_type = if &*program_type == b"vertex" {
@@ -364,7 +417,7 @@ pub fn clear<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let red = args[0].as_number(activation.context.gc_context)?;
         let green = args[1].as_number(activation.context.gc_context)?;
@@ -383,7 +436,7 @@ pub fn create_texture<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let width = args[0].as_integer(activation.context.gc_context)? as u32;
         let height = args[1].as_integer(activation.context.gc_context)? as u32;
@@ -416,7 +469,7 @@ pub fn create_rectangle_texture<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let width = args[0].as_integer(activation.context.gc_context)? as u32;
         let height = args[1].as_integer(activation.context.gc_context)? as u32;
@@ -447,12 +500,23 @@ pub fn create_rectangle_texture<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn create_video_texture<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(context) = context_3d(activation, this)? {
+        return context.create_video_texture(activation);
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn create_cube_texture<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let size = args[0].as_integer(activation.context.gc_context)? as u32;
         let format = args[1].coerce_to_string(activation)?;
@@ -484,7 +548,7 @@ pub fn set_texture_at<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let sampler = args[0].as_integer(activation.context.gc_context)? as u32;
         let mut cube = false;
@@ -500,7 +564,11 @@ pub fn set_texture_at<'gc>(
                     .inner_class_definition(),
                 &mut activation.context,
             );
-            Some(obj.as_texture().unwrap().handle())
+            let texture = obj.as_texture().unwrap();
+            if texture.disposed() {
+                return Err(make_error_3694(activation));
+            }
+            Some(texture.handle())
         };
         context.set_texture_at(sampler, texture, cube);
     }
@@ -508,11 +576,11 @@ pub fn set_texture_at<'gc>(
 }
 
 pub fn set_color_mask<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
+    activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let red = args[0].coerce_to_boolean();
         let green = args[1].coerce_to_boolean();
@@ -528,7 +596,7 @@ pub fn set_depth_test<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let depth_mask = args[0].coerce_to_boolean();
         let pass_compare_mode = args[1].coerce_to_string(activation)?;
@@ -543,12 +611,69 @@ pub fn set_depth_test<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn set_stencil_actions<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(context) = context_3d(activation, this)? {
+        let triangle_face = args.get_string(activation, 0)?;
+        let Some(triangle_face) = Context3DTriangleFace::from_wstr(&triangle_face) else {
+            return Err(make_error_2008(activation, "triangleFace"));
+        };
+
+        let compare_mode = args.get_string(activation, 1)?;
+        let Some(compare_mode) = Context3DCompareMode::from_wstr(&compare_mode) else {
+            return Err(make_error_2008(activation, "compareMode"));
+        };
+
+        let action_on_both_pass = get_stencil_action(activation, args, 2, "actionOnBothPass")?;
+        let action_on_depth_fail = get_stencil_action(activation, args, 3, "actionOnDepthFail")?;
+        let action_on_depth_pass_stencil_fail =
+            get_stencil_action(activation, args, 4, "actionOnDepthPassStencilFail")?;
+
+        context.set_stencil_actions(
+            triangle_face,
+            compare_mode,
+            action_on_both_pass,
+            action_on_depth_fail,
+            action_on_depth_pass_stencil_fail,
+        );
+    }
+    Ok(Value::Undefined)
+}
+
+fn get_stencil_action<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    args: &[Value<'gc>],
+    index: usize,
+    name: &str,
+) -> Result<Context3DStencilAction, Error<'gc>> {
+    let action = args.get_string(activation, index)?;
+    Context3DStencilAction::from_wstr(&action).ok_or_else(|| make_error_2008(activation, name))
+}
+
+pub fn set_stencil_reference_value<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(context) = context_3d(activation, this)? {
+        // The stencil buffer only has 8 bits, so only the lowest byte of each value is used.
+        let reference_value = args.get_u32(activation, 0)? & 0xFF;
+        let read_mask = args.get_u32(activation, 1)? & 0xFF;
+        let write_mask = args.get_u32(activation, 2)? & 0xFF;
+        context.set_stencil_reference_value(reference_value, read_mask, write_mask);
+    }
+    Ok(Value::Undefined)
+}
+
 pub fn set_blend_factors<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let source_factor = args[0].coerce_to_string(activation)?;
         let destination_factor = args[1].coerce_to_string(activation)?;
@@ -575,11 +700,14 @@ pub fn set_render_to_texture<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let context = this.as_context_3d().unwrap();
+    let context = context_3d(activation, this)?.unwrap();
     let texture = args
         .get_object(activation, 0, "texture")?
         .as_texture()
         .unwrap();
+    if texture.disposed() {
+        return Err(make_error_3694(activation));
+    }
     let enable_depth_and_stencil = args.get_bool(1);
     let anti_alias = args.get_u32(activation, 2)?;
     let surface_selector = args.get_u32(activation, 3)?;
@@ -603,30 +731,108 @@ pub fn set_render_to_texture<'gc>(
         );
     }
 
-    if color_output_index != 0 {
-        avm2_stub_method!(
+    // Only the standard profiles support multiple render targets.
+    if color_output_index >= context.profile().num_color_outputs() {
+        return Err(make_error_2008(activation, "colorOutputIndex"));
+    }
+
+    let handle = texture.handle();
+    let target = RenderTarget {
+        width: handle.width(),
+        height: handle.height(),
+        anti_alias: anti_alias.max(1),
+    };
+    if !context.can_render_to(target, color_output_index) {
+        return Err(Error::AvmError(argument_error(
             activation,
-            "flash.display3D.Context3D",
-            "setRenderToTexture",
-            "colorOutputIndex != 0"
-        );
+            "Error #2004: One of the parameters is invalid.",
+            2004,
+        )?));
     }
 
     context.set_render_to_texture(
-        texture.handle(),
+        handle,
         enable_depth_and_stencil,
         anti_alias,
         surface_selector,
+        color_output_index,
     );
     Ok(Value::Undefined)
 }
 
-pub fn set_render_to_back_buffer<'gc>(
+pub fn get_profile<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let context = this.as_context_3d().unwrap();
+    Ok(AvmString::from(context.profile().as_str()).into())
+}
+
+pub fn get_driver_info<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let context = this.as_context_3d().unwrap();
+    // Content checks for this to find out whether the context was lost.
+    if context.disposed() {
+        return Ok(AvmString::from("Disposed").into());
+    }
+    avm2_stub_getter!(activation, "flash.display3D.Context3D", "driverInfo");
+    Ok(AvmString::from("Dummy Ruffle software driver").into())
+}
+
+/// Implements `Context3D.dispose`.
+pub fn dispose<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let context = this.as_context_3d().unwrap();
+    if context.disposed() {
+        return Ok(Value::Undefined);
+    }
+    context.dispose(activation);
+
+    // With `recreate`, disposing the context is handled like a lost device: the `Stage3D`
+    // gets a new context if something listens for `context3DCreate`.
+    if args.get_bool(0) {
+        let stage3d = activation
+            .context
+            .stage
+            .stage3ds()
+            .iter()
+            .copied()
+            .find(|stage3d| {
+                stage3d
+                    .as_stage_3d()
+                    .and_then(|stage3d| stage3d.context3d())
+                    .map_or(false, |context| Object::ptr_eq(context, this))
+            });
+        if let Some(stage3d) = stage3d {
+            let has_listener = stage3d
+                .call_public_property("hasEventListener", &["context3DCreate".into()], activation)?
+                .coerce_to_boolean();
+            if has_listener {
+                recreate_context3d(
+                    activation,
+                    stage3d.as_stage_3d().unwrap(),
+                    context.profile(),
+                )?;
+            }
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_render_to_back_buffer<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let context = context_3d(activation, this)?.unwrap();
     context.set_render_to_back_buffer();
     Ok(Value::Undefined)
 }
@@ -636,7 +842,7 @@ pub fn set_sampler_state_at<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(context) = this.as_context_3d() {
+    if let Some(context) = context_3d(activation, this)? {
         // This is a native method, so all of the arguments have been checked and coerced for us
         let sampler = args[0].as_integer(activation.context.gc_context)? as u32;
         let wrap = args[1].coerce_to_string(activation)?;
@@ -649,31 +855,10 @@ pub fn set_sampler_state_at<'gc>(
         let filter = Context3DTextureFilter::from_wstr(&filter)
             .ok_or_else(|| make_error_2008(activation, "filter"))?;
 
-        if matches!(
-            filter,
-            Context3DTextureFilter::Anisotropic2X
-                | Context3DTextureFilter::Anisotropic4X
-                | Context3DTextureFilter::Anisotropic8X
-                | Context3DTextureFilter::Anisotropic16X
-        ) {
-            avm2_stub_method!(
-                activation,
-                "flash.display3D.Context3D",
-                "setSamplerStateAt",
-                "filter == 'anisotropic'"
-            );
-        }
-
-        if &*mip_filter != b"mipnone" {
-            avm2_stub_method!(
-                activation,
-                "flash.display3D.Context3D",
-                "setSamplerStateAt",
-                "mipFilter != 'none'"
-            );
-        }
+        let mip_filter = Context3DMipFilter::from_wstr(&mip_filter)
+            .ok_or_else(|| make_error_2008(activation, "mipfilter"))?;
 
-        context.set_sampler_state_at(sampler, wrap, filter);
+        context.set_sampler_state_at(sampler, wrap, filter, mip_filter);
     }
     Ok(Value::Undefined)
 }
@@ -683,7 +868,7 @@ pub fn set_scissor_rectangle<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let context3d = this.as_context_3d().unwrap();
+    let context3d = context_3d(activation, this)?.unwrap();
     let rectangle = args.try_get_object(activation, 0);
     let rectangle = if let Some(rectangle) = rectangle {
         let x = rectangle
@@ -1,3 +1,4 @@
+use super::{byte_range, make_error_3669, make_error_3694};
 use crate::avm2::object::{ClassObject, TObject};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::Activation;
@@ -17,26 +18,41 @@ pub fn upload_from_byte_array<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(index_buffer) = this.as_index_buffer() {
-        let byte_array = args.get_object(activation, 0, "byteArray")?;
-        let byte_array = byte_array
-            .as_bytearray()
-            .ok_or_else(|| Error::from("ArgumentError: Parameter must be a ByteArray"))?;
+        if index_buffer.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let byte_array = args.get_object(activation, 0, "data")?;
+        let byte_array_offset = args.get_i32(activation, 1)?;
+        let start_offset = args.get_i32(activation, 2)?;
+        let count = args.get_i32(activation, 3)?;
 
-        let byte_offset = args.get_u32(activation, 1)?;
-        let start_offset = args.get_u32(activation, 2)?;
-        let count = args.get_u32(activation, 3)?;
+        let (Ok(byte_array_offset), Ok(start_offset), Ok(count)) = (
+            usize::try_from(byte_array_offset),
+            usize::try_from(start_offset),
+            usize::try_from(count),
+        ) else {
+            return Err(make_error_3669(activation));
+        };
+        if start_offset.saturating_add(count) > index_buffer.num_indices() {
+            return Err(make_error_3669(activation));
+        }
 
-        let data = byte_array
-            // Each index is always 16 bits (2 bytes)
-            .read_at(count as usize * 2, byte_offset as usize)
-            .map_err(|e| e.to_avm(activation))?
-            .to_vec();
+        // Each index is always a 16-bit little-endian value, whatever the `endian` of the ByteArray.
+        let data = {
+            let byte_array = byte_array
+                .as_bytearray()
+                .ok_or_else(|| Error::from("ArgumentError: Parameter must be a ByteArray"))?;
+            byte_range(byte_array_offset, count * 2, byte_array.len())
+                .map(|range| byte_array.bytes()[range].to_vec())
+        };
+        let Some(data) = data else {
+            return Err(make_error_3669(activation));
+        };
 
-        index_buffer.context3d().upload_index_buffer_data(
-            index_buffer,
-            data,
-            start_offset as usize,
-        );
+        index_buffer.set_count(count);
+        index_buffer
+            .context3d()
+            .upload_index_buffer_data(index_buffer, data, start_offset);
     }
     Ok(Value::Undefined)
 }
@@ -47,6 +63,9 @@ pub fn upload_from_vector<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(index_buffer) = this.as_index_buffer() {
+        if index_buffer.disposed() {
+            return Err(make_error_3694(activation));
+        }
         let vector = args
             .get(0)
             .unwrap_or(&Value::Undefined)
@@ -80,3 +99,14 @@ pub fn upload_from_vector<'gc>(
     }
     Ok(Value::Undefined)
 }
+
+pub fn dispose<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(index_buffer) = this.as_index_buffer() {
+        index_buffer.dispose();
+    }
+    Ok(Value::Undefined)
+}
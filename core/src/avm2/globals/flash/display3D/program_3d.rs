@@ -1,3 +1,5 @@
+use crate::avm2::error::error;
+use crate::avm2::globals::flash::display3D::make_error_3694;
 use crate::avm2::Activation;
 
 use crate::avm2::TObject;
@@ -10,6 +12,9 @@ pub fn upload<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this.as_program_3d() {
+        if this.disposed() {
+            return Err(make_error_3694(activation));
+        }
         let vertex_agal = args
             .get(0)
             .unwrap_or(&Value::Undefined)
@@ -28,8 +33,27 @@ pub fn upload<'gc>(
             .ok_or_else(|| Error::from("ArgumentError: Parameter must be a ByteArray"))?;
         let fragment_agal = fragment_agal.bytes().to_vec();
 
-        this.context3d()
-            .upload_shaders(this, vertex_agal, fragment_agal);
+        if let Err(e) = this
+            .context3d()
+            .upload_shaders(this, vertex_agal, fragment_agal)
+        {
+            return Err(Error::AvmError(error(
+                activation,
+                &format!("Error #3621: AGAL validation failed: {e}"),
+                3621,
+            )?));
+        }
+    }
+    Ok(Value::Undefined)
+}
+
+pub fn dispose<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this.as_program_3d() {
+        this.dispose();
     }
     Ok(Value::Undefined)
 }
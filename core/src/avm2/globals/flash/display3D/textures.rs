@@ -1,5 +1,26 @@
 //! `flash.display3D.textures` namespace
 
+mod atf;
+mod byte_array;
 pub mod cube_texture;
 pub mod rectangle_texture;
 pub mod texture;
+pub mod texture_base;
+pub mod video_texture;
+
+use ruffle_render::backend::Texture;
+
+/// The number of mip levels of a texture of the given size.
+fn mip_levels(width: u32, height: u32) -> u32 {
+    width.max(height).max(1).ilog2() + 1
+}
+
+/// The size of the mip level `mip_level` of `texture`, if it's one of its first `mip_levels`.
+fn mip_size(texture: &dyn Texture, mip_level: u32, mip_levels: u32) -> Option<(u32, u32)> {
+    (mip_level < mip_levels).then(|| {
+        (
+            (texture.width() >> mip_level).max(1),
+            (texture.height() >> mip_level).max(1),
+        )
+    })
+}
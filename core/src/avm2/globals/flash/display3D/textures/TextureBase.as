@@ -1,10 +1,7 @@
 package flash.display3D.textures {
     import flash.events.EventDispatcher;
-    import __ruffle__.stub_method;
 
     public class TextureBase extends EventDispatcher {
-        public function dispose():void {
-            stub_method("flash.display3D.textures.TextureBase", "dispose");
-        }
+        public native function dispose():void;
     }
 }
\ No newline at end of file
@@ -0,0 +1,12 @@
+package flash.display3D.textures {
+    import flash.media.Camera;
+    import flash.net.NetStream;
+
+    public final class VideoTexture extends TextureBase {
+        public native function attachNetStream(netStream:NetStream):void;
+        public native function attachCamera(theCamera:Camera):void;
+
+        public native function get videoWidth():int;
+        public native function get videoHeight():int;
+    }
+}
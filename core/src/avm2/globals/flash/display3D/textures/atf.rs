@@ -0,0 +1,117 @@
+//! Uploading of ATF data, shared by `Texture` and `CubeTexture`
+
+use crate::avm2::error::error;
+use crate::avm2::object::TextureObject;
+use crate::avm2::Activation;
+use crate::avm2::TObject;
+use crate::avm2::{Error, Object};
+use crate::timer::TimerCallback;
+use ruffle_render::atf::{AtfError, AtfTexture};
+
+/// Parse the ATF data at `byte_array_offset` in `data`, and upload every one of its faces
+/// and mip levels to `texture`.
+///
+/// An `is_async` upload completes right away too, but `textureReady` is only dispatched
+/// once the current script is done, as content expects.
+pub fn do_compressed_upload<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    texture: TextureObject<'gc>,
+    data: Object<'gc>,
+    byte_array_offset: usize,
+    cube: bool,
+    is_async: bool,
+) -> Result<(), Error<'gc>> {
+    let bytes = match data.as_bytearray() {
+        Some(bytearray) => bytearray
+            .bytes()
+            .get(byte_array_offset..)
+            .map(<[u8]>::to_vec),
+        None => {
+            return Err("uploadCompressedTextureFromByteArray: data must be a ByteArray".into())
+        }
+    };
+    let Some(bytes) = bytes else {
+        return decoding_failed(
+            activation,
+            AtfError::UnexpectedEof(std::io::ErrorKind::UnexpectedEof.into()),
+        );
+    };
+
+    let atf = match AtfTexture::from_bytes(&bytes) {
+        Ok(atf) => atf,
+        Err(e) => return decoding_failed(activation, e),
+    };
+
+    if !atf.format.is_compatible_with(texture.original_format()) || atf.cubemap != cube {
+        return Err(Error::AvmError(error(
+            activation,
+            "Error #3675: Texture format mismatch.",
+            3675,
+        )?));
+    }
+
+    let handle = texture.handle();
+    if atf.width != handle.width() || atf.height != handle.height() {
+        return Err(Error::AvmError(error(
+            activation,
+            "Error #3679: Texture size does not match.",
+            3679,
+        )?));
+    }
+
+    let faces = if cube { 6 } else { 1 };
+    for face in 0..faces {
+        for mip_level in 0..atf.mip_count() {
+            let bitmap = match atf.decode(face, mip_level) {
+                Ok(bitmap) => bitmap,
+                Err(e) => return decoding_failed(activation, e),
+            };
+            texture
+                .context3d()
+                .copy_rgba_to_texture(bitmap, handle.clone(), face, mip_level);
+        }
+    }
+
+    if is_async {
+        queue_texture_ready(activation, texture)?;
+    }
+
+    Ok(())
+}
+
+/// Queue a `textureReady` event on `texture`, to be dispatched by the timer loop.
+fn queue_texture_ready<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    texture: TextureObject<'gc>,
+) -> Result<(), Error<'gc>> {
+    let event = activation
+        .avm2()
+        .classes()
+        .event
+        .construct(activation, &["textureReady".into()])?;
+    let dispatch_event = Object::from(texture)
+        .get_public_property("dispatchEvent", activation)?
+        .coerce_to_object(activation)?;
+
+    activation.context.timers.add_timer(
+        TimerCallback::Avm2Callback {
+            closure: dispatch_event,
+            params: vec![event.into()],
+        },
+        0,
+        true,
+    );
+    Ok(())
+}
+
+fn decoding_failed<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    e: AtfError,
+) -> Result<(), Error<'gc>> {
+    tracing::error!("Failed to decode ATF texture: {}", e);
+    Err(Error::AvmError(error(
+        activation,
+        "Error #3677: Texture decoding failed. Internal error.",
+        3677,
+    )?))
+}
@@ -0,0 +1,64 @@
+//! Uploading of raw pixel data, shared by all texture classes
+
+use super::mip_size;
+use crate::avm2::globals::flash::display3D::{byte_range, make_error_3669};
+use crate::avm2::object::TextureObject;
+use crate::avm2::Activation;
+use crate::avm2::TObject;
+use crate::avm2::{Error, Object};
+use ruffle_render::backend::Context3DTextureFormat;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+
+/// Upload the pixels at `byte_array_offset` in `data` to the mip level `mip_level` of
+/// the face `side` of `texture`.
+///
+/// `mip_levels` is the number of mip levels that can be uploaded to, which is only the
+/// first one for a `RectangleTexture`. Only `bgra` textures are supported, which callers
+/// check with [`is_supported_format`].
+pub fn do_byte_array_upload<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    texture: TextureObject<'gc>,
+    data: Object<'gc>,
+    byte_array_offset: usize,
+    side: u32,
+    mip_level: u32,
+    mip_levels: u32,
+) -> Result<(), Error<'gc>> {
+    let handle = texture.handle();
+    let Some((width, height)) = mip_size(&*handle, mip_level, mip_levels) else {
+        return Err(make_error_3669(activation));
+    };
+
+    let pixels = {
+        let byte_array = data
+            .as_bytearray()
+            .ok_or_else(|| Error::from("uploadFromByteArray: data must be a ByteArray"))?;
+        byte_range(
+            byte_array_offset,
+            width as usize * height as usize * 4,
+            byte_array.len(),
+        )
+        .map(|range| byte_array.bytes()[range].to_vec())
+    };
+    let Some(mut pixels) = pixels else {
+        return Err(make_error_3669(activation));
+    };
+
+    // Pixels are stored as B, G, R, A bytes, whatever the `endian` of the ByteArray.
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    texture.context3d().copy_rgba_to_texture(
+        Bitmap::new(width, height, BitmapFormat::Rgba, pixels),
+        handle,
+        side,
+        mip_level,
+    );
+    Ok(())
+}
+
+/// Whether raw pixel data can be uploaded to a texture of the given format.
+pub fn is_supported_format(format: Context3DTextureFormat) -> bool {
+    matches!(format, Context3DTextureFormat::Bgra)
+}
@@ -1,3 +1,9 @@
+use super::atf::do_compressed_upload;
+use super::byte_array::{do_byte_array_upload, is_supported_format};
+use super::{mip_levels, mip_size};
+use crate::avm2::error::make_error_2008;
+use crate::avm2::globals::flash::display3D::{make_error_3669, make_error_3694};
+use crate::avm2::parameters::ParametersExt;
 use crate::avm2::Activation;
 use crate::avm2::TObject;
 use crate::avm2::Value;
@@ -10,27 +16,87 @@ pub fn upload_from_bitmap_data<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
         if let Some(source) = args[0].coerce_to_object(activation)?.as_bitmap_data() {
             let side = args[1].coerce_to_u32(activation)?;
             let mip_level = args[2].coerce_to_u32(activation)?;
-            if mip_level == 0 {
-                texture.context3d().copy_bitmap_to_texture(
-                    source.sync(),
-                    texture.handle(),
-                    // FIXME - is this right?
-                    side,
-                );
-            } else {
-                avm2_stub_method!(
-                    activation,
-                    "flash.display3D.textures.CubeTexture",
-                    "uploadFromBitmapData",
-                    "with miplevel != 0"
-                );
+            if side >= 6 {
+                return Err(make_error_2008(activation, "side"));
+            }
+            let handle = texture.handle();
+            let source = source.sync();
+            let mip_levels = mip_levels(handle.width(), handle.height());
+            let source_size = (source.read().width(), source.read().height());
+            if mip_size(&*handle, mip_level, mip_levels) != Some(source_size) {
+                return Err(make_error_3669(activation));
             }
+            // Each side is a layer of the texture.
+            texture
+                .context3d()
+                .copy_bitmap_to_texture(source, handle, side, mip_level);
         } else {
             panic!("Invalid source: {:?}", args[0]);
         }
     }
     Ok(Value::Undefined)
 }
+
+pub fn upload_compressed_texture_from_byte_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let data = args.get_object(activation, 0, "data")?;
+        let byte_array_offset = args.get_u32(activation, 1)? as usize;
+        let is_async = args.get_bool(2);
+        do_compressed_upload(activation, texture, data, byte_array_offset, true, is_async)?;
+    }
+    Ok(Value::Undefined)
+}
+
+pub fn upload_from_byte_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let data = args.get_object(activation, 0, "data")?;
+        let byte_array_offset = args.get_u32(activation, 1)? as usize;
+        let side = args.get_u32(activation, 2)?;
+        let mip_level = args.get_u32(activation, 3)?;
+        if side >= 6 {
+            return Err(make_error_2008(activation, "side"));
+        }
+        if !is_supported_format(texture.original_format()) {
+            avm2_stub_method!(
+                activation,
+                "flash.display3D.textures.CubeTexture",
+                "uploadFromByteArray",
+                "with formats other than bgra"
+            );
+            return Ok(Value::Undefined);
+        }
+
+        let handle = texture.handle();
+        let mip_levels = mip_levels(handle.width(), handle.height());
+        do_byte_array_upload(
+            activation,
+            texture,
+            data,
+            byte_array_offset,
+            side,
+            mip_level,
+            mip_levels,
+        )?;
+    }
+    Ok(Value::Undefined)
+}
@@ -1,7 +1,11 @@
+use super::byte_array::{do_byte_array_upload, is_supported_format};
+use crate::avm2::globals::flash::display3D::make_error_3694;
+use crate::avm2::parameters::ParametersExt;
 use crate::avm2::Activation;
 use crate::avm2::TObject;
 use crate::avm2::Value;
 use crate::avm2::{Error, Object};
+use crate::avm2_stub_method;
 
 pub fn upload_from_bitmap_data<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -9,13 +13,43 @@ pub fn upload_from_bitmap_data<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
         if let Some(source) = args[0].coerce_to_object(activation)?.as_bitmap_data() {
             texture
                 .context3d()
-                .copy_bitmap_to_texture(source.sync(), texture.handle(), 0);
+                .copy_bitmap_to_texture(source.sync(), texture.handle(), 0, 0);
         } else {
             panic!("Invalid source: {:?}", args[0]);
         }
     }
     Ok(Value::Undefined)
 }
+
+pub fn upload_from_byte_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let data = args.get_object(activation, 0, "data")?;
+        let byte_array_offset = args.get_u32(activation, 1)? as usize;
+        if !is_supported_format(texture.original_format()) {
+            avm2_stub_method!(
+                activation,
+                "flash.display3D.textures.RectangleTexture",
+                "uploadFromByteArray",
+                "with formats other than bgra"
+            );
+            return Ok(Value::Undefined);
+        }
+
+        // A `RectangleTexture` never has mipmaps.
+        do_byte_array_upload(activation, texture, data, byte_array_offset, 0, 0, 1)?;
+    }
+    Ok(Value::Undefined)
+}
@@ -0,0 +1,78 @@
+use crate::avm2::globals::flash::display3D::make_error_3694;
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::Activation;
+use crate::avm2::TObject;
+use crate::avm2::Value;
+use crate::avm2::{Error, Object};
+use crate::avm2_stub_method;
+
+pub fn attach_net_stream<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let stream = args
+            .try_get_object(activation, 0)
+            .and_then(|stream| stream.as_netstream());
+        texture.attach_video_stream(activation.context.gc_context, stream);
+    }
+    Ok(Value::Undefined)
+}
+
+pub fn attach_camera<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if texture.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        // A texture displays either a camera or a `NetStream`, never both.
+        texture.attach_video_stream(activation.context.gc_context, None);
+        if args.try_get_object(activation, 0).is_some() {
+            avm2_stub_method!(
+                activation,
+                "flash.display3D.textures.VideoTexture",
+                "attachCamera"
+            );
+        }
+    }
+    Ok(Value::Undefined)
+}
+
+pub fn get_video_width<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if let Some(bitmap) = texture
+            .video_stream()
+            .and_then(|stream| stream.last_decoded_bitmap())
+        {
+            return Ok(bitmap.width.into());
+        }
+    }
+    Ok(0.into())
+}
+
+pub fn get_video_height<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(texture) = this.as_texture() {
+        if let Some(bitmap) = texture
+            .video_stream()
+            .and_then(|stream| stream.last_decoded_bitmap())
+        {
+            return Ok(bitmap.height.into());
+        }
+    }
+    Ok(0.into())
+}
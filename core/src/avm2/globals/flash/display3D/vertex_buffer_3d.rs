@@ -1,3 +1,4 @@
+use super::{byte_range, make_error_3669, make_error_3694};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::Activation;
 use crate::avm2::ClassObject;
@@ -18,31 +19,42 @@ pub fn upload_from_byte_array<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(vertex_buffer) = this.as_vertex_buffer() {
-        let byte_array = args
-            .get(0)
-            .unwrap_or(&Value::Undefined)
-            .coerce_to_object(activation)?;
-
-        let byte_array = byte_array
-            .as_bytearray()
-            .ok_or_else(|| Error::from("ArgumentError: Parameter must be a ByteArray"))?;
+        if vertex_buffer.disposed() {
+            return Err(make_error_3694(activation));
+        }
+        let byte_array = args.get_object(activation, 0, "data")?;
+        let byte_array_offset = args.get_i32(activation, 1)?;
+        let start_vertex = args.get_i32(activation, 2)?;
+        let num_vertices = args.get_i32(activation, 3)?;
 
-        let byte_offset = args.get_u32(activation, 1)?;
-        let start_vertex = args.get_u32(activation, 2)?;
-        let num_vertices = args.get_u32(activation, 3)?;
+        let (Ok(byte_array_offset), Ok(start_vertex), Ok(num_vertices)) = (
+            usize::try_from(byte_array_offset),
+            usize::try_from(start_vertex),
+            usize::try_from(num_vertices),
+        ) else {
+            return Err(make_error_3669(activation));
+        };
+        if start_vertex.saturating_add(num_vertices) > vertex_buffer.num_vertices() {
+            return Err(make_error_3669(activation));
+        }
 
-        let data = byte_array
-            .read_at(
-                num_vertices as usize * 4 * vertex_buffer.data32_per_vertex() as usize,
-                byte_offset as usize,
-            )
-            .map_err(|e| e.to_avm(activation))?
-            .to_vec();
+        // The data is read as little-endian 32-bit values, whatever the `endian` of the ByteArray.
+        let len = num_vertices * 4 * vertex_buffer.data32_per_vertex() as usize;
+        let data = {
+            let byte_array = byte_array
+                .as_bytearray()
+                .ok_or_else(|| Error::from("ArgumentError: Parameter must be a ByteArray"))?;
+            byte_range(byte_array_offset, len, byte_array.len())
+                .map(|range| byte_array.bytes()[range].to_vec())
+        };
+        let Some(data) = data else {
+            return Err(make_error_3669(activation));
+        };
 
         vertex_buffer.context3d().upload_vertex_buffer_data(
             vertex_buffer,
             data,
-            start_vertex as usize,
+            start_vertex,
             vertex_buffer.data32_per_vertex(),
         );
     }
@@ -55,6 +67,9 @@ pub fn upload_from_vector<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(vertex_buffer) = this.as_vertex_buffer() {
+        if vertex_buffer.disposed() {
+            return Err(make_error_3694(activation));
+        }
         let vector = args
             .get(0)
             .unwrap_or(&Value::Undefined)
@@ -84,3 +99,14 @@ pub fn upload_from_vector<'gc>(
     }
     Ok(Value::Undefined)
 }
+
+pub fn dispose<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(vertex_buffer) = this.as_vertex_buffer() {
+        vertex_buffer.dispose();
+    }
+    Ok(Value::Undefined)
+}
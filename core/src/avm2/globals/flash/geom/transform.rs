@@ -1,9 +1,12 @@
+use crate::avm2::object::VectorObject;
 use crate::avm2::parameters::ParametersExt;
+use crate::avm2::vector::VectorStorage;
 use crate::avm2::Multiname;
 use crate::avm2::{Activation, Error, Object, TObject, Value};
 use crate::avm2_stub_getter;
-use crate::display_object::TDisplayObject;
-use crate::prelude::{DisplayObject, Matrix, Twips};
+use crate::display_object::{PerspectiveProjection, TDisplayObject};
+use crate::prelude::{DisplayObject, Matrix, Point, Twips};
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::quality::StageQuality;
 use swf::{ColorTransform, Fixed8, Rectangle};
 
@@ -64,7 +67,12 @@ pub fn get_matrix<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let matrix = *get_display_object(this, activation)?.base().matrix();
+    let dobj = get_display_object(this, activation)?;
+    // Objects with a 3D transform have a `matrix3D` instead.
+    if dobj.transform_3d().is_some() {
+        return Ok(Value::Null);
+    }
+    let matrix = *dobj.base().matrix();
     matrix_to_object(matrix, activation)
 }
 
@@ -76,6 +84,10 @@ pub fn set_matrix<'gc>(
     let matrix = object_to_matrix(args.get_object(activation, 0, "value")?, activation)?;
     let dobj = get_display_object(this, activation)?;
     dobj.set_matrix(activation.context.gc_context, matrix);
+    if dobj.transform_3d().is_some() {
+        // Setting a 2D matrix removes the 3D transform.
+        dobj.set_matrix_3d(activation.context.gc_context, None);
+    }
     if let Some(parent) = dobj.parent() {
         // Self-transform changes are automatically handled,
         // we only want to inform ancestors to avoid unnecessary invalidations for tx/ty
@@ -136,6 +148,128 @@ pub fn get_concatenated_color_transform<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn get_matrix_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let dobj = get_display_object(this, activation)?;
+    match dobj.matrix_3d(activation.context.gc_context) {
+        Some(matrix_3d) => matrix_3d_to_object(matrix_3d, activation),
+        None => Ok(Value::Null),
+    }
+}
+
+pub fn set_matrix_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let matrix_3d = match args.try_get_object(activation, 0) {
+        Some(object) => Some(object_to_matrix_3d(object, activation)?),
+        None => None,
+    };
+    let dobj = get_display_object(this, activation)?;
+    dobj.set_matrix_3d(activation.context.gc_context, matrix_3d.as_ref());
+    Ok(Value::Undefined)
+}
+
+pub fn get_perspective_projection<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let dobj = get_display_object(this, activation)?;
+    let movie_size = activation.context.stage.movie_size();
+    let is_root = dobj
+        .parent()
+        .map_or(false, |parent| parent.as_stage().is_some());
+    let projection = match dobj.perspective_projection() {
+        Some(projection) => projection,
+        // Objects at the top of the display list always have a projection.
+        None if is_root => PerspectiveProjection::default_for_movie(movie_size),
+        None => return Ok(Value::Null),
+    };
+
+    let mut object = activation
+        .avm2()
+        .classes()
+        .perspectiveprojection
+        .construct(activation, &[])?;
+    object.set_property(
+        &Multiname::new(activation.avm2().flash_geom_internal, "_width"),
+        movie_size.0.into(),
+        activation,
+    )?;
+    object.set_public_property("fieldOfView", projection.field_of_view.into(), activation)?;
+    let center = activation.avm2().classes().point.construct(
+        activation,
+        &[
+            projection.center.x.to_pixels().into(),
+            projection.center.y.to_pixels().into(),
+        ],
+    )?;
+    object.set_public_property("projectionCenter", center.into(), activation)?;
+    Ok(object.into())
+}
+
+pub fn set_perspective_projection<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let projection = match args.try_get_object(activation, 0) {
+        Some(object) => {
+            let field_of_view = object
+                .get_public_property("fieldOfView", activation)?
+                .coerce_to_number(activation)?;
+            let center = object
+                .get_public_property("projectionCenter", activation)?
+                .coerce_to_object(activation)?;
+            let x = center
+                .get_public_property("x", activation)?
+                .coerce_to_number(activation)?;
+            let y = center
+                .get_public_property("y", activation)?
+                .coerce_to_number(activation)?;
+            Some(PerspectiveProjection {
+                field_of_view,
+                center: Point::from_pixels(x, y),
+            })
+        }
+        None => None,
+    };
+    let dobj = get_display_object(this, activation)?;
+    dobj.set_perspective_projection(activation.context.gc_context, projection);
+    Ok(Value::Undefined)
+}
+
+pub fn get_relative_matrix_3d<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let dobj = get_display_object(this, activation)?;
+    let relative_to = args
+        .get_object(activation, 0, "relativeTo")?
+        .as_display_object();
+    let gc_context = activation.context.gc_context;
+    let stage = activation.context.stage;
+
+    let matrix_3d = dobj.local_to_global_matrix_3d(gc_context, stage);
+    let relative_matrix_3d = match relative_to {
+        Some(relative_to) => relative_to
+            .local_to_global_matrix_3d(gc_context, stage)
+            .inverse()
+            .map(|inverse| inverse * matrix_3d),
+        None => Some(matrix_3d),
+    };
+    match relative_matrix_3d {
+        Some(relative_matrix_3d) => matrix_3d_to_object(relative_matrix_3d, activation),
+        None => Ok(Value::Null),
+    }
+}
+
 // FIXME - handle clamping. We're throwing away precision here in converting to an integer:
 // is that what we should be doing?
 pub fn object_to_color_transform<'gc>(
@@ -247,6 +381,45 @@ pub fn object_to_matrix<'gc>(
     Ok(Matrix { a, b, c, d, tx, ty })
 }
 
+pub fn matrix_3d_to_object<'gc>(
+    matrix_3d: Matrix3D,
+    activation: &mut Activation<'_, 'gc>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let number_class = activation.avm2().classes().number;
+    let raw_data = matrix_3d
+        .raw_data
+        .iter()
+        .map(|value| (*value).into())
+        .collect();
+    let raw_data = VectorStorage::from_values(raw_data, false, number_class);
+    let raw_data = VectorObject::from_vector(raw_data, activation)?;
+    let object = activation
+        .avm2()
+        .classes()
+        .matrix3d
+        .construct(activation, &[raw_data.into()])?;
+    Ok(object.into())
+}
+
+pub fn object_to_matrix_3d<'gc>(
+    object: Object<'gc>,
+    activation: &mut Activation<'_, 'gc>,
+) -> Result<Matrix3D, Error<'gc>> {
+    let raw_data = object
+        .get_public_property("rawData", activation)?
+        .coerce_to_object(activation)?;
+    let values: Vec<Value<'gc>> = raw_data
+        .as_vector_storage()
+        .map(|storage| storage.iter().collect())
+        .unwrap_or_default();
+
+    let mut matrix_3d = Matrix3D::IDENTITY;
+    for (raw, value) in matrix_3d.raw_data.iter_mut().zip(values) {
+        *raw = value.coerce_to_number(activation)?;
+    }
+    Ok(matrix_3d)
+}
+
 pub fn get_pixel_bounds<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
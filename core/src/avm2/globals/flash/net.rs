@@ -7,9 +7,11 @@ pub mod local_connection;
 pub mod net_connection;
 pub mod net_stream;
 pub mod object_encoding;
+pub mod secure_socket;
 pub mod shared_object;
 pub mod socket;
 pub mod url_loader;
+pub mod xml_socket;
 
 /// Implements `flash.net.navigateToURL`
 pub fn navigate_to_url<'gc>(
@@ -0,0 +1,28 @@
+package flash.net {
+    import flash.security.X509Certificate;
+    import flash.utils.ByteArray;
+    import __ruffle__.stub_getter;
+
+    public class SecureSocket extends Socket {
+        public function SecureSocket() {
+            super();
+        }
+
+        public static native function get isSupported():Boolean;
+
+        public function get serverCertificate():X509Certificate {
+            stub_getter("flash.net.SecureSocket", "serverCertificate");
+            return null;
+        }
+
+        public native function get serverCertificateStatus():String;
+
+        public native function addBinaryChainBuildingCertificate(certificate:ByteArray, trusted:Boolean):void;
+
+        override public function connect(host:String, port:int):void {
+            this.connectSecure(host, port);
+        }
+
+        private native function connectSecure(host:String, port:int):void;
+    }
+}
@@ -1,20 +1,13 @@
 package flash.net {
     import flash.events.EventDispatcher;
     import flash.utils.ByteArray;
-    import flash.utils.Endian;
     import flash.utils.IDataInput;
     import flash.utils.IDataOutput;
 
-    import __ruffle__.stub_method;
-    import __ruffle__.stub_getter;
-    import __ruffle__.stub_setter;
-
     [Ruffle(InstanceAllocator)]
     public class Socket extends EventDispatcher implements IDataOutput, IDataInput {
         private var _timeout:uint;
 
-        private var _endian:String = Endian.BIG_ENDIAN;
-
         public function Socket(host:String = null, port:int = 0) {
             this._timeout = 20000;
             if (host != null) {
@@ -39,152 +32,46 @@ package flash.net {
 
         public native function close():void;
 
-        public function get bytesAvailable():uint {
-            stub_getter("flash.net.Socket", "bytesAvailable");
-            return 0;
-        }
+        public native function get bytesAvailable():uint;
 
-        public function get bytesPending():uint {
-            stub_getter("flash.net.Socket", "bytesPending");
-            return 0;
-        }
+        public native function get bytesPending():uint;
 
-        public function get endian():String {
-            return this._endian;
-        }
+        public native function get endian():String;
+        public native function set endian(value:String):void;
 
-        public function set endian(value:String):void {
-            if (value === Endian.BIG_ENDIAN || value === Endian.LITTLE_ENDIAN) {
-                this._endian = value;
-            } else {
-                throw new ArgumentError("Error #2008: Parameter endian must be one of the accepted values.", 2008);
-            }
-        }
+        public native function get connected():Boolean;
 
-        public function get connected():Boolean {
-            stub_getter("flash.net.Socket", "connected");
-            return false;
-        }
-
-        public function get objectEncoding():uint {
-            stub_getter("flash.net.Socket", "objectEncoding");
-            return 0;
-        }
-
-        public function set objectEncoding(value:uint):void {
-            stub_setter("flash.net.Socket", "objectEncoding");
-        }
+        public native function get objectEncoding():uint;
+        public native function set objectEncoding(value:uint):void;
 
         public native function flush():void;
 
-        public function readBoolean():Boolean {
-            stub_method("flash.net.Socket", "readBoolean");
-            return false;
-        }
-
-        public function readByte():int {
-            stub_method("flash.net.Socket", "readByte");
-            return 0;
-        }
-
+        public native function readBoolean():Boolean;
+        public native function readByte():int;
         public native function readBytes(bytes:ByteArray, offset:uint = 0, length:uint = 0):void;
-
-        public function readDouble():Number {
-            stub_method("flash.net.Socket", "readDouble");
-            return 0.0;
-        }
-
-        public function readFloat():Number {
-            stub_method("flash.net.Socket", "readFloat");
-            return 0.0;
-        }
-
-        public function readInt():int {
-            stub_method("flash.net.Socket", "readInt");
-            return 0;
-        }
-
-        public function readMultiByte(length:uint, charSet:String):String {
-            stub_method("flash.net.Socket", "readMultiByte");
-            return "";
-        }
-
-        public function readObject():* {
-            stub_method("flash.net.Socket", "readObject");
-            return null;
-        }
-
-        public function readShort():int {
-            stub_method("flash.net.Socket", "readShort");
-            return 0;
-        }
-
-        public function readUnsignedByte():uint {
-            stub_method("flash.net.Socket", "readUnsignedByte");
-            return 0;
-        }
-
-        public function readUnsignedInt():uint {
-            stub_method("flash.net.Socket", "readUnsignedInt");
-            return 0;
-        }
-
-        public function readUnsignedShort():uint {
-            stub_method("flash.net.Socket", "readUnsignedShort");
-            return 0;
-        }
-
-        public function readUTF():String {
-            stub_method("flash.net.Socket", "readUTF");
-            return "";
-        }
-
-        public function readUTFBytes(length:uint):String {
-            stub_method("flash.net.Socket", "readUTFBytes");
-            return "";
-        }
-
-        public function writeBoolean(value:Boolean):void {
-            stub_method("flash.net.Socket", "writeBoolean");
-        }
-
+        public native function readDouble():Number;
+        public native function readFloat():Number;
+        public native function readInt():int;
+        public native function readMultiByte(length:uint, charSet:String):String;
+        public native function readObject():*;
+        public native function readShort():int;
+        public native function readUnsignedByte():uint;
+        public native function readUnsignedInt():uint;
+        public native function readUnsignedShort():uint;
+        public native function readUTF():String;
+        public native function readUTFBytes(length:uint):String;
+
+        public native function writeBoolean(value:Boolean):void;
         public native function writeByte(value:int):void;
         public native function writeBytes(bytes:ByteArray, offset:uint = 0, length:uint = 0):void;
-
-        public function writeDouble(value:Number):void {
-            stub_method("flash.net.Socket", "writeDouble");
-        }
-
-        public function writeFloat(value:Number):void {
-            stub_method("flash.net.Socket", "writeFloat");
-        }
-
-        public function writeInt(value:int):void {
-            stub_method("flash.net.Socket", "writeInt");
-        }
-
-        public function writeMultiByte(value:String, charSet:String):void {
-            stub_method("flash.net.Socket", "writeMultiByte");
-        }
-
-        public function writeObject(value:*):void {
-            stub_method("flash.net.Socket", "writeObject");
-        }
-
-        public function writeShort(value:int):void {
-            stub_method("flash.net.Socket", "writeShort");
-        }
-
-        public function writeUnsignedInt(value:uint):void {
-            stub_method("flash.net.Socket", "writeUnsignedInt");
-        }
-
-        public function writeUTF(value:String):void {
-            stub_method("flash.net.Socket", "writeUTF");
-        }
-
-        public function writeUTFBytes(value:String):void {
-            stub_method("flash.net.Socket", "writeUTFBytes");
-        }
+        public native function writeDouble(value:Number):void;
+        public native function writeFloat(value:Number):void;
+        public native function writeInt(value:int):void;
+        public native function writeMultiByte(value:String, charSet:String):void;
+        public native function writeObject(value:*):void;
+        public native function writeShort(value:int):void;
+        public native function writeUnsignedInt(value:uint):void;
+        public native function writeUTF(value:String):void;
+        public native function writeUTFBytes(value:String):void;
     }
 }
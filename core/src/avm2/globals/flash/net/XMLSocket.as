@@ -1,23 +1,43 @@
 package flash.net {
     import flash.events.EventDispatcher;
-    import __ruffle__.stub_constructor;
-    import __ruffle__.stub_method;
 
+    [Ruffle(InstanceAllocator)]
     public class XMLSocket extends EventDispatcher {
-        public function XMLSocket(host: String = null, port: int = 0) {
-            stub_constructor("flash.net.XMLSocket");
+        private var _timeout:uint;
+
+        public function XMLSocket(host:String = null, port:int = 0) {
+            this._timeout = 20000;
+            if (host != null) {
+                this.connect(host, port);
+            }
         }
 
-        public function close(): void {
-            stub_method("flash.net.XMLSocket", "close");
+        public native function connect(host:String, port:int):void;
+
+        public native function close():void;
+
+        public native function get connected():Boolean;
+
+        public function get timeout():uint {
+            return this._timeout;
         }
 
-        public function connect(host: String, port: int): void {
-            stub_method("flash.net.XMLSocket", "connect");
+        public function set timeout(value:uint):void {
+            if (value < 250) {
+                this._timeout = 250;
+            } else {
+                this._timeout = value;
+            }
         }
 
-        public function send(object: *): void {
-            stub_method("flash.net.XMLSocket", "send");
+        public function send(object:*):void {
+            if (object is XML || object is XMLList) {
+                this.sendString(object.toXMLString());
+            } else {
+                this.sendString(String(object));
+            }
         }
+
+        private native function sendString(data:String):void;
     }
 }
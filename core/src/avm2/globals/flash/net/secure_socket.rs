@@ -0,0 +1,107 @@
+//! `flash.net.SecureSocket` native function definitions
+//!
+//! `SecureSocket` instances are regular socket objects, which the socket
+//! loader connects over TLS instead.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::error::security_error;
+use crate::avm2::object::TObject;
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::value::Value;
+use crate::avm2::{Error, Object};
+use crate::backend::navigator::{CertificateStatus, ChainBuildingCertificate};
+use crate::loader::{default_socket_host, SocketTarget};
+use crate::string::AvmString;
+use std::time::Duration;
+
+/// Native function definition for `SecureSocket.isSupported`
+pub fn get_is_supported<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(activation
+        .context
+        .navigator
+        .supports_secure_sockets()
+        .into())
+}
+
+/// Native function definition for `SecureSocket.serverCertificateStatus`
+pub fn get_server_certificate_status<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(AvmString::from(socket.server_certificate_status().as_str()).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `SecureSocket.addBinaryChainBuildingCertificate`
+pub fn add_binary_chain_building_certificate<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let certificate = args.get_object(activation, 0, "certificate")?;
+        let trusted = args.get_bool(1);
+
+        let der = match certificate.as_bytearray() {
+            Some(bytearray) => bytearray.bytes().to_vec(),
+            None => return Err("Certificate must be a ByteArray".into()),
+        };
+        socket.add_chain_building_certificate(
+            ChainBuildingCertificate { der, trusted },
+            activation.context.gc_context,
+        );
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `SecureSocket.connectSecure`
+pub fn connect_secure<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this.as_socket() {
+        let host = match args.try_get_string(activation, 0)? {
+            Some(host) => host.to_string(),
+            None => default_socket_host(activation.context.swf.url()),
+        };
+        let port = args.get_u32(activation, 1)?;
+        if port > 65535 {
+            return Err(Error::AvmError(security_error(
+                activation,
+                "Error #2003: Invalid socket port number specified.",
+                2003,
+            )?));
+        }
+
+        let timeout = this
+            .get_public_property("timeout", activation)?
+            .coerce_to_u32(activation)?;
+
+        // The status of a previous connection no longer applies.
+        this.set_server_certificate_status(
+            CertificateStatus::Unknown,
+            activation.context.gc_context,
+        );
+
+        let future = activation.context.load_manager.load_socket(
+            activation.context.player.clone(),
+            SocketTarget::Avm2Secure(this),
+            (host, port as u16),
+            Duration::from_millis(timeout.into()),
+        );
+        activation.context.navigator.spawn_future(future);
+
+        return Ok(Value::Undefined);
+    }
+    Err("SecureSocket.prototype.connect has been called on an incompatible object".into())
+}
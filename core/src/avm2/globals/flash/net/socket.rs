@@ -1,14 +1,81 @@
 //! `flash.net.Socket` native function definitions
 
 use crate::avm2::activation::Activation;
-use crate::avm2::error::security_error;
-use crate::avm2::object::{OutgoingSocketAction, TObject};
+use crate::avm2::bytearray::{Endian, ObjectEncoding};
+use crate::avm2::error::{io_error, make_error_2008, security_error};
+use crate::avm2::object::{SocketObject, TObject};
 use crate::avm2::value::Value;
 use crate::avm2::{Error, Object};
+use crate::backend::navigator::OutgoingSocketAction;
+use crate::loader::{default_socket_host, SocketTarget};
+use crate::string::AvmString;
+use encoding_rs::Encoding;
+use encoding_rs::UTF_8;
+use std::time::Duration;
 
 pub use crate::avm2::object::socket_allocator;
 use crate::avm2::parameters::ParametersExt;
 
+/// The error Flash throws when reading from or writing to a socket that is not connected.
+pub(super) fn invalid_socket_error<'gc>(activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
+    match io_error(
+        activation,
+        "Error #2002: Operation attempted on invalid socket.",
+        2002,
+    ) {
+        Ok(e) => Error::AvmError(e),
+        Err(e) => e,
+    }
+}
+
+/// Ensure that the socket has been connected before it is read from.
+///
+/// Data that arrived before the connection was closed can still be read.
+fn check_readable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    socket: SocketObject<'gc>,
+) -> Result<(), Error<'gc>> {
+    if socket.recv_queue().is_none() {
+        return Err(invalid_socket_error(activation));
+    }
+
+    Ok(())
+}
+
+/// Ensure that the socket is currently connected before it is written to.
+pub(super) fn check_writable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    socket: SocketObject<'gc>,
+) -> Result<(), Error<'gc>> {
+    if !socket.is_connected() || socket.outgoing_queue().is_none() {
+        return Err(invalid_socket_error(activation));
+    }
+
+    Ok(())
+}
+
+/// Hand an action over to the IO task.
+pub(super) fn send_action<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    socket: SocketObject<'gc>,
+    action: OutgoingSocketAction,
+) -> Result<(), Error<'gc>> {
+    let queue = match socket.outgoing_queue() {
+        Some(queue) => queue,
+        None => return Err(invalid_socket_error(activation)),
+    };
+    let queue = queue.read();
+    if queue.send(action).is_err() {
+        return Err(Error::AvmError(io_error(
+            activation,
+            "Error #2031: Socket Error.",
+            2031,
+        )?));
+    }
+
+    Ok(())
+}
+
 /// Native function definition for `Socket.connect`
 pub fn connect<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -16,7 +83,10 @@ pub fn connect<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this.as_socket() {
-        let host = args.get_string(activation, 0)?;
+        let host = match args.try_get_string(activation, 0)? {
+            Some(host) => host.to_string(),
+            None => default_socket_host(activation.context.swf.url()),
+        };
         let port = args.get_u32(activation, 1)?;
         if port > 65535 {
             return Err(Error::AvmError(security_error(
@@ -26,10 +96,15 @@ pub fn connect<'gc>(
             )?));
         }
 
+        let timeout = this
+            .get_public_property("timeout", activation)?
+            .coerce_to_u32(activation)?;
+
         let future = activation.context.load_manager.load_socket(
             activation.context.player.clone(),
-            this,
-            (host.to_string(), port as u16),
+            SocketTarget::Avm2(this),
+            (host, port as u16),
+            Duration::from_millis(timeout.into()),
         );
         activation.context.navigator.spawn_future(future);
 
@@ -38,227 +113,520 @@ pub fn connect<'gc>(
     Err("Socket.prototype.connect has been called on an incompatible object".into())
 }
 
-/// Native function definition for `Socket.writeBytes`
-pub fn write_bytes<'gc>(
+/// Native function definition for `Socket.flush`
+pub fn flush<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    args: &[Value<'gc>],
+    _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(this) = this.as_socket() {
-        let bytes = args.get_object(activation, 0, "bytes")?;
-        let bytes = bytes
-            .as_bytearray()
-            .ok_or("ArgumentError: Parameter must be a ByteArray")?;
-        let offset = args.get_u32(activation, 1)?;
-        let length = args.get_u32(activation, 2)?;
-
-        tracing::debug!(
-            "Socket.writeBytes: {:?} offset={} len={}",
-            bytes,
-            offset,
-            length
-        );
+    if let Some(socket) = this.as_socket() {
+        check_writable(activation, socket)?;
 
-        let queue = match this.outgoing_queue() {
-            Some(queue) => Ok(queue),
-            None => Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?)),
-        }?;
-        let queue = queue.read();
-        match queue.send(OutgoingSocketAction::Write(bytes.bytes().to_vec())) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::AvmError(security_error(
-                    activation,
-                    "Error #2031: Socket Error.",
-                    2031,
-                )?))
-            }
+        let bytes = {
+            let mut write_buffer = socket.write_buffer(activation.context.gc_context);
+            let bytes = write_buffer.bytes().to_vec();
+            write_buffer.clear();
+            bytes
+        };
+
+        if !bytes.is_empty() {
+            send_action(activation, socket, OutgoingSocketAction::Write(bytes))?;
         }
+        send_action(activation, socket, OutgoingSocketAction::Flush)?;
+    }
 
-        return Ok(Value::Undefined);
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.close`
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        check_writable(activation, socket)?;
+
+        // Anything that was never flushed is discarded.
+        send_action(activation, socket, OutgoingSocketAction::Close)?;
+        socket.close(activation.context.gc_context);
     }
-    Err("Socket.prototype.writeBytes has been called on an incompatible object".into())
+
+    Ok(Value::Undefined)
 }
 
-/// Native function definition for `Socket.writeByte`
-pub fn write_byte<'gc>(
+/// Native function definition for `Socket.bytesAvailable`
+pub fn get_bytes_available<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(socket.bytes_available(activation.context.gc_context).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.bytesPending`
+pub fn get_bytes_pending<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(socket.bytes_pending().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.connected`
+pub fn get_connected<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(socket.is_connected().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.endian` getter
+pub fn get_endian<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(match socket.endian() {
+            Endian::Big => "bigEndian".into(),
+            Endian::Little => "littleEndian".into(),
+        });
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.endian` setter
+pub fn set_endian<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(this) = this.as_socket() {
-        let byte = args
-            .get(0)
-            .cloned()
-            .unwrap_or(Value::Undefined)
-            .coerce_to_i32(activation)?;
-        tracing::debug!("Socket.writeByte: {:?}", byte);
-
-        let queue = match this.outgoing_queue() {
-            Some(queue) => Ok(queue),
-            None => Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?)),
-        }?;
-        let queue = queue.read();
-        match queue.send(OutgoingSocketAction::Write(vec![byte as u8])) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::AvmError(security_error(
-                    activation,
-                    "Error #2031: Socket Error.",
-                    2031,
-                )?))
-            }
+    if let Some(socket) = this.as_socket() {
+        let endian = args.get_string(activation, 0)?;
+        if &endian == b"bigEndian" {
+            socket.set_endian(Endian::Big, activation.context.gc_context);
+        } else if &endian == b"littleEndian" {
+            socket.set_endian(Endian::Little, activation.context.gc_context);
+        } else {
+            return Err(make_error_2008(activation, "endian"));
         }
+    }
 
-        return Ok(Value::Undefined);
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.objectEncoding` getter
+pub fn get_object_encoding<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok((socket.object_encoding() as u8).into());
     }
-    Err("Socket.prototype.writeByte has been called on an incompatible object".into())
+
+    Ok(Value::Undefined)
 }
 
-/// Native function definition for `Socket.flush`
-pub fn flush<'gc>(
+/// Native function definition for `Socket.objectEncoding` setter
+pub fn set_object_encoding<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(this) = this.as_socket() {
-        let queue = match this.outgoing_queue() {
-            Some(queue) => Ok(queue),
-            None => Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?)),
-        }?;
-        let queue = queue.read();
-        match queue.send(OutgoingSocketAction::Flush) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::AvmError(security_error(
-                    activation,
-                    "Error #2031: Socket Error.",
-                    2031,
-                )?))
+    if let Some(socket) = this.as_socket() {
+        let new_encoding = args.get_u32(activation, 0)?;
+        match new_encoding {
+            0 => socket.set_object_encoding(ObjectEncoding::Amf0, activation.context.gc_context),
+            3 => socket.set_object_encoding(ObjectEncoding::Amf3, activation.context.gc_context),
+            _ => return Err(make_error_2008(activation, "objectEncoding")),
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Defines natives that read a single fixed-size value from the socket,
+/// honouring its `endian`.
+macro_rules! impl_read {
+    ($($name:ident => $method:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Native function definition for `Socket.", stringify!($name), "`")]
+            pub fn $name<'gc>(
+                activation: &mut Activation<'_, 'gc>,
+                this: Object<'gc>,
+                _args: &[Value<'gc>],
+            ) -> Result<Value<'gc>, Error<'gc>> {
+                if let Some(socket) = this.as_socket() {
+                    check_readable(activation, socket)?;
+
+                    let value = socket
+                        .read_buffer(activation.context.gc_context)
+                        .$method();
+                    return Ok(value.map_err(|e| e.to_avm(activation))?.into());
+                }
+
+                Ok(Value::Undefined)
             }
+        )*
+    };
+}
+
+impl_read!(
+    read_boolean => read_boolean,
+    read_byte => read_byte,
+    read_double => read_double,
+    read_float => read_float,
+    read_int => read_int,
+    read_short => read_short,
+    read_unsigned_byte => read_unsigned_byte,
+    read_unsigned_int => read_unsigned_int,
+    read_unsigned_short => read_unsigned_short,
+);
+
+/// Defines natives that write a single fixed-size value to the socket,
+/// honouring its `endian`.
+macro_rules! impl_write {
+    ($($name:ident => $method:ident($arg:ident) $(as $data_type:ty)?),* $(,)?) => {
+        $(
+            #[doc = concat!("Native function definition for `Socket.", stringify!($name), "`")]
+            pub fn $name<'gc>(
+                activation: &mut Activation<'_, 'gc>,
+                this: Object<'gc>,
+                args: &[Value<'gc>],
+            ) -> Result<Value<'gc>, Error<'gc>> {
+                if let Some(socket) = this.as_socket() {
+                    let value = args
+                        .get(0)
+                        .unwrap_or(&Value::Undefined)
+                        .$arg(activation)?;
+                    check_writable(activation, socket)?;
+
+                    socket
+                        .write_buffer(activation.context.gc_context)
+                        .$method(value $(as $data_type)?)?;
+                }
+
+                Ok(Value::Undefined)
+            }
+        )*
+    };
+}
+
+impl_write!(
+    write_double => write_double(coerce_to_number),
+    write_float => write_float(coerce_to_number) as f32,
+    write_int => write_int(coerce_to_i32),
+    write_short => write_short(coerce_to_i32) as i16,
+    write_unsigned_int => write_unsigned_int(coerce_to_u32),
+);
+
+/// Native function definition for `Socket.readBytes`
+pub fn read_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let bytearray = args.get_object(activation, 0, "bytes")?;
+        let offset = args.get_u32(activation, 1)? as usize;
+        let length = args.get_u32(activation, 2)? as usize;
+        check_readable(activation, socket)?;
+
+        let bytes = {
+            let read_buffer = socket.read_buffer(activation.context.gc_context);
+            // If length is 0, read everything that has arrived so far
+            let length = if length != 0 {
+                length
+            } else {
+                read_buffer.bytes_available()
+            };
+            read_buffer.read_bytes(length).map(|bytes| bytes.to_vec())
+        };
+        let bytes = bytes.map_err(|e| e.to_avm(activation))?;
+
+        let mut ba_write = bytearray
+            .as_bytearray_mut(activation.context.gc_context)
+            .ok_or("ArgumentError: Parameter must be a bytearray")?;
+        ba_write.write_at(&bytes, offset)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.readMultiByte`
+pub fn read_multi_byte<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let len = args.get_u32(activation, 0)?;
+        let charset_label = args.get_string(activation, 1)?;
+        check_readable(activation, socket)?;
+
+        let read_buffer = socket.read_buffer(activation.context.gc_context);
+        let mut bytes = read_buffer
+            .read_bytes(len as usize)
+            .map_err(|e| e.to_avm(activation))?;
+
+        // Flash cuts off the string at the first null byte, just like with ByteArray
+        if let Some(null) = bytes.iter().position(|b| *b == b'\0') {
+            bytes = &bytes[..null];
         }
 
-        return Ok(Value::Undefined);
+        let encoder =
+            Encoding::for_label(charset_label.to_utf8_lossy().as_bytes()).unwrap_or(UTF_8);
+        let (decoded_str, _, _) = encoder.decode(bytes);
+        return Ok(AvmString::new_utf8(activation.context.gc_context, decoded_str).into());
     }
-    Err("Socket.prototype.flush has been called on an incompatible object".into())
+
+    Ok(Value::Undefined)
 }
 
-/// Native function definition for `Socket.close`
-pub fn close<'gc>(
+/// Native function definition for `Socket.readObject`
+pub fn read_object<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(this) = this.as_socket() {
-        let queue = match this.outgoing_queue() {
-            Some(queue) => Ok(queue),
-            None => Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?)),
-        }?;
-        let queue = queue.read();
-        match queue.send(OutgoingSocketAction::Close) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::AvmError(security_error(
-                    activation,
-                    "Error #2031: Socket Error.",
-                    2031,
-                )?))
+    if let Some(socket) = this.as_socket() {
+        check_readable(activation, socket)?;
+
+        // Copy the data out, so that the socket isn't borrowed while user code
+        // (e.g. a registered class constructor) runs during deserialization.
+        let (bytes, object_encoding) = {
+            let read_buffer = socket.read_buffer(activation.context.gc_context);
+            let bytes = read_buffer
+                .read_at(read_buffer.bytes_available(), read_buffer.position())
+                .map(|bytes| bytes.to_vec());
+            (bytes, read_buffer.object_encoding())
+        };
+        let bytes = bytes.map_err(|e| e.to_avm(activation))?;
+        let (bytes_left, value) =
+            crate::avm2::amf::deserialize_from_bytes(activation, &bytes, object_encoding)?;
+
+        let read_buffer = socket.read_buffer(activation.context.gc_context);
+        read_buffer.set_position(read_buffer.position() + bytes.len() - bytes_left);
+        return Ok(value);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.readUTF`
+pub fn read_utf<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        check_readable(activation, socket)?;
+
+        let read_buffer = socket.read_buffer(activation.context.gc_context);
+        let position = read_buffer.position();
+        let bytes = match read_buffer.read_utf() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                // Leave the length prefix in place, so the read can be retried
+                // once the rest of the string has arrived.
+                read_buffer.set_position(position);
+                return Err(e.to_avm(activation));
             }
-        }
+        };
+        return Ok(AvmString::new_utf8_bytes(activation.context.gc_context, bytes).into());
+    }
 
-        return Ok(Value::Undefined);
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.readUTFBytes`
+pub fn read_utf_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let len = args.get_u32(activation, 0)?;
+        check_readable(activation, socket)?;
+
+        let read_buffer = socket.read_buffer(activation.context.gc_context);
+        let bytes = read_buffer
+            .read_utf_bytes(len as usize)
+            .map_err(|e| e.to_avm(activation))?;
+        return Ok(AvmString::new_utf8(
+            activation.context.gc_context,
+            String::from_utf8_lossy(bytes),
+        )
+        .into());
     }
-    Err("Socket.prototype.close has been called on an incompatible object".into())
+
+    Ok(Value::Undefined)
 }
 
-/// Native function definition for `Socket.readBytes`
-pub fn read_bytes<'gc>(
+/// Native function definition for `Socket.writeBoolean`
+pub fn write_boolean<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    if let Some(this) = this.as_socket() {
-        let bytes = args.get_object(activation, 0, "bytes")?;
-        let mut bytes = bytes
-            .as_bytearray_mut(activation.context.gc_context)
-            .ok_or("ArgumentError: Parameter must be a ByteArray")?;
+    if let Some(socket) = this.as_socket() {
+        let value = args.get_bool(0);
+        check_writable(activation, socket)?;
+
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_boolean(value)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.writeByte`
+pub fn write_byte<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let byte = args.get_i32(activation, 0)?;
+        check_writable(activation, socket)?;
+
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_bytes(&[byte as u8])?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.writeBytes`
+pub fn write_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let bytearray = args.get_object(activation, 0, "bytes")?;
         let offset = args.get_u32(activation, 1)? as usize;
         let length = args.get_u32(activation, 2)? as usize;
+        check_writable(activation, socket)?;
 
-        tracing::debug!(
-            "Socket.readBytes: {:?} offset={} len={}",
-            bytes,
-            offset,
-            length
-        );
+        let ba_read = bytearray
+            .as_bytearray()
+            .ok_or("ArgumentError: Parameter must be a bytearray")?;
+        let to_write = ba_read
+            .read_at(
+                // If length is 0, write the remaining bytes of the ByteArray from the supplied offset
+                if length != 0 {
+                    length
+                } else {
+                    ba_read.len().saturating_sub(offset)
+                },
+                offset,
+            )
+            .map_err(|e| e.to_avm(activation))?;
 
-        let queue = match this.recv_queue() {
-            Some(queue) => Ok(queue),
-            None => Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?)),
-        }?;
-        let queue = queue.read();
-        if queue.is_disconnected() {
-            return Err(Error::AvmError(security_error(
-                activation,
-                "Error #2031: Socket Error.",
-                2031,
-            )?));
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_bytes(to_write)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.writeMultiByte`
+pub fn write_multi_byte<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let string = args.get_string(activation, 0)?;
+        let charset_label = args.get_string(activation, 1)?;
+        check_writable(activation, socket)?;
+
+        let encoder =
+            Encoding::for_label(charset_label.to_utf8_lossy().as_bytes()).unwrap_or(UTF_8);
+        let utf8 = string.to_utf8_lossy();
+        let (encoded_bytes, _, _) = encoder.encode(&utf8);
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_bytes(&encoded_bytes)?;
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `Socket.writeObject`
+pub fn write_object<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let value = args.get(0).cloned().unwrap_or(Value::Undefined);
+        check_writable(activation, socket)?;
+
+        if let Some(bytes) =
+            crate::avm2::amf::serialize_to_bytes(activation, value, socket.object_encoding())?
+        {
+            socket
+                .write_buffer(activation.context.gc_context)
+                .write_bytes(&bytes)?;
         }
+    }
 
-        let buffer = if length == 0 {
-            tracing::debug!("Socket.readBytes: reading unbounded");
-            let mut buffer = Vec::with_capacity(1024);
-            while queue.len() > 0 {
-                let chunk = queue.recv().unwrap();
-                buffer.extend(chunk);
-            }
-            tracing::debug!("Socket.readBytes: read unbounded: {} bytes", buffer.len());
+    Ok(Value::Undefined)
+}
 
-            buffer
-        } else {
-            tracing::debug!("Socket.readBytes: reading bounded");
-            let mut buffer = Vec::with_capacity(length);
-            while buffer.len() < length {
-                let chunk = queue.recv().unwrap();
-                buffer.extend(chunk);
-            }
-            tracing::debug!("Socket.readBytes: read bounded: {} bytes", buffer.len());
+/// Native function definition for `Socket.writeUTF`
+pub fn write_utf<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let string = args.get_string(activation, 0)?;
+        check_writable(activation, socket)?;
 
-            buffer
-        };
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_utf(&string.to_utf8_lossy())?;
+    }
 
-        let position = bytes.position();
-        bytes.write_at(&buffer, position).unwrap();
+    Ok(Value::Undefined)
+}
 
-        tracing::debug!(
-            "read {} bytes out of {}, pos: {}\n{:?}",
-            buffer.len(),
-            length,
-            position,
-            buffer
-        );
+/// Native function definition for `Socket.writeUTFBytes`
+pub fn write_utf_bytes<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        let string = args.get_string(activation, 0)?;
+        check_writable(activation, socket)?;
 
-        return Ok(Value::Undefined);
+        socket
+            .write_buffer(activation.context.gc_context)
+            .write_bytes(string.to_utf8_lossy().as_bytes())?;
     }
-    Err("Socket.prototype.readBytes has been called on an incompatible object".into())
+
+    Ok(Value::Undefined)
 }
@@ -0,0 +1,107 @@
+//! `flash.net.XMLSocket` native function definitions
+//!
+//! `XMLSocket` instances are socket objects too, but they only use the
+//! outgoing queue: received messages are dispatched as `DataEvent`s by the
+//! socket loader.
+
+use crate::avm2::activation::Activation;
+use crate::avm2::error::security_error;
+use crate::avm2::globals::flash::net::socket::{check_writable, send_action};
+use crate::avm2::object::TObject;
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::value::Value;
+use crate::avm2::{Error, Object};
+use crate::backend::navigator::OutgoingSocketAction;
+use crate::loader::{default_socket_host, SocketTarget};
+use std::time::Duration;
+
+pub use crate::avm2::object::socket_allocator as xml_socket_allocator;
+
+/// Native function definition for `XMLSocket.connect`
+pub fn connect<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this.as_socket() {
+        let host = match args.try_get_string(activation, 0)? {
+            Some(host) => host.to_string(),
+            None => default_socket_host(activation.context.swf.url()),
+        };
+        let port = args.get_u32(activation, 1)?;
+        if port > 65535 {
+            return Err(Error::AvmError(security_error(
+                activation,
+                "Error #2003: Invalid socket port number specified.",
+                2003,
+            )?));
+        }
+
+        let timeout = this
+            .get_public_property("timeout", activation)?
+            .coerce_to_u32(activation)?;
+
+        let future = activation.context.load_manager.load_socket(
+            activation.context.player.clone(),
+            SocketTarget::Avm2Xml(this),
+            (host, port as u16),
+            Duration::from_millis(timeout.into()),
+        );
+        activation.context.navigator.spawn_future(future);
+
+        return Ok(Value::Undefined);
+    }
+    Err("XMLSocket.prototype.connect has been called on an incompatible object".into())
+}
+
+/// Native function definition for `XMLSocket.close`
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        if let Some(queue) = socket.outgoing_queue() {
+            let _ = queue.read().send(OutgoingSocketAction::Close);
+        }
+        // Like Flash, `connected` is false as soon as the movie closes the socket.
+        socket.close(activation.context.gc_context);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `XMLSocket.connected`
+pub fn get_connected<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        return Ok(socket.is_connected().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Native function definition for `XMLSocket.sendString`
+///
+/// Every message is terminated by a zero byte.
+pub fn send_string<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        check_writable(activation, socket)?;
+
+        let data = args.get_string(activation, 0)?;
+        let mut bytes = data.to_utf8_lossy().into_owned().into_bytes();
+        bytes.push(0);
+
+        send_action(activation, socket, OutgoingSocketAction::Write(bytes))?;
+        send_action(activation, socket, OutgoingSocketAction::Flush)?;
+    }
+
+    Ok(Value::Undefined)
+}
@@ -2,6 +2,7 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::object::Object;
+use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
 use crate::avm2::Error;
 use crate::avm2_stub_method;
@@ -37,9 +38,22 @@ pub fn allow_insecure_domain<'gc>(
 pub fn load_policy_file<'gc>(
     activation: &mut Activation<'_, 'gc>,
     _this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_method!(activation, "flash.system.Security", "loadPolicyFile");
+    let url = args.get_string(activation, 0)?;
+
+    if !activation
+        .context
+        .system
+        .add_socket_policy_file(&url.to_utf8_lossy())
+    {
+        avm2_stub_method!(
+            activation,
+            "flash.system.Security",
+            "loadPolicyFile",
+            "with non-xmlsocket URL"
+        );
+    }
     Ok(Value::Undefined)
 }
 
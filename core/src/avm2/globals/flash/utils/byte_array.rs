@@ -7,9 +7,6 @@ use crate::avm2::Error;
 use crate::string::AvmString;
 use encoding_rs::Encoding;
 use encoding_rs::UTF_8;
-use flash_lso::amf0::read::AMF0Decoder;
-use flash_lso::amf3::read::AMF3Decoder;
-use flash_lso::types::{AMFVersion, Element};
 
 /// Writes a single byte to the bytearray
 pub fn write_byte<'gc>(
@@ -702,28 +699,11 @@ pub fn read_object<'gc>(
         let bytes = bytearray
             .read_at(bytearray.bytes_available(), bytearray.position())
             .map_err(|e| e.to_avm(activation))?;
-        let (bytes_left, value) = match bytearray.object_encoding() {
-            ObjectEncoding::Amf0 => {
-                let mut decoder = AMF0Decoder::default();
-                let (extra, amf) = decoder
-                    .parse_single_element(bytes)
-                    .map_err(|_| "Error: Invalid object")?;
-                (
-                    extra.len(),
-                    crate::avm2::amf::deserialize_value(activation, &amf)?,
-                )
-            }
-            ObjectEncoding::Amf3 => {
-                let mut decoder = AMF3Decoder::default();
-                let (extra, amf) = decoder
-                    .parse_single_element(bytes)
-                    .map_err(|_| "Error: Invalid object")?;
-                (
-                    extra.len(),
-                    crate::avm2::amf::deserialize_value(activation, &amf)?,
-                )
-            }
-        };
+        let (bytes_left, value) = crate::avm2::amf::deserialize_from_bytes(
+            activation,
+            bytes,
+            bytearray.object_encoding(),
+        )?;
 
         bytearray.set_position(bytearray.len() - bytes_left);
         return Ok(value);
@@ -739,25 +719,10 @@ pub fn write_object<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
         let obj = args.get(0).cloned().unwrap_or(Value::Undefined);
-        let amf_version = match bytearray.object_encoding() {
-            ObjectEncoding::Amf0 => AMFVersion::AMF0,
-            ObjectEncoding::Amf3 => AMFVersion::AMF3,
-        };
-        if let Some(amf) = crate::avm2::amf::serialize_value(activation, obj, amf_version) {
-            let element = Element::new("", amf);
-            let mut lso = flash_lso::types::Lso::new(vec![element], "", amf_version);
-            let bytes = flash_lso::write::write_to_bytes(&mut lso)
-                .map_err(|_| "Failed to serialize object")?;
-            // This is kind of hacky: We need to strip out the header and any padding so that we only write
-            // the value. In the future, there should be a method to do this in the flash_lso crate.
-            let element_padding = match amf_version {
-                AMFVersion::AMF0 => 8,
-                AMFVersion::AMF3 => 7,
-            };
-            bytearray.write_bytes(
-                &bytes[flash_lso::write::header_length(&lso.header) + element_padding
-                    ..bytes.len() - 1],
-            )?;
+        let object_encoding = bytearray.object_encoding();
+        if let Some(bytes) = crate::avm2::amf::serialize_to_bytes(activation, obj, object_encoding)?
+        {
+            bytearray.write_bytes(&bytes)?;
         }
     }
 
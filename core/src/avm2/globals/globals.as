@@ -125,6 +125,7 @@ include "flash/display3D/textures/TextureBase.as"
 include "flash/display3D/textures/CubeTexture.as"
 include "flash/display3D/textures/Texture.as"
 include "flash/display3D/textures/RectangleTexture.as"
+include "flash/display3D/textures/VideoTexture.as"
 include "flash/display3D/VertexBuffer3D.as"
 
 include "flash/display/MovieClip.as"
@@ -268,6 +269,7 @@ include "flash/net/Responder.as"
 include "flash/net/SharedObject.as"
 include "flash/net/SharedObjectFlushStatus.as"
 include "flash/net/Socket.as"
+include "flash/net/SecureSocket.as"
 include "flash/net/URLLoader.as"
 include "flash/net/URLLoaderDataFormat.as"
 include "flash/net/URLRequest.as"
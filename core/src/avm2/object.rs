@@ -72,7 +72,9 @@ pub use crate::avm2::object::bytearray_object::{
     byte_array_allocator, ByteArrayObject, ByteArrayObjectWeak,
 };
 pub use crate::avm2::object::class_object::{ClassObject, ClassObjectWeak};
-pub use crate::avm2::object::context3d_object::{Context3DObject, Context3DObjectWeak};
+pub use crate::avm2::object::context3d_object::{
+    Context3DObject, Context3DObjectWeak, RenderTarget,
+};
 pub use crate::avm2::object::date_object::{date_allocator, DateObject, DateObjectWeak};
 pub use crate::avm2::object::dictionary_object::{
     dictionary_allocator, DictionaryObject, DictionaryObjectWeak,
@@ -110,8 +112,7 @@ pub use crate::avm2::object::shader_data_object::{
     shader_data_allocator, ShaderDataObject, ShaderDataObjectWeak,
 };
 pub use crate::avm2::object::socket_object::{
-    socket_allocator, GcOutgoingQueue, GcRecvQueue, OutgoingSocketAction, SocketObject,
-    SocketObjectWeak,
+    socket_allocator, GcOutgoingQueue, GcRecvQueue, SocketObject, SocketObjectWeak,
 };
 pub use crate::avm2::object::sound_object::{
     sound_allocator, QueuedPlay, SoundData, SoundObject, SoundObjectWeak,
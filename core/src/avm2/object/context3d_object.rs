@@ -2,23 +2,23 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::object::script_object::ScriptObjectData;
-use crate::avm2::object::{Object, ObjectPtr, TObject};
+use crate::avm2::object::{Object, ObjectPtr, TObject, WeakObject};
 use crate::avm2::value::Value;
 use crate::avm2::Error;
 use crate::avm2_stub_method;
-use crate::bitmap::bitmap_data::BitmapData;
+use crate::bitmap::bitmap_data::{BitmapData, BitmapDataWrapper};
 use crate::context::RenderContext;
 use gc_arena::barrier::unlock;
 use gc_arena::lock::RefLock;
 use gc_arena::{Collect, Gc, GcCell, GcWeak, Mutation};
 use ruffle_render::backend::{
     BufferUsage, Context3D, Context3DBlendFactor, Context3DCommand, Context3DCompareMode,
-    Context3DTextureFormat, Context3DTriangleFace, Context3DVertexBufferFormat, ProgramType,
-    Texture,
+    Context3DProfile, Context3DStencilAction, Context3DTextureFormat, Context3DTriangleFace,
+    Context3DVertexBufferFormat, ProgramType, Texture,
 };
-use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, PixelRegion};
 use ruffle_render::commands::CommandHandler;
-use std::cell::{Cell, Ref, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::rc::Rc;
 use swf::{Rectangle, Twips};
 
@@ -38,6 +38,7 @@ impl<'gc> Context3DObject<'gc> {
     pub fn from_context(
         activation: &mut Activation<'_, 'gc>,
         context: Box<dyn Context3D>,
+        profile: Context3DProfile,
     ) -> Result<Object<'gc>, Error<'gc>> {
         let class = activation.avm2().classes().context3d;
 
@@ -46,6 +47,12 @@ impl<'gc> Context3DObject<'gc> {
             Context3DData {
                 base: RefLock::new(ScriptObjectData::new(class)),
                 render_context: Cell::new(Some(context)),
+                back_buffer_size: Cell::new(None),
+                render_targets: Cell::new([None; 4]),
+                bound_textures: RefCell::new(Default::default()),
+                profile,
+                disposed: Cell::new(false),
+                resources: RefLock::new(Vec::new()),
             },
         ))
         .into();
@@ -56,6 +63,58 @@ impl<'gc> Context3DObject<'gc> {
         Ok(this)
     }
 
+    pub fn profile(&self) -> Context3DProfile {
+        self.0.profile
+    }
+
+    /// Whether `Context3D.dispose` was called, or the context was lost.
+    pub fn disposed(&self) -> bool {
+        self.0.disposed.get()
+    }
+
+    /// Keeps track of a buffer, texture or program created by this context,
+    /// so that it can be disposed along with it.
+    fn add_resource(&self, activation: &mut Activation<'_, 'gc>, resource: Object<'gc>) {
+        let mc = activation.context.gc_context;
+        let mut resources = unlock!(Gc::write(mc, self.0), Context3DData, resources).borrow_mut();
+        // Forget about the garbage collected ones before having to grow.
+        if resources.len() == resources.capacity() {
+            resources.retain(|resource| resource.upgrade(mc).is_some());
+        }
+        resources.push(resource.downgrade());
+    }
+
+    /// Disposes every buffer, texture and program created by this context, then the
+    /// context itself, which frees its back buffer.
+    pub fn dispose(&self, activation: &mut Activation<'_, 'gc>) {
+        if self.disposed() {
+            return;
+        }
+
+        let mc = activation.context.gc_context;
+        let resources = std::mem::take(
+            &mut *unlock!(Gc::write(mc, self.0), Context3DData, resources).borrow_mut(),
+        );
+        for resource in resources {
+            let Some(resource) = resource.upgrade(mc) else {
+                continue;
+            };
+            if let Some(buffer) = resource.as_index_buffer() {
+                buffer.dispose();
+            } else if let Some(buffer) = resource.as_vertex_buffer() {
+                buffer.dispose();
+            } else if let Some(texture) = resource.as_texture() {
+                texture.dispose();
+            } else if let Some(program) = resource.as_program_3d() {
+                program.dispose();
+            }
+        }
+
+        self.0.disposed.set(true);
+        self.0.bound_textures.take();
+        self.0.render_context.take();
+    }
+
     fn with_context_3d<R>(&self, f: impl FnOnce(&mut dyn Context3D) -> R) -> R {
         // Temporarily take ownership of the Context3D instance.
         let cell = &self.0.render_context;
@@ -74,6 +133,7 @@ impl<'gc> Context3DObject<'gc> {
         wants_best_resolution: bool,
         wants_best_resolution_on_browser_zoom: bool,
     ) {
+        self.0.back_buffer_size.set(Some((width, height)));
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::ConfigureBackBuffer {
                 width,
@@ -94,11 +154,14 @@ impl<'gc> Context3DObject<'gc> {
         let index_buffer = self
             .with_context_3d(|ctx| ctx.create_index_buffer(BufferUsage::StaticDraw, num_indices));
 
-        Ok(Value::Object(IndexBuffer3DObject::from_handle(
+        let index_buffer = IndexBuffer3DObject::from_handle(
             activation,
             *self,
             index_buffer,
-        )?))
+            num_indices as usize,
+        )?;
+        self.add_resource(activation, index_buffer);
+        Ok(index_buffer.into())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -123,9 +186,39 @@ impl<'gc> Context3DObject<'gc> {
             )
         })?;
 
-        Ok(Value::Object(TextureObject::from_handle(
-            activation, *self, texture, class,
-        )?))
+        let texture = TextureObject::from_handle(activation, *self, texture, format, class)?;
+        self.add_resource(activation, texture);
+        Ok(texture.into())
+    }
+
+    /// Creates a `VideoTexture`, which starts out empty until a video frame
+    /// is uploaded to it.
+    pub fn create_video_texture(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let texture = self.create_texture_handle(1, 1, Context3DTextureFormat::Bgra)?;
+
+        let class = activation.avm2().classes().videotexture;
+        let texture = TextureObject::from_handle(
+            activation,
+            *self,
+            texture,
+            Context3DTextureFormat::Bgra,
+            class,
+        )?;
+        self.add_resource(activation, texture);
+        Ok(texture.into())
+    }
+
+    /// Creates a texture in the backend, without an AVM2 object for it.
+    pub(crate) fn create_texture_handle(
+        &self,
+        width: u32,
+        height: u32,
+        format: Context3DTextureFormat,
+    ) -> Result<Rc<dyn Texture>, ruffle_render::error::Error> {
+        self.with_context_3d(|ctx| ctx.create_texture(width, height, format, false, 0))
     }
 
     pub fn create_vertex_buffer(
@@ -138,12 +231,15 @@ impl<'gc> Context3DObject<'gc> {
         let handle = self.with_context_3d(|ctx| {
             ctx.create_vertex_buffer(usage, num_vertices, data_32_per_vertex)
         });
-        Ok(Value::Object(VertexBuffer3DObject::from_handle(
+        let vertex_buffer = VertexBuffer3DObject::from_handle(
             activation,
             *self,
             handle,
+            num_vertices as usize,
             data_32_per_vertex,
-        )?))
+        )?;
+        self.add_resource(activation, vertex_buffer);
+        Ok(vertex_buffer.into())
     }
 
     pub fn upload_vertex_buffer_data(
@@ -179,6 +275,23 @@ impl<'gc> Context3DObject<'gc> {
         });
     }
 
+    pub(crate) fn dispose_index_buffer(&self, buffer: IndexBuffer3DObject<'gc>) {
+        let mut handle = buffer.handle();
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::DisposeIndexBuffer {
+                buffer: &mut *handle,
+            })
+        });
+    }
+
+    pub(crate) fn dispose_vertex_buffer(&self, buffer: VertexBuffer3DObject<'gc>) {
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::DisposeVertexBuffer {
+                buffer: buffer.handle(),
+            })
+        });
+    }
+
     pub fn set_vertex_buffer_at(
         &self,
         index: u32,
@@ -198,24 +311,28 @@ impl<'gc> Context3DObject<'gc> {
         &self,
         activation: &mut Activation<'_, 'gc>,
     ) -> Result<Value<'gc>, Error<'gc>> {
-        Ok(Value::Object(Program3DObject::from_context(
-            activation, *self,
-        )?))
+        let program = Program3DObject::from_context(activation, *self)?;
+        self.add_resource(activation, program);
+        Ok(program.into())
     }
 
+    /// Replaces the programs of `program`, failing if either of them is malformed.
     pub fn upload_shaders(
         &self,
         program: Program3DObject<'gc>,
         vertex_shader_agal: Vec<u8>,
         fragment_shader_agal: Vec<u8>,
-    ) {
+    ) -> Result<(), ruffle_render::error::Error> {
+        let mut error = None;
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::UploadShaders {
                 module: program.shader_module_handle(),
                 vertex_shader_agal,
                 fragment_shader_agal,
+                error: &mut error,
             })
         });
+        error.map_or(Ok(()), Err)
     }
 
     pub fn set_program(&self, program: Option<Program3DObject<'gc>>) {
@@ -277,33 +394,64 @@ impl<'gc> Context3DObject<'gc> {
         });
     }
 
+    /// Whether `target` can be rendered to by the given color output, along with the
+    /// textures that the other color outputs render to.
+    ///
+    /// They all have to share the same size and anti-aliasing, and the additional color
+    /// outputs can't be used while the first one renders to the back buffer.
+    pub fn can_render_to(&self, target: RenderTarget, color_output_index: u32) -> bool {
+        let targets = self.0.render_targets.get();
+        if color_output_index == 0 {
+            targets[1..].iter().flatten().all(|other| *other == target)
+        } else {
+            targets[0] == Some(target)
+        }
+    }
+
     pub fn set_render_to_texture(
         &self,
         texture: Rc<dyn Texture>,
         enable_depth_and_stencil: bool,
         anti_alias: u32,
         surface_selector: u32,
+        color_output_index: u32,
     ) {
+        let mut targets = self.0.render_targets.get();
+        targets[color_output_index as usize] = Some(RenderTarget {
+            width: texture.width(),
+            height: texture.height(),
+            anti_alias: anti_alias.max(1),
+        });
+        self.0.render_targets.set(targets);
+
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::SetRenderToTexture {
                 texture,
                 enable_depth_and_stencil,
                 anti_alias,
                 surface_selector,
+                color_output_index,
             })
         });
     }
 
     pub fn set_render_to_back_buffer(&self) {
+        self.0.render_targets.set([None; 4]);
         self.with_context_3d(|ctx| ctx.process_command(Context3DCommand::SetRenderToBackBuffer));
     }
 
     pub fn present(&self, activation: &mut Activation<'_, 'gc>) -> Result<(), Error<'gc>> {
+        // Presenting goes back to rendering to the back buffer.
+        self.0.render_targets.set([None; 4]);
         Ok(self.with_context_3d(|ctx| activation.context.renderer.context3d_present(ctx))?)
     }
 
     // Renders our finalized frame to the screen, as part of the Ruffle rendering process.
     pub fn render(&self, context: &mut RenderContext<'_, 'gc>) {
+        if self.disposed() {
+            return;
+        }
+
         self.with_context_3d(|context3d| {
             if context3d.should_render() {
                 let handle = context3d.bitmap_handle();
@@ -346,28 +494,50 @@ impl<'gc> Context3DObject<'gc> {
         source: GcCell<'gc, BitmapData<'gc>>,
         dest: Rc<dyn Texture>,
         layer: u32,
+        mip_level: u32,
     ) {
         let source = source.read();
+        let source = Bitmap::new(
+            source.width(),
+            source.height(),
+            BitmapFormat::Rgba,
+            source.pixels_rgba(),
+        );
+        self.copy_rgba_to_texture(source, dest, layer, mip_level);
+    }
+
+    pub(crate) fn copy_rgba_to_texture(
+        &self,
+        source: Bitmap,
+        dest: Rc<dyn Texture>,
+        layer: u32,
+        mip_level: u32,
+    ) {
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::CopyBitmapToTexture {
-                source: Bitmap::new(
-                    source.width(),
-                    source.height(),
-                    BitmapFormat::Rgba,
-                    source.pixels_rgba(),
-                ),
+                source,
                 dest,
                 layer,
+                mip_level,
             })
         });
     }
 
+    pub(crate) fn dispose_texture(&self, texture: Rc<dyn Texture>) {
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::DisposeTexture { texture })
+        });
+    }
+
     pub(crate) fn set_texture_at(
         &self,
         sampler: u32,
         texture: Option<Rc<dyn Texture>>,
         cube: bool,
     ) {
+        if let Some(bound) = self.0.bound_textures.borrow_mut().get_mut(sampler as usize) {
+            *bound = texture.clone().map(|texture| (texture, cube));
+        }
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::SetTextureAt {
                 sampler,
@@ -377,6 +547,29 @@ impl<'gc> Context3DObject<'gc> {
         });
     }
 
+    /// Binds `new` to every sampler that `old` is bound to, for a texture whose
+    /// handle was recreated.
+    pub(crate) fn rebind_texture(&self, old: &Rc<dyn Texture>, new: &Rc<dyn Texture>) {
+        let samplers: Vec<(u32, bool)> = self
+            .0
+            .bound_textures
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter_map(|(sampler, bound)| match bound {
+                Some((texture, cube))
+                    if Rc::as_ptr(texture).cast::<()>() == Rc::as_ptr(old).cast() =>
+                {
+                    Some((sampler as u32, *cube))
+                }
+                _ => None,
+            })
+            .collect();
+        for (sampler, cube) in samplers {
+            self.set_texture_at(sampler, Some(new.clone()), cube);
+        }
+    }
+
     pub(crate) fn set_color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool) {
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::SetColorMask {
@@ -397,6 +590,40 @@ impl<'gc> Context3DObject<'gc> {
         });
     }
 
+    pub(crate) fn set_stencil_actions(
+        &self,
+        triangle_face: Context3DTriangleFace,
+        compare_mode: Context3DCompareMode,
+        action_on_both_pass: Context3DStencilAction,
+        action_on_depth_fail: Context3DStencilAction,
+        action_on_depth_pass_stencil_fail: Context3DStencilAction,
+    ) {
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::SetStencilActions {
+                triangle_face,
+                compare_mode,
+                action_on_both_pass,
+                action_on_depth_fail,
+                action_on_depth_pass_stencil_fail,
+            })
+        });
+    }
+
+    pub(crate) fn set_stencil_reference_value(
+        &self,
+        reference_value: u32,
+        read_mask: u32,
+        write_mask: u32,
+    ) {
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::SetStencilReferenceValue {
+                reference_value,
+                read_mask,
+                write_mask,
+            })
+        });
+    }
+
     pub(crate) fn create_cube_texture(
         &self,
         size: u32,
@@ -417,9 +644,9 @@ impl<'gc> Context3DObject<'gc> {
 
         let class = activation.avm2().classes().cubetexture;
 
-        Ok(Value::Object(TextureObject::from_handle(
-            activation, *self, texture, class,
-        )?))
+        let texture = TextureObject::from_handle(activation, *self, texture, format, class)?;
+        self.add_resource(activation, texture);
+        Ok(texture.into())
     }
 
     pub(crate) fn set_sampler_state_at(
@@ -427,16 +654,72 @@ impl<'gc> Context3DObject<'gc> {
         sampler: u32,
         wrap: ruffle_render::backend::Context3DWrapMode,
         filter: ruffle_render::backend::Context3DTextureFilter,
+        mip_filter: ruffle_render::backend::Context3DMipFilter,
     ) {
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::SetSamplerStateAt {
                 sampler,
                 wrap,
                 filter,
+                mip_filter,
             })
         });
     }
 
+    /// Copies `source_rect` of the back buffer (or all of it) into `destination` at `dest_point`,
+    /// clipped to both of them.
+    pub(crate) fn draw_to_bitmap_data(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        destination: BitmapDataWrapper<'gc>,
+        source_rect: Option<(i32, i32, i32, i32)>,
+        dest_point: (i32, i32),
+    ) {
+        let Some((width, height)) = self.0.back_buffer_size.get() else {
+            return;
+        };
+        let (src_x, src_y, src_width, src_height) =
+            source_rect.unwrap_or((0, 0, width as i32, height as i32));
+
+        let mut source_region = PixelRegion::for_whole_size(width, height);
+        let mut dest_region =
+            PixelRegion::for_whole_size(destination.width(), destination.height());
+        dest_region.clamp_with_intersection(
+            dest_point,
+            (src_x, src_y),
+            (src_width, src_height),
+            &mut source_region,
+        );
+        if dest_region.width() == 0 || dest_region.height() == 0 {
+            return;
+        }
+
+        // Bring both sides of the BitmapData up to date, since only the copied
+        // area will be marked as modified on the GPU.
+        let destination = destination.sync();
+        let mut write = destination.write(activation.context.gc_context);
+        write.update_dirty_texture(activation.context.renderer);
+        let Some(handle) = write.bitmap_handle(activation.context.renderer) else {
+            // The renderer couldn't register the BitmapData (and has already
+            // logged why), so there's nothing to copy into.
+            return;
+        };
+
+        let mut sync_handle = None;
+        self.with_context_3d(|ctx| {
+            ctx.process_command(Context3DCommand::DrawToBitmapData {
+                destination: handle,
+                source_region,
+                dest_point: (dest_region.x_min, dest_region.y_min),
+                sync_handle: &mut sync_handle,
+            })
+        });
+
+        if let Some(sync_handle) = sync_handle {
+            write.set_gpu_dirty(sync_handle, dest_region);
+        }
+    }
+
     pub(crate) fn set_scissor_rectangle(&self, rect: Option<Rectangle<Twips>>) {
         self.with_context_3d(|ctx| {
             ctx.process_command(Context3DCommand::SetScissorRectangle { rect })
@@ -444,6 +727,14 @@ impl<'gc> Context3DObject<'gc> {
     }
 }
 
+/// What has to match between the textures that are rendered to at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderTarget {
+    pub width: u32,
+    pub height: u32,
+    pub anti_alias: u32,
+}
+
 #[derive(Collect)]
 #[collect(no_drop)]
 pub struct Context3DData<'gc> {
@@ -452,6 +743,27 @@ pub struct Context3DData<'gc> {
 
     #[collect(require_static)]
     render_context: Cell<Option<Box<dyn Context3D>>>,
+
+    /// The size of the back buffer, once it has been configured.
+    back_buffer_size: Cell<Option<(u32, u32)>>,
+
+    /// The textures rendered to by each color output, which are all `None`
+    /// while rendering to the back buffer.
+    render_targets: Cell<[Option<RenderTarget>; 4]>,
+
+    /// The texture bound to each sampler with `setTextureAt`, and whether it's a cube texture.
+    #[collect(require_static)]
+    bound_textures: RefCell<[Option<(Rc<dyn Texture>, bool)>; 8]>,
+
+    /// The profile that was requested with `Stage3D.requestContext3D`.
+    #[collect(require_static)]
+    profile: Context3DProfile,
+
+    /// Whether the context was disposed, after which `render_context` is gone.
+    disposed: Cell<bool>,
+
+    /// The buffers, textures and programs created by this context.
+    resources: RefLock<Vec<WeakObject<'gc>>>,
 }
 
 impl<'gc> TObject<'gc> for Context3DObject<'gc> {
@@ -485,23 +797,12 @@ impl std::fmt::Debug for Context3DObject<'_> {
 // This would ideally be placed closer to the actual usage, but
 // we don't have stub support in 'render' crates
 fn check_texture_stub(activation: &mut Activation<'_, '_>, format: Context3DTextureFormat) {
-    match format {
-        Context3DTextureFormat::BgrPacked => {
-            avm2_stub_method!(
-                activation,
-                "flash.display3D.Context3D",
-                "createTexture",
-                "with BgrPacked"
-            );
-        }
-        Context3DTextureFormat::Compressed => {
-            avm2_stub_method!(
-                activation,
-                "flash.display3D.Context3D",
-                "createTexture",
-                "with Compressed"
-            );
-        }
-        _ => {}
+    if let Context3DTextureFormat::BgrPacked = format {
+        avm2_stub_method!(
+            activation,
+            "flash.display3D.Context3D",
+            "createTexture",
+            "with BgrPacked"
+        );
     }
 }
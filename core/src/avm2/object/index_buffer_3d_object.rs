@@ -26,6 +26,7 @@ impl<'gc> IndexBuffer3DObject<'gc> {
         activation: &mut Activation<'_, 'gc>,
         context3d: Context3DObject<'gc>,
         handle: Box<dyn IndexBuffer>,
+        num_indices: usize,
     ) -> Result<Object<'gc>, Error<'gc>> {
         let class = activation.avm2().classes().indexbuffer3d;
 
@@ -36,6 +37,8 @@ impl<'gc> IndexBuffer3DObject<'gc> {
                 context3d,
                 handle: RefCell::new(handle),
                 count: Cell::new(0),
+                num_indices,
+                disposed: Cell::new(false),
             },
         ))
         .into();
@@ -54,6 +57,11 @@ impl<'gc> IndexBuffer3DObject<'gc> {
         self.0.count.set(val);
     }
 
+    /// The number of indices the buffer was created with.
+    pub fn num_indices(&self) -> usize {
+        self.0.num_indices
+    }
+
     pub fn handle(&self) -> RefMut<'_, dyn IndexBuffer> {
         RefMut::map(self.0.handle.borrow_mut(), |h| h.as_mut())
     }
@@ -61,6 +69,17 @@ impl<'gc> IndexBuffer3DObject<'gc> {
     pub fn context3d(&self) -> Context3DObject<'gc> {
         self.0.context3d
     }
+
+    pub fn disposed(&self) -> bool {
+        self.0.disposed.get()
+    }
+
+    /// Frees the memory of the buffer, which can't be used anymore afterwards.
+    pub fn dispose(&self) {
+        if !self.0.disposed.replace(true) {
+            self.0.context3d.dispose_index_buffer(*self);
+        }
+    }
 }
 
 #[derive(Collect)]
@@ -73,7 +92,12 @@ pub struct IndexBuffer3DObjectData<'gc> {
 
     count: Cell<usize>,
 
+    num_indices: usize,
+
     context3d: Context3DObject<'gc>,
+
+    /// Whether `dispose` was called on this buffer or on its context.
+    disposed: Cell<bool>,
 }
 
 impl<'gc> TObject<'gc> for IndexBuffer3DObject<'gc> {
@@ -9,7 +9,7 @@ use gc_arena::barrier::unlock;
 use gc_arena::lock::RefLock;
 use gc_arena::{Collect, Gc, GcWeak, Mutation};
 use ruffle_render::backend::ShaderModule;
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::rc::Rc;
 
 use super::Context3DObject;
@@ -36,6 +36,7 @@ impl<'gc> Program3DObject<'gc> {
                 base: RefLock::new(base),
                 context3d,
                 shader_module_handle: RefCell::new(None),
+                disposed: Cell::new(false),
             },
         ))
         .into();
@@ -53,6 +54,16 @@ impl<'gc> Program3DObject<'gc> {
     pub fn context3d(&self) -> Context3DObject<'gc> {
         self.0.context3d
     }
+
+    pub fn disposed(&self) -> bool {
+        self.0.disposed.get()
+    }
+
+    /// Drops the compiled programs, which can't be used anymore afterwards.
+    pub fn dispose(&self) {
+        self.0.disposed.set(true);
+        self.0.shader_module_handle.replace(None);
+    }
 }
 
 #[derive(Collect)]
@@ -64,6 +75,9 @@ pub struct Program3DObjectData<'gc> {
     context3d: Context3DObject<'gc>,
 
     shader_module_handle: RefCell<Option<Rc<dyn ShaderModule>>>,
+
+    /// Whether `dispose` was called on this program or on its context.
+    disposed: Cell<bool>,
 }
 
 impl<'gc> TObject<'gc> for Program3DObject<'gc> {
@@ -1,16 +1,25 @@
 //! Object representation for Socket objects
 
 use crate::avm2::activation::Activation;
+use crate::avm2::bytearray::{ByteArrayStorage, Endian, ObjectEncoding};
 use crate::avm2::object::script_object::ScriptObjectData;
 use crate::avm2::object::{ClassObject, Object, ObjectPtr, TObject};
 use crate::avm2::value::Value;
 use crate::avm2::Error;
+use crate::backend::navigator::{CertificateStatus, ChainBuildingCertificate};
+use crate::socket_stats::SocketSender;
 use core::fmt;
-use flume::{Receiver, Sender};
+use flume::Receiver;
 use gc_arena::{Collect, GcCell, GcWeakCell, MutationContext};
 use std::cell::{Ref, RefMut};
 use std::ops::Deref;
 
+/// How many bytes must have been read before they are dropped from the read buffer.
+///
+/// Dropping them moves the unread bytes to the front, so doing it on every poll
+/// would make many small reads from a large buffer quadratic.
+const READ_BUFFER_COMPACT_THRESHOLD: usize = 64 * 1024;
+
 /// A class instance allocator that allocates Socket objects.
 pub fn socket_allocator<'gc>(
     class: ClassObject<'gc>,
@@ -24,6 +33,10 @@ pub fn socket_allocator<'gc>(
             base,
             recv_queue: None,
             outgoing_queue: None,
+            read_buffer: ByteArrayStorage::new(),
+            write_buffer: ByteArrayStorage::new(),
+            chain_building_certificates: Vec::new(),
+            server_certificate_status: CertificateStatus::Unknown,
         },
     ))
     .into())
@@ -69,6 +82,126 @@ impl<'gc> SocketObject<'gc> {
     ) {
         self.0.write(mc).outgoing_queue = outgoing_queue;
     }
+
+    /// Whether the IO task has connected this socket and not yet hung up.
+    ///
+    /// The loader owns the sending half of the receive queue, and drops it as
+    /// soon as the connection is closed. Closing the socket from the movie
+    /// drops the outgoing queue instead.
+    pub fn is_connected(self) -> bool {
+        let read = self.0.read();
+        let recv_queue = read.recv_queue;
+        read.outgoing_queue.is_some()
+            && recv_queue.map_or(false, |queue| !queue.read().is_disconnected())
+    }
+
+    /// Forget the connection after the movie has closed the socket.
+    ///
+    /// Nothing can be read or written afterwards, and `connected` is false.
+    pub fn close(self, mc: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(mc);
+        write.recv_queue = None;
+        write.outgoing_queue = None;
+        write.read_buffer.clear();
+        write.write_buffer.clear();
+    }
+
+    /// Move every chunk that has arrived from the IO task into the read buffer,
+    /// dropping the bytes that have already been read once there are enough of them.
+    fn poll_recv_queue(self, mc: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(mc);
+        let Some(queue) = write.recv_queue else {
+            return;
+        };
+
+        let read_buffer = &mut write.read_buffer;
+        let position = read_buffer.position().min(read_buffer.len());
+        if position >= READ_BUFFER_COMPACT_THRESHOLD && position * 2 >= read_buffer.len() {
+            let remaining = read_buffer.len() - position;
+            read_buffer.bytes_mut().copy_within(position.., 0);
+            read_buffer.set_length(remaining);
+            read_buffer.set_position(0);
+        }
+
+        for chunk in queue.read().try_iter() {
+            let length = read_buffer.len();
+            read_buffer.set_length(length + chunk.len());
+            read_buffer.bytes_mut()[length..].copy_from_slice(&chunk);
+        }
+    }
+
+    /// The buffer of received bytes which have not been read yet.
+    ///
+    /// The buffer shares the socket's `endian` and `objectEncoding`, so the
+    /// `ByteArrayStorage` read methods can be used directly on it.
+    pub fn read_buffer(&self, mc: MutationContext<'gc, '_>) -> RefMut<ByteArrayStorage> {
+        self.poll_recv_queue(mc);
+        RefMut::map(self.0.write(mc), |write| &mut write.read_buffer)
+    }
+
+    /// The buffer of written bytes which have not been flushed yet.
+    pub fn write_buffer(&self, mc: MutationContext<'gc, '_>) -> RefMut<ByteArrayStorage> {
+        RefMut::map(self.0.write(mc), |write| &mut write.write_buffer)
+    }
+
+    pub fn bytes_available(&self, mc: MutationContext<'gc, '_>) -> usize {
+        self.read_buffer(mc).bytes_available()
+    }
+
+    pub fn bytes_pending(self) -> usize {
+        self.0.read().write_buffer.len()
+    }
+
+    pub fn endian(self) -> Endian {
+        self.0.read().read_buffer.endian()
+    }
+
+    pub fn set_endian(self, endian: Endian, mc: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(mc);
+        write.read_buffer.set_endian(endian);
+        write.write_buffer.set_endian(endian);
+    }
+
+    pub fn object_encoding(self) -> ObjectEncoding {
+        self.0.read().read_buffer.object_encoding()
+    }
+
+    pub fn set_object_encoding(
+        self,
+        object_encoding: ObjectEncoding,
+        mc: MutationContext<'gc, '_>,
+    ) {
+        let mut write = self.0.write(mc);
+        write.read_buffer.set_object_encoding(object_encoding);
+        write.write_buffer.set_object_encoding(object_encoding);
+    }
+
+    pub fn chain_building_certificates(self) -> Vec<ChainBuildingCertificate> {
+        self.0.read().chain_building_certificates.clone()
+    }
+
+    pub fn add_chain_building_certificate(
+        self,
+        certificate: ChainBuildingCertificate,
+        mc: MutationContext<'gc, '_>,
+    ) {
+        self.0
+            .write(mc)
+            .chain_building_certificates
+            .push(certificate);
+    }
+
+    pub fn server_certificate_status(self) -> CertificateStatus {
+        self.0.read().server_certificate_status
+    }
+
+    pub fn set_server_certificate_status(
+        self,
+        status: CertificateStatus,
+        mc: MutationContext<'gc, '_>,
+    ) {
+        self.0.write(mc).server_certificate_status = status;
+    }
 }
 
 #[derive(Collect)]
@@ -85,22 +218,16 @@ impl Deref for GcRecvQueue {
 
 #[derive(Collect)]
 #[collect(require_static)]
-pub struct GcOutgoingQueue(pub Sender<OutgoingSocketAction>);
+pub struct GcOutgoingQueue(pub SocketSender);
 
 impl Deref for GcOutgoingQueue {
-    type Target = Sender<OutgoingSocketAction>;
+    type Target = SocketSender;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-pub enum OutgoingSocketAction {
-    Write(Vec<u8>),
-    Flush,
-    Close,
-}
-
 #[derive(Clone, Collect)]
 #[collect(no_drop)]
 pub struct SocketObjectData<'gc> {
@@ -109,6 +236,20 @@ pub struct SocketObjectData<'gc> {
 
     recv_queue: Option<GcCell<'gc, GcRecvQueue>>,
     outgoing_queue: Option<GcCell<'gc, GcOutgoingQueue>>,
+
+    /// Bytes received from the IO task, waiting to be read by user code.
+    read_buffer: ByteArrayStorage,
+
+    /// Bytes written by user code, waiting for the next `flush`.
+    write_buffer: ByteArrayStorage,
+
+    /// Certificates added with `SecureSocket.addBinaryChainBuildingCertificate`.
+    #[collect(require_static)]
+    chain_building_certificates: Vec<ChainBuildingCertificate>,
+
+    /// The outcome of validating the server's certificate, for `SecureSocket`.
+    #[collect(require_static)]
+    server_certificate_status: CertificateStatus,
 }
 
 impl<'gc> TObject<'gc> for SocketObject<'gc> {
@@ -5,11 +5,13 @@ use crate::avm2::object::script_object::ScriptObjectData;
 use crate::avm2::object::{Object, ObjectPtr, TObject};
 use crate::avm2::value::Value;
 use crate::avm2::Error;
+use crate::streams::NetStream;
 use gc_arena::barrier::unlock;
-use gc_arena::lock::RefLock;
+use gc_arena::lock::{Lock, RefLock};
 use gc_arena::{Collect, Gc, GcWeak, Mutation};
-use ruffle_render::backend::Texture;
-use std::cell::{Ref, RefMut};
+use ruffle_render::backend::{Context3DTextureFormat, Texture};
+use ruffle_render::bitmap::Bitmap;
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::rc::Rc;
 
 use super::{ClassObject, Context3DObject};
@@ -27,6 +29,7 @@ impl<'gc> TextureObject<'gc> {
         activation: &mut Activation<'_, 'gc>,
         context3d: Context3DObject<'gc>,
         handle: Rc<dyn Texture>,
+        original_format: Context3DTextureFormat,
         class: ClassObject<'gc>,
     ) -> Result<Object<'gc>, Error<'gc>> {
         let mut this: Object<'gc> = TextureObject(Gc::new(
@@ -34,7 +37,10 @@ impl<'gc> TextureObject<'gc> {
             TextureObjectData {
                 base: RefLock::new(ScriptObjectData::new(class)),
                 context3d,
-                handle,
+                handle: RefCell::new(handle),
+                original_format,
+                disposed: Cell::new(false),
+                video_stream: Lock::new(None),
             },
         ))
         .into();
@@ -46,12 +52,74 @@ impl<'gc> TextureObject<'gc> {
     }
 
     pub fn handle(&self) -> Rc<dyn Texture> {
-        self.0.handle.clone()
+        self.0.handle.borrow().clone()
     }
 
     pub fn context3d(&self) -> Context3DObject<'gc> {
         self.0.context3d
     }
+
+    /// The format the texture was created with, before being mapped to a format
+    /// supported by the render backend.
+    pub fn original_format(&self) -> Context3DTextureFormat {
+        self.0.original_format
+    }
+
+    pub fn disposed(&self) -> bool {
+        self.0.disposed.get()
+    }
+
+    /// Frees the memory of the texture, which can't be used anymore afterwards.
+    pub fn dispose(&self) {
+        if !self.0.disposed.replace(true) {
+            self.0.context3d.dispose_texture(self.handle());
+        }
+    }
+
+    /// The `NetStream` whose frames are displayed by this `VideoTexture`.
+    pub fn video_stream(&self) -> Option<NetStream<'gc>> {
+        self.0.video_stream.get()
+    }
+
+    /// Makes this `VideoTexture` display the frames of `stream`, replacing any
+    /// previously attached stream.
+    pub fn attach_video_stream(&self, mc: &Mutation<'gc>, stream: Option<NetStream<'gc>>) {
+        if let Some(old_stream) = self.video_stream() {
+            old_stream.detach_video_texture(mc, *self);
+        }
+        if let Some(stream) = stream {
+            stream.attach_video_texture(mc, *self);
+        }
+        unlock!(Gc::write(mc, self.0), TextureObjectData, video_stream).set(stream);
+    }
+
+    /// Uploads a decoded video frame to this `VideoTexture`.
+    ///
+    /// The size of a video is only known once it starts playing, so the
+    /// texture is recreated whenever the size of the frames changes, and bound
+    /// in place of the old one.
+    pub fn upload_video_frame(&self, frame: Bitmap) -> Result<(), ruffle_render::error::Error> {
+        let context3d = self.0.context3d;
+        if self.disposed() || context3d.disposed() {
+            return Ok(());
+        }
+
+        let handle = self.handle();
+        let handle = if (handle.width(), handle.height()) == (frame.width(), frame.height()) {
+            handle
+        } else {
+            let new_handle = context3d.create_texture_handle(
+                frame.width(),
+                frame.height(),
+                self.original_format(),
+            )?;
+            *self.0.handle.borrow_mut() = new_handle.clone();
+            context3d.rebind_texture(&handle, &new_handle);
+            new_handle
+        };
+        context3d.copy_rgba_to_texture(frame.to_rgba(), handle, 0, 0);
+        Ok(())
+    }
 }
 
 #[derive(Collect)]
@@ -62,8 +130,18 @@ pub struct TextureObjectData<'gc> {
 
     context3d: Context3DObject<'gc>,
 
+    /// This is only replaced for a `VideoTexture`, whose size changes with its video.
     #[collect(require_static)]
-    handle: Rc<dyn Texture>,
+    handle: RefCell<Rc<dyn Texture>>,
+
+    #[collect(require_static)]
+    original_format: Context3DTextureFormat,
+
+    /// Whether `dispose` was called on this texture or on its context.
+    disposed: Cell<bool>,
+
+    /// The `NetStream` attached to a `VideoTexture`.
+    video_stream: Lock<Option<NetStream<'gc>>>,
 }
 
 impl<'gc> TObject<'gc> for TextureObject<'gc> {
@@ -9,7 +9,7 @@ use gc_arena::barrier::unlock;
 use gc_arena::lock::RefLock;
 use gc_arena::{Collect, Gc, GcWeak, Mutation};
 use ruffle_render::backend::VertexBuffer;
-use std::cell::{Ref, RefMut};
+use std::cell::{Cell, Ref, RefMut};
 use std::rc::Rc;
 
 use super::Context3DObject;
@@ -27,6 +27,7 @@ impl<'gc> VertexBuffer3DObject<'gc> {
         activation: &mut Activation<'_, 'gc>,
         context3d: Context3DObject<'gc>,
         handle: Rc<dyn VertexBuffer>,
+        num_vertices: usize,
         data32_per_vertex: u8,
     ) -> Result<Object<'gc>, Error<'gc>> {
         let class = activation.avm2().classes().vertexbuffer3d;
@@ -37,7 +38,9 @@ impl<'gc> VertexBuffer3DObject<'gc> {
                 base: RefLock::new(ScriptObjectData::new(class)),
                 context3d,
                 handle,
+                num_vertices,
                 data32_per_vertex,
+                disposed: Cell::new(false),
             },
         ))
         .into();
@@ -59,6 +62,22 @@ impl<'gc> VertexBuffer3DObject<'gc> {
     pub fn data32_per_vertex(&self) -> u8 {
         self.0.data32_per_vertex
     }
+
+    /// The number of vertices the buffer was created with.
+    pub fn num_vertices(&self) -> usize {
+        self.0.num_vertices
+    }
+
+    pub fn disposed(&self) -> bool {
+        self.0.disposed.get()
+    }
+
+    /// Frees the memory of the buffer, which can't be used anymore afterwards.
+    pub fn dispose(&self) {
+        if !self.0.disposed.replace(true) {
+            self.0.context3d.dispose_vertex_buffer(*self);
+        }
+    }
 }
 
 #[derive(Collect)]
@@ -72,10 +91,15 @@ pub struct VertexBuffer3DObjectData<'gc> {
     #[collect(require_static)]
     handle: Rc<dyn VertexBuffer>,
 
+    num_vertices: usize,
+
     /// The 'data32PerVertex' value that this object was created with.
     /// This is the number of 32-bit values associated with each vertex,
     /// and is at most 64
     data32_per_vertex: u8,
+
+    /// Whether `dispose` was called on this buffer or on its context.
+    disposed: Cell<bool>,
 }
 
 impl<'gc> TObject<'gc> for VertexBuffer3DObject<'gc> {
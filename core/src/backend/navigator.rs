@@ -2,6 +2,7 @@
 
 use crate::loader::Error;
 use crate::string::WStr;
+use flume::{Receiver, Sender};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -9,6 +10,7 @@ use std::fmt::Display;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::time::Duration;
 use swf::avm1::types::SendVarsMethod;
 use url::{ParseError, Url};
 
@@ -178,6 +180,88 @@ pub struct ErrorResponse {
     pub error: Error,
 }
 
+/// An action requested by a movie on one of its sockets.
+#[derive(Debug)]
+pub enum OutgoingSocketAction {
+    /// Queue bytes to be sent on the next flush.
+    Write(Vec<u8>),
+
+    /// Send every queued byte.
+    Flush,
+
+    /// Close the connection. No events should be reported afterwards.
+    Close,
+}
+
+/// An event reported by a `NavigatorBackend` about a socket it is driving.
+#[derive(Debug, Clone)]
+pub enum SocketEvent {
+    /// The connection was established.
+    Connected,
+
+    /// The connection could not be established.
+    ConnectFailed,
+
+    /// The connection was not established before the timeout elapsed.
+    ConnectTimedOut,
+
+    /// Bytes were received from the remote end.
+    Data(Vec<u8>),
+
+    /// Reading from or writing to an established connection failed.
+    IoError,
+
+    /// The connection was closed by the remote end, or broke down.
+    Closed,
+
+    /// The TLS handshake of a secure connection failed, because the server's
+    /// certificate couldn't be validated.
+    CertificateRejected(CertificateStatus),
+}
+
+/// The outcome of validating the certificate of a TLS server.
+///
+/// This is exposed to movies as `SecureSocket.serverCertificateStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateStatus {
+    Trusted,
+    Unknown,
+    Revoked,
+    Invalid,
+    InvalidChain,
+    UntrustedSigners,
+    PrincipalMismatch,
+    Expired,
+    NotYetValid,
+}
+
+impl CertificateStatus {
+    /// The matching `flash.security.CertificateStatus` constant.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Trusted => "trusted",
+            Self::Unknown => "unknown",
+            Self::Revoked => "revoked",
+            Self::Invalid => "invalid",
+            Self::InvalidChain => "invalidChain",
+            Self::UntrustedSigners => "untrustedSigners",
+            Self::PrincipalMismatch => "principalMismatch",
+            Self::Expired => "expired",
+            Self::NotYetValid => "notYetValid",
+        }
+    }
+}
+
+/// A DER-encoded certificate added by a movie to validate a TLS server with.
+#[derive(Debug, Clone)]
+pub struct ChainBuildingCertificate {
+    pub der: Vec<u8>,
+
+    /// Whether the certificate is a trusted root, as opposed to an
+    /// intermediate certificate that only helps building the chain.
+    pub trusted: bool,
+}
+
 /// Type alias for pinned, boxed, and owned futures that output a falliable
 /// result of type `Result<T, E>`.
 pub type OwnedFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'static>>;
@@ -233,10 +317,53 @@ pub trait NavigatorBackend {
     /// This seems highly limiting.
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>);
 
-    /// Spawn a future on the IO scheduler.
+    /// Create a future that completes once `duration` has passed.
+    ///
+    /// This is used to give up on network operations that have no deadline of
+    /// their own, such as waiting for a socket policy file.
+    fn sleep(&self, duration: Duration) -> OwnedFuture<(), Error>;
+
+    /// Open a TCP connection to `host:port` and drive it until it is closed.
     ///
-    /// This function will use Tokio's runtime on desktop.
-    fn spawn_io_future(&mut self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>);
+    /// The outcome of the connection attempt, any data received and the
+    /// eventual closing of the connection must be reported through `events`,
+    /// in that order. If the connection isn't established within `timeout`,
+    /// the attempt is abandoned and `SocketEvent::ConnectTimedOut` is reported.
+    ///
+    /// Actions requested by the movie arrive through `actions`. The connection
+    /// must be torn down once `OutgoingSocketAction::Close` is received or the
+    /// sending half of `actions` is dropped.
+    ///
+    /// How the connection is made is up to the backend: desktop uses a real
+    /// TCP socket, whereas web has to tunnel the traffic over a WebSocket.
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        actions: Receiver<OutgoingSocketAction>,
+        events: Sender<SocketEvent>,
+    );
+
+    /// Whether `connect_secure_socket` is able to make TLS connections.
+    fn supports_secure_sockets(&self) -> bool;
+
+    /// Open a TLS connection to `host:port` and drive it until it is closed.
+    ///
+    /// This works like `connect_socket`, except that the TLS handshake must
+    /// complete before `SocketEvent::Connected` is reported. The server's
+    /// certificate is validated against the system's trusted roots and the
+    /// `certificates` added by the movie; if that fails,
+    /// `SocketEvent::CertificateRejected` is reported instead.
+    fn connect_secure_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        certificates: Vec<ChainBuildingCertificate>,
+        actions: Receiver<OutgoingSocketAction>,
+        events: Sender<SocketEvent>,
+    );
 
     /// Handle any context specific pre-processing
     ///
@@ -261,6 +388,12 @@ impl NullExecutor {
     pub fn run(&mut self) {
         self.0.run();
     }
+
+    /// Run every task until none of them can make progress, without waiting
+    /// for tasks that are blocked on something outside of this executor.
+    pub fn run_until_stalled(&mut self) {
+        self.0.run_until_stalled();
+    }
 }
 
 impl Default for NullExecutor {
@@ -298,6 +431,8 @@ impl NullExecutor {
     }
 
     pub fn run(&mut self) {}
+
+    pub fn run_until_stalled(&mut self) {}
 }
 
 #[cfg(target_family = "wasm")]
@@ -369,11 +504,37 @@ impl NavigatorBackend for NullNavigatorBackend {
         self.spawner.spawn_local(future);
     }
 
-    fn spawn_io_future(&mut self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
-        self.spawner.spawn_local(Box::pin(async move {
-            future.await;
-            Ok(())
-        }));
+    fn sleep(&self, _duration: Duration) -> OwnedFuture<(), Error> {
+        // There is no clock to wait on, and no network to wait for.
+        Box::pin(std::future::pending())
+    }
+
+    fn connect_socket(
+        &mut self,
+        _host: String,
+        _port: u16,
+        _timeout: Duration,
+        _actions: Receiver<OutgoingSocketAction>,
+        events: Sender<SocketEvent>,
+    ) {
+        // There is no network to connect to.
+        let _ = events.send(SocketEvent::ConnectFailed);
+    }
+
+    fn supports_secure_sockets(&self) -> bool {
+        false
+    }
+
+    fn connect_secure_socket(
+        &mut self,
+        _host: String,
+        _port: u16,
+        _timeout: Duration,
+        _certificates: Vec<ChainBuildingCertificate>,
+        _actions: Receiver<OutgoingSocketAction>,
+        events: Sender<SocketEvent>,
+    ) {
+        let _ = events.send(SocketEvent::ConnectFailed);
     }
 
     fn pre_process_url(&self, url: Url) -> Url {
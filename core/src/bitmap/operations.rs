@@ -10,8 +10,9 @@ use crate::bitmap::turbulence::Turbulence;
 use crate::context::{RenderContext, UpdateContext};
 use crate::display_object::TDisplayObject;
 use gc_arena::MutationContext;
-use ruffle_render::bitmap::PixelRegion;
+use ruffle_render::bitmap::{Bitmap, BitmapFormat, PixelRegion};
 use ruffle_render::commands::{CommandHandler, CommandList};
+use ruffle_render::filters::software::{self, FilterImage};
 use ruffle_render::filters::Filter;
 use ruffle_render::matrix::Matrix;
 use ruffle_render::quality::StageQuality;
@@ -1159,6 +1160,11 @@ pub fn copy_pixels_with_alpha_source<'gc>(
     write.set_cpu_dirty(dirty_region);
 }
 
+/// Applies a filter, falling back to doing so in software if the renderer can't.
+///
+/// `map_bitmap` is the map of a `DisplacementMapFilter`, which the software filters need
+/// to be able to read.
+#[allow(clippy::too_many_arguments)]
 pub fn apply_filter<'gc>(
     context: &mut UpdateContext<'_, 'gc>,
     target: BitmapDataWrapper<'gc>,
@@ -1167,6 +1173,7 @@ pub fn apply_filter<'gc>(
     source_size: (u32, u32),
     dest_point: (u32, u32),
     filter: Filter,
+    map_bitmap: Option<BitmapDataWrapper<'gc>>,
 ) {
     if !context.renderer.is_filter_supported(&filter) {
         let mut source_region = PixelRegion::for_whole_size(source.width(), source.height());
@@ -1182,7 +1189,26 @@ pub fn apply_filter<'gc>(
             return;
         }
 
-        // Until we support these filters, treat this like a copy
+        let map = map_bitmap.map(|map| {
+            read_filter_image(map, PixelRegion::for_whole_size(map.width(), map.height()))
+        });
+        let source_image = read_filter_image(source, source_region);
+        if let Some(result) = software::apply_filter(&filter, &source_image, map.as_ref()) {
+            let target = target.sync();
+            let mut write = target.write(context.gc_context);
+            let opaque = !write.transparency();
+            let width = result.width;
+            for (index, [r, g, b, a]) in result.pixels.into_iter().enumerate() {
+                let x = dest_region.x_min + index as u32 % width;
+                let y = dest_region.y_min + index as u32 / width;
+                let alpha = if opaque { 255 } else { a };
+                write.set_pixel32_raw(x, y, Color::argb(alpha, r, g, b));
+            }
+            write.set_cpu_dirty(dest_region);
+            return;
+        }
+
+        // Filters that can't be applied in software are treated like a copy
         copy_on_cpu(
             context.gc_context,
             source,
@@ -1216,6 +1242,23 @@ pub fn apply_filter<'gc>(
     }
 }
 
+/// Reads an area of a BitmapData into an image that the software filters can work on.
+fn read_filter_image(bitmap: BitmapDataWrapper<'_>, region: PixelRegion) -> FilterImage {
+    let read = bitmap.read_area(region);
+    let mut pixels = Vec::with_capacity((region.width() * region.height()) as usize);
+    for y in region.y_min..region.y_max {
+        for x in region.x_min..region.x_max {
+            let color = read.get_pixel32_raw(x, y);
+            pixels.push([color.red(), color.green(), color.blue(), color.alpha()]);
+        }
+    }
+    FilterImage {
+        width: region.width(),
+        height: region.height(),
+        pixels,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn copy_on_cpu<'gc>(
     context: MutationContext<'gc, '_>,
@@ -1623,6 +1666,19 @@ pub fn get_pixels_as_byte_array<'gc>(
     height: i32,
 ) -> Result<ByteArrayStorage, Error<'gc>> {
     let mut result = ByteArrayStorage::new();
+    copy_pixels_to_byte_array(target, x, y, width, height, &mut result)?;
+    Ok(result)
+}
+
+/// Writes the unmultiplied ARGB pixels of a region into `bytearray`, at its current position.
+pub fn copy_pixels_to_byte_array<'gc>(
+    target: BitmapDataWrapper,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    bytearray: &mut ByteArrayStorage,
+) -> Result<(), Error<'gc>> {
     let mut region = PixelRegion::for_region_i32(x, y, width, height);
     region.clamp(target.width(), target.height());
 
@@ -1630,11 +1686,73 @@ pub fn get_pixels_as_byte_array<'gc>(
     for y in region.y_min..region.y_max {
         for x in region.x_min..region.x_max {
             let color = read.get_pixel32_raw(x, y);
-            result.write_unsigned_int(color.to_un_multiplied_alpha().into())?;
+            bytearray.write_unsigned_int(color.to_un_multiplied_alpha().into())?;
         }
     }
 
-    Ok(result)
+    Ok(())
+}
+
+/// Counts how many pixels of a region have each value, for each unmultiplied channel
+/// in red, green, blue, alpha order.
+pub fn histogram(
+    target: BitmapDataWrapper,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> [[u32; 256]; 4] {
+    let mut region = PixelRegion::for_region_i32(x, y, width, height);
+    region.clamp(target.width(), target.height());
+
+    let mut result = [[0; 256]; 4];
+    let read = target.read_area(region);
+    for y in region.y_min..region.y_max {
+        for x in region.x_min..region.x_max {
+            let color = read.get_pixel32_raw(x, y).to_un_multiplied_alpha();
+            result[0][usize::from(color.red())] += 1;
+            result[1][usize::from(color.green())] += 1;
+            result[2][usize::from(color.blue())] += 1;
+            result[3][usize::from(color.alpha())] += 1;
+        }
+    }
+
+    result
+}
+
+/// Copies a region of the bitmap out into a standalone `Bitmap`, such as to encode it as an image file.
+/// Transparent bitmaps produce premultiplied RGBA, opaque ones produce RGB.
+pub fn get_region_as_bitmap(
+    target: BitmapDataWrapper,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Bitmap {
+    let mut region = PixelRegion::for_region_i32(x, y, width, height);
+    region.clamp(target.width(), target.height());
+
+    let transparent = target.transparency();
+    let bytes_per_pixel = if transparent { 4 } else { 3 };
+    let mut data =
+        Vec::with_capacity((region.width() * region.height()) as usize * bytes_per_pixel);
+    let read = target.read_area(region);
+    for y in region.y_min..region.y_max {
+        for x in region.x_min..region.x_max {
+            let color = read.get_pixel32_raw(x, y);
+            data.extend_from_slice(&[color.red(), color.green(), color.blue()]);
+            if transparent {
+                data.push(color.alpha());
+            }
+        }
+    }
+
+    let format = if transparent {
+        BitmapFormat::Rgba
+    } else {
+        BitmapFormat::Rgb
+    };
+    Bitmap::new(region.width(), region.height(), format, data)
 }
 
 pub fn set_pixels_from_byte_array<'gc>(
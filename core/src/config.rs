@@ -53,3 +53,18 @@ pub enum NetworkingAccessMode {
     #[serde(rename = "none")]
     None,
 }
+
+/// Whether socket connections must be authorized by a socket policy file.
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SocketPolicyMode {
+    /// Sockets connect without requesting a policy file.
+    #[serde(rename = "ignore")]
+    Ignore,
+
+    /// Like Flash Player, a policy file permitting the connection is requested from the
+    /// master policy port (843), any locations registered with `Security.loadPolicyFile`,
+    /// and finally the target port itself. Trusted local content is exempt.
+    #[serde(rename = "enforce")]
+    Enforce,
+}
@@ -3,13 +3,17 @@ mod avm2;
 mod display_object;
 mod handle;
 mod movie;
+mod socket;
 
+use crate::avm2::globals::flash::display::stage_3d::lose_context3d;
+use crate::avm2::{Activation, TObject};
 use crate::context::{RenderContext, UpdateContext};
 use crate::debug_ui::avm1::Avm1ObjectWindow;
 use crate::debug_ui::avm2::Avm2ObjectWindow;
 use crate::debug_ui::display_object::{DisplayObjectSearchWindow, DisplayObjectWindow};
 use crate::debug_ui::handle::{AVM1ObjectHandle, AVM2ObjectHandle, DisplayObjectHandle};
 use crate::debug_ui::movie::{MovieListWindow, MovieWindow};
+use crate::debug_ui::socket::SocketListWindow;
 use crate::display_object::TDisplayObject;
 use crate::tag_utils::SwfMovie;
 use gc_arena::DynamicRootSet;
@@ -31,6 +35,7 @@ pub struct DebugUi {
     items_to_save: Vec<ItemToSave>,
     movie_list: Option<MovieListWindow>,
     display_object_search: Option<DisplayObjectSearchWindow>,
+    socket_list: Option<SocketListWindow>,
 }
 
 #[derive(Debug)]
@@ -44,6 +49,8 @@ pub enum Message {
     ShowKnownMovies,
     SaveFile(ItemToSave),
     SearchForDisplayObject,
+    ShowSockets,
+    LoseStage3DContexts,
 }
 
 impl DebugUi {
@@ -79,6 +86,12 @@ impl DebugUi {
             }
         }
 
+        if let Some(mut socket_list) = self.socket_list.take() {
+            if socket_list.show(egui_ctx, context, &mut messages) {
+                self.socket_list = Some(socket_list);
+            }
+        }
+
         if let Some(mut search) = self.display_object_search.take() {
             if search.show(egui_ctx, context, &mut messages, movie_offset) {
                 self.display_object_search = Some(search);
@@ -114,6 +127,12 @@ impl DebugUi {
                 Message::SearchForDisplayObject => {
                     self.display_object_search = Some(Default::default());
                 }
+                Message::ShowSockets => {
+                    self.socket_list = Some(Default::default());
+                }
+                Message::LoseStage3DContexts => {
+                    lose_stage3d_contexts(context);
+                }
             }
         }
     }
@@ -186,6 +205,20 @@ impl DebugUi {
     }
 }
 
+/// Simulates losing the graphics device, to test how a movie restores its Stage3D content.
+fn lose_stage3d_contexts(context: &mut UpdateContext) {
+    let stage3ds = context.stage.stage3ds().clone();
+    let mut activation = Activation::from_nothing(context.reborrow());
+    for stage3d in stage3ds {
+        let Some(stage3d) = stage3d.as_stage_3d() else {
+            continue;
+        };
+        if let Err(e) = lose_context3d(&mut activation, stage3d) {
+            tracing::error!("Error when recreating Context3D: {e:?}");
+        }
+    }
+}
+
 pub struct ItemToSave {
     pub suggested_name: String,
     pub data: Vec<u8>,
@@ -0,0 +1,166 @@
+use crate::avm1::NativeObject;
+use crate::context::UpdateContext;
+use crate::debug_ui::{ItemToSave, Message};
+use crate::loader::{Handle, SocketTarget};
+use crate::socket_stats::{hex_dump, SocketDirection, SocketState, SocketStats};
+use egui::{Grid, RichText, ScrollArea, Ui, Window};
+
+#[derive(Debug, Default)]
+pub struct SocketListWindow {
+    selected: Option<Handle>,
+    show_closed: bool,
+}
+
+impl SocketListWindow {
+    pub fn show(
+        &mut self,
+        egui_ctx: &egui::Context,
+        context: &mut UpdateContext,
+        messages: &mut Vec<Message>,
+    ) -> bool {
+        let mut keep_open = true;
+
+        Window::new("Sockets")
+            .open(&mut keep_open)
+            .show(egui_ctx, |ui| {
+                ui.checkbox(&mut self.show_closed, "Show Closed Sockets");
+
+                let sockets: Vec<_> = context
+                    .load_manager
+                    .sockets()
+                    .filter(|(_, _, stats)| {
+                        self.show_closed
+                            || matches!(
+                                stats.borrow().state(),
+                                SocketState::Connecting | SocketState::Connected
+                            )
+                    })
+                    .collect();
+
+                Grid::new("socket_list")
+                    .num_columns(8)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Address");
+                        ui.strong("Type");
+                        ui.strong("State");
+                        ui.strong("Sent");
+                        ui.strong("Received");
+                        ui.strong("Unread");
+                        ui.strong("Unflushed");
+                        ui.strong("Outgoing Queue");
+                        ui.end_row();
+
+                        for (handle, target, stats) in &sockets {
+                            let stats = stats.borrow();
+                            let (host, port) = stats.address();
+                            if ui
+                                .selectable_label(
+                                    self.selected == Some(*handle),
+                                    format!("{host}:{port}"),
+                                )
+                                .clicked()
+                            {
+                                self.selected = Some(*handle);
+                            }
+                            show_target(ui, *target);
+                            ui.label(stats.state().as_str());
+                            ui.label(format!("{} bytes", stats.bytes_sent()));
+                            ui.label(format!("{} bytes", stats.bytes_received()));
+                            show_queue_depths(ui, context, *target);
+                            ui.end_row();
+                        }
+                    });
+
+                let selected = sockets
+                    .iter()
+                    .find(|(handle, _, _)| self.selected == Some(*handle));
+                if let Some((_, _, stats)) = selected {
+                    ui.separator();
+                    show_packets(ui, &stats.borrow(), messages);
+                }
+            });
+        keep_open
+    }
+}
+
+fn show_target(ui: &mut Ui, target: SocketTarget) {
+    match target {
+        SocketTarget::Avm2(_) => ui.label("AVM2 Socket"),
+        SocketTarget::Avm2Secure(socket) => ui.label(format!(
+            "AVM2 SecureSocket ({})",
+            socket.server_certificate_status().as_str()
+        )),
+        SocketTarget::Avm2Xml(_) => ui.label("AVM2 XMLSocket"),
+        SocketTarget::Avm1Xml(_) => ui.label("AVM1 XMLSocket"),
+    };
+}
+
+/// Show how much data is waiting on either side of the socket, in the "Unread",
+/// "Unflushed" and "Outgoing Queue" columns.
+fn show_queue_depths(ui: &mut Ui, context: &mut UpdateContext, target: SocketTarget) {
+    match target {
+        SocketTarget::Avm2(socket) | SocketTarget::Avm2Secure(socket) => {
+            ui.label(format!(
+                "{} bytes",
+                socket.bytes_available(context.gc_context)
+            ));
+            ui.label(format!("{} bytes", socket.bytes_pending()));
+            show_outgoing_queue(ui, socket.outgoing_queue().map(|q| q.read().queue_depth()));
+        }
+        SocketTarget::Avm2Xml(socket) => {
+            // Received messages are dispatched right away.
+            ui.weak("-");
+            ui.weak("-");
+            show_outgoing_queue(ui, socket.outgoing_queue().map(|q| q.read().queue_depth()));
+        }
+        SocketTarget::Avm1Xml(object) => {
+            ui.weak("-");
+            ui.weak("-");
+            let queue_depth = match object.native() {
+                NativeObject::XmlSocket(xml_socket) => xml_socket.queue_depth(),
+                _ => None,
+            };
+            show_outgoing_queue(ui, queue_depth);
+        }
+    }
+}
+
+fn show_outgoing_queue(ui: &mut Ui, queue_depth: Option<usize>) {
+    match queue_depth {
+        Some(queue_depth) => ui.label(format!("{queue_depth} actions")),
+        None => ui.weak("(Closed)"),
+    };
+}
+
+fn show_packets(ui: &mut Ui, stats: &SocketStats, messages: &mut Vec<Message>) {
+    let (host, port) = stats.address();
+    ui.horizontal(|ui| {
+        ui.strong(format!("Recent packets of {host}:{port}"));
+        if ui.button("Save Session...").clicked() {
+            messages.push(Message::SaveFile(ItemToSave {
+                suggested_name: format!("{host}_{port}.txt"),
+                data: stats.session().into_bytes(),
+            }));
+        }
+    });
+
+    ScrollArea::vertical()
+        .max_height(400.0)
+        .stick_to_bottom(true)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for packet in stats.packets() {
+                let direction = match packet.direction {
+                    SocketDirection::Sent => "Sent",
+                    SocketDirection::Received => "Received",
+                };
+                ui.strong(format!(
+                    "#{} {direction}, {} bytes",
+                    packet.number,
+                    packet.data.len()
+                ));
+                ui.label(RichText::new(hex_dump(&packet.data)).monospace());
+            }
+        });
+}
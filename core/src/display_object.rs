@@ -13,6 +13,7 @@ use crate::vminterface::Instantiator;
 use bitflags::bitflags;
 use gc_arena::{Collect, MutationContext};
 use ruffle_macros::enum_trait_object;
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::transform::{Transform, TransformStack};
 use std::cell::{Ref, RefMut};
 use std::fmt::Debug;
@@ -99,6 +100,9 @@ pub struct BitmapCache {
 
     /// Whether we warned that this bitmap was too large to be cached
     warned_for_oversize: bool,
+
+    /// Whether we warned that this bitmap was skipped because of a 3D transform
+    warned_for_3d: bool,
 }
 
 impl BitmapCache {
@@ -168,6 +172,77 @@ impl BitmapCache {
     }
 }
 
+/// The parts of a display object's 3D transform that don't fit in its 2D matrix.
+///
+/// A display object only has these once ActionScript sets one of `z`, `rotationX`,
+/// `rotationY`, `rotationZ`, `scaleZ` or `transform.matrix3D`. The `x`, `y`, `rotationZ`,
+/// `scaleX` and `scaleY` components are still stored in the 2D matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform3D {
+    pub z: f64,
+    pub rotation_x: Degrees,
+    pub rotation_y: Degrees,
+    pub scale_z: f64,
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self {
+            z: 0.0,
+            rotation_x: Degrees::from(0.0),
+            rotation_y: Degrees::from(0.0),
+            scale_z: 1.0,
+        }
+    }
+}
+
+/// The perspective projection used to draw 3D display objects,
+/// as set by `Transform.perspectiveProjection`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PerspectiveProjection {
+    /// The field of view, in degrees.
+    pub field_of_view: f64,
+
+    /// The vanishing point, in the local space of the object that owns this projection.
+    pub center: Point<Twips>,
+}
+
+impl PerspectiveProjection {
+    /// The field of view Flash uses when nothing else has been set.
+    pub const DEFAULT_FIELD_OF_VIEW: f64 = 55.0;
+
+    /// The projection used by the root movie, centered on its stage.
+    pub fn default_for_movie(movie_size: (u32, u32)) -> Self {
+        Self {
+            field_of_view: Self::DEFAULT_FIELD_OF_VIEW,
+            center: Point::from_pixels(
+                f64::from(movie_size.0) / 2.0,
+                f64::from(movie_size.1) / 2.0,
+            ),
+        }
+    }
+
+    /// The distance between the viewer and the `z = 0` plane.
+    ///
+    /// Flash derives this from the field of view and the width of the movie.
+    pub fn focal_length(&self, movie_width: u32) -> f64 {
+        f64::from(movie_width) / 2.0 / (self.field_of_view.to_radians() / 2.0).tan()
+    }
+
+    pub fn to_matrix_3d(&self, movie_width: u32) -> Matrix3D {
+        let focal_length = self.focal_length(movie_width);
+        if focal_length > 0.0 && focal_length.is_finite() {
+            Matrix3D::perspective(
+                focal_length,
+                self.center.x.to_pixels(),
+                self.center.y.to_pixels(),
+            )
+        } else {
+            Matrix3D::IDENTITY
+        }
+    }
+}
+
 #[derive(Clone, Collect)]
 #[collect(no_drop)]
 pub struct DisplayObjectBase<'gc> {
@@ -237,6 +312,15 @@ pub struct DisplayObjectBase<'gc> {
     /// None means not cached, Some means cached.
     #[collect(require_static)]
     cache: Option<BitmapCache>,
+
+    /// The 3D part of this display object's transform, if it has one.
+    #[collect(require_static)]
+    transform_3d: Option<Transform3D>,
+
+    /// The perspective projection used for this object's 3D descendants.
+    /// If this is `None`, the projection is inherited from our parent.
+    #[collect(require_static)]
+    perspective_projection: Option<PerspectiveProjection>,
 }
 
 impl<'gc> Default for DisplayObjectBase<'gc> {
@@ -263,6 +347,8 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             scroll_rect: None,
             next_scroll_rect: Default::default(),
             cache: None,
+            transform_3d: None,
+            perspective_projection: None,
         }
     }
 }
@@ -455,6 +541,74 @@ impl<'gc> DisplayObjectBase<'gc> {
         matrix.d = (cos * value.unit()) as f32;
     }
 
+    fn transform_3d(&self) -> Option<Transform3D> {
+        self.transform_3d
+    }
+
+    fn set_transform_3d(&mut self, transform_3d: Option<Transform3D>) {
+        self.set_transformed_by_script(true);
+        self.transform_3d = transform_3d;
+    }
+
+    /// The full 3D matrix of this display object, combining the 3D transform with the 2D one.
+    /// Returns `None` if this object only has a 2D transform.
+    fn matrix_3d(&mut self) -> Option<Matrix3D> {
+        let transform_3d = self.transform_3d?;
+        self.cache_scale_rotation();
+        Some(Matrix3D::compose(
+            [
+                self.transform.matrix.tx.to_pixels(),
+                self.transform.matrix.ty.to_pixels(),
+                transform_3d.z,
+            ],
+            [
+                transform_3d.rotation_x.into_radians(),
+                transform_3d.rotation_y.into_radians(),
+                self.rotation.into_radians(),
+            ],
+            [
+                self.scale_x.unit(),
+                self.scale_y.unit(),
+                transform_3d.scale_z,
+            ],
+        ))
+    }
+
+    /// Sets the full 3D matrix of this display object.
+    /// The matrix is split up into its components, which are stored in the 2D and 3D transforms.
+    fn set_matrix_3d(&mut self, matrix_3d: &Matrix3D) {
+        self.set_transformed_by_script(true);
+        let (translation, rotation, scale) = matrix_3d.decompose();
+        self.rotation = Degrees::from_radians(rotation[2]);
+        self.scale_x = Percent::from_unit(scale[0]);
+        self.scale_y = Percent::from_unit(scale[1]);
+        self.skew = 0.0;
+
+        let (sin, cos) = rotation[2].sin_cos();
+        self.transform.matrix = Matrix {
+            a: (scale[0] * cos) as f32,
+            b: (scale[0] * sin) as f32,
+            c: (scale[1] * -sin) as f32,
+            d: (scale[1] * cos) as f32,
+            tx: Twips::from_pixels(translation[0]),
+            ty: Twips::from_pixels(translation[1]),
+        };
+        self.transform_3d = Some(Transform3D {
+            z: translation[2],
+            rotation_x: Degrees::from_radians(rotation[0]),
+            rotation_y: Degrees::from_radians(rotation[1]),
+            scale_z: scale[2],
+        });
+    }
+
+    fn perspective_projection(&self) -> Option<PerspectiveProjection> {
+        self.perspective_projection
+    }
+
+    fn set_perspective_projection(&mut self, projection: Option<PerspectiveProjection>) {
+        self.perspective_projection = projection;
+    }
+
     fn name(&self) -> AvmString<'gc> {
         self.name
     }
@@ -707,11 +861,70 @@ struct DrawCacheInfo {
     filters: Vec<Filter>,
 }
 
+/// Applies the nearest perspective projection above a display object to its 3D matrix.
+///
+/// The projection is done in the space of the ancestor that owns it: the nearest one with its
+/// own `perspectiveProjection`, or else the root movie. The result still transforms into the
+/// object's parent space, so it can be pushed onto a transform stack like any other matrix.
+fn apply_perspective_projection<'gc>(
+    this: DisplayObject<'gc>,
+    stage: Stage<'gc>,
+    matrix_3d: Matrix3D,
+) -> Matrix3D {
+    let mut owner = None;
+    let mut projection = None;
+    let mut node = this.parent();
+    while let Some(ancestor) = node {
+        if ancestor.as_stage().is_some() {
+            break;
+        }
+        owner = Some(ancestor);
+        projection = ancestor.perspective_projection();
+        if projection.is_some() {
+            break;
+        }
+        node = ancestor.parent();
+    }
+
+    let movie_size = stage.movie_size();
+    let projection = projection
+        .unwrap_or_else(|| PerspectiveProjection::default_for_movie(movie_size))
+        .to_matrix_3d(movie_size.0);
+
+    let to_owner = match (owner, this.parent()) {
+        (Some(owner), Some(parent)) => owner
+            .global_to_local_matrix()
+            .map(|matrix| matrix * parent.local_to_global_matrix()),
+        _ => Some(Matrix::IDENTITY),
+    };
+    let to_owner = to_owner.map(Matrix3D::from);
+    match to_owner.and_then(|to_owner| to_owner.inverse().map(|from_owner| (to_owner, from_owner)))
+    {
+        Some((to_owner, from_owner)) => from_owner * projection * to_owner * matrix_3d,
+        None => matrix_3d,
+    }
+}
+
 pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_, 'gc>) {
     if this.maskee().is_some() {
         return;
     }
-    context.transform_stack.push(this.base().transform());
+    if let Some(matrix_3d) = this.matrix_3d(context.gc_context) {
+        // Only the first 3D object in a branch is projected; any 3D descendants share its 3D space.
+        let matrix_3d = if context.transform_stack.transform().matrix3d.is_some() {
+            matrix_3d
+        } else {
+            apply_perspective_projection(this, context.stage, matrix_3d)
+        };
+        let base = this.base();
+        context.transform_stack.push(&Transform {
+            matrix: matrix_3d.to_affine(),
+            color_transform: *base.color_transform(),
+            matrix3d: Some(matrix_3d),
+        });
+    } else {
+        context.transform_stack.push(this.base().transform());
+    }
     let blend_mode = this.blend_mode();
     let original_commands = if blend_mode != BlendMode::Normal {
         Some(std::mem::take(&mut context.commands))
@@ -719,7 +932,12 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         None
     };
 
-    let cache_info = if context.use_bitmap_cache {
+    // Bitmap caches are drawn with a 2D transform, so they can't be used with a 3D one.
+    // Flash rasterises the object with its filters and then projects the result,
+    // which we don't support yet.
+    let cache_info = if context.use_bitmap_cache
+        && context.transform_stack.transform().matrix3d.is_none()
+    {
         let mut cache_info: Option<DrawCacheInfo> = None;
         let base_transform = context.transform_stack.transform();
         let bounds: Rectangle<Twips> = this.bounds_with_transform(&base_transform.matrix);
@@ -787,6 +1005,19 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         }
         cache_info
     } else {
+        if context.use_bitmap_cache {
+            let mut warn = false;
+            if let Some(cache) = this.base_mut(context.gc_context).bitmap_cache_mut() {
+                warn = !cache.warned_for_3d;
+                cache.warned_for_3d = true;
+            }
+            if warn {
+                tracing::warn!(
+                    "Skipping cacheAsBitmap and filters for object with a 3D transform at {:?}",
+                    this.path()
+                );
+            }
+        }
         None
     };
 
@@ -809,6 +1040,7 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
                     ty: -offset_y,
                     ..cache_info.base_transform.matrix
                 },
+                matrix3d: None,
             });
             let mut offscreen_context = RenderContext {
                 renderer: context.renderer,
@@ -840,6 +1072,7 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
                     ..Default::default()
                 },
                 color_transform: cache_info.base_transform.color_transform,
+                matrix3d: None,
             },
             true,
         );
@@ -892,6 +1125,7 @@ pub fn render_base_inner<'gc>(this: DisplayObject<'gc>, context: &mut RenderCont
         context.transform_stack.push(&Transform {
             matrix: Matrix::translate(-rect.x_min, -rect.y_min),
             color_transform: Default::default(),
+            matrix3d: None,
         });
     }
 
@@ -1223,6 +1457,152 @@ pub trait TDisplayObject<'gc>:
         }
     }
 
+    /// The 3D part of this display object's transform, if it has one.
+    fn transform_3d(&self) -> Option<Transform3D> {
+        self.base().transform_3d()
+    }
+
+    /// Sets the 3D part of this display object's transform.
+    /// Set by the `z`, `rotationX`, `rotationY`, `rotationZ` and `scaleZ` ActionScript properties.
+    /// This invalidates any ancestors cacheAsBitmap automatically.
+    fn set_transform_3d(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        transform_3d: Option<Transform3D>,
+    ) {
+        self.base_mut(gc_context).set_transform_3d(transform_3d);
+        if let Some(parent) = self.parent() {
+            parent.invalidate_cached_bitmap(gc_context);
+        }
+    }
+
+    /// The 3D matrix of this display object in local space,
+    /// or `None` if it only has a 2D transform.
+    /// Returned by the `transform.matrix3D` ActionScript property.
+    fn matrix_3d(&self, gc_context: MutationContext<'gc, '_>) -> Option<Matrix3D> {
+        // Avoid mutably borrowing 2D objects, as this is checked for every object we render.
+        self.transform_3d()?;
+        let matrix_3d = self.base_mut(gc_context).matrix_3d();
+        self.set_scale_rotation_cached(gc_context);
+        matrix_3d
+    }
+
+    /// Sets the 3D matrix of this display object in local space.
+    /// Setting it to `None` removes the 3D transform, leaving only the 2D one.
+    /// Set by the `transform.matrix3D` ActionScript property.
+    /// This invalidates any ancestors cacheAsBitmap automatically.
+    fn set_matrix_3d(&self, gc_context: MutationContext<'gc, '_>, matrix_3d: Option<&Matrix3D>) {
+        match matrix_3d {
+            Some(matrix_3d) => {
+                self.base_mut(gc_context).set_matrix_3d(matrix_3d);
+                self.set_scale_rotation_cached(gc_context);
+            }
+            None => self.base_mut(gc_context).set_transform_3d(None),
+        }
+        if let Some(parent) = self.parent() {
+            parent.invalidate_cached_bitmap(gc_context);
+        }
+    }
+
+    /// The perspective projection this display object applies to its 3D descendants,
+    /// if it has its own.
+    fn perspective_projection(&self) -> Option<PerspectiveProjection> {
+        self.base().perspective_projection()
+    }
+
+    /// Sets the perspective projection this display object applies to its 3D descendants.
+    /// Set by the `transform.perspectiveProjection` ActionScript property.
+    fn set_perspective_projection(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        projection: Option<PerspectiveProjection>,
+    ) {
+        self.base_mut(gc_context)
+            .set_perspective_projection(projection);
+        self.invalidate_cached_bitmap(gc_context);
+    }
+
+    /// Returns the matrix for transforming from this object's local 3D space to global stage space.
+    /// This includes the perspective projection, so transformed points must be divided by `w`.
+    fn local_to_global_matrix_3d(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        stage: Stage<'gc>,
+    ) -> Matrix3D {
+        let mut ancestors = vec![];
+        let mut node: Option<DisplayObject<'gc>> = Some((*self).into());
+        while let Some(display_object) = node {
+            // We want to transform to Stage-local coordinates,
+            // so do *not* apply the Stage's matrix
+            if display_object.as_stage().is_some() {
+                break;
+            }
+            ancestors.push(display_object);
+            node = display_object.parent();
+        }
+
+        // Build the matrix from the top down, the same way it's built when rendering:
+        // only the topmost 3D object is projected, and its 3D descendants share its space.
+        let mut matrix = Matrix3D::IDENTITY;
+        let mut is_3d = false;
+        for display_object in ancestors.into_iter().rev() {
+            if let Some(matrix_3d) = display_object.matrix_3d(gc_context) {
+                if is_3d {
+                    matrix *= matrix_3d;
+                } else {
+                    matrix *= apply_perspective_projection(display_object, stage, matrix_3d);
+                    is_3d = true;
+                }
+            } else {
+                matrix *= Matrix3D::from(*display_object.base().matrix());
+            }
+            if let Some(rect) = display_object.scroll_rect() {
+                matrix *= Matrix3D::from(Matrix::translate(-rect.x_min, -rect.y_min));
+            }
+        }
+        matrix
+    }
+
+    /// Converts a local 3D position, in pixels, to a global stage position.
+    /// Used by the `local3DToGlobal` ActionScript method.
+    fn local_3d_to_global(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        stage: Stage<'gc>,
+        local: (f64, f64, f64),
+    ) -> Point<Twips> {
+        let matrix = self.local_to_global_matrix_3d(gc_context, stage);
+        let [x, y, _, w] = matrix.transform_point(local.0, local.1, local.2);
+        Point::from_pixels(x / w, y / w)
+    }
+
+    /// Converts a global stage position to the local position on this object's `z = 0` plane
+    /// that is drawn there, in pixels.
+    /// Returns `None` if the plane is seen edge-on, so no single position is drawn there.
+    /// Used by the `globalToLocal3D` ActionScript method.
+    fn global_to_local_3d(
+        &self,
+        gc_context: MutationContext<'gc, '_>,
+        stage: Stage<'gc>,
+        global: Point<Twips>,
+    ) -> Option<(f64, f64)> {
+        let m = self.local_to_global_matrix_3d(gc_context, stage);
+        let (x, y) = (global.x.to_pixels(), global.y.to_pixels());
+
+        // Solve `m * (u, v, 0, 1) = w * (x, y, _, 1)` for `u` and `v`.
+        let a = m.get(0, 0) - x * m.get(3, 0);
+        let b = m.get(0, 1) - x * m.get(3, 1);
+        let c = m.get(1, 0) - y * m.get(3, 0);
+        let d = m.get(1, 1) - y * m.get(3, 1);
+        let e = x * m.get(3, 3) - m.get(0, 3);
+        let f = y * m.get(3, 3) - m.get(1, 3);
+        let determinant = a * d - b * c;
+        if determinant.abs() < f64::EPSILON {
+            return None;
+        }
+        Some(((e * d - b * f) / determinant, (a * f - e * c) / determinant))
+    }
+
     /// Gets the pixel width of the AABB containing this display object in local space.
     /// Returned by the ActionScript `_width`/`width` properties.
     fn width(&self) -> f64 {
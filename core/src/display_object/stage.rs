@@ -811,6 +811,7 @@ impl<'gc> TDisplayObject<'gc> for Stage<'gc> {
         context.transform_stack.push(&Transform {
             matrix: self.0.read().viewport_matrix,
             color_transform: Default::default(),
+            matrix3d: None,
         });
 
         // All of our Stage3D instances get rendered *underneath* the main stage.
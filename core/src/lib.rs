@@ -37,6 +37,8 @@ mod locale;
 pub mod pixel_bender;
 mod player;
 mod prelude;
+mod socket_policy;
+mod socket_stats;
 mod streams;
 pub mod string;
 pub mod tag_utils;
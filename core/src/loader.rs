@@ -1,21 +1,25 @@
 //! Management of async loaders
 
+use crate::avm1::globals::system::SandboxType;
 use crate::avm1::Avm1;
 use crate::avm1::ExecutionReason;
 use crate::avm1::{Activation, ActivationIdentifier};
-use crate::avm1::{Object, SoundObject, TObject, Value};
+use crate::avm1::{NativeObject, Object, SoundObject, TObject, Value};
 use crate::avm2::bytearray::ByteArrayStorage;
 use crate::avm2::object::{
     BitmapDataObject, ByteArrayObject, EventObject as Avm2EventObject, GcOutgoingQueue,
-    GcRecvQueue, LoaderStream, OutgoingSocketAction, SocketObject, TObject as _,
+    GcRecvQueue, LoaderStream, SocketObject, TObject as _,
 };
 use crate::avm2::{
     Activation as Avm2Activation, Avm2, Domain as Avm2Domain, EventObject, Object as Avm2Object,
     Value as Avm2Value,
 };
-use crate::backend::navigator::{OwnedFuture, Request};
+use crate::backend::navigator::{
+    CertificateStatus, OutgoingSocketAction, OwnedFuture, Request, SocketEvent,
+};
 use crate::bitmap::bitmap_data::Color;
 use crate::bitmap::bitmap_data::{BitmapData, BitmapDataWrapper};
+use crate::config::SocketPolicyMode;
 use crate::context::{ActionQueue, ActionType, UpdateContext};
 use crate::display_object::{
     DisplayObject, MovieClip, TDisplayObject, TDisplayObjectContainer, TInteractiveObject,
@@ -24,6 +28,10 @@ use crate::events::ClipEvent;
 use crate::frame_lifecycle::catchup_display_object_to_frame;
 use crate::limits::ExecutionLimit;
 use crate::player::Player;
+use crate::socket_policy::{
+    MetaPolicy, SocketPolicy, MASTER_POLICY_PORT, POLICY_FILE_REQUEST, POLICY_FILE_TIMEOUT,
+};
+use crate::socket_stats::{SharedSocketStats, SocketSender, SocketState, SocketStats};
 use crate::streams::NetStream;
 use crate::string::AvmString;
 use crate::tag_utils::SwfMovie;
@@ -33,13 +41,12 @@ use gc_arena::{Collect, GcCell};
 use generational_arena::{Arena, Index};
 use ruffle_render::utils::{determine_jpeg_tag_format, JpegTagFormat};
 use std::fmt;
+use std::future::Future;
 use std::sync::{Arc, Mutex, Weak};
+use std::task::Poll;
 use std::time::Duration;
 use swf::read::{extract_swz, read_compression_type};
 use thiserror::Error;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
-use tokio::select;
 use url::{form_urlencoded, ParseError, Url};
 
 pub type Handle = Index;
@@ -237,6 +244,16 @@ impl<'gc> LoadManager<'gc> {
         handle
     }
 
+    /// Every socket loader, with what the debug UI shows about its connection.
+    pub fn sockets(
+        &self,
+    ) -> impl Iterator<Item = (Handle, SocketTarget<'gc>, SharedSocketStats)> + '_ {
+        self.0.iter().filter_map(|(handle, loader)| match loader {
+            Loader::Socket { target, stats, .. } => Some((handle, *target, stats.clone())),
+            _ => None,
+        })
+    }
+
     /// Retrieve a loader by handle.
     pub fn get_loader(&self, handle: Handle) -> Option<&Loader<'gc>> {
         self.0.get(handle)
@@ -439,16 +456,18 @@ impl<'gc> LoadManager<'gc> {
     pub fn load_socket(
         &mut self,
         player: Weak<Mutex<Player>>,
-        target_socket: SocketObject<'gc>,
+        target: SocketTarget<'gc>,
         addr: (String, u16),
+        timeout: Duration,
     ) -> OwnedFuture<(), Error> {
         let loader = Loader::Socket {
             self_handle: None,
-            target_socket,
+            target,
+            stats: SocketStats::new(addr.clone()),
         };
         let handle = self.add_loader(loader);
         let loader = self.get_loader_mut(handle).unwrap();
-        loader.socket_loader(player, addr)
+        loader.socket_loader(player, addr, timeout)
     }
 
     /// Process tags on all loaders in the Parsing phase.
@@ -625,17 +644,38 @@ pub enum Loader<'gc> {
         target_clip: DisplayObject<'gc>,
     },
 
-    /// Loader that is attached to a Socket.
+    /// Loader that is attached to a Socket or XMLSocket.
     Socket {
         /// The handle to refer to this loader instance.
         #[collect(require_static)]
         self_handle: Option<Handle>,
 
-        /// The target Socket object.
-        target_socket: SocketObject<'gc>,
+        /// The object that receives the socket's data and events.
+        target: SocketTarget<'gc>,
+
+        /// What the debug UI shows about the connection.
+        #[collect(require_static)]
+        stats: SharedSocketStats,
     },
 }
 
+/// The object that a socket loader reports to.
+#[derive(Clone, Copy, Collect)]
+#[collect(no_drop)]
+pub enum SocketTarget<'gc> {
+    /// An AVM2 `Socket`, which receives raw bytes.
+    Avm2(SocketObject<'gc>),
+
+    /// An AVM2 `SecureSocket`, which receives raw bytes over TLS.
+    Avm2Secure(SocketObject<'gc>),
+
+    /// An AVM2 `XMLSocket`, which receives NUL-terminated messages.
+    Avm2Xml(SocketObject<'gc>),
+
+    /// An AVM1 `XMLSocket`, which receives NUL-terminated messages.
+    Avm1Xml(Object<'gc>),
+}
+
 impl<'gc> Loader<'gc> {
     /// Process tags on a loaded movie.
     ///
@@ -2106,15 +2146,35 @@ impl<'gc> Loader<'gc> {
         }
     }
 
+    /// Connect a Socket and dispatch its events until the connection is closed.
+    ///
+    /// The connection itself is driven by the `NavigatorBackend`; the attempt
+    /// is abandoned once `timeout` has elapsed.
     fn socket_loader(
         &mut self,
         player: Weak<Mutex<Player>>,
         addr: (String, u16),
+        timeout: Duration,
     ) -> OwnedFuture<(), Error> {
         tracing::debug!("started socket_loader");
 
-        let handle = match self {
-            Loader::Socket { self_handle, .. } => self_handle.expect("Loader not self-introduced"),
+        let (handle, certificates, stats) = match self {
+            Loader::Socket {
+                self_handle,
+                target,
+                stats,
+            } => {
+                // Secure sockets are validated with the certificates added before connecting.
+                let certificates = match target {
+                    SocketTarget::Avm2Secure(socket) => Some(socket.chain_building_certificates()),
+                    _ => None,
+                };
+                (
+                    self_handle.expect("Loader not self-introduced"),
+                    certificates,
+                    stats.clone(),
+                )
+            }
             _ => return Box::pin(async { Err(Error::NotSocketLoader) }),
         };
 
@@ -2122,194 +2182,549 @@ impl<'gc> Loader<'gc> {
             .upgrade()
             .expect("Could not upgrade weak reference to player");
 
-        let (connect_tx, connect_rx) = flume::bounded(0);
         let (outgoing_tx, outgoing_rx) = flume::unbounded::<OutgoingSocketAction>();
         let (recv_tx, recv_rx) = flume::unbounded::<Vec<u8>>();
-        let (recv_event_tx, recv_event_rx) = flume::unbounded();
+        let (event_tx, event_rx) = flume::unbounded::<SocketEvent>();
 
         Box::pin(async move {
-            tracing::debug!("started read future");
+            let (host, port) = addr.clone();
+
+            let mut player_lock = player.lock().unwrap();
+            let enforce_policy = player_lock.socket_policy_mode() == SocketPolicyMode::Enforce;
+            let (enforce_policy, domain, policy_ports) = player_lock.update(|uc| {
+                let trusted = matches!(uc.system.sandbox_type, SandboxType::LocalTrusted);
+                let domain = Url::parse(uc.swf.url())
+                    .ok()
+                    .filter(|url| matches!(url.scheme(), "http" | "https"))
+                    .and_then(|url| url.host_str().map(str::to_owned));
+                let policy_ports: Vec<u16> = uc
+                    .system
+                    .socket_policy_files
+                    .iter()
+                    .filter(|(policy_host, _)| policy_host.eq_ignore_ascii_case(&host))
+                    .map(|(_, policy_port)| *policy_port)
+                    .collect();
+                (enforce_policy && !trusted, domain, policy_ports)
+            });
+            drop(player_lock);
+
+            let mut connection = SocketConnection {
+                addr,
+                policy_checked: enforce_policy,
+                recv_tx: Some(recv_tx),
+                recv_rx: Some(recv_rx),
+                outgoing_tx: Some(SocketSender::new(outgoing_tx, stats.clone())),
+                stats,
+                closed: false,
+                partial_message: Vec::new(),
+            };
 
-            // It should be possible to get rid of the lock if we pass `&mut NavigatorBackend`
-            // to this method, but "cannot borrow `...` as mutable more than once at a time".
-            player.lock().unwrap().update(|uc| {
-                tracing::debug!("locked player to start IO future");
-
-                let activation = Avm2Activation::from_nothing(uc.reborrow());
-                activation.context.navigator.spawn_io_future(Box::pin(async move {
-                    tracing::debug!("started IO future");
-
-                    tracing::debug!("TcpStream::connect to {:?}", addr);
-                    let mut socket = TcpStream::connect(addr).await.unwrap();
-
-                    tracing::debug!("connect_tx.send_async enter");
-                    connect_tx.send_async(()).await.unwrap();
-                    tracing::debug!("connect_tx.send_async exit");
-
-                    let mut send_buffer = Vec::new();
-                    let mut buffer = Vec::new();
-                    buffer.resize(1024, 0);
-                    loop {
-                        tracing::debug!("waiting for IO...");
-                        select! {
-                            length = socket.read(&mut buffer) => {
-                                let length = length.unwrap();
-                                tracing::debug!("read {} bytes into buffer", length);
-                                recv_event_tx.send_async(length).await.unwrap();
-                                if length == 0 {
-                                    break;
-                                }
-
-                                recv_tx.send_async(buffer[..length].to_vec()).await.unwrap();
-                            }
-
-                            action = outgoing_rx.recv_async() => {
-                                let action = action.unwrap();
-                                match action {
-                                    OutgoingSocketAction::Write(buffer) => {
-                                        tracing::debug!("write {} bytes into socket (buffered)", buffer.len());
-                                        send_buffer.extend(&buffer);
-                                    }
-
-                                    OutgoingSocketAction::Flush => {
-                                        tracing::debug!("flush socket");
-                                        socket.write_all(&send_buffer).await.unwrap();
-                                        send_buffer.clear();
-                                        socket.flush().await.unwrap();
-                                    }
-
-                                    OutgoingSocketAction::Close => {
-                                        tracing::debug!("close socket");
-                                        socket.shutdown().await.unwrap();
-                                        break;
-                                    }
-                                };
-                            }
-                        }
-                    }
+            if enforce_policy
+                && !socket_policy_permits(
+                    &player,
+                    &host,
+                    port,
+                    domain.as_deref(),
+                    &policy_ports,
+                    timeout,
+                )
+                .await
+            {
+                tracing::warn!(
+                    "no socket policy file permits connecting to {}:{}",
+                    host,
+                    port
+                );
+                return player.lock().unwrap().update(|uc| {
+                    let target = match uc.load_manager.get_loader(handle) {
+                        Some(&Loader::Socket { target, .. }) => target,
+                        _ => return Err(Error::Cancelled),
+                    };
+                    connection.handle_policy_denied(uc, target)
+                });
+            }
 
-                    tracing::debug!("IO loop exited");
-                }));
+            player.lock().unwrap().update(|uc| {
+                tracing::debug!("connecting socket to {}:{}", host, port);
+                match certificates {
+                    Some(certificates) => uc.navigator.connect_secure_socket(
+                        host,
+                        port,
+                        timeout,
+                        certificates,
+                        outgoing_rx,
+                        event_tx,
+                    ),
+                    None => uc
+                        .navigator
+                        .connect_socket(host, port, timeout, outgoing_rx, event_tx),
+                }
             });
 
-            connect_rx.recv_async().await.unwrap();
-            player.lock().unwrap().update(|uc| {
-                tracing::debug!("lock player to initialize socket object");
+            while let Ok(event) = event_rx.recv_async().await {
+                player.lock().unwrap().update(|uc| {
+                    tracing::debug!("locked player to handle socket event {:?}", event);
 
-                let loader = uc.load_manager.get_loader(handle);
-                let target = match loader {
-                    Some(&Loader::Socket { target_socket, .. }) => target_socket,
-                    // We would have already returned after the previous 'update' call
-                    _ => unreachable!(),
-                };
+                    let target = match uc.load_manager.get_loader(handle) {
+                        Some(&Loader::Socket { target, .. }) => target,
+                        // We would have already returned after the previous 'update' call
+                        _ => unreachable!(),
+                    };
+                    connection.handle_event(uc, target, event)
+                })?;
+            }
 
-                let mut activation = Avm2Activation::from_nothing(uc.reborrow());
+            tracing::debug!("socket connection finished");
 
-                let gc_context = activation.context.gc_context;
+            Ok(())
+        })
+    }
+}
 
-                target.set_recv_queue(
-                    Some(GcCell::new(gc_context, GcRecvQueue(recv_rx))),
-                    gc_context,
-                );
-                tracing::debug!(
-                    "Socket.connect: set recv queue in native object {:?}",
-                    target
-                );
+/// The state that a socket loader keeps for its connection between events.
+struct SocketConnection {
+    /// The host and port being connected to.
+    addr: (String, u16),
 
-                target.set_outgoing_queue(
-                    Some(GcCell::new(gc_context, GcOutgoingQueue(outgoing_tx))),
-                    gc_context,
-                );
-                tracing::debug!(
-                    "Socket.connect: set outgoing queue in native object {:?}",
-                    target
-                );
+    /// Whether a socket policy file had to permit the connection.
+    policy_checked: bool,
 
-                let value = target.value_of(activation.context.gc_context).unwrap();
-                if let Avm2Value::Object(object) = value {
-                    let event = EventObject::bare_default_event(&mut activation.context, "connect");
-                    Avm2::dispatch_event(&mut activation.context, event, object);
-                    tracing::debug!("Socket.connect: dispatch CONNECT event");
-                }
-            });
+    /// The sending half of the receive queue, dropped once the connection is closed.
+    recv_tx: Option<flume::Sender<Vec<u8>>>,
+
+    /// The queues handed over to the target once connected.
+    recv_rx: Option<flume::Receiver<Vec<u8>>>,
+    outgoing_tx: Option<SocketSender>,
 
-            loop {
-                let length = recv_event_rx.recv_async().await.unwrap();
-                if length == 0 {
-                    tracing::debug!("got 0 bytes");
-                    break;
+    /// What the debug UI shows about the connection.
+    stats: SharedSocketStats,
+
+    /// Whether the target has already been told that the connection was closed.
+    closed: bool,
+
+    /// The bytes of an XMLSocket message whose NUL terminator hasn't arrived yet.
+    partial_message: Vec<u8>,
+}
+
+impl SocketConnection {
+    fn handle_event<'gc>(
+        &mut self,
+        uc: &mut UpdateContext<'_, 'gc>,
+        target: SocketTarget<'gc>,
+        event: SocketEvent,
+    ) -> Result<(), Error> {
+        self.stats.borrow_mut().record_event(&event);
+
+        match target {
+            SocketTarget::Avm2(socket) => self.handle_avm2_event(uc, socket, false, event),
+            SocketTarget::Avm2Secure(socket) => {
+                // Connecting at all means that the server's certificate was validated.
+                if matches!(event, SocketEvent::Connected) {
+                    socket.set_server_certificate_status(CertificateStatus::Trusted, uc.gc_context);
                 }
+                self.handle_avm2_event(uc, socket, false, event)
+            }
+            SocketTarget::Avm2Xml(socket) => self.handle_avm2_event(uc, socket, true, event),
+            SocketTarget::Avm1Xml(object) => self.handle_avm1_event(uc, object, event),
+        }
+    }
 
-                player.lock().unwrap().update(|uc| {
-                    tracing::debug!("locked player to send ProgressEvent.SOCKET_DATA event");
+    /// Report that no socket policy file permits the connection.
+    fn handle_policy_denied<'gc>(
+        &mut self,
+        uc: &mut UpdateContext<'_, 'gc>,
+        target: SocketTarget<'gc>,
+    ) -> Result<(), Error> {
+        self.stats.borrow_mut().set_state(SocketState::Failed);
 
-                    let loader = uc.load_manager.get_loader(handle);
-                    let target = match loader {
-                        Some(&Loader::Socket { target_socket, .. }) => target_socket,
-                        // We would have already returned after the previous 'update' call
-                        _ => unreachable!(),
-                    };
+        match target {
+            SocketTarget::Avm2(socket)
+            | SocketTarget::Avm2Secure(socket)
+            | SocketTarget::Avm2Xml(socket) => {
+                let mut activation = Avm2Activation::from_nothing(uc.reborrow());
+                let event = socket_security_error_event(&mut activation, &self.addr)?;
+                Avm2::dispatch_event(&mut activation.context, event, socket.into());
+                Ok(())
+            }
+            // AVM1 has no security errors; the connection simply fails.
+            SocketTarget::Avm1Xml(_) => self.handle_event(uc, target, SocketEvent::ConnectFailed),
+        }
+    }
 
-                    let mut activation = Avm2Activation::from_nothing(uc.reborrow());
+    /// Split received XMLSocket data into its complete, NUL-terminated messages.
+    fn split_messages(&mut self, data: Vec<u8>) -> Vec<Vec<u8>> {
+        let mut messages = Vec::new();
+        for byte in data {
+            if byte == 0 {
+                messages.push(std::mem::take(&mut self.partial_message));
+            } else {
+                self.partial_message.push(byte);
+            }
+        }
+        messages
+    }
 
-                    let event = activation
+    fn handle_avm2_event<'gc>(
+        &mut self,
+        uc: &mut UpdateContext<'_, 'gc>,
+        target: SocketObject<'gc>,
+        is_xml: bool,
+        event: SocketEvent,
+    ) -> Result<(), Error> {
+        let mut activation = Avm2Activation::from_nothing(uc.reborrow());
+
+        let connect_failed = matches!(event, SocketEvent::ConnectFailed);
+        let event = match event {
+            SocketEvent::Connected => {
+                let gc_context = activation.context.gc_context;
+                if let (Some(recv_rx), Some(outgoing_tx)) =
+                    (self.recv_rx.take(), self.outgoing_tx.take())
+                {
+                    target.set_recv_queue(
+                        Some(GcCell::new(gc_context, GcRecvQueue(recv_rx))),
+                        gc_context,
+                    );
+                    target.set_outgoing_queue(
+                        Some(GcCell::new(gc_context, GcOutgoingQueue(outgoing_tx))),
+                        gc_context,
+                    );
+                }
+
+                EventObject::bare_default_event(&mut activation.context, "connect")
+            }
+            SocketEvent::Data(data) if is_xml => {
+                for message in self.split_messages(data) {
+                    let message = AvmString::new_utf8(
+                        activation.context.gc_context,
+                        UTF_8.decode(&message).0,
+                    );
+                    let data_evt = activation
                         .avm2()
                         .classes()
-                        .progressevent
+                        .dataevent
                         .construct(
                             &mut activation,
-                            &[
-                                "socketData".into(),
-                                false.into(),
-                                false.into(),
-                                length.into(),
-                                0.into(),
-                            ],
+                            &["data".into(), false.into(), false.into(), message.into()],
                         )
-                        .map_err(|e| Error::Avm2Error(e.to_string()))
-                        .unwrap();
-
-                    let value = target.value_of(activation.context.gc_context).unwrap();
-                    if let Avm2Value::Object(object) = value {
-                        Avm2::dispatch_event(&mut activation.context, event, object);
-                        tracing::debug!("dispatched ProgressEvent.SOCKET_DATA event");
-                    }
-                });
+                        .map_err(|e| Error::Avm2Error(e.to_string()))?;
+                    Avm2::dispatch_event(&mut activation.context, data_evt, target.into());
+                }
+                return Ok(());
             }
+            SocketEvent::Data(data) => {
+                let length = data.len();
+                // The data must be queued before the event is dispatched, so that
+                // `bytesAvailable` already includes it in the `socketData` handler.
+                if let Some(recv_tx) = &self.recv_tx {
+                    let _ = recv_tx.send(data);
+                }
 
-            tracing::debug!("socket end of file");
-
-            player.lock().unwrap().update(|uc| {
-                tracing::debug!("locked player to send Event.CLOSE event");
-
-                let loader = uc.load_manager.get_loader(handle);
-                let target = match loader {
-                    Some(&Loader::Socket { target_socket, .. }) => target_socket,
-                    // We would have already returned after the previous 'update' call
-                    _ => unreachable!(),
-                };
-
-                let mut activation = Avm2Activation::from_nothing(uc.reborrow());
-
-                let event = activation
+                activation
                     .avm2()
                     .classes()
                     .progressevent
                     .construct(
                         &mut activation,
-                        &["close".into(), false.into(), false.into()],
+                        &[
+                            "socketData".into(),
+                            false.into(),
+                            false.into(),
+                            length.into(),
+                            0.into(),
+                        ],
                     )
-                    .map_err(|e| Error::Avm2Error(e.to_string()))
-                    .unwrap();
+                    .map_err(|e| Error::Avm2Error(e.to_string()))?
+            }
+            SocketEvent::ConnectFailed | SocketEvent::IoError => {
+                let io_error_evt = socket_io_error_event(&mut activation, &self.addr)?;
+                Avm2::dispatch_event(&mut activation.context, io_error_evt, target.into());
+
+                // Flash tries to fetch a policy file before connecting, which fails
+                // along with the connection itself. Only trusted content is exempt,
+                // and there's nothing more to report if a policy file was found.
+                if !connect_failed
+                    || self.policy_checked
+                    || matches!(
+                        activation.context.system.sandbox_type,
+                        SandboxType::LocalTrusted
+                    )
+                {
+                    return Ok(());
+                }
 
-                let value = target.value_of(activation.context.gc_context).unwrap();
-                if let Avm2Value::Object(object) = value {
-                    Avm2::dispatch_event(&mut activation.context, event, object);
-                    tracing::debug!("dispatched Event.CLOSE event");
+                socket_security_error_event(&mut activation, &self.addr)?
+            }
+            SocketEvent::ConnectTimedOut => {
+                // Flash reports a timeout as a failure to fetch the policy file,
+                // unless the content is trusted or a policy file was found.
+                if self.policy_checked
+                    || matches!(
+                        activation.context.system.sandbox_type,
+                        SandboxType::LocalTrusted
+                    )
+                {
+                    socket_io_error_event(&mut activation, &self.addr)?
+                } else {
+                    socket_security_error_event(&mut activation, &self.addr)?
                 }
-            });
+            }
+            SocketEvent::Closed => {
+                if self.closed {
+                    return Ok(());
+                }
+                self.closed = true;
 
-            Ok(())
-        })
+                // `connected` must already be false in the `close` handler.
+                self.recv_tx = None;
+
+                EventObject::bare_default_event(&mut activation.context, "close")
+            }
+            SocketEvent::CertificateRejected(status) => {
+                tracing::warn!(
+                    "certificate of {}:{} was rejected: {:?}",
+                    self.addr.0,
+                    self.addr.1,
+                    status
+                );
+                target.set_server_certificate_status(status, activation.context.gc_context);
+                socket_security_error_event(&mut activation, &self.addr)?
+            }
+        };
+
+        Avm2::dispatch_event(&mut activation.context, event, target.into());
+        Ok(())
+    }
+
+    fn handle_avm1_event<'gc>(
+        &mut self,
+        uc: &mut UpdateContext<'_, 'gc>,
+        target: Object<'gc>,
+        event: SocketEvent,
+    ) -> Result<(), Error> {
+        let NativeObject::XmlSocket(xml_socket) = target.native() else {
+            return Ok(());
+        };
+
+        let mut activation =
+            Activation::from_stub(uc.reborrow(), ActivationIdentifier::root("[XMLSocket]"));
+
+        match event {
+            SocketEvent::Connected => {
+                xml_socket
+                    .set_outgoing_queue(activation.context.gc_context, self.outgoing_tx.take());
+                let _ = target.call_method(
+                    "onConnect".into(),
+                    &[true.into()],
+                    &mut activation,
+                    ExecutionReason::Special,
+                );
+            }
+            SocketEvent::Data(data) => {
+                for message in self.split_messages(data) {
+                    let message = AvmString::new_utf8(
+                        activation.context.gc_context,
+                        UTF_8.decode(&message).0,
+                    );
+                    let _ = target.call_method(
+                        "onData".into(),
+                        &[message.into()],
+                        &mut activation,
+                        ExecutionReason::Special,
+                    );
+                }
+            }
+            SocketEvent::ConnectFailed
+            | SocketEvent::ConnectTimedOut
+            | SocketEvent::CertificateRejected(_) => {
+                let _ = target.call_method(
+                    "onConnect".into(),
+                    &[false.into()],
+                    &mut activation,
+                    ExecutionReason::Special,
+                );
+            }
+            SocketEvent::IoError | SocketEvent::Closed => {
+                if self.closed {
+                    return Ok(());
+                }
+                self.closed = true;
+
+                xml_socket.set_outgoing_queue(activation.context.gc_context, None);
+                let _ = target.call_method(
+                    "onClose".into(),
+                    &[],
+                    &mut activation,
+                    ExecutionReason::Special,
+                );
+            }
+        }
+
+        Ok(())
     }
 }
+
+/// Check whether a socket policy file permits connecting to `host:port`.
+///
+/// Like Flash Player, the master policy file is requested first. Unless its meta-policy
+/// forbids it, the locations registered with `loadPolicyFile` and the target port
+/// itself are tried next.
+async fn socket_policy_permits(
+    player: &Arc<Mutex<Player>>,
+    host: &str,
+    port: u16,
+    domain: Option<&str>,
+    policy_ports: &[u16],
+    timeout: Duration,
+) -> bool {
+    let master_policy = fetch_socket_policy(player, host, MASTER_POLICY_PORT, timeout).await;
+    match master_policy.as_ref().map(SocketPolicy::meta_policy) {
+        Some(MetaPolicy::None) => return false,
+        Some(MetaPolicy::MasterOnly) => {
+            return master_policy.map_or(false, |policy| policy.allows(domain, port))
+        }
+        Some(MetaPolicy::All) | None => {}
+    }
+    if master_policy.map_or(false, |policy| policy.allows(domain, port)) {
+        return true;
+    }
+
+    let mut ports = policy_ports.to_vec();
+    if !ports.contains(&port) {
+        ports.push(port);
+    }
+    ports.retain(|&policy_port| policy_port != MASTER_POLICY_PORT);
+
+    for policy_port in ports {
+        if let Some(policy) = fetch_socket_policy(player, host, policy_port, timeout).await {
+            if policy.allows(domain, port) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Request a socket policy file from `host:port`.
+///
+/// The server is expected to answer `<policy-file-request/>` with a NUL-terminated
+/// policy file, after which the connection is closed. Like Flash, a server that takes
+/// longer than `POLICY_FILE_TIMEOUT` to answer is treated as having no policy file.
+async fn fetch_socket_policy(
+    player: &Arc<Mutex<Player>>,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Option<SocketPolicy> {
+    tracing::debug!("requesting socket policy file from {}:{}", host, port);
+
+    let (action_tx, action_rx) = flume::unbounded::<OutgoingSocketAction>();
+    let (event_tx, event_rx) = flume::unbounded::<SocketEvent>();
+    let mut deadline = player.lock().unwrap().update(|uc| {
+        uc.navigator
+            .connect_socket(host.to_owned(), port, timeout, action_rx, event_tx);
+        uc.navigator.sleep(POLICY_FILE_TIMEOUT)
+    });
+
+    let receive = async {
+        let mut data = Vec::new();
+        while let Ok(event) = event_rx.recv_async().await {
+            match event {
+                SocketEvent::Connected => {
+                    let _ =
+                        action_tx.send(OutgoingSocketAction::Write(POLICY_FILE_REQUEST.to_vec()));
+                    let _ = action_tx.send(OutgoingSocketAction::Flush);
+                }
+                SocketEvent::Data(chunk) => {
+                    data.extend(chunk);
+                    if let Some(end) = data.iter().position(|&byte| byte == 0) {
+                        data.truncate(end);
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        }
+        data
+    };
+    let mut receive = std::pin::pin!(receive);
+    let data = std::future::poll_fn(|cx| {
+        if let Poll::Ready(data) = receive.as_mut().poll(cx) {
+            return Poll::Ready(Some(data));
+        }
+        deadline.as_mut().poll(cx).map(|_| None)
+    })
+    .await;
+    let _ = action_tx.send(OutgoingSocketAction::Close);
+
+    match data {
+        Some(data) => SocketPolicy::parse(&String::from_utf8_lossy(&data)),
+        None => {
+            tracing::warn!(
+                "timed out waiting for the socket policy file from {}:{}",
+                host,
+                port
+            );
+            None
+        }
+    }
+}
+
+/// The host a socket connects to when none is given: the host the movie was loaded from.
+pub fn default_socket_host(swf_url: &str) -> String {
+    Url::parse(swf_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_else(|| "localhost".to_owned())
+}
+
+/// Construct the `ioError` event dispatched when a socket connection fails.
+fn socket_io_error_event<'gc>(
+    activation: &mut Avm2Activation<'_, 'gc>,
+    (host, _port): &(String, u16),
+) -> Result<Avm2Object<'gc>, Error> {
+    let message = format!("Error #2031: Socket Error. URL: {host}");
+    let message = AvmString::new_utf8(activation.context.gc_context, message);
+    activation
+        .avm2()
+        .classes()
+        .ioerrorevent
+        .construct(
+            activation,
+            &[
+                "ioError".into(),
+                false.into(),
+                false.into(),
+                message.into(),
+                2031.into(),
+            ],
+        )
+        .map_err(|e| Error::Avm2Error(e.to_string()))
+}
+
+/// Construct the `securityError` event dispatched when a socket may not connect.
+fn socket_security_error_event<'gc>(
+    activation: &mut Avm2Activation<'_, 'gc>,
+    (host, port): &(String, u16),
+) -> Result<Avm2Object<'gc>, Error> {
+    let message = format!(
+        "Error #2048: Security sandbox violation: {} cannot load data from {host}:{port}.",
+        activation.context.swf.url()
+    );
+    let message = AvmString::new_utf8(activation.context.gc_context, message);
+    activation
+        .avm2()
+        .classes()
+        .securityerrorevent
+        .construct(
+            activation,
+            &[
+                "securityError".into(),
+                false.into(),
+                false.into(),
+                message.into(),
+                2048.into(),
+            ],
+        )
+        .map_err(|e| Error::Avm2Error(e.to_string()))
+}
@@ -18,7 +18,7 @@ use crate::backend::{
     ui::{InputManager, MouseCursor, UiBackend},
 };
 use crate::compatibility_rules::CompatibilityRules;
-use crate::config::Letterbox;
+use crate::config::{Letterbox, SocketPolicyMode};
 use crate::context::GcContext;
 use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
 use crate::context_menu::{
@@ -318,6 +318,9 @@ pub struct Player {
     /// Any compatibility rules to apply for this movie.
     compatibility_rules: CompatibilityRules,
 
+    /// Whether sockets must be authorized by a socket policy file.
+    socket_policy_mode: SocketPolicyMode,
+
     /// Debug UI windows
     #[cfg(feature = "egui")]
     debug_ui: Rc<RefCell<crate::debug_ui::DebugUi>>,
@@ -2049,6 +2052,10 @@ impl Player {
         &self.compatibility_rules
     }
 
+    pub fn socket_policy_mode(&self) -> SocketPolicyMode {
+        self.socket_policy_mode
+    }
+
     pub fn log_backend(&self) -> &Log {
         &self.log
     }
@@ -2100,6 +2107,7 @@ pub struct PlayerBuilder {
     player_version: Option<u8>,
     quality: StageQuality,
     sandbox_type: SandboxType,
+    socket_policy_mode: SocketPolicyMode,
     frame_rate: Option<f64>,
     external_interface_providers: Vec<Box<dyn ExternalInterfaceProvider>>,
 }
@@ -2145,6 +2153,7 @@ impl PlayerBuilder {
             player_version: None,
             quality: StageQuality::High,
             sandbox_type: SandboxType::LocalTrusted,
+            socket_policy_mode: SocketPolicyMode::Ignore,
             frame_rate: None,
             external_interface_providers: vec![],
         }
@@ -2306,6 +2315,13 @@ impl PlayerBuilder {
         self
     }
 
+    /// Configures whether sockets must be authorized by a socket policy file
+    /// (default is `SocketPolicyMode::Ignore`)
+    pub fn with_socket_policy_mode(mut self, socket_policy_mode: SocketPolicyMode) -> Self {
+        self.socket_policy_mode = socket_policy_mode;
+        self
+    }
+
     /// Sets and locks the player's frame rate. If None is provided, this has no effect.
     pub fn with_frame_rate(mut self, frame_rate: Option<f64>) -> Self {
         self.frame_rate = frame_rate;
@@ -2445,6 +2461,7 @@ impl PlayerBuilder {
                 load_behavior: self.load_behavior,
                 spoofed_url: self.spoofed_url.clone(),
                 compatibility_rules: self.compatibility_rules.clone(),
+                socket_policy_mode: self.socket_policy_mode,
                 stub_tracker: StubCollection::new(),
                 #[cfg(feature = "egui")]
                 debug_ui: Default::default(),
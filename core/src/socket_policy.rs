@@ -0,0 +1,264 @@
+//! Socket policy files, as used by Flash to authorize `Socket` and `XMLSocket` connections.
+//!
+//! Before connecting, Flash requests a policy file by sending `<policy-file-request/>` followed
+//! by a NUL byte. The server answers with a `<cross-domain-policy>` document, which lists the
+//! domains and ports the movie may connect to.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// The request sent to a server to ask it for its socket policy file.
+pub const POLICY_FILE_REQUEST: &[u8] = b"<policy-file-request/>\0";
+
+/// The port on which servers serve their master socket policy file.
+pub const MASTER_POLICY_PORT: u16 = 843;
+
+/// How long to wait for a server to send its policy file before giving up on it.
+pub const POLICY_FILE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Which policy files of a host may be used, as declared by its master policy file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaPolicy {
+    /// No policy file may be used, not even the master policy file.
+    None,
+
+    /// Only the master policy file may be used.
+    MasterOnly,
+
+    /// Any policy file may be used.
+    All,
+}
+
+/// A single `<allow-access-from>` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AllowAccessFrom {
+    domain: String,
+    ports: Vec<RangeInclusive<u16>>,
+}
+
+/// A parsed `<cross-domain-policy>` document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketPolicy {
+    meta_policy: MetaPolicy,
+    rules: Vec<AllowAccessFrom>,
+}
+
+impl SocketPolicy {
+    /// Parse a socket policy file.
+    ///
+    /// Returns `None` if the data isn't a well-formed `<cross-domain-policy>` document.
+    pub fn parse(data: &str) -> Option<Self> {
+        let mut reader = Reader::from_str(data);
+        reader.trim_text(true);
+
+        let mut meta_policy = MetaPolicy::All;
+        let mut rules = Vec::new();
+        let mut depth = 0;
+        let mut found_root = false;
+
+        loop {
+            let (element, is_empty) = match reader.read_event() {
+                Ok(Event::Start(element)) => (element, false),
+                Ok(Event::Empty(element)) => (element, true),
+                Ok(Event::End(_)) => {
+                    depth -= 1;
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::warn!("Error while parsing socket policy file: {}", e);
+                    return None;
+                }
+            };
+
+            let attribute = |name: &[u8]| {
+                element
+                    .attributes()
+                    .with_checks(false)
+                    .filter_map(Result::ok)
+                    .find(|attribute| attribute.key.as_ref() == name)
+                    .and_then(|attribute| {
+                        attribute
+                            .unescape_value()
+                            .ok()
+                            .map(|value| value.trim().to_owned())
+                    })
+            };
+
+            match (depth, element.name().as_ref()) {
+                (0, b"cross-domain-policy") => found_root = true,
+                (1, b"site-control") if found_root => {
+                    meta_policy = match attribute(b"permitted-cross-domain-policies").as_deref() {
+                        Some("none") => MetaPolicy::None,
+                        Some("master-only") => MetaPolicy::MasterOnly,
+                        _ => MetaPolicy::All,
+                    };
+                }
+                (1, b"allow-access-from") if found_root => {
+                    // Socket policies must explicitly list the permitted ports.
+                    if let (Some(domain), Some(ports)) =
+                        (attribute(b"domain"), attribute(b"to-ports"))
+                    {
+                        rules.push(AllowAccessFrom {
+                            domain: domain.to_ascii_lowercase(),
+                            ports: parse_ports(&ports),
+                        });
+                    }
+                }
+                (0, _) => return None,
+                _ => {}
+            }
+
+            if !is_empty {
+                depth += 1;
+            }
+        }
+
+        (found_root && depth == 0).then_some(Self { meta_policy, rules })
+    }
+
+    /// The meta-policy declared by this policy file.
+    ///
+    /// This is only meaningful for master policy files.
+    pub fn meta_policy(&self) -> MetaPolicy {
+        self.meta_policy
+    }
+
+    /// Whether a movie from `domain` may connect to `port`.
+    ///
+    /// `domain` is `None` for movies that weren't loaded from a remote host; only
+    /// wildcard rules apply to those.
+    pub fn allows(&self, domain: Option<&str>, port: u16) -> bool {
+        self.rules.iter().any(|rule| {
+            domain_matches(&rule.domain, domain)
+                && rule.ports.iter().any(|ports| ports.contains(&port))
+        })
+    }
+}
+
+/// Parse a `to-ports` attribute, such as `"80,443,1000-2000"` or `"*"`.
+///
+/// Malformed entries are ignored.
+fn parse_ports(ports: &str) -> Vec<RangeInclusive<u16>> {
+    ports
+        .split(',')
+        .map(str::trim)
+        .filter_map(|entry| {
+            if entry == "*" {
+                return Some(0..=u16::MAX);
+            }
+            match entry.split_once('-') {
+                Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+                None => {
+                    let port = entry.parse().ok()?;
+                    Some(port..=port)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Whether a `domain` attribute of a rule matches the domain of a movie.
+fn domain_matches(pattern: &str, domain: Option<&str>) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+
+    let Some(domain) = domain else {
+        return false;
+    };
+    let domain = domain.to_ascii_lowercase();
+
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => {
+            domain == suffix
+                || domain
+                    .strip_suffix(suffix)
+                    .map_or(false, |prefix| prefix.ends_with('.'))
+        }
+        None => domain == pattern,
+    }
+}
+
+/// Parse an `xmlsocket://host:port` URL, as passed to `Security.loadPolicyFile`.
+pub fn parse_xmlsocket_url(url: &str) -> Option<(String, u16)> {
+    let url = url::Url::parse(url).ok()?;
+    if url.scheme() != "xmlsocket" {
+        return None;
+    }
+
+    Some((url.host_str()?.to_owned(), url.port()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_allow_access_from() {
+        let policy = SocketPolicy::parse(
+            r#"<?xml version="1.0"?>
+            <!DOCTYPE cross-domain-policy SYSTEM "/xml/dtds/cross-domain-policy.dtd">
+            <cross-domain-policy>
+                <site-control permitted-cross-domain-policies="master-only"/>
+                <allow-access-from domain="*.example.com" to-ports="507,516-523" />
+                <allow-access-from domain="localhost" to-ports="*" />
+            </cross-domain-policy>"#,
+        )
+        .expect("policy should parse");
+
+        assert_eq!(policy.meta_policy(), MetaPolicy::MasterOnly);
+        assert!(policy.allows(Some("example.com"), 507));
+        assert!(policy.allows(Some("www.example.com"), 520));
+        assert!(!policy.allows(Some("www.example.com"), 524));
+        assert!(!policy.allows(Some("badexample.com"), 507));
+        assert!(policy.allows(Some("LOCALHOST"), 12345));
+        assert!(!policy.allows(None, 507));
+    }
+
+    #[test]
+    fn parse_wildcard_domain() {
+        let policy = SocketPolicy::parse(
+            "<cross-domain-policy><allow-access-from domain=\"*\" to-ports=\"843, 5000\"/></cross-domain-policy>",
+        )
+        .expect("policy should parse");
+
+        assert_eq!(policy.meta_policy(), MetaPolicy::All);
+        assert!(policy.allows(None, 5000));
+        assert!(policy.allows(Some("example.com"), 843));
+        assert!(!policy.allows(Some("example.com"), 80));
+    }
+
+    #[test]
+    fn rules_without_ports_are_ignored() {
+        let policy = SocketPolicy::parse(
+            "<cross-domain-policy><allow-access-from domain=\"*\"/></cross-domain-policy>",
+        )
+        .expect("policy should parse");
+
+        assert!(!policy.allows(Some("example.com"), 80));
+    }
+
+    #[test]
+    fn parse_invalid_policy() {
+        assert_eq!(SocketPolicy::parse(""), None);
+        assert_eq!(SocketPolicy::parse("<html></html>"), None);
+        assert_eq!(SocketPolicy::parse("<cross-domain-policy>"), None);
+    }
+
+    #[test]
+    fn parse_xmlsocket_urls() {
+        assert_eq!(
+            parse_xmlsocket_url("xmlsocket://example.com:1234"),
+            Some(("example.com".to_owned(), 1234))
+        );
+        assert_eq!(parse_xmlsocket_url("xmlsocket://example.com"), None);
+        assert_eq!(
+            parse_xmlsocket_url("http://example.com/crossdomain.xml"),
+            None
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Statistics about socket connections, as shown by the debug UI.
+
+use crate::backend::navigator::{OutgoingSocketAction, SocketEvent};
+use flume::{SendError, Sender};
+use std::cell::RefCell;
+#[cfg(feature = "egui")]
+use std::collections::VecDeque;
+#[cfg(feature = "egui")]
+use std::fmt::Write;
+use std::rc::Rc;
+
+/// How many of the most recent packets are kept for each socket.
+///
+/// Packets are only kept when there is a debug UI to show them.
+#[cfg(feature = "egui")]
+const MAX_PACKETS: usize = 256;
+
+/// The statistics of a socket, shared between its loader and its outgoing queue.
+pub type SharedSocketStats = Rc<RefCell<SocketStats>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketState {
+    Connecting,
+    Connected,
+    Closed,
+    Failed,
+}
+
+impl SocketState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Connecting => "Connecting",
+            Self::Connected => "Connected",
+            Self::Closed => "Closed",
+            Self::Failed => "Failed",
+        }
+    }
+}
+
+#[cfg(feature = "egui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketDirection {
+    Sent,
+    Received,
+}
+
+/// A chunk of data, as written by the movie or as received from the backend.
+#[cfg(feature = "egui")]
+pub struct SocketPacket {
+    /// The number of this packet within its connection, counting from 1.
+    pub number: u64,
+    pub direction: SocketDirection,
+    pub data: Vec<u8>,
+}
+
+pub struct SocketStats {
+    address: (String, u16),
+    state: SocketState,
+    bytes_sent: u64,
+    bytes_received: u64,
+    #[cfg(feature = "egui")]
+    packet_count: u64,
+    #[cfg(feature = "egui")]
+    packets: VecDeque<SocketPacket>,
+}
+
+impl SocketStats {
+    pub fn new(address: (String, u16)) -> SharedSocketStats {
+        Rc::new(RefCell::new(Self {
+            address,
+            state: SocketState::Connecting,
+            bytes_sent: 0,
+            bytes_received: 0,
+            #[cfg(feature = "egui")]
+            packet_count: 0,
+            #[cfg(feature = "egui")]
+            packets: VecDeque::new(),
+        }))
+    }
+
+    pub fn address(&self) -> &(String, u16) {
+        &self.address
+    }
+
+    pub fn state(&self) -> SocketState {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: SocketState) {
+        self.state = state;
+    }
+
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// The most recent packets, oldest first.
+    #[cfg(feature = "egui")]
+    pub fn packets(&self) -> impl Iterator<Item = &SocketPacket> {
+        self.packets.iter()
+    }
+
+    #[cfg(feature = "egui")]
+    fn push_packet(&mut self, direction: SocketDirection, data: &[u8]) {
+        self.packet_count += 1;
+        if self.packets.len() == MAX_PACKETS {
+            self.packets.pop_front();
+        }
+        self.packets.push_back(SocketPacket {
+            number: self.packet_count,
+            direction,
+            data: data.to_vec(),
+        });
+    }
+
+    /// Take note of an event reported by the backend.
+    pub fn record_event(&mut self, event: &SocketEvent) {
+        match event {
+            SocketEvent::Connected => self.state = SocketState::Connected,
+            SocketEvent::Data(data) => {
+                self.bytes_received += data.len() as u64;
+                #[cfg(feature = "egui")]
+                self.push_packet(SocketDirection::Received, data);
+            }
+            SocketEvent::ConnectFailed
+            | SocketEvent::ConnectTimedOut
+            | SocketEvent::CertificateRejected(_)
+            | SocketEvent::IoError => self.state = SocketState::Failed,
+            SocketEvent::Closed => {
+                if self.state != SocketState::Failed {
+                    self.state = SocketState::Closed;
+                }
+            }
+        }
+    }
+
+    /// Take note of an action requested by the movie.
+    fn record_action(&mut self, action: &OutgoingSocketAction) {
+        match action {
+            OutgoingSocketAction::Write(data) => {
+                self.bytes_sent += data.len() as u64;
+                #[cfg(feature = "egui")]
+                self.push_packet(SocketDirection::Sent, data);
+            }
+            OutgoingSocketAction::Flush => {}
+            OutgoingSocketAction::Close => self.state = SocketState::Closed,
+        }
+    }
+
+    /// A text dump of the connection and of its most recent packets.
+    #[cfg(feature = "egui")]
+    pub fn session(&self) -> String {
+        let (host, port) = &self.address;
+        let mut session = format!(
+            "# {host}:{port}, {}, {} bytes sent, {} bytes received\n",
+            self.state.as_str(),
+            self.bytes_sent,
+            self.bytes_received
+        );
+        for packet in &self.packets {
+            let direction = match packet.direction {
+                SocketDirection::Sent => "sent",
+                SocketDirection::Received => "received",
+            };
+            let _ = writeln!(
+                session,
+                "\n#{} {direction}, {} bytes",
+                packet.number,
+                packet.data.len()
+            );
+            session.push_str(&hex_dump(&packet.data));
+        }
+        session
+    }
+}
+
+/// Format bytes as lines of an offset, 16 bytes in hexadecimal, and the same bytes in ASCII.
+#[cfg(feature = "egui")]
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(dump, "{:08x} ", line * 16);
+        for byte in chunk {
+            let _ = write!(dump, " {byte:02x}");
+        }
+        dump.push_str(&"   ".repeat(16 - chunk.len()));
+        dump.push_str("  |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+/// The movie's end of a socket's outgoing queue, which counts everything sent through it.
+pub struct SocketSender {
+    sender: Sender<OutgoingSocketAction>,
+    stats: SharedSocketStats,
+}
+
+impl SocketSender {
+    pub fn new(sender: Sender<OutgoingSocketAction>, stats: SharedSocketStats) -> Self {
+        Self { sender, stats }
+    }
+
+    pub fn send(
+        &self,
+        action: OutgoingSocketAction,
+    ) -> Result<(), SendError<OutgoingSocketAction>> {
+        self.stats.borrow_mut().record_action(&action);
+        self.sender.send(action)
+    }
+
+    /// How many actions the IO task hasn't picked up yet.
+    pub fn queue_depth(&self) -> usize {
+        self.sender.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_traffic() {
+        let stats = SocketStats::new(("localhost".to_string(), 8000));
+        let (sender, receiver) = flume::unbounded();
+        let sender = SocketSender::new(sender, stats.clone());
+
+        stats.borrow_mut().record_event(&SocketEvent::Connected);
+        sender
+            .send(OutgoingSocketAction::Write(b"hello".to_vec()))
+            .expect("receiver is alive");
+        sender
+            .send(OutgoingSocketAction::Flush)
+            .expect("receiver is alive");
+        stats
+            .borrow_mut()
+            .record_event(&SocketEvent::Data(b"world!".to_vec()));
+
+        let stats = stats.borrow();
+        assert_eq!(stats.state(), SocketState::Connected);
+        assert_eq!(stats.bytes_sent(), 5);
+        assert_eq!(stats.bytes_received(), 6);
+        assert_eq!(sender.queue_depth(), 2);
+        assert_eq!(receiver.drain().count(), 2);
+        assert_eq!(sender.queue_depth(), 0);
+    }
+
+    #[test]
+    fn tracks_state() {
+        let stats = SocketStats::new(("localhost".to_string(), 8000));
+        assert_eq!(stats.borrow().state(), SocketState::Connecting);
+
+        stats.borrow_mut().record_event(&SocketEvent::ConnectFailed);
+        assert_eq!(stats.borrow().state(), SocketState::Failed);
+
+        // A failure isn't hidden by the close that follows it.
+        stats.borrow_mut().record_event(&SocketEvent::Closed);
+        assert_eq!(stats.borrow().state(), SocketState::Failed);
+
+        let stats = SocketStats::new(("localhost".to_string(), 8000));
+        stats.borrow_mut().record_event(&SocketEvent::Connected);
+        stats.borrow_mut().record_event(&SocketEvent::Closed);
+        assert_eq!(stats.borrow().state(), SocketState::Closed);
+
+        let stats = SocketStats::new(("localhost".to_string(), 8000));
+        let (sender, _receiver) = flume::unbounded();
+        let sender = SocketSender::new(sender, stats.clone());
+        stats.borrow_mut().record_event(&SocketEvent::Connected);
+        sender
+            .send(OutgoingSocketAction::Close)
+            .expect("receiver is alive");
+        assert_eq!(stats.borrow().state(), SocketState::Closed);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn keeps_recent_packets() {
+        let stats = SocketStats::new(("localhost".to_string(), 8000));
+        for i in 0..MAX_PACKETS + 2 {
+            stats
+                .borrow_mut()
+                .record_event(&SocketEvent::Data(vec![i as u8]));
+        }
+
+        let stats = stats.borrow();
+        let numbers: Vec<u64> = stats.packets().map(|packet| packet.number).collect();
+        assert_eq!(numbers.len(), MAX_PACKETS);
+        assert_eq!(numbers.first(), Some(&3));
+        assert_eq!(numbers.last(), Some(&(MAX_PACKETS as u64 + 2)));
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn formats_hex_dump() {
+        assert_eq!(
+            hex_dump(b"<policy-file-request/>\0"),
+            "00000000  3c 70 6f 6c 69 63 79 2d 66 69 6c 65 2d 72 65 71  |<policy-file-req|\n\
+             00000010  75 65 73 74 2f 3e 00                             |uest/>.|\n"
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::avm1::{
     ExecutionReason as Avm1ExecutionReason, FlvValueAvm1Ext, ScriptObject as Avm1ScriptObject,
     TObject as Avm1TObject, Value as Avm1Value,
 };
+use crate::avm2::object::TextureObject;
 use crate::avm2::{Activation as Avm2Activation, Avm2, EventObject as Avm2EventObject};
 use crate::backend::navigator::Request;
 use crate::context::UpdateContext;
@@ -16,7 +17,7 @@ use flv_rs::{
     ScriptData as FlvScriptData, Tag as FlvTag, TagData as FlvTagData, Value as FlvValue,
     VideoData as FlvVideoData, VideoPacket as FlvVideoPacket,
 };
-use gc_arena::{Collect, GcCell, MutationContext};
+use gc_arena::{Collect, Gc, GcCell, MutationContext};
 use ruffle_render::bitmap::BitmapInfo;
 use ruffle_video::frame::EncodedFrame;
 use ruffle_video::VideoStreamHandle;
@@ -181,6 +182,9 @@ pub struct NetStreamData<'gc> {
     #[collect(require_static)]
     last_decoded_bitmap: Option<BitmapInfo>,
 
+    /// The Stage3D `VideoTexture`s that each decoded frame is uploaded to.
+    video_textures: Vec<TextureObject<'gc>>,
+
     /// The AVM side of this stream.
     avm_object: Option<AvmObject<'gc>>,
 }
@@ -196,6 +200,7 @@ impl<'gc> NetStream<'gc> {
                 stream_type: None,
                 stream_time: 0.0,
                 last_decoded_bitmap: None,
+                video_textures: Vec::new(),
                 avm_object,
             },
         ))
@@ -324,6 +329,7 @@ impl<'gc> NetStream<'gc> {
         let end_time = write.stream_time + dt;
         let mut end_of_video = false;
         let mut error = false;
+        let mut decoded_video_stream = None;
 
         //At this point we should know our stream type.
         if matches!(write.stream_type, Some(NetStreamType::Flv { .. })) {
@@ -452,6 +458,12 @@ impl<'gc> NetStream<'gc> {
                                     frame_id,
                                 };
 
+                                // Only keep the pixels of the frame around when a
+                                // `VideoTexture` needs them.
+                                context.video.keep_decoded_frames(
+                                    video_handle,
+                                    !write.video_textures.is_empty(),
+                                );
                                 match context.video.decode_video_stream_frame(
                                     video_handle,
                                     encoded_frame,
@@ -460,6 +472,7 @@ impl<'gc> NetStream<'gc> {
                                     Ok(bitmap_info) => {
                                         let (_, position) = reader.into_parts();
                                         write.last_decoded_bitmap = Some(bitmap_info);
+                                        decoded_video_stream = Some(video_handle);
                                         reader = FlvReader::from_parts(&buffer, position);
                                     }
                                     Err(e) => {
@@ -614,6 +627,10 @@ impl<'gc> NetStream<'gc> {
         write.stream_time = end_time;
         drop(write);
 
+        if let Some(video_handle) = decoded_video_stream {
+            self.update_video_textures(context, video_handle);
+        }
+
         if end_of_video {
             self.trigger_status_event(
                 context,
@@ -640,6 +657,65 @@ impl<'gc> NetStream<'gc> {
         self.0.read().last_decoded_bitmap.clone()
     }
 
+    /// Starts uploading the frames of this stream to a `VideoTexture`.
+    pub fn attach_video_texture(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        texture: TextureObject<'gc>,
+    ) {
+        let mut write = self.0.write(gc_context);
+        if !write
+            .video_textures
+            .iter()
+            .any(|t| Gc::ptr_eq(t.0, texture.0))
+        {
+            write.video_textures.push(texture);
+        }
+    }
+
+    /// Stops uploading the frames of this stream to a `VideoTexture`.
+    pub fn detach_video_texture(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        texture: TextureObject<'gc>,
+    ) {
+        self.0
+            .write(gc_context)
+            .video_textures
+            .retain(|t| !Gc::ptr_eq(t.0, texture.0));
+    }
+
+    /// Uploads the last decoded frame to every attached `VideoTexture`, then
+    /// lets them know with a `textureReady` event.
+    fn update_video_textures(
+        self,
+        context: &mut UpdateContext<'_, 'gc>,
+        video_handle: VideoStreamHandle,
+    ) {
+        let textures = {
+            let mut write = self.0.write(context.gc_context);
+            write.video_textures.retain(|texture| !texture.disposed());
+            write.video_textures.clone()
+        };
+        if textures.is_empty() {
+            return;
+        }
+
+        let Some(frame) = context.video.last_decoded_frame(video_handle).cloned() else {
+            tracing::warn!("Video backend doesn't support uploading frames to a VideoTexture");
+            return;
+        };
+
+        for texture in textures {
+            if let Err(e) = texture.upload_video_frame(frame.clone()) {
+                tracing::error!("Uploading video frame to VideoTexture failed: {}", e);
+                continue;
+            }
+            let texture_ready_event = Avm2EventObject::bare_default_event(context, "textureReady");
+            Avm2::dispatch_event(context, texture_ready_event, texture.into());
+        }
+    }
+
     /// Trigger a status event on the stream.
     pub fn trigger_status_event(
         self,
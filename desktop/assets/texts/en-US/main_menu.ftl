@@ -30,4 +30,6 @@ debug-menu-open-stage = View Stage Info
 debug-menu-open-movie = View Movie
 debug-menu-open-movie-list = Show Known Movies
 debug-menu-search-display-objects = Search Display Objects...
+debug-menu-open-sockets = Show Sockets...
+debug-menu-lose-stage3d-contexts = Simulate Stage3D Context Loss
 
@@ -1,3 +1,4 @@
+use crate::backends::SocketCapture;
 use crate::cli::Opt;
 use crate::custom_event::RuffleEvent;
 use crate::gui::{GuiController, MENU_HEIGHT};
@@ -51,10 +52,13 @@ impl App {
 
         let mut gui = GuiController::new(window.clone(), &event_loop, &opt)?;
 
+        let socket_capture =
+            SocketCapture::new(opt.record_sockets.as_deref(), opt.replay_sockets.as_deref());
         let mut player = PlayerController::new(
             event_loop.create_proxy(),
             window.clone(),
             gui.descriptors().clone(),
+            socket_capture,
         );
 
         if let Some(movie_url) = movie_url {
@@ -1,11 +1,13 @@
 mod audio;
 mod external_interface;
 mod navigator;
+mod socket_capture;
 mod storage;
 mod ui;
 
 pub use audio::CpalAudioBackend;
 pub use external_interface::DesktopExternalInterfaceProvider;
 pub use navigator::ExternalNavigatorBackend;
+pub use socket_capture::SocketCapture;
 pub use storage::DiskStorageBackend;
 pub use ui::DesktopUiBackend;
@@ -1,5 +1,6 @@
 //! Navigator backend for web
 
+use crate::backends::SocketCapture;
 use crate::custom_event::RuffleEvent;
 use isahc::http::{HeaderName, HeaderValue};
 use isahc::{
@@ -7,17 +8,30 @@ use isahc::{
 };
 use rfd::{MessageButtons, MessageDialog, MessageLevel};
 use ruffle_core::backend::navigator::{
-    async_return, create_fetch_error, create_specific_fetch_error, ErrorResponse, NavigationMethod,
-    NavigatorBackend, OpenURLMode, OwnedFuture, Request, SuccessResponse,
+    async_return, create_fetch_error, create_specific_fetch_error, CertificateStatus,
+    ChainBuildingCertificate, ErrorResponse, NavigationMethod, NavigatorBackend, OpenURLMode,
+    OutgoingSocketAction, OwnedFuture, Request, SocketEvent, SuccessResponse,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
-use std::future::Future;
-use std::pin::Pin;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tokio::runtime::Runtime;
+use tokio::select;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::client::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier, WebPkiVerifier,
+};
+use tokio_rustls::rustls::{
+    Certificate, CertificateError, ClientConfig, DigitallySignedStruct, OwnedTrustAnchor,
+    RootCertStore, ServerName, SignatureScheme,
+};
+use tokio_rustls::{rustls, TlsConnector};
 use url::{ParseError, Url};
 use winit::event_loop::EventLoopProxy;
 
@@ -41,6 +55,8 @@ pub struct ExternalNavigatorBackend {
     open_url_mode: OpenURLMode,
 
     tokio_runtime: Runtime,
+
+    socket_capture: SocketCapture,
 }
 
 impl ExternalNavigatorBackend {
@@ -52,6 +68,7 @@ impl ExternalNavigatorBackend {
         proxy: Option<Url>,
         upgrade_to_https: bool,
         open_url_mode: OpenURLMode,
+        socket_capture: SocketCapture,
     ) -> Self {
         let proxy = proxy.and_then(|url| url.as_str().parse().ok());
         let builder = HttpClient::builder()
@@ -74,6 +91,34 @@ impl ExternalNavigatorBackend {
             upgrade_to_https,
             open_url_mode,
             tokio_runtime: Runtime::new().unwrap(),
+            socket_capture,
+        }
+    }
+
+    /// Record or replay a new socket connection, if requested.
+    ///
+    /// Returns the channels that the connection should be driven with, or
+    /// `None` if it is being replayed instead.
+    fn capture_socket(
+        &mut self,
+        host: &str,
+        port: u16,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) -> Option<(
+        flume::Receiver<OutgoingSocketAction>,
+        flume::Sender<SocketEvent>,
+    )> {
+        let address = format!("{host}:{port}");
+        match &mut self.socket_capture {
+            SocketCapture::None => Some((actions, events)),
+            SocketCapture::Record(recorder) => {
+                Some(recorder.record(&self.tokio_runtime, address, actions, events))
+            }
+            SocketCapture::Replay(replay) => {
+                replay.replay(&self.tokio_runtime, address, actions, events);
+                None
+            }
         }
     }
 }
@@ -308,8 +353,60 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         }
     }
 
-    fn spawn_io_future(&mut self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
-        self.tokio_runtime.spawn(future);
+    fn sleep(&self, duration: Duration) -> OwnedFuture<(), Error> {
+        // Tasks are polled by the event loop, which has no timers of its own.
+        let (elapsed_tx, elapsed_rx) = flume::bounded(1);
+        self.tokio_runtime.spawn(async move {
+            tokio::time::sleep(duration).await;
+            let _ = elapsed_tx.send(());
+        });
+        Box::pin(async move {
+            let _ = elapsed_rx.recv_async().await;
+            Ok(())
+        })
+    }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        let Some((actions, events)) = self.capture_socket(&host, port, actions, events) else {
+            return;
+        };
+
+        self.tokio_runtime
+            .spawn(run_socket(host, port, timeout, None, actions, events));
+    }
+
+    fn supports_secure_sockets(&self) -> bool {
+        true
+    }
+
+    fn connect_secure_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        certificates: Vec<ChainBuildingCertificate>,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        let Some((actions, events)) = self.capture_socket(&host, port, actions, events) else {
+            return;
+        };
+
+        self.tokio_runtime.spawn(run_socket(
+            host,
+            port,
+            timeout,
+            Some(certificates),
+            actions,
+            events,
+        ));
     }
 
     fn pre_process_url(&self, mut url: Url) -> Url {
@@ -319,3 +416,327 @@ impl NavigatorBackend for ExternalNavigatorBackend {
         url
     }
 }
+
+/// Drive a single TCP connection on the Tokio runtime until either end closes it.
+///
+/// If `certificates` is given, the connection is made over TLS, and the server's
+/// certificate is validated with them in addition to the usual trusted roots.
+async fn run_socket(
+    host: String,
+    port: u16,
+    timeout: Duration,
+    certificates: Option<Vec<ChainBuildingCertificate>>,
+    actions: flume::Receiver<OutgoingSocketAction>,
+    events: flume::Sender<SocketEvent>,
+) {
+    tracing::debug!("TcpStream::connect to {}:{}", host, port);
+    let socket =
+        match tokio::time::timeout(timeout, TcpStream::connect((host.as_str(), port))).await {
+            Ok(Ok(socket)) => socket,
+            Ok(Err(e)) => {
+                tracing::warn!("Socket connection to {}:{} failed: {}", host, port, e);
+                // The player may already be gone, in which case nobody cares.
+                let _ = events.send_async(SocketEvent::ConnectFailed).await;
+                return;
+            }
+            Err(_) => {
+                tracing::warn!("Socket connection to {}:{} timed out", host, port);
+                let _ = events.send_async(SocketEvent::ConnectTimedOut).await;
+                return;
+            }
+        };
+
+    let Some(certificates) = certificates else {
+        drive_socket(socket, actions, events).await;
+        return;
+    };
+
+    let socket =
+        match tokio::time::timeout(timeout, tls_handshake(&host, socket, certificates)).await {
+            Ok(Ok(socket)) => socket,
+            Ok(Err(event)) => {
+                let _ = events.send_async(event).await;
+                return;
+            }
+            Err(_) => {
+                tracing::warn!("TLS handshake with {}:{} timed out", host, port);
+                let _ = events.send_async(SocketEvent::ConnectTimedOut).await;
+                return;
+            }
+        };
+    drive_socket(socket, actions, events).await;
+}
+
+/// Perform the TLS handshake of a `SecureSocket` over an established connection.
+///
+/// On failure, returns the event to report to the movie.
+async fn tls_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    host: &str,
+    socket: S,
+    certificates: Vec<ChainBuildingCertificate>,
+) -> Result<TlsStream<S>, SocketEvent> {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    let mut intermediates = Vec::new();
+    for certificate in certificates {
+        if !certificate.trusted {
+            intermediates.push(Certificate(certificate.der));
+        } else if let Err(e) = roots.add(&Certificate(certificate.der)) {
+            tracing::warn!("Ignoring invalid chain building certificate: {}", e);
+        }
+    }
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(ChainBuildingVerifier {
+            inner: WebPkiVerifier::new(roots, None),
+            intermediates,
+        }))
+        .with_no_client_auth();
+    let server_name = ServerName::try_from(host).map_err(|e| {
+        tracing::warn!("Invalid TLS server name {}: {}", host, e);
+        SocketEvent::ConnectFailed
+    })?;
+
+    TlsConnector::from(Arc::new(config))
+        .connect(server_name, socket)
+        .await
+        .map_err(|e| {
+            tracing::warn!("TLS handshake with {} failed: {}", host, e);
+            match e
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+            {
+                Some(rustls::Error::InvalidCertificate(error)) => {
+                    SocketEvent::CertificateRejected(certificate_status(error))
+                }
+                _ => SocketEvent::ConnectFailed,
+            }
+        })
+}
+
+/// Verifies server certificates like rustls usually does, but also builds chains out of
+/// the intermediate certificates given to `SecureSocket.addBinaryChainBuildingCertificate`,
+/// and not only out of those sent by the server.
+struct ChainBuildingVerifier {
+    inner: WebPkiVerifier,
+    intermediates: Vec<Certificate>,
+}
+
+impl ServerCertVerifier for ChainBuildingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let intermediates: Vec<_> = intermediates
+            .iter()
+            .chain(&self.intermediates)
+            .cloned()
+            .collect();
+        self.inner.verify_server_cert(
+            end_entity,
+            &intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+
+    fn request_scts(&self) -> bool {
+        self.inner.request_scts()
+    }
+}
+
+/// The `SecureSocket.serverCertificateStatus` matching a rejected certificate.
+fn certificate_status(error: &CertificateError) -> CertificateStatus {
+    match error {
+        CertificateError::Expired => CertificateStatus::Expired,
+        CertificateError::NotValidYet => CertificateStatus::NotYetValid,
+        CertificateError::NotValidForName => CertificateStatus::PrincipalMismatch,
+        CertificateError::UnknownIssuer => CertificateStatus::UntrustedSigners,
+        CertificateError::Revoked => CertificateStatus::Revoked,
+        CertificateError::BadSignature => CertificateStatus::InvalidChain,
+        _ => CertificateStatus::Invalid,
+    }
+}
+
+/// Report an established connection, then pass data both ways until either end closes it.
+async fn drive_socket<S: AsyncRead + AsyncWrite + Unpin>(
+    mut socket: S,
+    actions: flume::Receiver<OutgoingSocketAction>,
+    events: flume::Sender<SocketEvent>,
+) {
+    if events.send_async(SocketEvent::Connected).await.is_err() {
+        return;
+    }
+
+    let mut send_buffer = Vec::new();
+    let mut buffer = vec![0; 4096];
+    loop {
+        select! {
+            length = socket.read(&mut buffer) => {
+                let length = match length {
+                    Ok(length) => length,
+                    Err(e) => {
+                        tracing::warn!("Socket read failed: {}", e);
+                        let _ = events.send_async(SocketEvent::IoError).await;
+                        let _ = events.send_async(SocketEvent::Closed).await;
+                        break;
+                    }
+                };
+                if length == 0 {
+                    let _ = events.send_async(SocketEvent::Closed).await;
+                    break;
+                }
+
+                if events.send_async(SocketEvent::Data(buffer[..length].to_vec())).await.is_err() {
+                    break;
+                }
+            }
+
+            action = actions.recv_async() => {
+                match action {
+                    Ok(OutgoingSocketAction::Write(data)) => {
+                        send_buffer.extend(data);
+                    }
+                    Ok(OutgoingSocketAction::Flush) => {
+                        let result = match socket.write_all(&send_buffer).await {
+                            Ok(()) => socket.flush().await,
+                            Err(e) => Err(e),
+                        };
+                        send_buffer.clear();
+                        if let Err(e) = result {
+                            tracing::warn!("Socket write failed: {}", e);
+                            let _ = events.send_async(SocketEvent::IoError).await;
+                            let _ = events.send_async(SocketEvent::Closed).await;
+                            break;
+                        }
+                    }
+                    // Flash doesn't dispatch `close` when the movie closes the socket itself.
+                    Ok(OutgoingSocketAction::Close) | Err(_) => {
+                        if let Err(e) = socket.shutdown().await {
+                            tracing::warn!("Socket shutdown failed: {}", e);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_rustls::rustls::{PrivateKey, ServerConfig};
+    use tokio_rustls::TlsAcceptor;
+
+    // A root, an intermediate signed by the root, and a certificate for `localhost`
+    // signed by the intermediate, all made with OpenSSL and valid until 2126.
+    const ROOT: &[u8] = include_bytes!("test_certificates/root.der");
+    const INTERMEDIATE: &[u8] = include_bytes!("test_certificates/intermediate.der");
+    const LEAF: &[u8] = include_bytes!("test_certificates/leaf.der");
+    const LEAF_KEY: &[u8] = include_bytes!("test_certificates/leaf.key.der");
+
+    fn certificate(der: &[u8], trusted: bool) -> ChainBuildingCertificate {
+        ChainBuildingCertificate {
+            der: der.to_vec(),
+            trusted,
+        }
+    }
+
+    /// Handshake in memory with a server that only sends its own certificate,
+    /// and not the intermediate one.
+    async fn handshake(certificates: Vec<ChainBuildingCertificate>) -> Result<(), SocketEvent> {
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![Certificate(LEAF.to_vec())],
+                PrivateKey(LEAF_KEY.to_vec()),
+            )
+            .expect("Test certificate should be usable");
+        let (client, server) = tokio::io::duplex(4096);
+        tokio::spawn(TlsAcceptor::from(Arc::new(config)).accept(server));
+
+        tls_handshake("localhost", client, certificates)
+            .await
+            .map(drop)
+    }
+
+    #[tokio::test]
+    async fn builds_chain_with_untrusted_intermediate() {
+        let result = handshake(vec![
+            certificate(ROOT, true),
+            certificate(INTERMEDIATE, false),
+        ])
+        .await;
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn rejects_chain_without_intermediate() {
+        let result = handshake(vec![certificate(ROOT, true)]).await;
+        assert!(
+            matches!(
+                result,
+                Err(SocketEvent::CertificateRejected(
+                    CertificateStatus::UntrustedSigners
+                ))
+            ),
+            "{result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_chain_without_trusted_root() {
+        let result = handshake(vec![
+            certificate(ROOT, false),
+            certificate(INTERMEDIATE, false),
+        ])
+        .await;
+        assert!(
+            matches!(
+                result,
+                Err(SocketEvent::CertificateRejected(
+                    CertificateStatus::UntrustedSigners
+                ))
+            ),
+            "{result:?}"
+        );
+    }
+}
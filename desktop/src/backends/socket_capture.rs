@@ -0,0 +1,450 @@
+//! Recording and replaying of socket traffic.
+//!
+//! A recording is a text file with one line per event:
+//!
+//! ```text
+//! <seconds since recording started> <connection id> <host:port> <kind> [hex data]
+//! ```
+//!
+//! `kind` is `connect` when the movie starts connecting, `out` for data flushed by the movie,
+//! `client-closed` when the movie closes the socket, and otherwise the name of the
+//! `SocketEvent` that was reported back to the movie (`in` carries received data, and
+//! `certificate-rejected:<status>` names the status of a rejected TLS certificate).
+//!
+//! When replaying, the n-th connection to an address is answered by the n-th recorded
+//! connection to it, and its events are reported on the recorded schedule, measured from
+//! the `connect` line. Data sent by the movie is ignored.
+
+use ruffle_core::backend::navigator::{CertificateStatus, OutgoingSocketAction, SocketEvent};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::select;
+
+/// What to do with socket traffic, besides passing it on.
+///
+/// Clones of a recording write to the same file. Each clone of a replay answers
+/// connections on its own, starting from wherever the original had got to.
+#[derive(Clone)]
+pub enum SocketCapture {
+    /// Connect to servers as usual.
+    None,
+
+    /// Connect to servers as usual, and record all traffic.
+    Record(SocketRecorder),
+
+    /// Don't connect to any server, and replay recorded traffic instead.
+    Replay(SocketReplay),
+}
+
+impl SocketCapture {
+    /// Set up recording to, or replaying from, the given files.
+    ///
+    /// Falls back to connecting as usual if a file can't be used.
+    pub fn new(record: Option<&Path>, replay: Option<&Path>) -> Self {
+        if let Some(path) = replay {
+            match SocketReplay::read(path) {
+                Ok(replay) => return Self::Replay(replay),
+                Err(e) => {
+                    tracing::error!("Couldn't read socket recording {}: {}", path.display(), e)
+                }
+            }
+        } else if let Some(path) = record {
+            match SocketRecorder::create(path) {
+                Ok(recorder) => return Self::Record(recorder),
+                Err(e) => {
+                    tracing::error!("Couldn't create socket recording {}: {}", path.display(), e)
+                }
+            }
+        }
+
+        Self::None
+    }
+}
+
+/// Writes the traffic of every socket to a file.
+#[derive(Clone)]
+pub struct SocketRecorder(Arc<SocketRecorderData>);
+
+struct SocketRecorderData {
+    file: Mutex<File>,
+    start: Instant,
+    next_connection_id: AtomicU32,
+}
+
+impl SocketRecorder {
+    fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self(Arc::new(SocketRecorderData {
+            file: Mutex::new(File::create(path)?),
+            start: Instant::now(),
+            next_connection_id: AtomicU32::new(1),
+        })))
+    }
+
+    /// Record a new connection, by sitting between the player and the task driving the socket.
+    ///
+    /// Returns the channels to hand over to that task instead.
+    pub fn record(
+        &self,
+        runtime: &Runtime,
+        address: String,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) -> (
+        flume::Receiver<OutgoingSocketAction>,
+        flume::Sender<SocketEvent>,
+    ) {
+        let connection = RecordedConnection {
+            recorder: self.clone(),
+            id: self.0.next_connection_id.fetch_add(1, Ordering::Relaxed),
+            address,
+        };
+        connection.write("connect", None);
+
+        let (inner_actions_sender, inner_actions) = flume::unbounded();
+        let (inner_events, inner_events_receiver) = flume::unbounded();
+
+        let outgoing = connection.clone();
+        runtime.spawn(async move {
+            let mut pending = Vec::new();
+            while let Ok(action) = actions.recv_async().await {
+                match &action {
+                    OutgoingSocketAction::Write(data) => pending.extend_from_slice(data),
+                    OutgoingSocketAction::Flush => {
+                        if !pending.is_empty() {
+                            outgoing.write("out", Some(&pending));
+                            pending.clear();
+                        }
+                    }
+                    OutgoingSocketAction::Close => outgoing.write("client-closed", None),
+                }
+                if inner_actions_sender.send_async(action).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        runtime.spawn(async move {
+            while let Ok(event) = inner_events_receiver.recv_async().await {
+                let (kind, data) = event_kind(&event);
+                connection.write(&kind, data);
+                if events.send_async(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        (inner_actions, inner_events)
+    }
+}
+
+#[derive(Clone)]
+struct RecordedConnection {
+    recorder: SocketRecorder,
+    id: u32,
+    address: String,
+}
+
+impl RecordedConnection {
+    fn write(&self, kind: &str, data: Option<&[u8]>) {
+        let time = self.recorder.0.start.elapsed().as_secs_f64();
+        let mut line = format!("{time:.3} {} {} {kind}", self.id, self.address);
+        if let Some(data) = data {
+            line.push(' ');
+            line.extend(data.iter().map(|byte| format!("{byte:02x}")));
+        }
+        line.push('\n');
+
+        let mut file = self
+            .recorder
+            .0
+            .file
+            .lock()
+            .expect("Socket recording is poisoned");
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            tracing::warn!("Couldn't write socket recording: {}", e);
+        }
+    }
+}
+
+/// Recorded connections, waiting to be replayed.
+#[derive(Clone)]
+pub struct SocketReplay {
+    /// The schedule of every recorded connection, by address, in order of connection.
+    connections: HashMap<String, VecDeque<Vec<(Duration, SocketEvent)>>>,
+}
+
+impl SocketReplay {
+    fn read(path: &Path) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    fn parse(recording: &str) -> Self {
+        // Connections are kept in the order in which they were opened.
+        let mut order = Vec::new();
+        let mut recorded: HashMap<u32, (String, Duration, Vec<(Duration, SocketEvent)>)> =
+            HashMap::new();
+        for (number, line) in recording.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((time, id, address, kind, data)) = parse_line(line) else {
+                tracing::warn!("Ignoring malformed line {} of socket recording", number + 1);
+                continue;
+            };
+
+            if kind == "connect" {
+                order.push(id);
+                recorded.insert(id, (address.to_owned(), time, vec![]));
+                continue;
+            }
+
+            let Some((_, start, schedule)) = recorded.get_mut(&id) else {
+                tracing::warn!(
+                    "Ignoring line {} of socket recording, for a connection that was never opened",
+                    number + 1
+                );
+                continue;
+            };
+
+            // Only what the movie received is replayed.
+            if matches!(kind, "out" | "client-closed") {
+                continue;
+            }
+
+            let Some(event) = parse_event(kind, data) else {
+                tracing::warn!("Ignoring malformed line {} of socket recording", number + 1);
+                continue;
+            };
+            schedule.push((time.saturating_sub(*start), event));
+        }
+
+        let mut connections: HashMap<_, VecDeque<_>> = HashMap::new();
+        for id in order {
+            if let Some((address, _, schedule)) = recorded.remove(&id) {
+                connections.entry(address).or_default().push_back(schedule);
+            }
+        }
+
+        Self { connections }
+    }
+
+    /// Answer a new connection with the next recorded connection to the same address.
+    pub fn replay(
+        &mut self,
+        runtime: &Runtime,
+        address: String,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        let Some(schedule) = self
+            .connections
+            .get_mut(&address)
+            .and_then(VecDeque::pop_front)
+        else {
+            tracing::warn!("No recorded connection left to replay for {}", address);
+            let _ = events.send(SocketEvent::ConnectFailed);
+            return;
+        };
+
+        runtime.spawn(async move {
+            let start = tokio::time::Instant::now();
+            for (offset, event) in schedule {
+                loop {
+                    select! {
+                        _ = tokio::time::sleep_until(start + offset) => break,
+                        action = actions.recv_async() => match action {
+                            Ok(OutgoingSocketAction::Close) | Err(_) => return,
+                            Ok(_) => {}
+                        }
+                    }
+                }
+
+                if events.send_async(event).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// The name of an event in a recording, and its data.
+fn event_kind(event: &SocketEvent) -> (Cow<'static, str>, Option<&[u8]>) {
+    match event {
+        SocketEvent::Connected => ("connected".into(), None),
+        SocketEvent::ConnectFailed => ("connect-failed".into(), None),
+        SocketEvent::ConnectTimedOut => ("connect-timed-out".into(), None),
+        SocketEvent::Data(data) => ("in".into(), Some(data)),
+        SocketEvent::IoError => ("io-error".into(), None),
+        SocketEvent::Closed => ("closed".into(), None),
+        SocketEvent::CertificateRejected(status) => (
+            format!("certificate-rejected:{}", status.as_str()).into(),
+            None,
+        ),
+    }
+}
+
+/// The event named by `kind` in a recording, carrying `data`.
+fn parse_event(kind: &str, data: Option<Vec<u8>>) -> Option<SocketEvent> {
+    Some(match (kind, data) {
+        ("connected", None) => SocketEvent::Connected,
+        ("connect-failed", None) => SocketEvent::ConnectFailed,
+        ("connect-timed-out", None) => SocketEvent::ConnectTimedOut,
+        ("in", Some(data)) => SocketEvent::Data(data),
+        ("io-error", None) => SocketEvent::IoError,
+        ("closed", None) => SocketEvent::Closed,
+        (kind, None) => SocketEvent::CertificateRejected(parse_certificate_status(
+            kind.strip_prefix("certificate-rejected:")?,
+        )?),
+        _ => return None,
+    })
+}
+
+fn parse_certificate_status(status: &str) -> Option<CertificateStatus> {
+    Some(match status {
+        "trusted" => CertificateStatus::Trusted,
+        "unknown" => CertificateStatus::Unknown,
+        "revoked" => CertificateStatus::Revoked,
+        "invalid" => CertificateStatus::Invalid,
+        "invalidChain" => CertificateStatus::InvalidChain,
+        "untrustedSigners" => CertificateStatus::UntrustedSigners,
+        "principalMismatch" => CertificateStatus::PrincipalMismatch,
+        "expired" => CertificateStatus::Expired,
+        "notYetValid" => CertificateStatus::NotYetValid,
+        _ => return None,
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn parse_line(line: &str) -> Option<(Duration, u32, &str, &str, Option<Vec<u8>>)> {
+    let mut fields = line.split_whitespace();
+    let time = Duration::try_from_secs_f64(fields.next()?.parse().ok()?).ok()?;
+    let id = fields.next()?.parse().ok()?;
+    let address = fields.next()?;
+    let kind = fields.next()?;
+    let data = match fields.next() {
+        Some(hex) => Some(parse_hex(hex)?),
+        None => None,
+    };
+
+    fields
+        .next()
+        .is_none()
+        .then_some((time, id, address, kind, data))
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines() {
+        let (time, id, address, kind, data) =
+            parse_line("1.250 3 example.com:843 in 3c3f00").unwrap();
+        assert_eq!(time, Duration::from_millis(1250));
+        assert_eq!(id, 3);
+        assert_eq!(address, "example.com:843");
+        assert_eq!(kind, "in");
+        assert_eq!(data, Some(b"<?\0".to_vec()));
+
+        let (_, _, _, kind, data) = parse_line("0.000 1 localhost:8000 connect").unwrap();
+        assert_eq!(kind, "connect");
+        assert_eq!(data, None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(parse_line("0.000 1 localhost:8000").is_none());
+        assert!(parse_line("soon 1 localhost:8000 connect").is_none());
+        assert!(parse_line("-1.000 1 localhost:8000 connect").is_none());
+        assert!(parse_line("0.000 one localhost:8000 connect").is_none());
+        assert!(parse_line("0.000 1 localhost:8000 in abc").is_none());
+        assert!(parse_line("0.000 1 localhost:8000 in zz").is_none());
+        assert!(parse_line("0.000 1 localhost:8000 in 00 00").is_none());
+    }
+
+    #[test]
+    fn event_kinds_round_trip() {
+        let events = [
+            SocketEvent::Connected,
+            SocketEvent::ConnectFailed,
+            SocketEvent::ConnectTimedOut,
+            SocketEvent::Data(vec![0, 1, 0xff]),
+            SocketEvent::IoError,
+            SocketEvent::Closed,
+            SocketEvent::CertificateRejected(CertificateStatus::PrincipalMismatch),
+            SocketEvent::CertificateRejected(CertificateStatus::NotYetValid),
+        ];
+        for event in events {
+            let (kind, data) = event_kind(&event);
+            let parsed = parse_event(&kind, data.map(<[u8]>::to_vec)).unwrap();
+            assert_eq!(format!("{parsed:?}"), format!("{event:?}"));
+        }
+
+        assert!(parse_event("in", None).is_none());
+        assert!(parse_event("closed", Some(vec![0])).is_none());
+        assert!(parse_event("certificate-rejected:bogus", None).is_none());
+        assert!(parse_event("bogus", None).is_none());
+    }
+
+    #[test]
+    fn replay_schedules_connections_by_address() {
+        let replay = SocketReplay::parse(
+            "# A recording
+            1.000 1 localhost:8000 connect
+            1.500 1 localhost:8000 connected
+            1.600 1 localhost:8000 out 6869
+            2.000 1 localhost:8000 in 6f6b00
+            2.100 2 localhost:9000 connect
+            2.200 3 localhost:8000 connect
+            2.300 3 localhost:8000 connect-failed
+            2.400 1 localhost:8000 client-closed
+            2.500 2 localhost:9000 bogus
+            2.600 4 localhost:8000 closed
+            ",
+        );
+
+        let first = &replay.connections["localhost:8000"];
+        assert_eq!(first.len(), 2);
+        assert_eq!(
+            format!("{:?}", first[0]),
+            format!(
+                "{:?}",
+                [
+                    (Duration::from_millis(500), SocketEvent::Connected),
+                    (Duration::from_secs(1), SocketEvent::Data(b"ok\0".to_vec())),
+                ]
+            )
+        );
+        assert_eq!(
+            format!("{:?}", first[1]),
+            format!(
+                "{:?}",
+                [(Duration::from_millis(100), SocketEvent::ConnectFailed)]
+            )
+        );
+
+        let second = &replay.connections["localhost:9000"];
+        assert_eq!(second.len(), 1);
+        assert!(second[0].is_empty());
+    }
+}
@@ -2,11 +2,11 @@ use crate::RUFFLE_VERSION;
 use anyhow::Error;
 use clap::Parser;
 use ruffle_core::backend::navigator::OpenURLMode;
-use ruffle_core::config::Letterbox;
+use ruffle_core::config::{Letterbox, SocketPolicyMode};
 use ruffle_core::{LoadBehavior, StageAlign, StageScaleMode};
 use ruffle_render::quality::StageQuality;
 use ruffle_render_wgpu::clap::{GraphicsBackend, PowerPreference};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 #[derive(Parser, Debug)]
@@ -123,6 +123,18 @@ pub struct Opt {
     #[clap(long, default_value = "allow")]
     pub open_url_mode: OpenURLMode,
 
+    /// Whether socket connections must be permitted by a socket policy file, like in Flash Player.
+    #[clap(long, default_value = "ignore")]
+    pub socket_policy: SocketPolicyMode,
+
+    /// Record all socket traffic to the given file, so that it can be replayed later.
+    #[clap(long)]
+    pub record_sockets: Option<PathBuf>,
+
+    /// Replay socket traffic recorded with --record-sockets, instead of connecting to any server.
+    #[clap(long, conflicts_with = "record_sockets")]
+    pub replay_sockets: Option<PathBuf>,
+
     /// Provide a dummy (completely empty) External Interface to the movie.
     /// This may break some movies that expect an External Interface to be functional,
     /// but may fix others that always require an External Interface.
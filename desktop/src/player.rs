@@ -1,6 +1,6 @@
 use crate::backends::{
     CpalAudioBackend, DesktopExternalInterfaceProvider, DesktopUiBackend, DiskStorageBackend,
-    ExternalNavigatorBackend,
+    ExternalNavigatorBackend, SocketCapture,
 };
 use crate::cli::Opt;
 use crate::custom_event::RuffleEvent;
@@ -10,7 +10,7 @@ use crate::{CALLSTACK, RENDER_INFO, SWF_INFO};
 use anyhow::anyhow;
 use ruffle_core::backend::audio::AudioBackend;
 use ruffle_core::backend::navigator::OpenURLMode;
-use ruffle_core::config::Letterbox;
+use ruffle_core::config::{Letterbox, SocketPolicyMode};
 use ruffle_core::{LoadBehavior, Player, PlayerBuilder, PlayerEvent, StageAlign, StageScaleMode};
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::quality::StageQuality;
@@ -47,6 +47,7 @@ pub struct PlayerOptions {
     pub frame_rate: Option<f64>,
     pub open_url_mode: OpenURLMode,
     pub dummy_external_interface: bool,
+    pub socket_policy: SocketPolicyMode,
 }
 
 impl From<&Opt> for PlayerOptions {
@@ -72,6 +73,7 @@ impl From<&Opt> for PlayerOptions {
             frame_rate: value.frame_rate,
             open_url_mode: value.open_url_mode,
             dummy_external_interface: value.dummy_external_interface,
+            socket_policy: value.socket_policy,
         }
     }
 }
@@ -91,6 +93,7 @@ impl ActivePlayer {
         window: Rc<Window>,
         descriptors: Arc<Descriptors>,
         movie_view: MovieView,
+        socket_capture: SocketCapture,
     ) -> Self {
         let mut builder = PlayerBuilder::new();
 
@@ -112,6 +115,7 @@ impl ActivePlayer {
             opt.proxy.clone(),
             opt.upgrade_to_https,
             opt.open_url_mode,
+            socket_capture,
         );
 
         if cfg!(feature = "software_video") {
@@ -148,6 +152,7 @@ impl ActivePlayer {
             .with_load_behavior(opt.load_behavior)
             .with_spoofed_url(opt.spoof_url.clone().map(|url| url.to_string()))
             .with_player_version(Some(opt.player_version))
+            .with_socket_policy_mode(opt.socket_policy)
             .with_frame_rate(opt.frame_rate);
         let player = builder.build();
 
@@ -188,6 +193,9 @@ pub struct PlayerController {
     event_loop: EventLoopProxy<RuffleEvent>,
     window: Rc<Window>,
     descriptors: Arc<Descriptors>,
+
+    /// Shared by every player, so that reloading a movie doesn't start a new recording.
+    socket_capture: SocketCapture,
 }
 
 impl PlayerController {
@@ -195,12 +203,14 @@ impl PlayerController {
         event_loop: EventLoopProxy<RuffleEvent>,
         window: Rc<Window>,
         descriptors: Arc<Descriptors>,
+        socket_capture: SocketCapture,
     ) -> Self {
         Self {
             player: None,
             event_loop,
             window,
             descriptors,
+            socket_capture,
         }
     }
 
@@ -212,6 +222,7 @@ impl PlayerController {
             self.window.clone(),
             self.descriptors.clone(),
             movie_view,
+            self.socket_capture.clone(),
         ));
     }
 
@@ -14,23 +14,16 @@ use num_traits::FromPrimitive;
 
 use crate::varying::VaryingRegisters;
 use crate::{
-    types::*, Error, ShaderType, VertexAttributeFormat, MAX_VERTEX_ATTRIBUTES, SHADER_ENTRY_POINT,
+    types::*, Error, NagaShader, ShaderType, VertexAttributeFormat, MAX_COLOR_OUTPUTS,
+    MAX_TEXTURES, MAX_VERTEX_ATTRIBUTES, SHADER_ENTRY_POINT,
 };
 
-const VERTEX_PROGRAM_CONTANTS: u64 = 128;
-const FRAGMENT_PROGRAM_CONSTANTS: u64 = 28;
-
-const SAMPLER_REPEAT_LINEAR: usize = 0;
-const SAMPLER_REPEAT_NEAREST: usize = 1;
-const SAMPLER_CLAMP_LINEAR: usize = 2;
-const SAMPLER_CLAMP_NEAREST: usize = 3;
-const SAMPLER_CLAMP_U_REPEAT_V_LINEAR: usize = 4;
-const SAMPLER_CLAMP_U_REPEAT_V_NEAREST: usize = 5;
-const SAMPLER_REPEAT_U_CLAMP_V_LINEAR: usize = 6;
-const SAMPLER_REPEAT_U_CLAMP_V_NEAREST: usize = 7;
-
 const TEXTURE_SAMPLER_START_BIND_INDEX: u32 = 2;
 const TEXTURE_START_BIND_INDEX: u32 = 10;
+const EXTRA_SAMPLER_START_BIND_INDEX: u32 = TEXTURE_START_BIND_INDEX + MAX_TEXTURES as u32;
+
+// wgpu allows 16 samplers per shader stage, 8 of which are the samplers of each texture.
+const MAX_EXTRA_SAMPLERS: usize = 8;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -40,17 +33,6 @@ const SWIZZLE_XXXX: u8 = 0b00000000;
 const SWIZZLE_YYYY: u8 = 0b01010101;
 const SWIZZLE_ZZZZ: u8 = 0b10101010;
 const SWIZZLE_WWWW: u8 = 0b11111111;
-struct TextureSamplers {
-    repeat_linear: Handle<Expression>,
-    repeat_nearest: Handle<Expression>,
-    clamp_linear: Handle<Expression>,
-    clamp_nearest: Handle<Expression>,
-    clamp_u_repeat_v_linear: Handle<Expression>,
-    clamp_u_repeat_v_nearest: Handle<Expression>,
-    repeat_u_clamp_v_linear: Handle<Expression>,
-    repeat_u_clamp_v_nearest: Handle<Expression>,
-}
-
 pub(crate) struct NagaBuilder<'a> {
     pub(crate) module: Module,
     pub(crate) func: Function,
@@ -103,14 +85,37 @@ pub(crate) struct NagaBuilder<'a> {
     // The Naga representation of `texture_cube<f32>`
     imagecube: Handle<Type>,
 
-    // For a fragment shader, our 4 bound texture samplers
-    texture_samplers: Option<TextureSamplers>,
+    // For a fragment shader, the sampler of each texture
+    texture_samplers: Option<Vec<Handle<Expression>>>,
+
+    // How each sampler is used by the shader, filled in by the first `tex` instruction using it
+    sampler_configs: [Option<SamplerConfig>; MAX_TEXTURES],
+
+    // The samplers for `tex` instructions that sample a texture differently from the first one
+    // using it, keyed by texture and configuration
+    extra_samplers: Vec<(usize, SamplerConfig, Handle<Expression>)>,
+
+    // For a fragment shader, the additional color outputs (`oc1` to `oc3`) that were written to.
+    // The first color output is `dest`, so the first entry is always `None`.
+    pub(crate) color_outputs: [Option<ExtraOutput>; MAX_COLOR_OUTPUTS],
+
+    // For a fragment shader, the depth output (`fd`), if it was written to.
+    pub(crate) depth_output: Option<ExtraOutput>,
 
     // A stack of if/else blocks, using to push statements
     // into the correct block.
     blocks: Vec<BlockStackEntry>,
 }
 
+/// An output register other than the 'main' one, which is stored in a local variable
+/// until the shader returns.
+#[derive(Copy, Clone)]
+pub(crate) struct ExtraOutput {
+    pub(crate) local: Handle<Expression>,
+    // The index of the corresponding member in `return_type`
+    pub(crate) struct_index: usize,
+}
+
 /// Handles 'if' and 'else' blocks in AGAL bytecode.
 /// When we encounter an 'if' opcode, we push an `IfElse` entry onto the block stack.
 /// Any subsequent opcodes will be added to the `after_if` block.
@@ -249,22 +254,59 @@ impl VertexAttributeFormat {
 pub struct ShaderConfig<'a> {
     pub shader_type: ShaderType,
     pub vertex_attributes: &'a [Option<VertexAttributeFormat>; 8],
-    pub sampler_overrides: &'a [Option<SamplerOverride>; 8],
+    pub sampler_overrides: &'a [Option<SamplerConfig>; 8],
     pub version: AgalVersion,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub enum AgalVersion {
+    /// Used by the `baseline` profiles
     Agal1,
+    /// Used by the `standard` and `standardConstrained` profiles
     Agal2,
+    /// Used by the `standardExtended` profile
+    Agal3,
+}
+
+impl AgalVersion {
+    fn num_constants(self, shader_type: &ShaderType) -> u64 {
+        match (self, shader_type) {
+            (AgalVersion::Agal1, ShaderType::Vertex) => 128,
+            (AgalVersion::Agal1, ShaderType::Fragment) => 28,
+            (AgalVersion::Agal2 | AgalVersion::Agal3, ShaderType::Vertex) => 250,
+            (AgalVersion::Agal2, ShaderType::Fragment) => 64,
+            (AgalVersion::Agal3, ShaderType::Fragment) => 200,
+        }
+    }
+
+    fn num_temporaries(self) -> usize {
+        match self {
+            AgalVersion::Agal1 => 8,
+            AgalVersion::Agal2 | AgalVersion::Agal3 => 26,
+        }
+    }
+
+    fn num_color_outputs(self) -> usize {
+        match self {
+            AgalVersion::Agal1 => 1,
+            AgalVersion::Agal2 | AgalVersion::Agal3 => MAX_COLOR_OUTPUTS,
+        }
+    }
+
+    fn num_varyings(self) -> usize {
+        match self {
+            AgalVersion::Agal1 => 8,
+            AgalVersion::Agal2 | AgalVersion::Agal3 => 10,
+        }
+    }
 }
 
 impl<'a> NagaBuilder<'a> {
     pub fn process_agal(
         mut agal: &[u8],
         vertex_attributes: &[Option<VertexAttributeFormat>; MAX_VERTEX_ATTRIBUTES],
-        sampler_overrides: &[Option<SamplerOverride>; 8],
-    ) -> Result<Module> {
+        sampler_overrides: &[Option<SamplerConfig>; 8],
+    ) -> Result<NagaShader> {
         let data = &mut agal;
 
         let mut header = [0; 7];
@@ -278,6 +320,7 @@ impl<'a> NagaBuilder<'a> {
         let version = match version {
             1 => AgalVersion::Agal1,
             2 => AgalVersion::Agal2,
+            3 => AgalVersion::Agal3,
             _ => return Err(Error::InvalidVersion(version)),
         };
 
@@ -303,12 +346,14 @@ impl<'a> NagaBuilder<'a> {
             data.read_exact(&mut token)?;
             let raw_opcode = u32::from_le_bytes(token[0..4].try_into().unwrap());
 
-            let opcode = Opcode::from_u32(raw_opcode).ok_or(Error::InvalidOpcode(raw_opcode))?;
+            let opcode = Opcode::from_u32(raw_opcode)
+                .filter(|opcode| !matches!((opcode, version), (Opcode::Ted, AgalVersion::Agal1)))
+                .ok_or(Error::InvalidOpcode(raw_opcode))?;
 
             let dest = DestField::parse(u32::from_le_bytes(token[4..8].try_into().unwrap()))?;
             let source1 = SourceField::parse(u64::from_le_bytes(token[8..16].try_into().unwrap()))?;
 
-            let source2 = if let Opcode::Tex = opcode {
+            let source2 = if let Opcode::Tex | Opcode::Ted = opcode {
                 Source2::Sampler(SamplerField::parse(u64::from_le_bytes(
                     token[16..24].try_into().unwrap(),
                 ))?)
@@ -491,10 +536,11 @@ impl<'a> NagaBuilder<'a> {
                 specialization: None,
                 inner: ConstantInner::Scalar {
                     width: 4,
-                    value: ScalarValue::Uint(match shader_config.shader_type {
-                        ShaderType::Vertex => VERTEX_PROGRAM_CONTANTS,
-                        ShaderType::Fragment => FRAGMENT_PROGRAM_CONSTANTS,
-                    }),
+                    value: ScalarValue::Uint(
+                        shader_config
+                            .version
+                            .num_constants(&shader_config.shader_type),
+                    ),
                 },
             },
             Span::UNDEFINED,
@@ -530,7 +576,7 @@ impl<'a> NagaBuilder<'a> {
         );
 
         let texture_samplers = if let ShaderType::Fragment = shader_config.shader_type {
-            let samplers = (0..8)
+            let samplers = (0..MAX_TEXTURES as u32)
                 .map(|i| {
                     let var = module.global_variables.append(
                         GlobalVariable {
@@ -555,16 +601,7 @@ impl<'a> NagaBuilder<'a> {
                         .append(Expression::GlobalVariable(var), Span::UNDEFINED)
                 })
                 .collect::<Vec<_>>();
-            Some(TextureSamplers {
-                clamp_linear: samplers[SAMPLER_CLAMP_LINEAR],
-                clamp_nearest: samplers[SAMPLER_CLAMP_NEAREST],
-                repeat_linear: samplers[SAMPLER_REPEAT_LINEAR],
-                repeat_nearest: samplers[SAMPLER_REPEAT_NEAREST],
-                clamp_u_repeat_v_linear: samplers[SAMPLER_CLAMP_U_REPEAT_V_LINEAR],
-                clamp_u_repeat_v_nearest: samplers[SAMPLER_CLAMP_U_REPEAT_V_NEAREST],
-                repeat_u_clamp_v_linear: samplers[SAMPLER_REPEAT_U_CLAMP_V_LINEAR],
-                repeat_u_clamp_v_nearest: samplers[SAMPLER_REPEAT_U_CLAMP_V_NEAREST],
-            })
+            Some(samplers)
         } else {
             None
         };
@@ -574,11 +611,7 @@ impl<'a> NagaBuilder<'a> {
             Span::UNDEFINED,
         );
 
-        // FIXME - expose this to the wgpu code
-        let num_temporaries = match shader_config.version {
-            AgalVersion::Agal1 => 8,
-            AgalVersion::Agal2 => 26,
-        };
+        let num_temporaries = shader_config.version.num_temporaries();
 
         NagaBuilder {
             module,
@@ -594,6 +627,10 @@ impl<'a> NagaBuilder<'a> {
             vec4f,
             constant_registers,
             texture_samplers,
+            sampler_configs: [None; MAX_TEXTURES],
+            extra_samplers: Vec::new(),
+            color_outputs: [None; MAX_COLOR_OUTPUTS],
+            depth_output: None,
             texture_bindings: [None; 8],
             temporary_registers: vec![None; num_temporaries],
             image2d,
@@ -602,7 +639,32 @@ impl<'a> NagaBuilder<'a> {
         }
     }
 
+    /// Checks that a register exists in this kind of shader, so that a malformed program
+    /// results in an error instead of an invalid module.
+    pub(crate) fn check_register(&self, register_type: RegisterType, index: usize) -> Result<()> {
+        let ShaderConfig {
+            shader_type,
+            version,
+            ..
+        } = &self.shader_config;
+        let num_registers = match (register_type, shader_type) {
+            (RegisterType::Attribute, ShaderType::Vertex) => MAX_VERTEX_ATTRIBUTES,
+            (RegisterType::Constant, _) => version.num_constants(shader_type) as usize,
+            (RegisterType::Temporary, _) => version.num_temporaries(),
+            (RegisterType::Varying, _) => version.num_varyings(),
+            (RegisterType::Sampler, ShaderType::Fragment) => MAX_TEXTURES,
+            _ => 0,
+        };
+        if index >= num_registers {
+            return Err(Error::InvalidRegister(format!(
+                "{register_type:?} register {index} in {version:?} {shader_type:?} shader"
+            )));
+        }
+        Ok(())
+    }
+
     fn get_vertex_input(&mut self, index: usize) -> Result<Handle<Expression>> {
+        self.check_register(RegisterType::Attribute, index)?;
         if index >= self.vertex_input_expressions.len() {
             self.vertex_input_expressions.resize(index + 1, None);
         }
@@ -640,6 +702,7 @@ impl<'a> NagaBuilder<'a> {
     }
 
     fn get_temporary_register(&mut self, index: usize) -> Result<Handle<Expression>> {
+        self.check_register(RegisterType::Temporary, index)?;
         if self.temporary_registers[index].is_none() {
             let local = self.func.local_variables.append(
                 LocalVariable {
@@ -659,7 +722,97 @@ impl<'a> NagaBuilder<'a> {
         Ok(self.temporary_registers[index].unwrap())
     }
 
+    fn get_color_output_pointer(&mut self, index: usize) -> Result<Handle<Expression>> {
+        if index == 0 {
+            return Ok(self.dest);
+        }
+
+        if !matches!(self.shader_config.shader_type, ShaderType::Fragment)
+            || index >= self.shader_config.version.num_color_outputs()
+        {
+            return Err(Error::InvalidRegister(format!(
+                "Output register {index} in {:?} {:?} shader",
+                self.shader_config.version, self.shader_config.shader_type
+            )));
+        }
+
+        if self.color_outputs[index].is_none() {
+            let output = self.add_extra_output(
+                format!("color_output{index}"),
+                self.vec4f,
+                Binding::Location {
+                    location: index as u32,
+                    interpolation: None,
+                    sampling: None,
+                },
+            );
+            self.color_outputs[index] = Some(output);
+        }
+        Ok(self.color_outputs[index].unwrap().local)
+    }
+
+    fn get_depth_output_pointer(&mut self) -> Result<Handle<Expression>> {
+        if !matches!(self.shader_config.shader_type, ShaderType::Fragment)
+            || matches!(self.shader_config.version, AgalVersion::Agal1)
+        {
+            return Err(Error::InvalidRegister(format!(
+                "Depth output in {:?} {:?} shader",
+                self.shader_config.version, self.shader_config.shader_type
+            )));
+        }
+
+        if self.depth_output.is_none() {
+            // Only the first component of the register ends up in the depth buffer.
+            let float = VertexAttributeFormat::Float1.to_naga_type(&mut self.module);
+            let output = self.add_extra_output(
+                "depth_output".to_string(),
+                float,
+                Binding::BuiltIn(BuiltIn::FragDepth),
+            );
+            self.depth_output = Some(output);
+        }
+        Ok(self.depth_output.unwrap().local)
+    }
+
+    /// Creates the local variable backing an output register, along with the member
+    /// of the return type that it's eventually copied to.
+    fn add_extra_output(
+        &mut self,
+        name: String,
+        ty: Handle<Type>,
+        binding: Binding,
+    ) -> ExtraOutput {
+        let local = self.func.local_variables.append(
+            LocalVariable {
+                name: Some(name.clone()),
+                ty: self.vec4f,
+                init: None,
+            },
+            Span::UNDEFINED,
+        );
+        let local = self
+            .func
+            .expressions
+            .append(Expression::LocalVariable(local), Span::UNDEFINED);
+
+        let TypeInner::Struct { members, .. } = &mut self.return_type.inner else {
+            unreachable!();
+        };
+        members.push(StructMember {
+            name: Some(name),
+            ty,
+            binding: Some(binding),
+            offset: 0,
+        });
+
+        ExtraOutput {
+            local,
+            struct_index: members.len() - 1,
+        }
+    }
+
     fn emit_const_register_load(&mut self, index: usize) -> Result<Handle<Expression>> {
+        self.check_register(RegisterType::Constant, index)?;
         let index_const = self.module.constants.append(
             Constant {
                 name: None,
@@ -894,7 +1047,8 @@ impl<'a> NagaBuilder<'a> {
 
     fn emit_dest_store(&mut self, dest: &DestField, expr: Handle<Expression>) -> Result<()> {
         let base_expr = match dest.register_type {
-            RegisterType::Output => self.dest,
+            RegisterType::Output => self.get_color_output_pointer(dest.reg_num as usize)?,
+            RegisterType::Depth => self.get_depth_output_pointer()?,
             RegisterType::Varying => self.get_varying_pointer(dest.reg_num as usize)?,
             RegisterType::Temporary => self.get_temporary_register(dest.reg_num as usize)?,
             _ => {
@@ -1052,7 +1206,12 @@ impl<'a> NagaBuilder<'a> {
                 for i in 0..num_rows {
                     let source2_row = self.emit_source_field_load_with_swizzle_out(
                         &SourceField {
-                            reg_num: source2.reg_num + i,
+                            reg_num: source2.reg_num.checked_add(i).ok_or_else(|| {
+                                Error::InvalidRegister(format!(
+                                    "Matrix starting at register {}",
+                                    source2.reg_num
+                                ))
+                            })?,
                             ..source2.clone()
                         },
                         false,
@@ -1091,61 +1250,46 @@ impl<'a> NagaBuilder<'a> {
 
                 self.emit_dest_store(dest, extended_out)?;
             }
-            Opcode::Tex => {
+            Opcode::Tex | Opcode::Ted => {
                 let sampler_field = source2.assert_sampler();
 
-                let texture_samplers = self.texture_samplers.as_ref().unwrap();
-
                 let texture_id = sampler_field.reg_num;
                 if sampler_field.reg_type != RegisterType::Sampler {
-                    panic!("Invalid sample register type {:?}", sampler_field);
+                    return Err(Error::InvalidSampler(format!(
+                        "Invalid sample register type {:?}",
+                        sampler_field
+                    )));
                 }
-
-                let mut filter = sampler_field.filter;
-                let mut wrapping = sampler_field.wrapping;
+                self.check_register(RegisterType::Sampler, texture_id as usize)?;
 
                 // See https://github.com/openfl/openfl/issues/1332
 
                 // FIXME - Flash Player seems to unconditionally use sampler overrides,
                 // regardless of whether or not `ignore_sampler` is set. I haven't
-                // found any real SWFs that use it, so let's panic so that get
+                // found any real SWFs that use it, so let's report it so that we
                 // get a bug report if it ever happens.
                 if sampler_field.special.ignore_sampler {
-                    panic!("Found ignore_sampler in {:?}", sampler_field);
+                    return Err(Error::Unimplemented(format!(
+                        "Found ignore_sampler in {:?}",
+                        sampler_field
+                    )));
                 }
 
-                if let Some(sampler_override) =
-                    &self.shader_config.sampler_overrides[texture_id as usize]
-                {
-                    filter = sampler_override.filter;
-                    wrapping = sampler_override.wrapping;
-                }
-
-                let sampler_binding = match (filter, wrapping) {
-                    (Filter::Linear, Wrapping::Clamp) => texture_samplers.clamp_linear,
-                    (Filter::Linear, Wrapping::Repeat) => texture_samplers.repeat_linear,
-                    (Filter::Linear, Wrapping::ClampURepeatV) => {
-                        texture_samplers.clamp_u_repeat_v_linear
-                    }
-                    (Filter::Linear, Wrapping::RepeatUClampV) => {
-                        texture_samplers.repeat_u_clamp_v_linear
-                    }
-                    (Filter::Nearest, Wrapping::Clamp) => texture_samplers.clamp_nearest,
-                    (Filter::Nearest, Wrapping::Repeat) => texture_samplers.repeat_nearest,
-                    (Filter::Nearest, Wrapping::ClampURepeatV) => {
-                        texture_samplers.clamp_u_repeat_v_nearest
-                    }
-                    (Filter::Nearest, Wrapping::RepeatUClampV) => {
-                        texture_samplers.repeat_u_clamp_v_nearest
-                    }
-                };
+                let config = self.shader_config.sampler_overrides[texture_id as usize].unwrap_or(
+                    SamplerConfig {
+                        wrapping: sampler_field.wrapping,
+                        filter: sampler_field.filter,
+                        mipmap: sampler_field.mipmap,
+                    },
+                );
+                let sampler_binding = self.get_sampler(texture_id as usize, config);
 
-                let coord = self.emit_source_field_load(source1, false)?;
+                let source = self.emit_source_field_load(source1, false)?;
                 let coord = match sampler_field.dimension {
                     Dimension::TwoD => {
                         self.evaluate_expr(Expression::Swizzle {
                             size: VectorSize::Bi,
-                            vector: coord,
+                            vector: source,
                             // Only the first two components matter here
                             pattern: [
                                 SwizzleComponent::X,
@@ -1158,7 +1302,7 @@ impl<'a> NagaBuilder<'a> {
                     Dimension::Cube => {
                         self.evaluate_expr(Expression::Swizzle {
                             size: VectorSize::Tri,
-                            vector: coord,
+                            vector: source,
                             // Only the first three components matter here
                             pattern: [
                                 SwizzleComponent::X,
@@ -1170,6 +1314,53 @@ impl<'a> NagaBuilder<'a> {
                     }
                 };
 
+                // The LOD bias is stored in eighths of a mip level, and only matters when
+                // sampling from mip levels.
+                let uses_bias = matches!(opcode, Opcode::Ted) || config.mipmap != Mipmap::Disable;
+                let constant_bias = if uses_bias && sampler_field.texture_lod_bias != 0 {
+                    let bias = self.module.constants.append(
+                        Constant {
+                            name: None,
+                            specialization: None,
+                            inner: ConstantInner::Scalar {
+                                width: 4,
+                                value: ScalarValue::Float(
+                                    sampler_field.texture_lod_bias as f64 / 8.0,
+                                ),
+                            },
+                        },
+                        Span::UNDEFINED,
+                    );
+                    Some(
+                        self.func
+                            .expressions
+                            .append(Expression::Constant(bias), Span::UNDEFINED),
+                    )
+                } else {
+                    None
+                };
+
+                let level = if let Opcode::Ted = opcode {
+                    // `ted` takes an additional LOD bias from the last component of the coordinates.
+                    let bias = self.evaluate_expr(Expression::AccessIndex {
+                        base: source,
+                        index: 3,
+                    });
+                    let bias = match constant_bias {
+                        Some(constant_bias) => self.evaluate_expr(Expression::Binary {
+                            op: BinaryOperator::Add,
+                            left: bias,
+                            right: constant_bias,
+                        }),
+                        None => bias,
+                    };
+                    naga::SampleLevel::Bias(bias)
+                } else if let Some(bias) = constant_bias {
+                    naga::SampleLevel::Bias(bias)
+                } else {
+                    naga::SampleLevel::Auto
+                };
+
                 let image = self.emit_texture_load(texture_id as usize, sampler_field.dimension)?;
                 let tex = self.evaluate_expr(Expression::ImageSample {
                     image,
@@ -1177,8 +1368,7 @@ impl<'a> NagaBuilder<'a> {
                     coordinate: coord,
                     array_index: None,
                     offset: None,
-                    // FIXME - get this from 'LOD_bias' in the sampler field
-                    level: naga::SampleLevel::Auto,
+                    level,
                     depth_ref: None,
                     gather: None,
                 });
@@ -1360,14 +1550,24 @@ impl<'a> NagaBuilder<'a> {
                 } = self.blocks.last_mut().unwrap()
                 {
                     if !*in_after_if {
-                        panic!("Multiple' els' opcodes for single 'if' opcode");
+                        return Err(Error::InvalidControlFlow(
+                            "Multiple 'els' opcodes for single 'if' opcode".to_string(),
+                        ));
                     }
                     *in_after_if = false;
                 } else {
-                    unreachable!()
+                    return Err(Error::InvalidControlFlow(
+                        "'els' opcode without matching 'if'".to_string(),
+                    ));
                 }
             }
             Opcode::Eif => {
+                // The outermost block is never an 'if', and has to stay on the stack.
+                if self.blocks.len() < 2 {
+                    return Err(Error::InvalidControlFlow(
+                        "'eif' opcode without matching 'if'".to_string(),
+                    ));
+                }
                 let block = self.blocks.pop().unwrap();
 
                 match block {
@@ -1388,7 +1588,7 @@ impl<'a> NagaBuilder<'a> {
                         });
                     }
                     BlockStackEntry::Normal(block) => {
-                        panic!("Eif opcode without matching 'if': {:?}", block)
+                        unreachable!("Nested normal block: {:?}", block)
                     }
                 }
             }
@@ -1553,10 +1753,10 @@ impl<'a> NagaBuilder<'a> {
                 if ![SWIZZLE_XXXX, SWIZZLE_YYYY, SWIZZLE_ZZZZ, SWIZZLE_WWWW]
                     .contains(&source1.swizzle)
                 {
-                    panic!(
+                    return Err(Error::Unimplemented(format!(
                         "Kil op with source swizzle involving multiple distinct components: {:?}",
                         source1.swizzle
-                    );
+                    )));
                 }
 
                 let source = self.emit_source_field_load(source1, false)?;
@@ -1600,7 +1800,7 @@ impl<'a> NagaBuilder<'a> {
         Ok(())
     }
 
-    fn finish(mut self) -> Result<Module> {
+    fn finish(mut self) -> Result<NagaShader> {
         // We're consuming 'self', so just store store garbage here so that we can continue
         // to use methods on 'self'
         let return_ty = std::mem::replace(
@@ -1626,14 +1826,15 @@ impl<'a> NagaBuilder<'a> {
             value: Some(return_expr),
         });
 
+        if self.blocks.len() != 1 {
+            return Err(Error::InvalidControlFlow(
+                "'if' opcode without matching 'eif'".to_string(),
+            ));
+        }
         let block = match self.blocks.pop().unwrap() {
             BlockStackEntry::Normal(block) => block,
-            block => panic!("Unfinished if statement: {:?}", block),
+            block => unreachable!("Unbalanced blocks: {:?}", block),
         };
-
-        if !self.blocks.is_empty() {
-            panic!("Unbalanced blocks: {:?}", self.blocks);
-        }
         if !self.func.body.is_empty() {
             panic!("Incorrectly wrote to function body: {:?}", self.func.body);
         }
@@ -1650,7 +1851,76 @@ impl<'a> NagaBuilder<'a> {
             function: self.func,
         };
 
+        let is_fragment = matches!(self.shader_config.shader_type, ShaderType::Fragment);
+        let color_outputs =
+            std::array::from_fn(|i| is_fragment && (i == 0 || self.color_outputs[i].is_some()));
+
         self.module.entry_points.push(entry_point);
-        Ok(self.module)
+        Ok(NagaShader {
+            module: self.module,
+            sampler_configs: self.sampler_configs,
+            extra_sampler_configs: self
+                .extra_samplers
+                .into_iter()
+                .map(|(_, config, _)| config)
+                .collect(),
+            color_outputs,
+        })
+    }
+
+    /// The sampler to sample `texture_id` with, as configured by `config`.
+    ///
+    /// The first configuration used with a texture gets the sampler of that texture,
+    /// and every other one gets an extra sampler of its own.
+    fn get_sampler(&mut self, texture_id: usize, config: SamplerConfig) -> Handle<Expression> {
+        // `check_register` only allows samplers in fragment shaders, which always have these.
+        let Some(texture_samplers) = &self.texture_samplers else {
+            unreachable!("Missing samplers in fragment shader");
+        };
+        let texture_sampler = texture_samplers[texture_id];
+        let texture_config = *self.sampler_configs[texture_id].get_or_insert(config);
+        if texture_config == config {
+            return texture_sampler;
+        }
+
+        if let Some((_, _, sampler)) = self
+            .extra_samplers
+            .iter()
+            .find(|(id, extra_config, _)| *id == texture_id && *extra_config == config)
+        {
+            return *sampler;
+        }
+
+        // FIXME - there's no binding left, so this instruction samples like the first one.
+        if self.extra_samplers.len() == MAX_EXTRA_SAMPLERS {
+            return texture_sampler;
+        }
+
+        let index = self.extra_samplers.len() as u32;
+        let var = self.module.global_variables.append(
+            GlobalVariable {
+                name: Some(format!("sampler{}", MAX_TEXTURES as u32 + index)),
+                space: naga::AddressSpace::Handle,
+                binding: Some(naga::ResourceBinding {
+                    group: 0,
+                    binding: EXTRA_SAMPLER_START_BIND_INDEX + index,
+                }),
+                ty: self.module.types.insert(
+                    Type {
+                        name: None,
+                        inner: TypeInner::Sampler { comparison: false },
+                    },
+                    Span::UNDEFINED,
+                ),
+                init: None,
+            },
+            Span::UNDEFINED,
+        );
+        let sampler = self
+            .func
+            .expressions
+            .append(Expression::GlobalVariable(var), Span::UNDEFINED);
+        self.extra_samplers.push((texture_id, config, sampler));
+        sampler
     }
 }
@@ -1,4 +1,6 @@
-use naga::Module;
+use naga::valid::{Capabilities, ValidationError, ValidationFlags, Validator};
+use naga::{Module, WithSpan};
+use std::fmt;
 
 mod builder;
 mod types;
@@ -10,6 +12,12 @@ pub const SHADER_ENTRY_POINT: &str = "main";
 
 pub const MAX_VERTEX_ATTRIBUTES: usize = 8;
 pub const MAX_TEXTURES: usize = 8;
+pub const MAX_COLOR_OUTPUTS: usize = 4;
+
+/// The largest number of program constants available to a vertex shader, in any AGAL version.
+pub const MAX_VERTEX_CONSTANTS: usize = 250;
+/// The largest number of program constants available to a fragment shader, in any AGAL version.
+pub const MAX_FRAGMENT_CONSTANTS: usize = 200;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum VertexAttributeFormat {
@@ -29,24 +37,73 @@ pub enum Error {
     ReadError(std::io::Error),
     InvalidOpcode(u32),
     InvalidVersion(u32),
+    InvalidRegister(String),
+    InvalidRegisterType(u8),
+    InvalidSampler(String),
+    InvalidControlFlow(String),
+    Validation(Box<WithSpan<ValidationError>>),
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidHeader => write!(f, "Invalid program header"),
+            Error::InvalidShaderType(ty) => write!(f, "Invalid shader type {ty}"),
+            Error::MissingVertexAttributeData(index) => {
+                write!(f, "Missing vertex attribute data for va{index}")
+            }
+            Error::Unimplemented(message) => write!(f, "Unimplemented: {message}"),
+            Error::ReadError(err) => write!(f, "Program is truncated: {err}"),
+            Error::InvalidOpcode(opcode) => write!(f, "Invalid opcode {opcode:#x}"),
+            Error::InvalidVersion(version) => write!(f, "Invalid version {version}"),
+            Error::InvalidRegister(message) => write!(f, "Invalid register: {message}"),
+            Error::InvalidRegisterType(ty) => write!(f, "Invalid register type {ty}"),
+            Error::InvalidSampler(message) => write!(f, "Invalid sampler: {message}"),
+            Error::InvalidControlFlow(message) => write!(f, "Invalid control flow: {message}"),
+            Error::Validation(err) => write!(f, "Invalid program: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Error::ReadError(err)
     }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ShaderType {
     Vertex,
     Fragment,
 }
 
-pub use types::{Filter, Mipmap, SamplerOverride, Wrapping};
+pub use types::{Filter, Mipmap, SamplerConfig, Wrapping};
+
+// The parsed fields of AGAL instructions, for other consumers of the bytecode.
+pub use types::{
+    DestField, Dimension, DirectMode, Mask, Opcode, RegisterType, SamplerField, SourceField,
+};
+
+/// An AGAL shader, compiled to a Naga Module.
+pub struct NagaShader {
+    pub module: Module,
+
+    /// How each sampler is used by the shader. These have to match the samplers
+    /// bound alongside the shader - see `agal_to_naga`.
+    pub sampler_configs: [Option<SamplerConfig>; MAX_TEXTURES],
+
+    /// How each extra sampler is used by the shader, in binding order - see `agal_to_naga`.
+    pub extra_sampler_configs: Vec<SamplerConfig>,
+
+    /// Which color outputs are written by a fragment shader. These need a color
+    /// target bound at the same index - see `agal_to_naga`.
+    pub color_outputs: [bool; MAX_COLOR_OUTPUTS],
+}
 
 /**
- * Compiles an Adobe AGAL shader to a Naga Module.
+ * Compiles an Adobe AGAL shader to a Naga Module, along with the sampler configuration it needs.
  *
  * The `vertex_attributes` parameter is only used when compiling
  * a vertex shader.
@@ -56,6 +113,14 @@ pub use types::{Filter, Mipmap, SamplerOverride, Wrapping};
  *
  * The shader entrypoint is always named `main`.
  *
+ * AGAL versions 1 to 3 are supported, as used by the `baseline`, `standard` and
+ * `standardExtended` Context3D profiles. The register limits below are the ones of version 1,
+ * later versions have more constant, temporary and varying registers.
+ *
+ * Malformed bytecode (such as an unknown opcode, a register beyond these limits,
+ * or unbalanced 'if' opcodes) results in an `Error`, and the returned module has
+ * already been validated by Naga.
+ *
  * We compile an AGAL shader as follows:
  *
  * # Vertex Shader
@@ -89,11 +154,32 @@ pub use types::{Filter, Mipmap, SamplerOverride, Wrapping};
  * * Program constants - An AGAL fragment shader has access to 28 program constants.
  *   These are mapped to a single Naga uniform buffer, with a binding id of 1.
  *
+ * * Textures - An AGAL fragment shader can sample from 8 textures, with the binding ids 10 to 17.
+ *   Each texture is sampled with its own sampler, with the binding ids 2 to 9. Its configuration
+ *   comes from `sampler_overrides` if present, and from the first `tex` instruction using it otherwise,
+ *   and is returned in `NagaShader::sampler_configs`. A `tex` instruction that samples a texture
+ *   with different flags gets an extra sampler, with the binding ids 18 to 25, whose configurations
+ *   are returned in `NagaShader::extra_sampler_configs`. The LOD bias of each `tex` instruction
+ *   is applied by the shader itself.
+ *
+ * * Fragment output - The main color output (`oc` or `oc0`) has location 0. From AGAL version 2,
+ *   each *used* additional color output (`oc1` to `oc3`) is mapped to the location of the same
+ *   number, and the depth output (`fd`) is mapped to the Naga 'FragDepth' output, using its first
+ *   component.
+ *
  */
 pub fn agal_to_naga(
     agal: &[u8],
     vertex_attributes: &[Option<VertexAttributeFormat>; MAX_VERTEX_ATTRIBUTES],
-    sampler_overrides: &[Option<SamplerOverride>; MAX_TEXTURES],
-) -> Result<Module, Error> {
-    NagaBuilder::process_agal(agal, vertex_attributes, sampler_overrides)
+    sampler_overrides: &[Option<SamplerConfig>; MAX_TEXTURES],
+) -> Result<NagaShader, Error> {
+    let shader = NagaBuilder::process_agal(agal, vertex_attributes, sampler_overrides)?;
+
+    // A well-formed program can still be meaningless (e.g. a `kil` in a vertex shader),
+    // which we'd otherwise only find out about when handing the module to the GPU.
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&shader.module)
+        .map_err(|e| Error::Validation(Box::new(e)))?;
+
+    Ok(shader)
 }
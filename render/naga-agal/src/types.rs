@@ -2,7 +2,7 @@ use crate::Error;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 
-#[derive(num_derive::FromPrimitive, Debug)]
+#[derive(num_derive::FromPrimitive, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Opcode {
     Mov = 0x00,
     Add = 0x01,
@@ -30,6 +30,8 @@ pub enum Opcode {
     M33 = 0x17,
     M44 = 0x18,
     M34 = 0x19,
+    // Only available from AGAL version 2
+    Ted = 0x26,
     Kil = 0x27,
     Tex = 0x28,
     Sge = 0x29,
@@ -46,7 +48,7 @@ pub enum Opcode {
     Eif = 0x21,
 }
 
-#[derive(FromPrimitive, Debug, Clone, PartialEq, Eq)]
+#[derive(FromPrimitive, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RegisterType {
     Attribute = 0,
     Constant = 1,
@@ -54,10 +56,17 @@ pub enum RegisterType {
     Output = 3,
     Varying = 4,
     Sampler = 5,
-    FragmentRegister = 6,
+    // The fragment depth output, only available from AGAL version 2
+    Depth = 6,
 }
 
-#[derive(Debug, FromPrimitive, Clone)]
+impl RegisterType {
+    pub fn parse(val: u8) -> Result<RegisterType, Error> {
+        RegisterType::from_u8(val).ok_or(Error::InvalidRegisterType(val))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum DirectMode {
     Direct = 0,
     Indirect = 1,
@@ -73,8 +82,8 @@ pub struct DestField {
 impl DestField {
     pub fn parse(val: u32) -> Result<DestField, Error> {
         let reg_num = (val & 0xFFFF) as u16;
-        let write_mask = Mask::from_bits(((val >> 16) & 0xF) as u8).unwrap();
-        let reg_type = RegisterType::from_u16(((val >> 24) & 0xF) as u16).unwrap();
+        let write_mask = Mask::from_bits_truncate(((val >> 16) & 0xF) as u8);
+        let reg_type = RegisterType::parse(((val >> 24) & 0xF) as u8)?;
         Ok(DestField {
             register_type: reg_type,
             write_mask,
@@ -111,10 +120,14 @@ impl SourceField {
         let reg_num = (val & 0xFFFF) as u16;
         let indirect_offset = ((val >> 16) & 0xFF) as u8;
         let swizzle = ((val >> 24) & 0xFF) as u8;
-        let register_type = RegisterType::from_u16(((val >> 32) & 0xF) as u16).unwrap();
-        let index_type = RegisterType::from_u16(((val >> 40) & 0xF) as u16).unwrap();
+        let register_type = RegisterType::parse(((val >> 32) & 0xF) as u8)?;
+        let index_type = RegisterType::parse(((val >> 40) & 0xF) as u8)?;
         let index_select = ((val >> 48) & 0x3) as u8;
-        let direct_mode = DirectMode::from_u16(((val >> 63) & 0x1) as u16).unwrap();
+        let direct_mode = if (val >> 63) & 0x1 == 0 {
+            DirectMode::Direct
+        } else {
+            DirectMode::Indirect
+        };
         Ok(SourceField {
             direct_mode,
             index_select,
@@ -131,6 +144,10 @@ impl SourceField {
 pub enum Filter {
     Nearest = 0,
     Linear = 1,
+    Anisotropic2X = 2,
+    Anisotropic4X = 3,
+    Anisotropic8X = 4,
+    Anisotropic16X = 5,
 }
 
 #[derive(FromPrimitive, Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -181,8 +198,10 @@ pub struct SamplerField {
     pub reg_type: RegisterType,
 }
 
+/// How a texture is sampled, either as set by `Context3D.setSamplerStateAt`,
+/// or as encoded in the `tex` instructions of a shader.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct SamplerOverride {
+pub struct SamplerConfig {
     pub wrapping: Wrapping,
     pub filter: Filter,
     pub mipmap: Mipmap,
@@ -192,12 +211,16 @@ impl SamplerField {
     pub fn parse(val: u64) -> Result<SamplerField, Error> {
         let reg_num = (val & 0xFFFF) as u16;
         let load_bias = ((val >> 16) & 0xFF) as i8;
-        let reg_type = RegisterType::from_u64((val >> 32) & 0xF).unwrap();
-        let dimension = Dimension::from_u64((val >> 44) & 0xF).unwrap();
-        let special = Special::parse(((val >> 48) & 0xF) as u8).unwrap();
-        let wrapping = Wrapping::from_u64((val >> 52) & 0xF).unwrap();
-        let mipmap = Mipmap::from_u64((val >> 56) & 0xF).unwrap();
-        let filter = Filter::from_u64((val >> 60) & 0xF).unwrap();
+        let reg_type = RegisterType::parse(((val >> 32) & 0xF) as u8)?;
+        let dimension = Dimension::from_u64((val >> 44) & 0xF)
+            .ok_or_else(|| Error::InvalidSampler(format!("Invalid dimension in {val:#x}")))?;
+        let special = Special::parse(((val >> 48) & 0xF) as u8)?;
+        let wrapping = Wrapping::from_u64((val >> 52) & 0xF)
+            .ok_or_else(|| Error::InvalidSampler(format!("Invalid wrapping in {val:#x}")))?;
+        let mipmap = Mipmap::from_u64((val >> 56) & 0xF)
+            .ok_or_else(|| Error::InvalidSampler(format!("Invalid mipmap in {val:#x}")))?;
+        let filter = Filter::from_u64((val >> 60) & 0xF)
+            .ok_or_else(|| Error::InvalidSampler(format!("Invalid filter in {val:#x}")))?;
 
         Ok(SamplerField {
             filter,
@@ -3,7 +3,7 @@ use naga::{
     StructMember, Type, TypeInner,
 };
 
-use crate::{builder::NagaBuilder, Error, ShaderType};
+use crate::{builder::NagaBuilder, types::RegisterType, Error, ShaderType};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -24,6 +24,7 @@ pub struct VaryingRegister {
 
 impl<'a> NagaBuilder<'a> {
     pub fn get_varying_pointer(&mut self, index: usize) -> Result<Handle<Expression>> {
+        self.check_register(RegisterType::Varying, index)?;
         if index >= self.varying_registers.varying_pointers.len() {
             self.varying_registers
                 .varying_pointers
@@ -107,7 +108,8 @@ impl<'a> NagaBuilder<'a> {
     }
 
     /// Builds the final output struct expression, using the 'main' output (a position or color)
-    /// and any varying registers that were written to (if this is a vertex shader)
+    /// and any varying registers that were written to (if this is a vertex shader),
+    /// or any other color or depth outputs that were written to (if this is a fragment shader)
     pub fn build_output_expr(&mut self, return_ty: Handle<Type>) -> Result<Handle<Expression>> {
         // Load the 'main' output (a position or color) from our temporary location.
         let dest_load = self.evaluate_expr(Expression::Load { pointer: self.dest });
@@ -129,6 +131,31 @@ impl<'a> NagaBuilder<'a> {
             }
         }
 
+        if let ShaderType::Fragment = self.shader_config.shader_type {
+            for output in self.color_outputs.into_iter().flatten() {
+                if output.struct_index >= components.len() {
+                    components.resize(output.struct_index + 1, None);
+                }
+                components[output.struct_index] = Some(self.evaluate_expr(Expression::Load {
+                    pointer: output.local,
+                }));
+            }
+
+            if let Some(output) = self.depth_output {
+                if output.struct_index >= components.len() {
+                    components.resize(output.struct_index + 1, None);
+                }
+                let depth = self.evaluate_expr(Expression::Load {
+                    pointer: output.local,
+                });
+                components[output.struct_index] =
+                    Some(self.evaluate_expr(Expression::AccessIndex {
+                        base: depth,
+                        index: 0,
+                    }));
+            }
+        }
+
         let components = components.into_iter().map(|c| c.unwrap()).collect();
 
         Ok(self.evaluate_expr(Expression::Compose {
@@ -0,0 +1,353 @@
+//! Malformed AGAL programs, which have to result in an `Error` rather than a panic
+//! or an invalid module.
+
+use naga_agal::{agal_to_naga, Error, SamplerConfig, VertexAttributeFormat};
+
+const ATTRIBUTE: u8 = 0;
+const CONSTANT: u8 = 1;
+const TEMPORARY: u8 = 2;
+const OUTPUT: u8 = 3;
+const SAMPLER: u8 = 5;
+
+const MOV: u32 = 0x00;
+const M44: u32 = 0x18;
+const IFE: u32 = 0x1c;
+const ELS: u32 = 0x20;
+const EIF: u32 = 0x21;
+const KIL: u32 = 0x27;
+const TEX: u32 = 0x28;
+
+const VERTEX_ATTRIBUTES: [Option<VertexAttributeFormat>; 8] =
+    [Some(VertexAttributeFormat::Float4); 8];
+
+fn program(version: u8, fragment: bool, tokens: &[[u8; 24]]) -> Vec<u8> {
+    let mut program = vec![0xa0, version, 0, 0, 0, 0xa1, fragment as u8];
+    for token in tokens {
+        program.extend_from_slice(token);
+    }
+    program
+}
+
+fn token(opcode: u32, dest: u32, source1: u64, source2: u64) -> [u8; 24] {
+    let mut token = [0; 24];
+    token[0..4].copy_from_slice(&opcode.to_le_bytes());
+    token[4..8].copy_from_slice(&dest.to_le_bytes());
+    token[8..16].copy_from_slice(&source1.to_le_bytes());
+    token[16..24].copy_from_slice(&source2.to_le_bytes());
+    token
+}
+
+/// A destination register, with all components written.
+fn dest(register_type: u8, reg_num: u16) -> u32 {
+    reg_num as u32 | 0xf << 16 | (register_type as u32) << 24
+}
+
+/// A source register, with the `xyzw` swizzle.
+fn source(register_type: u8, reg_num: u16) -> u64 {
+    reg_num as u64 | 0xe4 << 24 | (register_type as u64) << 32
+}
+
+/// A 2D sampler with the default flags.
+fn sampler(reg_num: u16) -> u64 {
+    reg_num as u64 | (SAMPLER as u64) << 32
+}
+
+fn compile(program: &[u8]) -> Result<(), Error> {
+    agal_to_naga(program, &VERTEX_ATTRIBUTES, &[None; 8]).map(|_| ())
+}
+
+#[test]
+fn test_malformed_header() {
+    assert!(matches!(compile(&[]), Err(Error::ReadError(_))));
+
+    let mut bad_magic = program(1, false, &[]);
+    bad_magic[0] = 0;
+    assert!(matches!(compile(&bad_magic), Err(Error::InvalidHeader)));
+
+    assert!(matches!(
+        compile(&program(4, false, &[])),
+        Err(Error::InvalidVersion(4))
+    ));
+
+    let mut bad_shader_type = program(1, false, &[]);
+    bad_shader_type[6] = 2;
+    assert!(matches!(
+        compile(&bad_shader_type),
+        Err(Error::InvalidShaderType(2))
+    ));
+
+    let mov = token(MOV, dest(OUTPUT, 0), source(CONSTANT, 0), 0);
+    let truncated = program(1, false, &[mov]);
+    assert!(matches!(
+        compile(&truncated[..truncated.len() - 1]),
+        Err(Error::ReadError(_))
+    ));
+}
+
+#[test]
+fn test_malformed_tokens() {
+    assert!(matches!(
+        compile(&program(1, false, &[token(0x99, 0, 0, 0)])),
+        Err(Error::InvalidOpcode(0x99))
+    ));
+
+    assert!(matches!(
+        compile(&program(
+            1,
+            false,
+            &[token(MOV, dest(7, 0), source(CONSTANT, 0), 0)]
+        )),
+        Err(Error::InvalidRegisterType(7))
+    ));
+
+    assert!(matches!(
+        compile(&program(
+            1,
+            false,
+            &[token(MOV, dest(OUTPUT, 0), source(15, 0), 0)]
+        )),
+        Err(Error::InvalidRegisterType(15))
+    ));
+}
+
+#[test]
+fn test_register_limits() {
+    let read_constant = |version, reg_num| {
+        compile(&program(
+            version,
+            false,
+            &[token(MOV, dest(OUTPUT, 0), source(CONSTANT, reg_num), 0)],
+        ))
+    };
+    assert!(read_constant(1, 127).is_ok());
+    assert!(matches!(
+        read_constant(1, 128),
+        Err(Error::InvalidRegister(_))
+    ));
+    assert!(read_constant(2, 249).is_ok());
+    assert!(matches!(
+        read_constant(2, 250),
+        Err(Error::InvalidRegister(_))
+    ));
+
+    assert!(matches!(
+        compile(&program(
+            1,
+            false,
+            &[token(MOV, dest(TEMPORARY, 8), source(CONSTANT, 0), 0)]
+        )),
+        Err(Error::InvalidRegister(_))
+    ));
+
+    assert!(matches!(
+        compile(&program(
+            1,
+            true,
+            &[token(MOV, dest(OUTPUT, 0), source(ATTRIBUTE, 0), 0)]
+        )),
+        Err(Error::InvalidRegister(_))
+    ));
+
+    // The last rows of the matrix would be past the last register.
+    assert!(matches!(
+        compile(&program(
+            1,
+            false,
+            &[token(
+                M44,
+                dest(OUTPUT, 0),
+                source(ATTRIBUTE, 0),
+                source(CONSTANT, u16::MAX)
+            )]
+        )),
+        Err(Error::InvalidRegister(_))
+    ));
+}
+
+#[test]
+fn test_malformed_samplers() {
+    let tex = |fragment, sampler| {
+        compile(&program(
+            1,
+            fragment,
+            &[token(TEX, dest(OUTPUT, 0), source(CONSTANT, 0), sampler)],
+        ))
+    };
+    assert!(tex(true, sampler(7)).is_ok());
+    assert!(matches!(
+        tex(true, sampler(8)),
+        Err(Error::InvalidRegister(_))
+    ));
+    assert!(matches!(
+        tex(false, sampler(0)),
+        Err(Error::InvalidRegister(_))
+    ));
+    assert!(matches!(
+        tex(true, source(CONSTANT, 0)),
+        Err(Error::InvalidSampler(_))
+    ));
+    assert!(matches!(
+        tex(true, sampler(0) | 0xf << 60),
+        Err(Error::InvalidSampler(_))
+    ));
+}
+
+#[test]
+fn test_unbalanced_conditionals() {
+    let ife = token(IFE, 0, source(CONSTANT, 0), source(CONSTANT, 1));
+    let els = token(ELS, 0, 0, 0);
+    let eif = token(EIF, 0, 0, 0);
+    let mov = token(MOV, dest(OUTPUT, 0), source(CONSTANT, 0), 0);
+
+    assert!(compile(&program(1, true, &[ife, mov, els, mov, eif])).is_ok());
+
+    let unbalanced: [&[[u8; 24]]; 5] = [
+        &[els],
+        &[eif],
+        &[ife, mov],
+        &[ife, els, els, eif],
+        &[ife, eif, eif],
+    ];
+    for tokens in unbalanced {
+        assert!(matches!(
+            compile(&program(1, true, tokens)),
+            Err(Error::InvalidControlFlow(_))
+        ));
+    }
+}
+
+#[test]
+fn test_invalid_modules() {
+    // `kil` can only be used in fragment shaders.
+    let kil = token(KIL, 0, 1 << 32, 0);
+    assert!(compile(&program(1, true, &[kil])).is_ok());
+    assert!(matches!(
+        compile(&program(1, false, &[kil])),
+        Err(Error::Validation(_))
+    ));
+}
+
+/// A small xorshift generator, so that the same programs are generated on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// Registers are mostly small, with the occasional arbitrary number.
+fn random_reg_num(rng: &mut Rng) -> u64 {
+    if rng.below(16) == 0 {
+        rng.below(1 << 16)
+    } else {
+        rng.below(12)
+    }
+}
+
+fn random_source(rng: &mut Rng) -> u64 {
+    let mut source = random_reg_num(rng) | rng.below(256) << 24 | rng.below(7) << 32;
+    if rng.below(8) == 0 {
+        source |= rng.below(256) << 16 | rng.below(7) << 40 | rng.below(4) << 48 | 1 << 63;
+    }
+    source
+}
+
+fn random_token(rng: &mut Rng) -> [u8; 24] {
+    const OPCODES: &[u32] = &[
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f, 0x20, 0x21, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2c, 0x2d,
+    ];
+
+    if rng.below(64) == 0 {
+        let mut token = [0; 24];
+        for byte in &mut token {
+            *byte = rng.next() as u8;
+        }
+        return token;
+    }
+
+    let opcode = OPCODES[rng.below(OPCODES.len() as u64) as usize];
+    let dest =
+        random_reg_num(rng) as u32 | (rng.below(16) as u32) << 16 | (rng.below(7) as u32) << 24;
+    let source1 = random_source(rng);
+    let source2 = if opcode == TEX || opcode == 0x26 {
+        random_reg_num(rng)
+            | rng.below(256) << 16
+            | (SAMPLER as u64) << 32
+            | rng.below(2) << 44
+            | rng.below(4) << 52
+            | rng.below(3) << 56
+            | rng.below(6) << 60
+    } else {
+        random_source(rng)
+    };
+    token(opcode, dest, source1, source2)
+}
+
+#[test]
+fn test_random_programs() {
+    const FORMATS: &[VertexAttributeFormat] = &[
+        VertexAttributeFormat::Float1,
+        VertexAttributeFormat::Float2,
+        VertexAttributeFormat::Float3,
+        VertexAttributeFormat::Float4,
+        VertexAttributeFormat::Bytes4,
+    ];
+
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..5000 {
+        let tokens: Vec<_> = (0..1 + rng.below(8))
+            .map(|_| random_token(&mut rng))
+            .collect();
+        let agal = program(1 + rng.below(3) as u8, rng.below(2) == 1, &tokens);
+
+        let vertex_attributes = std::array::from_fn(|_| match rng.below(6) {
+            0 => None,
+            n => Some(FORMATS[n as usize - 1]),
+        });
+        let sampler_overrides: [Option<SamplerConfig>; 8] = [None; 8];
+
+        // Any result is fine, as long as we get one.
+        let _ = agal_to_naga(&agal, &vertex_attributes, &sampler_overrides);
+    }
+}
+
+#[test]
+fn test_corrupted_programs() {
+    let programs: &[&[u8]] = &[
+        include!("fractal_vertex.agal"),
+        include!("fractal_fragment.agal"),
+        include!("raytrace_vertex.agal"),
+        include!("raytrace_fragment.agal"),
+        include!("relative_vertex.agal"),
+        include!("misc_opcodes_vertex.agal"),
+        include!("misc_opcodes_fragment.agal"),
+    ];
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for original in programs {
+        assert!(compile(original).is_ok());
+
+        for len in 0..original.len() {
+            let _ = compile(&original[..len]);
+        }
+
+        for _ in 0..500 {
+            let mut corrupted = original.to_vec();
+            for _ in 0..1 + rng.below(4) {
+                let index = rng.below(corrupted.len() as u64) as usize;
+                corrupted[index] ^= 1 << rng.below(8);
+            }
+            let _ = compile(&corrupted);
+        }
+    }
+}
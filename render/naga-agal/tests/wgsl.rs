@@ -2,7 +2,9 @@ use naga::{
     valid::{Capabilities, ValidationFlags, Validator},
     Module,
 };
-use naga_agal::{agal_to_naga, VertexAttributeFormat};
+use naga_agal::{
+    agal_to_naga, Error, Filter, Mipmap, SamplerConfig, VertexAttributeFormat, Wrapping,
+};
 
 pub fn to_wgsl(module: &Module) -> String {
     let mut out = String::new();
@@ -22,7 +24,7 @@ pub fn to_wgsl(module: &Module) -> String {
 // Making this a macro gives us a better span in 'inta'
 macro_rules! test_shader {
     ($shader:expr, $attrs:expr, $shader_type:expr $(,)?) => {
-        let module = agal_to_naga(&$shader, $attrs, &[None; 8]).unwrap();
+        let module = agal_to_naga(&$shader, $attrs, &[None; 8]).unwrap().module;
         let output = to_wgsl(&module);
         insta::assert_display_snapshot!(output);
     };
@@ -198,3 +200,136 @@ fn test_misc_opcodes() {
         ShaderType::Fragment
     );
 }
+
+#[test]
+fn test_sampler_config() {
+    // tex oc, v0, fs0 <2d, linear, miplinear, repeat, 1.0>
+    const TEX_FRAGMENT: &[u8] = &[
+        160, 1, 0, 0, 0, 161, 1, 40, 0, 0, 0, 0, 0, 15, 3, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 8, 0, 5,
+        0, 16, 18,
+    ];
+
+    let shader = agal_to_naga(TEX_FRAGMENT, &[None; 8], &[None; 8]).unwrap();
+    assert_eq!(
+        shader.sampler_configs[0],
+        Some(SamplerConfig {
+            wrapping: Wrapping::Repeat,
+            filter: Filter::Linear,
+            mipmap: Mipmap::Linear,
+        })
+    );
+    assert!(shader.sampler_configs[1..].iter().all(Option::is_none));
+    assert!(to_wgsl(&shader.module).contains("textureSampleBias(texture0_, sampler0_, "));
+
+    // `setSamplerStateAt` takes precedence over the flags of the instruction,
+    // and the bias is ignored without mipmapping.
+    let sampler_override = SamplerConfig {
+        wrapping: Wrapping::Clamp,
+        filter: Filter::Anisotropic4X,
+        mipmap: Mipmap::Disable,
+    };
+    let mut sampler_overrides = [None; 8];
+    sampler_overrides[0] = Some(sampler_override);
+    let shader = agal_to_naga(TEX_FRAGMENT, &[None; 8], &sampler_overrides).unwrap();
+    assert_eq!(shader.sampler_configs[0], Some(sampler_override));
+    assert!(to_wgsl(&shader.module).contains("textureSample(texture0_, sampler0_, "));
+}
+
+#[test]
+fn test_sampler_per_config() {
+    // tex ft0, v0, fs0 <2d, linear, miplinear, repeat, 1.0>
+    // tex ft1, v0, fs0 <2d, nearest, mipnone, clamp>
+    // tex ft2, v0, fs0 <2d, linear, miplinear, repeat>
+    // add oc, ft0, ft1
+    const TEX_FRAGMENT: &[u8] = &[
+        160, 1, 0, 0, 0, 161, 1, 40, 0, 0, 0, 0, 0, 15, 2, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 8, 0, 5,
+        0, 16, 18, 40, 0, 0, 0, 1, 0, 15, 2, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 40,
+        0, 0, 0, 2, 0, 15, 2, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 0, 0, 5, 0, 16, 18, 1, 0, 0, 0, 0, 0,
+        15, 3, 0, 0, 0, 228, 2, 0, 0, 0, 1, 0, 0, 228, 2, 0, 0, 0,
+    ];
+
+    let shader = agal_to_naga(TEX_FRAGMENT, &[None; 8], &[None; 8]).unwrap();
+    assert_eq!(
+        shader.sampler_configs[0],
+        Some(SamplerConfig {
+            wrapping: Wrapping::Repeat,
+            filter: Filter::Linear,
+            mipmap: Mipmap::Linear,
+        })
+    );
+    // The second instruction samples the same texture differently, so it gets a sampler
+    // of its own. The third one shares the sampler of the first.
+    assert_eq!(
+        shader.extra_sampler_configs,
+        [SamplerConfig {
+            wrapping: Wrapping::Clamp,
+            filter: Filter::Nearest,
+            mipmap: Mipmap::Disable,
+        }]
+    );
+
+    let output = to_wgsl(&shader.module);
+    assert!(output.contains("@group(0) @binding(18) \nvar sampler8_: sampler;"));
+    assert!(!output.contains("sampler9_"));
+    assert_eq!(
+        output
+            .matches("textureSampleBias(texture0_, sampler0_, ")
+            .count(),
+        1
+    );
+    assert_eq!(
+        output
+            .matches("textureSample(texture0_, sampler8_, ")
+            .count(),
+        1
+    );
+    assert_eq!(
+        output
+            .matches("textureSample(texture0_, sampler0_, ")
+            .count(),
+        1
+    );
+
+    // With `setSamplerStateAt`, every instruction uses the same sampler.
+    let mut sampler_overrides = [None; 8];
+    sampler_overrides[0] = Some(SamplerConfig {
+        wrapping: Wrapping::Clamp,
+        filter: Filter::Linear,
+        mipmap: Mipmap::Nearest,
+    });
+    let shader = agal_to_naga(TEX_FRAGMENT, &[None; 8], &sampler_overrides).unwrap();
+    assert_eq!(shader.sampler_configs[0], sampler_overrides[0]);
+    assert!(shader.extra_sampler_configs.is_empty());
+}
+
+#[test]
+fn test_agal2_outputs() {
+    // ted ft0, v0, fs0 <2d, linear, miplinear, repeat>
+    // mov oc, ft0
+    // mov oc1, v0
+    // mov fd.x, ft0.z
+    let mut fragment = vec![
+        160, 2, 0, 0, 0, 161, 1, 38, 0, 0, 0, 0, 0, 15, 2, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 0, 0, 5,
+        0, 16, 18, 0, 0, 0, 0, 0, 0, 15, 3, 0, 0, 0, 228, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 1, 0, 15, 3, 0, 0, 0, 228, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        6, 0, 0, 0, 170, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    let shader = agal_to_naga(&fragment, &[None; 8], &[None; 8]).unwrap();
+    let output = to_wgsl(&shader.module);
+    assert!(output.contains("textureSampleBias(texture0_, sampler0_, "));
+    assert!(output.contains("@location(1)"));
+    assert!(output.contains("@builtin(frag_depth)"));
+
+    // AGAL 1 has neither `ted` nor the additional outputs.
+    fragment[1] = 1;
+    assert!(matches!(
+        agal_to_naga(&fragment, &[None; 8], &[None; 8]),
+        Err(Error::InvalidOpcode(0x26))
+    ));
+    fragment.drain(7..31);
+    assert!(matches!(
+        agal_to_naga(&fragment, &[None; 8], &[None; 8]),
+        Err(Error::InvalidRegister(_))
+    ));
+}
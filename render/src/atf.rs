@@ -0,0 +1,690 @@
+//! Adobe Texture Format (ATF) support, for `uploadCompressedTextureFromByteArray`.
+//!
+//! An ATF file holds the mip levels of one texture (or of the six faces of a cube texture),
+//! either as JPEG-XR images (`Rgb888` and `Rgba8888`) or as blocks of GPU compressed formats.
+//! The latter are either stored as is (`RawCompressed*`), or packed as LZMA-compressed block
+//! indices along with two JPEG-XR images holding the endpoints of every block.
+//! We only support the DXT1 and DXT5 blocks of either, and decode everything to RGBA,
+//! which is what our Stage3D textures use.
+
+use crate::backend::Context3DTextureFormat;
+use crate::bitmap::{Bitmap, BitmapFormat};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::{Cursor, Read};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AtfError {
+    #[error("Invalid ATF signature")]
+    InvalidSignature,
+
+    #[error("Unexpected end of ATF data")]
+    UnexpectedEof(#[from] std::io::Error),
+
+    #[error("Unknown ATF format {0:#x}")]
+    UnknownFormat(u8),
+
+    #[error("Invalid ATF dimensions: 2^{0} x 2^{1}")]
+    InvalidDimensions(u8, u8),
+
+    #[error("Invalid ATF mip level count {0}")]
+    InvalidMipCount(u8),
+
+    #[error("Image data is too short: expected {expected} bytes, got {actual}")]
+    TruncatedImage { expected: usize, actual: usize },
+
+    #[error("JPEG-XR images are not supported in this build")]
+    JpegXrUnsupported,
+
+    #[error("Couldn't decode JPEG-XR image: {0}")]
+    JpegXr(String),
+
+    #[error("Couldn't decompress LZMA data: {0}")]
+    Lzma(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AtfFormat {
+    Rgb888,
+    Rgba8888,
+    Compressed,
+    RawCompressed,
+    CompressedAlpha,
+    RawCompressedAlpha,
+    CompressedLossy,
+    CompressedLossyAlpha,
+}
+
+impl AtfFormat {
+    fn from_u8(format: u8) -> Result<Self, AtfError> {
+        Ok(match format {
+            0x0 => AtfFormat::Rgb888,
+            0x1 => AtfFormat::Rgba8888,
+            0x2 => AtfFormat::Compressed,
+            0x3 => AtfFormat::RawCompressed,
+            0x4 => AtfFormat::CompressedAlpha,
+            0x5 => AtfFormat::RawCompressedAlpha,
+            0xc => AtfFormat::CompressedLossy,
+            0xd => AtfFormat::CompressedLossyAlpha,
+            _ => return Err(AtfError::UnknownFormat(format)),
+        })
+    }
+
+    /// Whether a texture created with `format` can be uploaded from ATF data in this format.
+    pub fn is_compatible_with(self, format: Context3DTextureFormat) -> bool {
+        match self {
+            AtfFormat::Rgb888 | AtfFormat::Rgba8888 => matches!(
+                format,
+                Context3DTextureFormat::Bgra
+                    | Context3DTextureFormat::BgraPacked
+                    | Context3DTextureFormat::BgrPacked
+            ),
+            AtfFormat::Compressed | AtfFormat::RawCompressed | AtfFormat::CompressedLossy => {
+                matches!(format, Context3DTextureFormat::Compressed)
+            }
+            AtfFormat::CompressedAlpha
+            | AtfFormat::RawCompressedAlpha
+            | AtfFormat::CompressedLossyAlpha => {
+                matches!(format, Context3DTextureFormat::CompressedAlpha)
+            }
+        }
+    }
+}
+
+/// The encoded data of a single mip level of a single face.
+#[derive(Debug, Copy, Clone)]
+enum AtfImage<'a> {
+    JpegXr(&'a [u8]),
+    Dxt1(&'a [u8]),
+    Dxt5(&'a [u8]),
+
+    /// DXT1 (or DXT5, if `alpha` is set) blocks, split into their LZMA-compressed indices
+    /// and two JPEG-XR images holding the first and second endpoints of every block.
+    PackedDxt {
+        indices: &'a [u8],
+        top: &'a [u8],
+        bottom: &'a [u8],
+        alpha: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct AtfTexture<'a> {
+    pub format: AtfFormat,
+    pub cubemap: bool,
+    pub width: u32,
+    pub height: u32,
+
+    /// The images of each face (six for a cube map, one otherwise), with the largest
+    /// mip level first.
+    faces: Vec<Vec<AtfImage<'a>>>,
+}
+
+impl<'a> AtfTexture<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, AtfError> {
+        let mut reader = Cursor::new(data);
+
+        let mut signature = [0; 3];
+        reader.read_exact(&mut signature)?;
+        if &signature != b"ATF" {
+            return Err(AtfError::InvalidSignature);
+        }
+
+        // Version 0 files have a 24-bit length right after the signature. Later versions
+        // have three reserved bytes, a 0xFF marker, the version and a 32-bit length.
+        let version = if data.get(6) == Some(&0xFF) {
+            reader.set_position(7);
+            let version = reader.read_u8()?;
+            reader.read_u32::<BigEndian>()?;
+            version
+        } else {
+            reader.read_u24::<BigEndian>()?;
+            0
+        };
+
+        let format = reader.read_u8()?;
+        let cubemap = format & 0x80 != 0;
+        let format = AtfFormat::from_u8(format & 0x7F)?;
+
+        let log2_width = reader.read_u8()?;
+        let log2_height = reader.read_u8()?;
+        if log2_width > 12 || log2_height > 12 {
+            return Err(AtfError::InvalidDimensions(log2_width, log2_height));
+        }
+        let width = 1 << log2_width;
+        let height = 1 << log2_height;
+        let mip_count = reader.read_u8()?;
+        if mip_count == 0 || mip_count > log2_width.max(log2_height) + 1 {
+            return Err(AtfError::InvalidMipCount(mip_count));
+        }
+
+        let num_faces = if cubemap { 6 } else { 1 };
+        let mut faces = Vec::with_capacity(num_faces);
+        for _ in 0..num_faces {
+            let mut levels = Vec::with_capacity(mip_count as usize);
+            for _ in 0..mip_count {
+                let image = match format {
+                    AtfFormat::Rgb888 | AtfFormat::Rgba8888 => {
+                        AtfImage::JpegXr(read_block(&mut reader, version)?)
+                    }
+                    AtfFormat::RawCompressed | AtfFormat::RawCompressedAlpha => {
+                        let dxt = read_block(&mut reader, version)?;
+                        // Skip the PVRTC and ETC1 blocks, and the ETC2 block added in version 3.
+                        let skipped = if version >= 3 { 3 } else { 2 };
+                        for _ in 0..skipped {
+                            read_block(&mut reader, version)?;
+                        }
+                        if format == AtfFormat::RawCompressed {
+                            AtfImage::Dxt1(dxt)
+                        } else {
+                            AtfImage::Dxt5(dxt)
+                        }
+                    }
+                    AtfFormat::Compressed
+                    | AtfFormat::CompressedAlpha
+                    | AtfFormat::CompressedLossy
+                    | AtfFormat::CompressedLossyAlpha => {
+                        let indices = read_block(&mut reader, version)?;
+                        let top = read_block(&mut reader, version)?;
+                        let bottom = read_block(&mut reader, version)?;
+                        // Skip the PVRTC and ETC1 codecs, and the ETC2 codec added in version 3,
+                        // which are made of three blocks each too.
+                        let skipped = if version >= 3 { 9 } else { 6 };
+                        for _ in 0..skipped {
+                            read_block(&mut reader, version)?;
+                        }
+                        AtfImage::PackedDxt {
+                            indices,
+                            top,
+                            bottom,
+                            alpha: matches!(
+                                format,
+                                AtfFormat::CompressedAlpha | AtfFormat::CompressedLossyAlpha
+                            ),
+                        }
+                    }
+                };
+                levels.push(image);
+            }
+            faces.push(levels);
+        }
+
+        Ok(Self {
+            format,
+            cubemap,
+            width,
+            height,
+            faces,
+        })
+    }
+
+    /// The number of mip levels stored for each face.
+    pub fn mip_count(&self) -> u32 {
+        self.faces.first().map_or(0, |levels| levels.len() as u32)
+    }
+
+    /// Decode the given mip level of the given face into an RGBA bitmap.
+    pub fn decode(&self, face: u32, mip_level: u32) -> Result<Bitmap, AtfError> {
+        let width = (self.width >> mip_level).max(1);
+        let height = (self.height >> mip_level).max(1);
+        let image = self.faces[face as usize][mip_level as usize];
+
+        let rgba = match image {
+            AtfImage::JpegXr(data) => decode_jpegxr(data, width, height)?,
+            AtfImage::Dxt1(data) => decode_dxt(data, width, height, false)?,
+            AtfImage::Dxt5(data) => decode_dxt(data, width, height, true)?,
+            AtfImage::PackedDxt {
+                indices,
+                top,
+                bottom,
+                alpha,
+            } => {
+                let dxt = unpack_dxt(indices, top, bottom, width, height, alpha)?;
+                decode_dxt(&dxt, width, height, alpha)?
+            }
+        };
+
+        Ok(Bitmap::new(width, height, BitmapFormat::Rgba, rgba))
+    }
+}
+
+/// Read a length-prefixed block of data.
+fn read_block<'a>(reader: &mut Cursor<&'a [u8]>, version: u8) -> Result<&'a [u8], AtfError> {
+    let len = if version == 0 {
+        reader.read_u24::<BigEndian>()?
+    } else {
+        reader.read_u32::<BigEndian>()?
+    } as usize;
+
+    let data = *reader.get_ref();
+    let start = reader.position() as usize;
+    let block = data
+        .get(start..start.saturating_add(len))
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+    reader.set_position((start + len) as u64);
+    Ok(block)
+}
+
+#[cfg(feature = "jpegxr")]
+fn decode_jpegxr(data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, AtfError> {
+    use jpegxr::{ImageDecode, PixelInfo, Rect};
+
+    let to_error = |e: jpegxr::JXRError| AtfError::JpegXr(format!("{e:?}"));
+
+    let mut decoder = ImageDecode::with_reader(Cursor::new(data)).map_err(to_error)?;
+    let (image_width, image_height) = decoder.get_size().map_err(to_error)?;
+    if image_width as u32 != width || image_height as u32 != height {
+        return Err(AtfError::JpegXr(format!(
+            "image is {image_width}x{image_height}, expected {width}x{height}"
+        )));
+    }
+
+    let info = PixelInfo::from_format(decoder.get_pixel_format().map_err(to_error)?);
+    let bytes_per_pixel = info.bits_per_pixel() / 8;
+    let stride = width as usize * bytes_per_pixel;
+    let mut pixels = vec![0; stride * height as usize];
+    decoder
+        .copy(
+            &Rect::new(0, 0, width as i32, height as i32),
+            &mut pixels,
+            stride,
+        )
+        .map_err(to_error)?;
+
+    match bytes_per_pixel {
+        3 => Ok(pixels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 0xFF])
+            .collect()),
+        4 => Ok(pixels),
+        _ => Err(AtfError::JpegXr(format!(
+            "unexpected pixel format with {bytes_per_pixel} bytes per pixel"
+        ))),
+    }
+}
+
+#[cfg(not(feature = "jpegxr"))]
+fn decode_jpegxr(_data: &[u8], _width: u32, _height: u32) -> Result<Vec<u8>, AtfError> {
+    Err(AtfError::JpegXrUnsupported)
+}
+
+fn decompress_lzma(data: &[u8], size: usize) -> Result<Vec<u8>, AtfError> {
+    use lzma_rs::decompress::{Options, UnpackedSize};
+
+    // The LZMA properties aren't followed by the unpacked size, which we know already.
+    let mut output = Vec::with_capacity(size);
+    lzma_rs::lzma_decompress_with_options(
+        &mut Cursor::new(data),
+        &mut output,
+        &Options {
+            unpacked_size: UnpackedSize::UseProvided(Some(size as u64)),
+            allow_incomplete: false,
+            memlimit: None,
+        },
+    )
+    .map_err(|e| AtfError::Lzma(e.to_string()))?;
+
+    if output.len() < size {
+        return Err(AtfError::TruncatedImage {
+            expected: size,
+            actual: output.len(),
+        });
+    }
+    Ok(output)
+}
+
+/// Rebuild the DXT1 (or DXT5, if `alpha` is set) blocks of a `PackedDxt` image.
+fn unpack_dxt(
+    indices: &[u8],
+    top: &[u8],
+    bottom: &[u8],
+    width: u32,
+    height: u32,
+    alpha: bool,
+) -> Result<Vec<u8>, AtfError> {
+    let blocks_x = (width + 3) / 4;
+    let blocks_y = (height + 3) / 4;
+    let index_size = if alpha { 10 } else { 4 };
+    let indices = decompress_lzma(indices, blocks_x as usize * blocks_y as usize * index_size)?;
+    let top = decode_jpegxr(top, blocks_x, blocks_y)?;
+    let bottom = decode_jpegxr(bottom, blocks_x, blocks_y)?;
+    Ok(assemble_dxt(&indices, &top, &bottom, alpha))
+}
+
+/// Interleave the indices of every block with its endpoints, which are given as RGBA pixels.
+///
+/// The indices of a DXT5 block are its 6 bytes of alpha indices followed by its 4 bytes of
+/// color indices, and its alpha endpoints are the alpha of its color endpoints.
+fn assemble_dxt(indices: &[u8], top: &[u8], bottom: &[u8], alpha: bool) -> Vec<u8> {
+    let index_size = if alpha { 10 } else { 4 };
+    let block_size = if alpha { 16 } else { 8 };
+    let mut dxt = Vec::with_capacity(indices.len() / index_size * block_size);
+    for ((indices, c0), c1) in indices
+        .chunks_exact(index_size)
+        .zip(top.chunks_exact(4))
+        .zip(bottom.chunks_exact(4))
+    {
+        let (alpha_indices, color_indices) = indices.split_at(index_size - 4);
+        if alpha {
+            dxt.extend_from_slice(&[c0[3], c1[3]]);
+            dxt.extend_from_slice(alpha_indices);
+        }
+        dxt.extend_from_slice(&rgb888_to_rgb565(c0).to_le_bytes());
+        dxt.extend_from_slice(&rgb888_to_rgb565(c1).to_le_bytes());
+        dxt.extend_from_slice(color_indices);
+    }
+    dxt
+}
+
+/// Decode DXT1 (or DXT5, if `alpha` is set) blocks into RGBA pixels.
+fn decode_dxt(data: &[u8], width: u32, height: u32, alpha: bool) -> Result<Vec<u8>, AtfError> {
+    let block_size = if alpha { 16 } else { 8 };
+    let blocks_x = (width as usize + 3) / 4;
+    let blocks_y = (height as usize + 3) / 4;
+    let expected = blocks_x * blocks_y * block_size;
+    if data.len() < expected {
+        return Err(AtfError::TruncatedImage {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let mut rgba = vec![0; width as usize * height as usize * 4];
+    for (i, block) in data[..expected].chunks_exact(block_size).enumerate() {
+        let (alphas, colors) = if alpha {
+            let (alpha_block, color_block) = block.split_at(8);
+            (Some(decode_dxt5_alpha(alpha_block)), color_block)
+        } else {
+            (None, block)
+        };
+        // DXT5 always uses the four color mode.
+        let colors = decode_dxt1_colors(colors, alpha);
+
+        let block_x = (i % blocks_x) * 4;
+        let block_y = (i / blocks_x) * 4;
+        for (j, color) in colors.iter().enumerate() {
+            let x = block_x + j % 4;
+            let y = block_y + j / 4;
+            if x >= width as usize || y >= height as usize {
+                continue;
+            }
+            let offset = (y * width as usize + x) * 4;
+            rgba[offset..offset + 4].copy_from_slice(color);
+            if let Some(alphas) = &alphas {
+                rgba[offset + 3] = alphas[j];
+            }
+        }
+    }
+    Ok(rgba)
+}
+
+/// Decode the 16 colors of an 8 byte DXT1 color block, in row-major order.
+fn decode_dxt1_colors(block: &[u8], four_colors: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let rgb0 = rgb565_to_rgb888(c0);
+    let rgb1 = rgb565_to_rgb888(c1);
+    // Interpolates between both colors, with the given weights.
+    let mix = |w0: u16, w1: u16| -> [u8; 4] {
+        let channel = |k: usize| ((rgb0[k] as u16 * w0 + rgb1[k] as u16 * w1) / (w0 + w1)) as u8;
+        [channel(0), channel(1), channel(2), 0xFF]
+    };
+
+    let palette = if four_colors || c0 > c1 {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    } else {
+        // The fourth color is transparent black.
+        [mix(1, 0), mix(0, 1), mix(1, 1), [0, 0, 0, 0]]
+    };
+
+    std::array::from_fn(|j| palette[((indices >> (j * 2)) & 0b11) as usize])
+}
+
+/// Decode the 16 alpha values of an 8 byte DXT5 alpha block, in row-major order.
+fn decode_dxt5_alpha(block: &[u8]) -> [u8; 16] {
+    let a0 = block[0] as u16;
+    let a1 = block[1] as u16;
+    let indices = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+
+    // Both alphas are followed by six interpolated values, or by four interpolated values,
+    // zero and 255.
+    let palette: [u8; 8] = std::array::from_fn(|k| {
+        let k = k as u16;
+        match k {
+            0 => a0 as u8,
+            1 => a1 as u8,
+            _ if a0 > a1 => (((8 - k) * a0 + (k - 1) * a1) / 7) as u8,
+            2..=5 => (((6 - k) * a0 + (k - 1) * a1) / 5) as u8,
+            6 => 0,
+            _ => 0xFF,
+        }
+    });
+
+    std::array::from_fn(|j| palette[((indices >> (j * 3)) & 0b111) as usize])
+}
+
+fn rgb888_to_rgb565(color: &[u8]) -> u16 {
+    ((color[0] as u16 >> 3) << 11) | ((color[1] as u16 >> 2) << 5) | (color[2] as u16 >> 3)
+}
+
+fn rgb565_to_rgb888(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 0x1F) as u8;
+    let g = ((color >> 5) & 0x3F) as u8;
+    let b = (color & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atf_header(format: u8, log2_size: u8, mip_count: u8) -> Vec<u8> {
+        let mut data = b"ATF".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0xFF, 3]);
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&[format, log2_size, log2_size, mip_count]);
+        data
+    }
+
+    fn push_block(data: &mut Vec<u8>, block: &[u8]) {
+        data.extend_from_slice(&(block.len() as u32).to_be_bytes());
+        data.extend_from_slice(block);
+    }
+
+    #[test]
+    fn decode_raw_compressed_mips() {
+        // A 4x4 texture with two mip levels, each a single red DXT1 block.
+        let red_block = [0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0];
+        let mut data = atf_header(0x3, 2, 2);
+        for _ in 0..2 {
+            push_block(&mut data, &red_block);
+            for _ in 0..3 {
+                push_block(&mut data, &[]);
+            }
+        }
+
+        let texture = AtfTexture::from_bytes(&data).expect("Valid ATF data");
+        assert_eq!(texture.format, AtfFormat::RawCompressed);
+        assert!(!texture.cubemap);
+        assert_eq!((texture.width, texture.height), (4, 4));
+        assert_eq!(texture.mip_count(), 2);
+
+        let level0 = texture.decode(0, 0).expect("Valid image");
+        assert_eq!((level0.width(), level0.height()), (4, 4));
+        assert!(level0
+            .data()
+            .chunks_exact(4)
+            .all(|pixel| pixel == [0xFF, 0, 0, 0xFF]));
+
+        let level1 = texture.decode(0, 1).expect("Valid image");
+        assert_eq!((level1.width(), level1.height()), (2, 2));
+        assert_eq!(level1.data().len(), 2 * 2 * 4);
+    }
+
+    #[test]
+    fn decode_raw_compressed_alpha_cube() {
+        // Alpha 0x80 everywhere, and a blue color block.
+        let block = [
+            0x80, 0x80, 0, 0, 0, 0, 0, 0, 0x1F, 0x00, 0x1F, 0x00, 0, 0, 0, 0,
+        ];
+        let mut data = atf_header(0x80 | 0x5, 2, 1);
+        for _ in 0..6 {
+            push_block(&mut data, &block);
+            for _ in 0..3 {
+                push_block(&mut data, &[]);
+            }
+        }
+
+        let texture = AtfTexture::from_bytes(&data).expect("Valid ATF data");
+        assert!(texture.cubemap);
+        assert!(texture
+            .format
+            .is_compatible_with(Context3DTextureFormat::CompressedAlpha));
+        for face in 0..6 {
+            let bitmap = texture.decode(face, 0).expect("Valid image");
+            assert!(bitmap
+                .data()
+                .chunks_exact(4)
+                .all(|pixel| pixel == [0, 0, 0xFF, 0x80]));
+        }
+    }
+
+    #[test]
+    fn dxt1_transparent_color() {
+        // c0 <= c1 selects the three color mode, where index 3 is transparent black.
+        let block = [0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let colors = decode_dxt1_colors(&block, false);
+        assert!(colors.iter().all(|color| *color == [0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn parse_packed_compressed() {
+        let mut data = atf_header(0x2, 3, 1);
+        push_block(&mut data, b"indices");
+        push_block(&mut data, b"top");
+        push_block(&mut data, b"bottom");
+        for _ in 0..9 {
+            push_block(&mut data, &[]);
+        }
+
+        let texture = AtfTexture::from_bytes(&data).expect("Valid ATF data");
+        assert_eq!(texture.format, AtfFormat::Compressed);
+        assert!(texture
+            .format
+            .is_compatible_with(Context3DTextureFormat::Compressed));
+        assert!(matches!(
+            texture.faces[0][0],
+            AtfImage::PackedDxt {
+                indices: b"indices",
+                top: b"top",
+                bottom: b"bottom",
+                alpha: false,
+            }
+        ));
+
+        // The indices are decompressed first, and these aren't valid LZMA data.
+        assert!(matches!(
+            texture.decode(0, 0),
+            Err(AtfError::Lzma(_) | AtfError::TruncatedImage { .. })
+        ));
+    }
+
+    #[test]
+    fn assemble_packed_dxt1() {
+        // Two blocks: red to blue, and green to white.
+        let indices = [0x00, 0x00, 0x00, 0x00, 0x55, 0x55, 0x55, 0x55];
+        let top = [0xFF, 0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF];
+        let bottom = [0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let dxt = assemble_dxt(&indices, &top, &bottom, false);
+        assert_eq!(
+            dxt,
+            [
+                0x00, 0xF8, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x00, //
+                0xE0, 0x07, 0xFF, 0xFF, 0x55, 0x55, 0x55, 0x55,
+            ]
+        );
+
+        // The first block only uses its first endpoint, and the second only its second one.
+        let rgba = decode_dxt(&dxt, 8, 4, false).expect("Valid blocks");
+        for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+            let expected = if i % 8 < 4 {
+                [0xFF, 0, 0, 0xFF]
+            } else {
+                [0xFF, 0xFF, 0xFF, 0xFF]
+            };
+            assert_eq!(pixel, expected);
+        }
+    }
+
+    #[test]
+    fn assemble_packed_dxt5() {
+        let indices = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let top = [0xFF, 0x00, 0x00, 0x80];
+        let bottom = [0x00, 0x00, 0xFF, 0x40];
+        let dxt = assemble_dxt(&indices, &top, &bottom, true);
+        assert_eq!(
+            dxt,
+            [0x80, 0x40, 1, 2, 3, 4, 5, 6, 0x00, 0xF8, 0x1F, 0x00, 7, 8, 9, 10]
+        );
+    }
+
+    #[test]
+    fn decompress_lzma_indices() {
+        use lzma_rs::compress::{Options, UnpackedSize};
+
+        let indices: Vec<u8> = (0..40).collect();
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress_with_options(
+            &mut Cursor::new(&indices),
+            &mut compressed,
+            &Options {
+                unpacked_size: UnpackedSize::SkipWritingToHeader,
+            },
+        )
+        .expect("Compression succeeds");
+
+        assert_eq!(
+            decompress_lzma(&compressed, indices.len()).expect("Valid LZMA data"),
+            indices
+        );
+        assert!(matches!(
+            decompress_lzma(&compressed[..compressed.len() / 2], indices.len()),
+            Err(AtfError::Lzma(_) | AtfError::TruncatedImage { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_data() {
+        let mut data = atf_header(0x3, 3, 1);
+        push_block(&mut data, &[0; 8]);
+        for _ in 0..3 {
+            push_block(&mut data, &[]);
+        }
+        let texture = AtfTexture::from_bytes(&data).expect("Valid ATF data");
+        assert!(matches!(
+            texture.decode(0, 0),
+            Err(AtfError::TruncatedImage {
+                expected: 32,
+                actual: 8
+            })
+        ));
+
+        assert!(matches!(
+            AtfTexture::from_bytes(b"ATX"),
+            Err(AtfError::InvalidSignature)
+        ));
+        assert!(matches!(
+            AtfTexture::from_bytes(&data[..data.len() - 1]),
+            Err(AtfError::UnexpectedEof(_))
+        ));
+    }
+}
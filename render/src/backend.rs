@@ -1,4 +1,5 @@
 pub mod null;
+pub mod software_context3d;
 
 use crate::bitmap::{Bitmap, BitmapHandle, BitmapSource, PixelRegion, SyncHandle};
 use crate::commands::CommandList;
@@ -122,7 +123,12 @@ impl_downcast!(VertexBuffer);
 pub trait ShaderModule: Downcast {}
 impl_downcast!(ShaderModule);
 
-pub trait Texture: Downcast {}
+pub trait Texture: Downcast {
+    /// The width of the largest mip level.
+    fn width(&self) -> u32;
+    /// The height of the largest mip level.
+    fn height(&self) -> u32;
+}
 impl_downcast!(Texture);
 
 pub trait RawTexture: Downcast + Debug {}
@@ -220,14 +226,6 @@ pub trait Context3D: Downcast {
     // as part of stage rendering
     fn should_render(&self) -> bool;
 
-    // Get a 'disposed' handle - this is what we store in all IndexBuffer3D
-    // objects after dispose() has been called.
-    fn disposed_index_buffer_handle(&self) -> Rc<dyn IndexBuffer>;
-
-    // Get a 'disposed' handle - this is what we store in all VertexBuffer3D
-    // objects after dispose() has been called.
-    fn disposed_vertex_buffer_handle(&self) -> Rc<dyn VertexBuffer>;
-
     fn create_index_buffer(&mut self, usage: BufferUsage, num_indices: u32)
         -> Box<dyn IndexBuffer>;
     fn create_vertex_buffer(
@@ -274,6 +272,22 @@ pub enum Context3DTriangleFace {
     FrontAndBack,
 }
 
+impl Context3DTriangleFace {
+    pub fn from_wstr(s: &WStr) -> Option<Self> {
+        if s == b"none" {
+            Some(Context3DTriangleFace::None)
+        } else if s == b"back" {
+            Some(Context3DTriangleFace::Back)
+        } else if s == b"front" {
+            Some(Context3DTriangleFace::Front)
+        } else if s == b"frontAndBack" {
+            Some(Context3DTriangleFace::FrontAndBack)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Context3DCompareMode {
     Never,
@@ -310,6 +324,42 @@ impl Context3DCompareMode {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+pub enum Context3DStencilAction {
+    DecrementSaturate,
+    DecrementWrap,
+    IncrementSaturate,
+    IncrementWrap,
+    Invert,
+    Keep,
+    Set,
+    Zero,
+}
+
+impl Context3DStencilAction {
+    pub fn from_wstr(s: &WStr) -> Option<Self> {
+        if s == b"decrementSaturate" {
+            Some(Context3DStencilAction::DecrementSaturate)
+        } else if s == b"decrementWrap" {
+            Some(Context3DStencilAction::DecrementWrap)
+        } else if s == b"incrementSaturate" {
+            Some(Context3DStencilAction::IncrementSaturate)
+        } else if s == b"incrementWrap" {
+            Some(Context3DStencilAction::IncrementWrap)
+        } else if s == b"invert" {
+            Some(Context3DStencilAction::Invert)
+        } else if s == b"keep" {
+            Some(Context3DStencilAction::Keep)
+        } else if s == b"set" {
+            Some(Context3DStencilAction::Set)
+        } else if s == b"zero" {
+            Some(Context3DStencilAction::Zero)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Context3DWrapMode {
     Clamp,
@@ -334,6 +384,85 @@ impl Context3DWrapMode {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+pub enum Context3DProfile {
+    Baseline,
+    BaselineConstrained,
+    BaselineExtended,
+    Standard,
+    StandardConstrained,
+    StandardExtended,
+    Enhanced,
+}
+
+impl Context3DProfile {
+    pub fn from_wstr(s: &WStr) -> Option<Self> {
+        if s == b"baseline" {
+            Some(Context3DProfile::Baseline)
+        } else if s == b"baselineConstrained" {
+            Some(Context3DProfile::BaselineConstrained)
+        } else if s == b"baselineExtended" {
+            Some(Context3DProfile::BaselineExtended)
+        } else if s == b"standard" {
+            Some(Context3DProfile::Standard)
+        } else if s == b"standardConstrained" {
+            Some(Context3DProfile::StandardConstrained)
+        } else if s == b"standardExtended" {
+            Some(Context3DProfile::StandardExtended)
+        } else if s == b"enhanced" {
+            Some(Context3DProfile::Enhanced)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Context3DProfile::Baseline => "baseline",
+            Context3DProfile::BaselineConstrained => "baselineConstrained",
+            Context3DProfile::BaselineExtended => "baselineExtended",
+            Context3DProfile::Standard => "standard",
+            Context3DProfile::StandardConstrained => "standardConstrained",
+            Context3DProfile::StandardExtended => "standardExtended",
+            Context3DProfile::Enhanced => "enhanced",
+        }
+    }
+
+    /// How many color outputs a fragment shader can render to at once.
+    pub fn num_color_outputs(self) -> u32 {
+        match self {
+            Context3DProfile::Baseline
+            | Context3DProfile::BaselineConstrained
+            | Context3DProfile::BaselineExtended => 1,
+            Context3DProfile::Standard
+            | Context3DProfile::StandardConstrained
+            | Context3DProfile::StandardExtended
+            | Context3DProfile::Enhanced => 4,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Context3DMipFilter {
+    MipLinear,
+    MipNearest,
+    MipNone,
+}
+
+impl Context3DMipFilter {
+    pub fn from_wstr(s: &WStr) -> Option<Self> {
+        if s == b"miplinear" {
+            Some(Context3DMipFilter::MipLinear)
+        } else if s == b"mipnearest" {
+            Some(Context3DMipFilter::MipNearest)
+        } else if s == b"mipnone" {
+            Some(Context3DMipFilter::MipNone)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Context3DTextureFilter {
     Anisotropic16X,
@@ -386,6 +515,8 @@ pub enum Context3DCommand<'a> {
         enable_depth_and_stencil: bool,
         anti_alias: u32,
         surface_selector: u32,
+        /// The fragment shader output (`oc0` to `oc3`) that renders to this texture.
+        color_output_index: u32,
     },
     SetRenderToBackBuffer,
 
@@ -402,6 +533,17 @@ pub enum Context3DCommand<'a> {
         data: Vec<u8>,
     },
 
+    /// Frees the memory of `buffer`, which can't be used afterwards.
+    DisposeIndexBuffer {
+        buffer: &'a mut dyn IndexBuffer,
+    },
+
+    /// Frees the memory of `buffer`, which can't be used afterwards.
+    /// Any vertex attribute reading from it is unbound.
+    DisposeVertexBuffer {
+        buffer: Rc<dyn VertexBuffer>,
+    },
+
     DrawTriangles {
         index_buffer: &'a dyn IndexBuffer,
         first_index: usize,
@@ -414,10 +556,13 @@ pub enum Context3DCommand<'a> {
         buffer_offset: u32,
     },
 
+    /// Replaces the programs of `module`. If either program is malformed, `error`
+    /// is set and `module` is left untouched.
     UploadShaders {
         module: &'a RefCell<Option<Rc<dyn ShaderModule>>>,
         vertex_shader_agal: Vec<u8>,
         fragment_shader_agal: Vec<u8>,
+        error: &'a mut Option<Error>,
     },
 
     SetShaders {
@@ -435,6 +580,13 @@ pub enum Context3DCommand<'a> {
         source: Bitmap,
         dest: Rc<dyn Texture>,
         layer: u32,
+        mip_level: u32,
+    },
+    /// Frees the memory of `texture`, which can't be used afterwards.
+    /// It's unbound from any sampler, and rendering goes back to the back buffer
+    /// if it was rendering to it.
+    DisposeTexture {
+        texture: Rc<dyn Texture>,
     },
     SetTextureAt {
         sampler: u32,
@@ -451,6 +603,18 @@ pub enum Context3DCommand<'a> {
         depth_mask: bool,
         pass_compare_mode: Context3DCompareMode,
     },
+    SetStencilActions {
+        triangle_face: Context3DTriangleFace,
+        compare_mode: Context3DCompareMode,
+        action_on_both_pass: Context3DStencilAction,
+        action_on_depth_fail: Context3DStencilAction,
+        action_on_depth_pass_stencil_fail: Context3DStencilAction,
+    },
+    SetStencilReferenceValue {
+        reference_value: u32,
+        read_mask: u32,
+        write_mask: u32,
+    },
     SetBlendFactors {
         source_factor: Context3DBlendFactor,
         destination_factor: Context3DBlendFactor,
@@ -459,10 +623,24 @@ pub enum Context3DCommand<'a> {
         sampler: u32,
         wrap: Context3DWrapMode,
         filter: Context3DTextureFilter,
+        mip_filter: Context3DMipFilter,
     },
     SetScissorRectangle {
         rect: Option<Rectangle<Twips>>,
     },
+
+    /// Copies `source_region` of the back buffer into `destination`, starting at `dest_point`.
+    /// Both areas must already be clipped to fit the back buffer and the destination.
+    ///
+    /// The copy happens immediately, so it is unaffected by any later draw calls or `present`.
+    /// If the copy was performed, `sync_handle` is set to a handle that can be used to read
+    /// the new pixels of `destination` back.
+    DrawToBitmapData {
+        destination: BitmapHandle,
+        source_region: PixelRegion,
+        dest_point: (u32, u32),
+        sync_handle: &'a mut Option<Box<dyn SyncHandle>>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -14,6 +14,7 @@ use crate::quality::StageQuality;
 use crate::shape_utils::DistilledShape;
 use swf::Color;
 
+use super::software_context3d::SoftwareContext3D;
 use super::Context3D;
 
 pub struct NullBitmapSource;
@@ -29,11 +30,25 @@ impl BitmapSource for NullBitmapSource {
 
 pub struct NullRenderer {
     dimensions: ViewportDimensions,
+    software_context3d: bool,
 }
 
 impl NullRenderer {
     pub fn new(dimensions: ViewportDimensions) -> Self {
-        Self { dimensions }
+        Self {
+            dimensions,
+            software_context3d: false,
+        }
+    }
+
+    /// Creates a renderer that runs Stage3D content on the CPU, using a `SoftwareContext3D`.
+    ///
+    /// This is much slower than a GPU backend, and is meant for headless runs without one.
+    pub fn with_software_context3d(dimensions: ViewportDimensions) -> Self {
+        Self {
+            dimensions,
+            software_context3d: true,
+        }
     }
 }
 
@@ -91,11 +106,18 @@ impl RenderBackend for NullRenderer {
     }
 
     fn create_context3d(&mut self) -> Result<Box<dyn super::Context3D>, Error> {
-        Err(Error::Unimplemented("createContext3D".into()))
-    }
-
-    fn context3d_present(&mut self, _context: &mut dyn Context3D) -> Result<(), Error> {
-        Err(Error::Unimplemented("Context3D.present".into()))
+        if !self.software_context3d {
+            return Err(Error::Unimplemented("createContext3D".into()));
+        }
+        Ok(Box::new(SoftwareContext3D::new(self)?))
+    }
+
+    fn context3d_present(&mut self, context: &mut dyn Context3D) -> Result<(), Error> {
+        let context = context
+            .as_any_mut()
+            .downcast_mut::<SoftwareContext3D>()
+            .ok_or_else(|| Error::Unimplemented("Context3D.present".into()))?;
+        context.present(self)
     }
 
     fn debug_info(&self) -> Cow<'static, str> {
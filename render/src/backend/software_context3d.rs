@@ -0,0 +1,1359 @@
+//! A `Context3D` that renders on the CPU, for when there's no GPU to render Stage3D content with.
+//!
+//! AGAL programs are run by an interpreter, and triangles are rasterized one 2x2 quad of
+//! pixels at a time. This is slow, but supports everything that the wgpu backend does,
+//! including depth and stencil tests, blending, and textures. Anti-aliasing is ignored.
+
+mod agal;
+mod texture;
+
+#[cfg(test)]
+mod tests;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use naga_agal::{
+    Dimension, Filter, Mipmap, SamplerConfig, ShaderType, Wrapping, MAX_COLOR_OUTPUTS,
+    MAX_FRAGMENT_CONSTANTS, MAX_TEXTURES, MAX_VERTEX_ATTRIBUTES, MAX_VERTEX_CONSTANTS,
+};
+use swf::{Rectangle, Twips};
+
+use crate::backend::{
+    BufferUsage, Context3D, Context3DBlendFactor, Context3DCommand, Context3DCompareMode,
+    Context3DMipFilter, Context3DStencilAction, Context3DTextureFilter, Context3DTextureFormat,
+    Context3DTriangleFace, Context3DVertexBufferFormat, Context3DWrapMode, IndexBuffer,
+    ProgramType, RenderBackend, ShaderModule, VertexBuffer,
+};
+use crate::bitmap::{Bitmap, BitmapFormat, BitmapHandle, PixelRegion, RgbaBufRead, SyncHandle};
+use crate::error::Error;
+
+use agal::{Lanes, Program, Registers, Vec4, LANES, MAX_VARYINGS};
+use texture::{BoundTexture, Pixels, SoftwareTexture};
+
+const COLOR_MASK: u32 = 1 << 0;
+const DEPTH_MASK: u32 = 1 << 1;
+const STENCIL_MASK: u32 = 1 << 2;
+
+pub struct SoftwareContext3D {
+    back_buffer: Option<Framebuffer>,
+    front_buffer: Option<Framebuffer>,
+
+    // The front buffer is uploaded to this by `present`, so that the stage can draw it.
+    front_buffer_handle: BitmapHandle,
+    front_buffer_handle_size: (u32, u32),
+
+    // When rendering to textures, the targets of each color output, along with
+    // the depth and stencil buffer used with them.
+    texture_targets: [Option<TextureTarget>; MAX_COLOR_OUTPUTS],
+    texture_depth_stencil: Option<DepthStencil>,
+
+    program: Option<Rc<SoftwareProgram>>,
+    vertex_attributes: [Option<VertexAttribute>; MAX_VERTEX_ATTRIBUTES],
+    vertex_constants: Vec<Vec4>,
+    fragment_constants: Vec<Vec4>,
+    textures: [Option<Rc<SoftwareTexture>>; MAX_TEXTURES],
+    sampler_overrides: [Option<SamplerConfig>; MAX_TEXTURES],
+
+    culling: Context3DTriangleFace,
+    color_mask: [bool; 4],
+    depth_mask: bool,
+    depth_compare_mode: Context3DCompareMode,
+    stencil_front: StencilFace,
+    stencil_back: StencilFace,
+    stencil_reference: u32,
+    stencil_read_mask: u32,
+    stencil_write_mask: u32,
+    source_factor: Context3DBlendFactor,
+    destination_factor: Context3DBlendFactor,
+    scissor_rectangle: Option<Rectangle<Twips>>,
+
+    // After a call to 'present()', the Context3D API requires a call to 'clear'
+    // before any new calls to 'drawTriangles'.
+    seen_clear_command: bool,
+}
+
+impl SoftwareContext3D {
+    pub fn new(renderer: &mut dyn RenderBackend) -> Result<Self, Error> {
+        Ok(Self {
+            back_buffer: None,
+            front_buffer: None,
+            front_buffer_handle: renderer.create_empty_texture(1, 1)?,
+            front_buffer_handle_size: (1, 1),
+            texture_targets: std::array::from_fn(|_| None),
+            texture_depth_stencil: None,
+            program: None,
+            vertex_attributes: std::array::from_fn(|_| None),
+            vertex_constants: vec![[0.0; 4]; MAX_VERTEX_CONSTANTS],
+            fragment_constants: vec![[0.0; 4]; MAX_FRAGMENT_CONSTANTS],
+            textures: std::array::from_fn(|_| None),
+            sampler_overrides: [None; MAX_TEXTURES],
+            culling: Context3DTriangleFace::None,
+            color_mask: [true; 4],
+            depth_mask: true,
+            depth_compare_mode: Context3DCompareMode::LessEqual,
+            stencil_front: StencilFace::IGNORE,
+            stencil_back: StencilFace::IGNORE,
+            stencil_reference: 0,
+            stencil_read_mask: 0xFF,
+            stencil_write_mask: 0xFF,
+            source_factor: Context3DBlendFactor::One,
+            destination_factor: Context3DBlendFactor::Zero,
+            scissor_rectangle: None,
+            seen_clear_command: false,
+        })
+    }
+
+    /// Swaps the back and front buffers, then uploads the new front buffer to `renderer`
+    /// so that it's drawn by the stage.
+    pub fn present(&mut self, renderer: &mut dyn RenderBackend) -> Result<(), Error> {
+        std::mem::swap(&mut self.back_buffer, &mut self.front_buffer);
+        self.set_render_to_back_buffer();
+        self.seen_clear_command = false;
+
+        if let Some(bitmap) = self.front_buffer() {
+            let size = (bitmap.width(), bitmap.height());
+            if size == self.front_buffer_handle_size {
+                let region = PixelRegion::for_whole_size(size.0, size.1);
+                renderer.update_texture(&self.front_buffer_handle, bitmap, region)?;
+            } else {
+                self.front_buffer_handle = renderer.register_bitmap(bitmap)?;
+                self.front_buffer_handle_size = size;
+            }
+        }
+        Ok(())
+    }
+
+    /// The pixels presented by the last call to `present`, if the back buffer was configured.
+    pub fn front_buffer(&self) -> Option<Bitmap> {
+        self.front_buffer.as_ref().map(|front_buffer| {
+            Bitmap::new(
+                front_buffer.width,
+                front_buffer.height,
+                BitmapFormat::Rgba,
+                front_buffer.color.clone(),
+            )
+        })
+    }
+
+    // This restores rendering to our normal buffer. It can be triggered explicitly
+    // from ActionScript via Context3D.setRenderToBackBuffer(), or automatically
+    // when calling Context3D.present()
+    fn set_render_to_back_buffer(&mut self) {
+        self.texture_targets = std::array::from_fn(|_| None);
+        self.texture_depth_stencil = None;
+    }
+
+    fn remove_texture(&mut self, texture: &Rc<SoftwareTexture>) {
+        for bound in &mut self.textures {
+            if matches!(bound, Some(bound) if Rc::ptr_eq(bound, texture)) {
+                *bound = None;
+            }
+        }
+    }
+
+    /// The size of the main color output, if there's anything to render to.
+    fn target_size(&self) -> Option<(u32, u32)> {
+        match &self.texture_targets[0] {
+            Some(target) => Some(target.texture.level_size(0)),
+            None => self
+                .back_buffer
+                .as_ref()
+                .map(|back_buffer| (back_buffer.width, back_buffer.height)),
+        }
+    }
+
+    /// Takes the buffers being rendered to, which have to be given back with
+    /// `restore_render_targets`.
+    fn take_render_targets(&mut self) -> RenderTargets {
+        let mut colors: [Option<ColorTarget>; MAX_COLOR_OUTPUTS] = std::array::from_fn(|_| None);
+        let depth_stencil = if self.texture_targets[0].is_some() {
+            for (color, target) in colors.iter_mut().zip(&self.texture_targets) {
+                *color = target.as_ref().and_then(|target| {
+                    let (width, height) = target.texture.level_size(0);
+                    let mut layers = target.texture.layers.borrow_mut();
+                    let pixels = std::mem::take(layers.get_mut(target.layer)?.get_mut(0)?);
+                    Some(ColorTarget {
+                        width,
+                        height,
+                        pixels,
+                    })
+                });
+            }
+            self.texture_depth_stencil.take()
+        } else if let Some(back_buffer) = &mut self.back_buffer {
+            colors[0] = Some(ColorTarget {
+                width: back_buffer.width,
+                height: back_buffer.height,
+                pixels: std::mem::take(&mut back_buffer.color),
+            });
+            back_buffer.depth_stencil.take()
+        } else {
+            None
+        };
+        RenderTargets {
+            colors,
+            depth_stencil,
+        }
+    }
+
+    fn restore_render_targets(&mut self, targets: RenderTargets) {
+        let RenderTargets {
+            colors,
+            depth_stencil,
+        } = targets;
+        if self.texture_targets[0].is_some() {
+            for (color, target) in colors.into_iter().zip(&self.texture_targets) {
+                if let (Some(color), Some(target)) = (color, target) {
+                    let mut layers = target.texture.layers.borrow_mut();
+                    if let Some(level) = layers
+                        .get_mut(target.layer)
+                        .and_then(|levels| levels.get_mut(0))
+                    {
+                        *level = color.pixels;
+                    }
+                }
+            }
+            self.texture_depth_stencil = depth_stencil;
+        } else if let Some(back_buffer) = &mut self.back_buffer {
+            if let [Some(color), ..] = colors {
+                back_buffer.color = color.pixels;
+            }
+            back_buffer.depth_stencil = depth_stencil;
+        }
+    }
+
+    fn clear(&mut self, color: [f64; 4], depth: f64, stencil: u32, mask: u32) {
+        let mut targets = self.take_render_targets();
+        if mask & COLOR_MASK != 0 {
+            let color = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            for target in targets.colors.iter_mut().flatten() {
+                for pixel in target.pixels.chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+        if let Some(depth_stencil) = &mut targets.depth_stencil {
+            if mask & DEPTH_MASK != 0 {
+                depth_stencil.depth.fill(depth.clamp(0.0, 1.0) as f32);
+            }
+            if mask & STENCIL_MASK != 0 {
+                depth_stencil.stencil.fill(stencil as u8);
+            }
+        }
+        self.restore_render_targets(targets);
+    }
+
+    fn draw_triangles(
+        &mut self,
+        index_buffer: &SoftwareIndexBuffer,
+        first_index: usize,
+        num_triangles: isize,
+    ) {
+        let Some(program) = self.program.clone() else {
+            tracing::error!("Context3D::drawTriangles called without a program");
+            return;
+        };
+        if let Some(index) = (0..MAX_VERTEX_ATTRIBUTES)
+            .find(|i| program.vertex.attributes[*i] && self.vertex_attributes[*i].is_none())
+        {
+            tracing::error!(
+                "Couldn't run Context3D program: Missing vertex attribute data for va{}",
+                index
+            );
+            return;
+        }
+        let Some((width, height)) = self.target_size() else {
+            return;
+        };
+
+        if !self.seen_clear_command {
+            tracing::warn!(
+                "Context3D::present: drawTriangles called without first calling clear()"
+            );
+        }
+
+        let mut bounds = (0, 0, width, height);
+        if let Some(rect) = &self.scissor_rectangle {
+            if rect.x_min.to_pixels() < 0.0
+                || rect.y_min.to_pixels() < 0.0
+                || rect.x_max.to_pixels() as u32 > width
+                || rect.y_max.to_pixels() as u32 > height
+            {
+                // FIXME - throw an error when Context3D.enableErrorChecking is set
+                tracing::error!(
+                    "Invalid scissor rectangle {:?} for texture size {:?}",
+                    rect,
+                    (width, height)
+                );
+                self.scissor_rectangle = None;
+            } else {
+                bounds = (
+                    rect.x_min.to_pixels() as u32,
+                    rect.y_min.to_pixels() as u32,
+                    rect.x_max.to_pixels() as u32,
+                    rect.y_max.to_pixels() as u32,
+                );
+            }
+        }
+
+        let num_indices = usize::try_from(num_triangles).unwrap_or(0) * 3;
+        let end = first_index
+            .saturating_add(num_indices)
+            .min(index_buffer.data.len());
+        let indices = index_buffer.data.get(first_index..end).unwrap_or_default();
+        let vertices = self.shade_vertices(&program.vertex, indices);
+
+        let mut targets = self.take_render_targets();
+        let rasterizer = Rasterizer {
+            context: self,
+            program: &program.fragment,
+            textures: self.bind_textures(&program.fragment),
+            width,
+            height,
+            bounds,
+        };
+        for triangle in indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|i| vertices.get(&triangle[i]));
+            if let [Some(a), Some(b), Some(c)] = vertices {
+                rasterizer.draw_triangle([a, b, c], &mut targets);
+            }
+        }
+        drop(rasterizer);
+        self.restore_render_targets(targets);
+    }
+
+    /// Runs the vertex program for each of the vertices used by `indices`.
+    fn shade_vertices(&self, program: &Program, indices: &[u16]) -> HashMap<u16, ClipVertex> {
+        let mut unique = indices.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let buffers: Vec<_> = self
+            .vertex_attributes
+            .iter()
+            .map(|attribute| {
+                attribute
+                    .as_ref()
+                    .map(|attribute| (attribute, attribute.buffer.data.borrow()))
+            })
+            .collect();
+
+        let mut vertices = HashMap::with_capacity(unique.len());
+        let mut registers = Registers::new();
+        for chunk in unique.chunks(LANES) {
+            for (register, buffer) in registers.attributes.iter_mut().zip(&buffers) {
+                if let Some((attribute, data)) = buffer {
+                    for (lane, index) in register.iter_mut().zip(chunk) {
+                        *lane = attribute.read(data, *index as usize);
+                    }
+                }
+            }
+            program.run(&mut registers, &self.vertex_constants, &NoTextures);
+            for (lane, index) in chunk.iter().enumerate() {
+                vertices.insert(
+                    *index,
+                    ClipVertex {
+                        position: registers.outputs[0][lane],
+                        varyings: registers.varyings.map(|varying| varying[lane]),
+                    },
+                );
+            }
+        }
+        vertices
+    }
+
+    fn bind_textures<'a>(&'a self, program: &Program) -> TextureUnits<'a> {
+        TextureUnits {
+            units: std::array::from_fn(|i| {
+                let texture = self.textures[i].as_deref()?;
+                let config = self.sampler_overrides[i]
+                    .or(program.sampler_configs[i])
+                    .unwrap_or(SamplerConfig {
+                        wrapping: Wrapping::Clamp,
+                        filter: Filter::Nearest,
+                        mipmap: Mipmap::Disable,
+                    });
+                Some(BoundTexture {
+                    texture,
+                    layers: texture.layers.borrow(),
+                    config,
+                })
+            }),
+        }
+    }
+
+    fn draw_to_bitmap_data(&self, source_region: PixelRegion) -> Option<Box<dyn SyncHandle>> {
+        let back_buffer = self.back_buffer.as_ref()?;
+        let row_length = source_region.width() as usize * 4;
+        let mut pixels = Vec::with_capacity(row_length * source_region.height() as usize);
+        for y in source_region.y_min..source_region.y_max {
+            let start =
+                (y as usize * back_buffer.width as usize + source_region.x_min as usize) * 4;
+            pixels.extend_from_slice(back_buffer.color.get(start..start + row_length)?);
+        }
+        Some(Box::new(SoftwareSyncHandle {
+            pixels,
+            row_length: row_length as u32,
+        }))
+    }
+}
+
+impl Context3D for SoftwareContext3D {
+    fn bitmap_handle(&self) -> BitmapHandle {
+        self.front_buffer_handle.clone()
+    }
+
+    fn should_render(&self) -> bool {
+        // If this is None, we haven't called configureBackBuffer yet.
+        self.back_buffer.is_some()
+    }
+
+    fn create_index_buffer(
+        &mut self,
+        _usage: BufferUsage,
+        num_indices: u32,
+    ) -> Box<dyn IndexBuffer> {
+        Box::new(SoftwareIndexBuffer {
+            data: vec![0; num_indices as usize],
+        })
+    }
+
+    fn create_vertex_buffer(
+        &mut self,
+        _usage: BufferUsage,
+        num_vertices: u32,
+        data_32_per_vertex: u8,
+    ) -> Rc<dyn VertexBuffer> {
+        Rc::new(SoftwareVertexBuffer {
+            data: RefCell::new(vec![
+                0;
+                num_vertices as usize * data_32_per_vertex as usize * 4
+            ]),
+            data_32_per_vertex,
+        })
+    }
+
+    fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: Context3DTextureFormat,
+        _optimize_for_render_to_texture: bool,
+        streaming_levels: u32,
+    ) -> Result<Rc<dyn crate::backend::Texture>, Error> {
+        check_texture_format(format)?;
+        if streaming_levels != 0 {
+            return Err(Error::Unimplemented(
+                format!("streamingLevels={streaming_levels}").into(),
+            ));
+        }
+        Ok(Rc::new(SoftwareTexture::new(width, height, 1)))
+    }
+
+    fn create_cube_texture(
+        &mut self,
+        size: u32,
+        format: Context3DTextureFormat,
+        _optimize_for_render_to_texture: bool,
+        streaming_levels: u32,
+    ) -> Result<Rc<dyn crate::backend::Texture>, Error> {
+        check_texture_format(format)?;
+        if streaming_levels != 0 {
+            tracing::warn!(
+                "createCubeTexture: streaming_levels={} is not yet implemented",
+                streaming_levels,
+            );
+        }
+        Ok(Rc::new(SoftwareTexture::new(size, size, 6)))
+    }
+
+    fn process_command(&mut self, command: Context3DCommand<'_>) {
+        match command {
+            Context3DCommand::Clear {
+                red,
+                green,
+                blue,
+                alpha,
+                depth,
+                stencil,
+                mask,
+            } => {
+                self.clear([red, green, blue, alpha], depth, stencil, mask);
+                self.seen_clear_command = true;
+            }
+            Context3DCommand::ConfigureBackBuffer {
+                width,
+                height,
+                anti_alias: _,
+                depth_and_stencil,
+                wants_best_resolution: _,
+                wants_best_resolution_on_browser_zoom: _,
+            } => {
+                self.back_buffer = Some(Framebuffer::new(width, height, depth_and_stencil));
+                self.front_buffer = Some(Framebuffer::new(width, height, depth_and_stencil));
+                self.set_render_to_back_buffer();
+            }
+            Context3DCommand::SetRenderToTexture {
+                texture,
+                enable_depth_and_stencil,
+                anti_alias: _,
+                surface_selector,
+                color_output_index,
+            } => {
+                let Ok(texture) = texture.into_any_rc().downcast::<SoftwareTexture>() else {
+                    return;
+                };
+                self.remove_texture(&texture);
+
+                // The additional color outputs of the fragment shader share the size and
+                // depth buffer of the first one.
+                if color_output_index > 0 {
+                    if let Some(target) = self.texture_targets.get_mut(color_output_index as usize)
+                    {
+                        *target = Some(TextureTarget {
+                            texture,
+                            layer: surface_selector as usize,
+                        });
+                    }
+                    return;
+                }
+
+                let (width, height) = texture.level_size(0);
+                self.texture_depth_stencil =
+                    enable_depth_and_stencil.then(|| DepthStencil::new(width, height));
+                self.texture_targets[0] = Some(TextureTarget {
+                    texture,
+                    layer: surface_selector as usize,
+                });
+            }
+            Context3DCommand::SetRenderToBackBuffer => {
+                self.set_render_to_back_buffer();
+            }
+            Context3DCommand::UploadToIndexBuffer {
+                buffer,
+                start_offset,
+                data,
+            } => {
+                let Some(buffer) = buffer.as_any_mut().downcast_mut::<SoftwareIndexBuffer>() else {
+                    return;
+                };
+                for (index, bytes) in buffer
+                    .data
+                    .iter_mut()
+                    .skip(start_offset)
+                    .zip(data.chunks_exact(2))
+                {
+                    *index = u16::from_le_bytes([bytes[0], bytes[1]]);
+                }
+            }
+            Context3DCommand::UploadToVertexBuffer {
+                buffer,
+                start_vertex,
+                data32_per_vertex,
+                data,
+            } => {
+                let Some(buffer) = buffer.as_any().downcast_ref::<SoftwareVertexBuffer>() else {
+                    return;
+                };
+                let mut buffer_data = buffer.data.borrow_mut();
+                let offset = start_vertex * data32_per_vertex as usize * 4;
+                for (dest, source) in buffer_data.iter_mut().skip(offset).zip(&data) {
+                    *dest = *source;
+                }
+            }
+            Context3DCommand::DisposeIndexBuffer { buffer } => {
+                if let Some(buffer) = buffer.as_any_mut().downcast_mut::<SoftwareIndexBuffer>() {
+                    buffer.data = Vec::new();
+                }
+            }
+            Context3DCommand::DisposeVertexBuffer { buffer } => {
+                let Ok(buffer) = buffer.into_any_rc().downcast::<SoftwareVertexBuffer>() else {
+                    return;
+                };
+                for attribute in &mut self.vertex_attributes {
+                    if matches!(attribute, Some(info) if Rc::ptr_eq(&info.buffer, &buffer)) {
+                        *attribute = None;
+                    }
+                }
+                *buffer.data.borrow_mut() = Vec::new();
+            }
+            Context3DCommand::DrawTriangles {
+                index_buffer,
+                first_index,
+                num_triangles,
+            } => {
+                if let Some(index_buffer) = index_buffer.as_any().downcast_ref() {
+                    self.draw_triangles(index_buffer, first_index, num_triangles);
+                }
+            }
+            Context3DCommand::SetVertexBufferAt {
+                index,
+                buffer,
+                buffer_offset,
+            } => {
+                let attribute = buffer.and_then(|(buffer, format)| {
+                    Some(VertexAttribute {
+                        buffer: buffer.into_any_rc().downcast().ok()?,
+                        offset: buffer_offset as usize,
+                        format,
+                    })
+                });
+                if let Some(slot) = self.vertex_attributes.get_mut(index as usize) {
+                    *slot = attribute;
+                }
+            }
+            Context3DCommand::UploadShaders {
+                module,
+                vertex_shader_agal,
+                fragment_shader_agal,
+                error,
+            } => match SoftwareProgram::new(&vertex_shader_agal, &fragment_shader_agal) {
+                Ok(program) => *module.borrow_mut() = Some(Rc::new(program)),
+                Err(e) => *error = Some(e),
+            },
+            Context3DCommand::SetShaders { module } => {
+                self.program = module.and_then(|module| module.into_any_rc().downcast().ok());
+            }
+            Context3DCommand::SetProgramConstantsFromVector {
+                program_type,
+                first_register,
+                matrix_raw_data_column_major,
+            } => {
+                let constants = match program_type {
+                    ProgramType::Vertex => &mut self.vertex_constants,
+                    ProgramType::Fragment => &mut self.fragment_constants,
+                };
+                // As with the wgpu backend, the data is copied in *column* major order.
+                let components = constants.iter_mut().skip(first_register as usize).flatten();
+                for (dest, source) in components.zip(&matrix_raw_data_column_major) {
+                    *dest = *source;
+                }
+            }
+            Context3DCommand::SetCulling { face } => {
+                self.culling = face;
+            }
+            Context3DCommand::CopyBitmapToTexture {
+                source,
+                dest,
+                layer,
+                mip_level,
+            } => {
+                let Some(dest) = dest.as_any().downcast_ref::<SoftwareTexture>() else {
+                    return;
+                };
+                let (level_width, level_height) = dest.level_size(mip_level as usize);
+                let source = source.to_rgba();
+                let mut layers = dest.layers.borrow_mut();
+                let Some(level) = layers
+                    .get_mut(layer as usize)
+                    .and_then(|levels| levels.get_mut(mip_level as usize))
+                else {
+                    return;
+                };
+                let row_length = source.width().min(level_width) as usize * 4;
+                let rows = source
+                    .data()
+                    .chunks_exact(source.width() as usize * 4)
+                    .zip(level.chunks_exact_mut(level_width as usize * 4))
+                    .take(level_height as usize);
+                for (source_row, dest_row) in rows {
+                    dest_row[..row_length].copy_from_slice(&source_row[..row_length]);
+                }
+            }
+            Context3DCommand::DisposeTexture { texture } => {
+                let Ok(texture) = texture.into_any_rc().downcast::<SoftwareTexture>() else {
+                    return;
+                };
+                self.remove_texture(&texture);
+                let is_render_target = self
+                    .texture_targets
+                    .iter()
+                    .flatten()
+                    .any(|target| Rc::ptr_eq(&target.texture, &texture));
+                if is_render_target {
+                    self.set_render_to_back_buffer();
+                }
+                *texture.layers.borrow_mut() = Vec::new();
+            }
+            Context3DCommand::SetTextureAt {
+                sampler,
+                texture,
+                cube: _,
+            } => {
+                let texture = texture.and_then(|texture| texture.into_any_rc().downcast().ok());
+                if let Some(slot) = self.textures.get_mut(sampler as usize) {
+                    *slot = texture;
+                }
+            }
+            Context3DCommand::SetColorMask {
+                red,
+                green,
+                blue,
+                alpha,
+            } => {
+                self.color_mask = [red, green, blue, alpha];
+            }
+            Context3DCommand::SetDepthTest {
+                depth_mask,
+                pass_compare_mode,
+            } => {
+                self.depth_mask = depth_mask;
+                self.depth_compare_mode = pass_compare_mode;
+            }
+            Context3DCommand::SetStencilActions {
+                triangle_face,
+                compare_mode,
+                action_on_both_pass,
+                action_on_depth_fail,
+                action_on_depth_pass_stencil_fail,
+            } => {
+                let face = StencilFace {
+                    compare_mode,
+                    action_on_both_pass,
+                    action_on_depth_fail,
+                    action_on_stencil_fail: action_on_depth_pass_stencil_fail,
+                };
+                if let Context3DTriangleFace::Front | Context3DTriangleFace::FrontAndBack =
+                    triangle_face
+                {
+                    self.stencil_front = face;
+                }
+                if let Context3DTriangleFace::Back | Context3DTriangleFace::FrontAndBack =
+                    triangle_face
+                {
+                    self.stencil_back = face;
+                }
+            }
+            Context3DCommand::SetStencilReferenceValue {
+                reference_value,
+                read_mask,
+                write_mask,
+            } => {
+                self.stencil_reference = reference_value;
+                self.stencil_read_mask = read_mask;
+                self.stencil_write_mask = write_mask;
+            }
+            Context3DCommand::SetBlendFactors {
+                source_factor,
+                destination_factor,
+            } => {
+                self.source_factor = source_factor;
+                self.destination_factor = destination_factor;
+            }
+            Context3DCommand::SetSamplerStateAt {
+                sampler,
+                wrap,
+                filter,
+                mip_filter,
+            } => {
+                if let Some(slot) = self.sampler_overrides.get_mut(sampler as usize) {
+                    *slot = Some(convert_sampler_state(wrap, filter, mip_filter));
+                }
+            }
+            Context3DCommand::SetScissorRectangle { rect } => {
+                self.scissor_rectangle = rect;
+            }
+            Context3DCommand::DrawToBitmapData {
+                destination: _,
+                source_region,
+                dest_point: _,
+                sync_handle,
+            } => {
+                // The pixels are only copied into the BitmapData when it syncs with
+                // `sync_handle`, so `destination` itself is left untouched.
+                *sync_handle = self.draw_to_bitmap_data(source_region);
+            }
+        }
+    }
+}
+
+fn check_texture_format(format: Context3DTextureFormat) -> Result<(), Error> {
+    // As with the wgpu backend, every supported format is stored as RGBA.
+    match format {
+        Context3DTextureFormat::Bgra
+        | Context3DTextureFormat::BgraPacked
+        | Context3DTextureFormat::BgrPacked
+        | Context3DTextureFormat::Compressed
+        | Context3DTextureFormat::CompressedAlpha => Ok(()),
+        Context3DTextureFormat::RgbaHalfFloat => Err(Error::Unimplemented(
+            format!("Texture format {format:?}").into(),
+        )),
+    }
+}
+
+fn convert_sampler_state(
+    wrap: Context3DWrapMode,
+    filter: Context3DTextureFilter,
+    mip_filter: Context3DMipFilter,
+) -> SamplerConfig {
+    SamplerConfig {
+        wrapping: match wrap {
+            Context3DWrapMode::Clamp => Wrapping::Clamp,
+            Context3DWrapMode::Repeat => Wrapping::Repeat,
+            Context3DWrapMode::ClampURepeatV => Wrapping::ClampURepeatV,
+            Context3DWrapMode::RepeatUClampV => Wrapping::RepeatUClampV,
+        },
+        filter: match filter {
+            Context3DTextureFilter::Nearest => Filter::Nearest,
+            Context3DTextureFilter::Linear => Filter::Linear,
+            Context3DTextureFilter::Anisotropic2X => Filter::Anisotropic2X,
+            Context3DTextureFilter::Anisotropic4X => Filter::Anisotropic4X,
+            Context3DTextureFilter::Anisotropic8X => Filter::Anisotropic8X,
+            Context3DTextureFilter::Anisotropic16X => Filter::Anisotropic16X,
+        },
+        mipmap: match mip_filter {
+            Context3DMipFilter::MipNone => Mipmap::Disable,
+            Context3DMipFilter::MipNearest => Mipmap::Nearest,
+            Context3DMipFilter::MipLinear => Mipmap::Linear,
+        },
+    }
+}
+
+/// A vertex and fragment program uploaded with `Program3D.upload`.
+pub struct SoftwareProgram {
+    vertex: Program,
+    fragment: Program,
+}
+
+impl SoftwareProgram {
+    /// Checks both programs, so that a malformed program is reported by `Program3D.upload`
+    /// rather than when drawing.
+    fn new(vertex_bytecode: &[u8], fragment_bytecode: &[u8]) -> Result<Self, Error> {
+        let vertex = Program::parse(vertex_bytecode)?;
+        let fragment = Program::parse(fragment_bytecode)?;
+        for (program, shader_type) in [
+            (&vertex, ShaderType::Vertex),
+            (&fragment, ShaderType::Fragment),
+        ] {
+            if program.shader_type() != shader_type {
+                return Err(Error::InvalidShader(format!(
+                    "Expected a {shader_type:?} program"
+                )));
+            }
+        }
+        Ok(Self { vertex, fragment })
+    }
+}
+
+impl ShaderModule for SoftwareProgram {}
+
+pub struct SoftwareIndexBuffer {
+    data: Vec<u16>,
+}
+
+impl IndexBuffer for SoftwareIndexBuffer {}
+
+pub struct SoftwareVertexBuffer {
+    data: RefCell<Vec<u8>>,
+    data_32_per_vertex: u8,
+}
+
+impl VertexBuffer for SoftwareVertexBuffer {}
+
+struct VertexAttribute {
+    buffer: Rc<SoftwareVertexBuffer>,
+    // An offset in units of buffer entries (f32 or u8)
+    offset: usize,
+    format: Context3DVertexBufferFormat,
+}
+
+impl VertexAttribute {
+    /// Reads the attribute of a vertex, extended to 4 components as AGAL expects.
+    fn read(&self, data: &[u8], vertex: usize) -> Vec4 {
+        let offset = (vertex * self.buffer.data_32_per_vertex as usize + self.offset) * 4;
+        let float = |i: usize| {
+            let start = offset + i * 4;
+            data.get(start..start + 4)
+                .map_or(0.0, |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+        match self.format {
+            Context3DVertexBufferFormat::Float1 => [float(0), 0.0, 0.0, 1.0],
+            Context3DVertexBufferFormat::Float2 => [float(0), float(1), 0.0, 1.0],
+            Context3DVertexBufferFormat::Float3 => [float(0), float(1), float(2), 1.0],
+            Context3DVertexBufferFormat::Float4 => [float(0), float(1), float(2), float(3)],
+            // Each byte is normalized to the range [0, 1].
+            Context3DVertexBufferFormat::Bytes4 => std::array::from_fn(|i| {
+                data.get(offset + i)
+                    .map_or(0.0, |byte| *byte as f32 / 255.0)
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SoftwareSyncHandle {
+    pixels: Vec<u8>,
+    row_length: u32,
+}
+
+impl SyncHandle for SoftwareSyncHandle {
+    fn retrieve_offscreen_texture(self: Box<Self>, with_rgba: RgbaBufRead) -> Result<(), Error> {
+        with_rgba(&self.pixels, self.row_length);
+        Ok(())
+    }
+}
+
+struct Framebuffer {
+    width: u32,
+    height: u32,
+    color: Pixels,
+    depth_stencil: Option<DepthStencil>,
+}
+
+impl Framebuffer {
+    fn new(width: u32, height: u32, depth_and_stencil: bool) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![0; width as usize * height as usize * 4],
+            depth_stencil: depth_and_stencil.then(|| DepthStencil::new(width, height)),
+        }
+    }
+}
+
+struct DepthStencil {
+    depth: Vec<f32>,
+    stencil: Vec<u8>,
+}
+
+impl DepthStencil {
+    fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            depth: vec![0.0; len],
+            stencil: vec![0; len],
+        }
+    }
+}
+
+struct TextureTarget {
+    texture: Rc<SoftwareTexture>,
+    // The face of a cube texture
+    layer: usize,
+}
+
+struct ColorTarget {
+    width: u32,
+    height: u32,
+    pixels: Pixels,
+}
+
+struct RenderTargets {
+    colors: [Option<ColorTarget>; MAX_COLOR_OUTPUTS],
+    depth_stencil: Option<DepthStencil>,
+}
+
+#[derive(Copy, Clone)]
+struct StencilFace {
+    compare_mode: Context3DCompareMode,
+    action_on_both_pass: Context3DStencilAction,
+    action_on_depth_fail: Context3DStencilAction,
+    action_on_stencil_fail: Context3DStencilAction,
+}
+
+impl StencilFace {
+    const IGNORE: Self = Self {
+        compare_mode: Context3DCompareMode::Always,
+        action_on_both_pass: Context3DStencilAction::Keep,
+        action_on_depth_fail: Context3DStencilAction::Keep,
+        action_on_stencil_fail: Context3DStencilAction::Keep,
+    };
+}
+
+struct NoTextures;
+
+impl agal::Textures for NoTextures {
+    fn sample(
+        &self,
+        _index: usize,
+        _dimension: Dimension,
+        _coords: &Lanes<Vec4>,
+        _bias: &Lanes<f32>,
+    ) -> Lanes<Vec4> {
+        [[0.0; 4]; LANES]
+    }
+}
+
+struct TextureUnits<'a> {
+    units: [Option<BoundTexture<'a>>; MAX_TEXTURES],
+}
+
+impl agal::Textures for TextureUnits<'_> {
+    fn sample(
+        &self,
+        index: usize,
+        dimension: Dimension,
+        coords: &Lanes<Vec4>,
+        bias: &Lanes<f32>,
+    ) -> Lanes<Vec4> {
+        match self.units.get(index) {
+            Some(Some(texture)) => texture.sample(dimension, coords, bias),
+            _ => [[0.0; 4]; LANES],
+        }
+    }
+}
+
+/// A vertex output by the vertex program, in clip space.
+#[derive(Copy, Clone)]
+struct ClipVertex {
+    position: Vec4,
+    varyings: [Vec4; MAX_VARYINGS],
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: &Vec4, b: &Vec4| std::array::from_fn(|c| a[c] + (b[c] - a[c]) * t);
+        Self {
+            position: lerp(&self.position, &other.position),
+            varyings: std::array::from_fn(|i| lerp(&self.varyings[i], &other.varyings[i])),
+        }
+    }
+}
+
+/// A vertex in pixel coordinates. The varyings are divided by `w` for perspective-correct
+/// interpolation.
+#[derive(Copy, Clone)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inverse_w: f32,
+    varyings: [Vec4; MAX_VARYINGS],
+}
+
+struct Rasterizer<'a> {
+    context: &'a SoftwareContext3D,
+    program: &'a Program,
+    textures: TextureUnits<'a>,
+    width: u32,
+    height: u32,
+    // The area that can be drawn to, as (x_min, y_min, x_max, y_max)
+    bounds: (u32, u32, u32, u32),
+}
+
+impl Rasterizer<'_> {
+    fn draw_triangle(&self, vertices: [&ClipVertex; 3], targets: &mut RenderTargets) {
+        let polygon = clip_polygon(vertices.iter().map(|vertex| **vertex).collect());
+        if polygon.len() < 3 {
+            return;
+        }
+        let polygon: Vec<ScreenVertex> = polygon
+            .iter()
+            .map(|vertex| self.to_screen(vertex))
+            .collect();
+
+        // Triangles are front-facing when they're clockwise in clip space, see
+        // https://stackoverflow.com/questions/8677498/stage3d-culling-confusion
+        // Screen coordinates go downwards, so this is a positive area on the screen.
+        let area: f32 = (0..polygon.len())
+            .map(|i| {
+                let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        let front_facing = area > 0.0;
+        let culled = match self.context.culling {
+            Context3DTriangleFace::None => false,
+            Context3DTriangleFace::Front => front_facing,
+            Context3DTriangleFace::Back => !front_facing,
+            Context3DTriangleFace::FrontAndBack => true,
+        };
+        if culled {
+            return;
+        }
+
+        for pair in polygon[1..].windows(2) {
+            self.rasterize([&polygon[0], &pair[0], &pair[1]], front_facing, targets);
+        }
+    }
+
+    fn to_screen(&self, vertex: &ClipVertex) -> ScreenVertex {
+        let [x, y, z, w] = vertex.position;
+        let inverse_w = 1.0 / w;
+        ScreenVertex {
+            x: (x * inverse_w + 1.0) * 0.5 * self.width as f32,
+            y: (1.0 - y * inverse_w) * 0.5 * self.height as f32,
+            z: z * inverse_w,
+            inverse_w,
+            varyings: vertex
+                .varyings
+                .map(|varying| varying.map(|c| c * inverse_w)),
+        }
+    }
+
+    fn rasterize(
+        &self,
+        vertices: [&ScreenVertex; 3],
+        front_facing: bool,
+        targets: &mut RenderTargets,
+    ) {
+        let [a, mut b, mut c] = vertices;
+        let mut area = edge(a, b, c.x, c.y);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+            area = -area;
+        }
+        let vertices = [a, b, c];
+        // The edges opposite to each vertex, used for its barycentric coordinate
+        let edges = [(b, c), (c, a), (a, b)];
+
+        let (x_min, y_min, x_max, y_max) = self.bounds;
+        let min = |a: f32, b: f32, c: f32| a.min(b).min(c).floor().max(0.0) as u32;
+        let max = |a: f32, b: f32, c: f32| a.max(b).max(c).ceil().max(0.0) as u32;
+        // Quads are aligned to even coordinates, so that neighbouring triangles
+        // compute the same derivatives.
+        let quad_x_min = min(a.x, b.x, c.x).max(x_min) & !1;
+        let quad_y_min = min(a.y, b.y, c.y).max(y_min) & !1;
+        let quad_x_max = max(a.x, b.x, c.x).min(x_max);
+        let quad_y_max = max(a.y, b.y, c.y).min(y_max);
+
+        let mut registers = Registers::new();
+        for quad_y in (quad_y_min..quad_y_max).step_by(2) {
+            for quad_x in (quad_x_min..quad_x_max).step_by(2) {
+                let pixels: Lanes<(u32, u32)> = std::array::from_fn(|lane| {
+                    (quad_x + (lane & 1) as u32, quad_y + (lane >> 1) as u32)
+                });
+                let weights: Lanes<[f32; 3]> = pixels.map(|(x, y)| {
+                    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                    edges.map(|(from, to)| edge(from, to, px, py))
+                });
+                let covered: Lanes<bool> = std::array::from_fn(|lane| {
+                    let (x, y) = pixels[lane];
+                    x >= x_min
+                        && y >= y_min
+                        && x < x_max
+                        && y < y_max
+                        && edges
+                            .iter()
+                            .zip(&weights[lane])
+                            .all(|((from, to), weight)| {
+                                *weight > 0.0 || (*weight == 0.0 && is_top_left(from, to))
+                            })
+                });
+                if !covered.contains(&true) {
+                    continue;
+                }
+
+                // Interpolate the varyings of every lane, including the ones that are
+                // only used to compute derivatives.
+                let mut depths = [0.0; LANES];
+                for (lane, weights) in weights.iter().enumerate() {
+                    let weights = weights.map(|weight| weight / area);
+                    let interpolate = |value: &dyn Fn(&ScreenVertex) -> f32| -> f32 {
+                        vertices
+                            .iter()
+                            .zip(&weights)
+                            .map(|(vertex, weight)| value(vertex) * weight)
+                            .sum()
+                    };
+                    depths[lane] = interpolate(&|vertex| vertex.z);
+                    let w = 1.0 / interpolate(&|vertex| vertex.inverse_w);
+                    for (i, varying) in registers.varyings.iter_mut().enumerate() {
+                        varying[lane] = std::array::from_fn(|c| {
+                            interpolate(&|vertex| vertex.varyings[i][c]) * w
+                        });
+                    }
+                }
+                registers.killed = [false; LANES];
+
+                self.program.run(
+                    &mut registers,
+                    &self.context.fragment_constants,
+                    &self.textures,
+                );
+
+                for lane in 0..LANES {
+                    if covered[lane] && !registers.killed[lane] {
+                        let depth = if self.program.writes_depth {
+                            registers.depth[lane][0]
+                        } else {
+                            depths[lane]
+                        };
+                        self.write_fragment(
+                            pixels[lane],
+                            depth,
+                            &registers,
+                            lane,
+                            front_facing,
+                            targets,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_fragment(
+        &self,
+        (x, y): (u32, u32),
+        depth: f32,
+        registers: &Registers,
+        lane: usize,
+        front_facing: bool,
+        targets: &mut RenderTargets,
+    ) {
+        let context = self.context;
+        let index = y as usize * self.width as usize + x as usize;
+
+        if let Some(depth_stencil) = &mut targets.depth_stencil {
+            let (Some(stored_depth), Some(stencil)) = (
+                depth_stencil.depth.get_mut(index),
+                depth_stencil.stencil.get_mut(index),
+            ) else {
+                return;
+            };
+            let face = if front_facing {
+                context.stencil_front
+            } else {
+                context.stencil_back
+            };
+            let reference = context.stencil_reference as u8;
+            let read_mask = context.stencil_read_mask as u8;
+            let write_mask = context.stencil_write_mask as u8;
+            let update_stencil = |stencil: &mut u8, action| {
+                let value = apply_stencil_action(action, *stencil, reference);
+                *stencil = (*stencil & !write_mask) | (value & write_mask);
+            };
+
+            if !compare(
+                face.compare_mode,
+                reference & read_mask,
+                *stencil & read_mask,
+            ) {
+                update_stencil(stencil, face.action_on_stencil_fail);
+                return;
+            }
+            let depth = depth.clamp(0.0, 1.0);
+            if !compare(context.depth_compare_mode, depth, *stored_depth) {
+                update_stencil(stencil, face.action_on_depth_fail);
+                return;
+            }
+            update_stencil(stencil, face.action_on_both_pass);
+            if context.depth_mask {
+                *stored_depth = depth;
+            }
+        }
+
+        for ((target, output), written) in targets
+            .colors
+            .iter_mut()
+            .zip(&registers.outputs)
+            .zip(&self.program.color_outputs)
+        {
+            let Some(target) = target else {
+                continue;
+            };
+            if !*written || x >= target.width || y >= target.height {
+                continue;
+            }
+            let start = (y as usize * target.width as usize + x as usize) * 4;
+            let Some(pixel) = target.pixels.get_mut(start..start + 4) else {
+                continue;
+            };
+            let source = output[lane].map(|c| c.clamp(0.0, 1.0));
+            let destination: Vec4 = std::array::from_fn(|c| pixel[c] as f32 / 255.0);
+            let source_factor = blend_factor(context.source_factor, &source, &destination);
+            let destination_factor =
+                blend_factor(context.destination_factor, &source, &destination);
+            for (c, value) in pixel.iter_mut().enumerate() {
+                if context.color_mask[c] {
+                    let blended =
+                        source[c] * source_factor[c] + destination[c] * destination_factor[c];
+                    *value = (blended.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Clips a polygon to the part that's in front of the camera, and not beyond the far plane.
+fn clip_polygon(mut polygon: Vec<ClipVertex>) -> Vec<ClipVertex> {
+    // Each plane is given by the distance of a position to it, which is positive inside.
+    let planes: [fn(&Vec4) -> f32; 3] = [
+        |[_, _, z, _]| *z,
+        |[_, _, z, w]| w - z,
+        // Avoids dividing by a `w` of zero.
+        |[_, _, _, w]| w - f32::EPSILON,
+    ];
+    for plane in planes {
+        if polygon.iter().all(|vertex| plane(&vertex.position) >= 0.0) {
+            continue;
+        }
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, current) in polygon.iter().enumerate() {
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (current_distance, next_distance) =
+                (plane(&current.position), plane(&next.position));
+            if current_distance >= 0.0 {
+                clipped.push(*current);
+            }
+            if (current_distance >= 0.0) != (next_distance >= 0.0) {
+                let t = current_distance / (current_distance - next_distance);
+                clipped.push(current.lerp(next, t));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+/// Twice the signed area of the triangle `(from, to, (x, y))`, which is positive when the
+/// point is to the right of the edge.
+fn edge(from: &ScreenVertex, to: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x)
+}
+
+/// Pixels exactly on an edge are only drawn for top and left edges, so that they
+/// aren't drawn twice by triangles sharing an edge.
+fn is_top_left(from: &ScreenVertex, to: &ScreenVertex) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    dy < 0.0 || (dy == 0.0 && dx > 0.0)
+}
+
+fn compare<T: PartialOrd>(mode: Context3DCompareMode, value: T, stored: T) -> bool {
+    match mode {
+        Context3DCompareMode::Never => false,
+        Context3DCompareMode::Less => value < stored,
+        Context3DCompareMode::Equal => value == stored,
+        Context3DCompareMode::LessEqual => value <= stored,
+        Context3DCompareMode::Greater => value > stored,
+        Context3DCompareMode::NotEqual => value != stored,
+        Context3DCompareMode::GreaterEqual => value >= stored,
+        Context3DCompareMode::Always => true,
+    }
+}
+
+fn apply_stencil_action(action: Context3DStencilAction, value: u8, reference: u8) -> u8 {
+    match action {
+        Context3DStencilAction::DecrementSaturate => value.saturating_sub(1),
+        Context3DStencilAction::DecrementWrap => value.wrapping_sub(1),
+        Context3DStencilAction::IncrementSaturate => value.saturating_add(1),
+        Context3DStencilAction::IncrementWrap => value.wrapping_add(1),
+        Context3DStencilAction::Invert => !value,
+        Context3DStencilAction::Keep => value,
+        Context3DStencilAction::Set => reference,
+        Context3DStencilAction::Zero => 0,
+    }
+}
+
+/// The factor that each component is multiplied by when blending. As with the wgpu
+/// backend, the color factors use the alpha of the same color for the alpha component.
+fn blend_factor(factor: Context3DBlendFactor, source: &Vec4, destination: &Vec4) -> Vec4 {
+    match factor {
+        Context3DBlendFactor::Zero => [0.0; 4],
+        Context3DBlendFactor::One => [1.0; 4],
+        Context3DBlendFactor::SourceAlpha => [source[3]; 4],
+        Context3DBlendFactor::OneMinusSourceAlpha => [1.0 - source[3]; 4],
+        Context3DBlendFactor::DestinationAlpha => [destination[3]; 4],
+        Context3DBlendFactor::OneMinusDestinationAlpha => [1.0 - destination[3]; 4],
+        Context3DBlendFactor::SourceColor => *source,
+        Context3DBlendFactor::OneMinusSourceColor => source.map(|c| 1.0 - c),
+        Context3DBlendFactor::DestinationColor => *destination,
+        Context3DBlendFactor::OneMinusDestinationColor => destination.map(|c| 1.0 - c),
+    }
+}
@@ -0,0 +1,803 @@
+//! An interpreter for AGAL bytecode.
+//!
+//! This follows the semantics of `naga-agal`, which compiles the same bytecode for the GPU
+//! backends, so that both produce the same output. Instructions are decoded with its parser,
+//! and programs are validated the same way when they are uploaded.
+
+use naga_agal::{
+    DestField, Dimension, DirectMode, Opcode, RegisterType, SamplerConfig, SamplerField,
+    ShaderType, SourceField, MAX_COLOR_OUTPUTS, MAX_FRAGMENT_CONSTANTS, MAX_TEXTURES,
+    MAX_VERTEX_ATTRIBUTES, MAX_VERTEX_CONSTANTS,
+};
+use num_traits::FromPrimitive;
+
+use crate::error::Error;
+
+/// The number of invocations of a program that run side by side. Fragments are shaded
+/// in 2x2 quads, so that `ddx`, `ddy` and the selection of texture mip levels can compare
+/// neighbouring pixels. The lanes of a quad are ordered left-to-right, then top-to-bottom.
+pub const LANES: usize = 4;
+
+pub type Vec4 = [f32; 4];
+pub type Lanes<T> = [T; LANES];
+
+pub const MAX_VARYINGS: usize = 10;
+const MAX_TEMPORARIES: usize = 26;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Version {
+    /// Used by the `baseline` profiles
+    Agal1,
+    /// Used by the `standard` and `standardConstrained` profiles
+    Agal2,
+    /// Used by the `standardExtended` profile
+    Agal3,
+}
+
+impl Version {
+    fn num_constants(self, shader_type: ShaderType) -> usize {
+        match (self, shader_type) {
+            (Version::Agal1, ShaderType::Vertex) => 128,
+            (Version::Agal1, ShaderType::Fragment) => 28,
+            (Version::Agal2 | Version::Agal3, ShaderType::Vertex) => MAX_VERTEX_CONSTANTS,
+            (Version::Agal2, ShaderType::Fragment) => 64,
+            (Version::Agal3, ShaderType::Fragment) => MAX_FRAGMENT_CONSTANTS,
+        }
+    }
+
+    fn num_temporaries(self) -> usize {
+        match self {
+            Version::Agal1 => 8,
+            Version::Agal2 | Version::Agal3 => MAX_TEMPORARIES,
+        }
+    }
+
+    fn num_color_outputs(self) -> usize {
+        match self {
+            Version::Agal1 => 1,
+            Version::Agal2 | Version::Agal3 => MAX_COLOR_OUTPUTS,
+        }
+    }
+
+    fn num_varyings(self) -> usize {
+        match self {
+            Version::Agal1 => 8,
+            Version::Agal2 | Version::Agal3 => MAX_VARYINGS,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Dest {
+    register_type: RegisterType,
+    reg_num: usize,
+    write_mask: u8,
+}
+
+/// The register used as an index by `vc[va0.x + offset]`.
+#[derive(Copy, Clone, Debug)]
+struct Indirect {
+    register_type: RegisterType,
+    component: usize,
+    offset: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Source {
+    register_type: RegisterType,
+    reg_num: usize,
+    swizzle: [usize; 4],
+    indirect: Option<Indirect>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Sampler {
+    index: usize,
+    dimension: Dimension,
+    /// In mip levels.
+    lod_bias: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Source2 {
+    Source(Source),
+    Sampler(Sampler),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Instruction {
+    opcode: Opcode,
+    dest: Dest,
+    source1: Source,
+    source2: Source2,
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidShader(message)
+}
+
+fn parse_error(error: naga_agal::Error) -> Error {
+    invalid(error.to_string())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(std::array::from_fn(|i| bytes[offset + i]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(std::array::from_fn(|i| bytes[offset + i]))
+}
+
+/// A program that has been checked to only use the registers available to it.
+pub struct Program {
+    shader_type: ShaderType,
+    instructions: Vec<Instruction>,
+
+    /// How each sampler is used, as given by the first `tex` instruction using it.
+    pub sampler_configs: [Option<SamplerConfig>; MAX_TEXTURES],
+
+    /// Which vertex attributes are read by a vertex program.
+    pub attributes: [bool; MAX_VERTEX_ATTRIBUTES],
+
+    /// Which color outputs are written by a fragment program.
+    pub color_outputs: [bool; MAX_COLOR_OUTPUTS],
+
+    /// Whether a fragment program writes to `fd`.
+    pub writes_depth: bool,
+}
+
+struct Parser {
+    shader_type: ShaderType,
+    version: Version,
+}
+
+impl Parser {
+    fn check_register(&self, register_type: RegisterType, index: usize) -> Result<(), Error> {
+        let num_registers = match (register_type, self.shader_type) {
+            (RegisterType::Attribute, ShaderType::Vertex) => MAX_VERTEX_ATTRIBUTES,
+            (RegisterType::Constant, _) => self.version.num_constants(self.shader_type),
+            (RegisterType::Temporary, _) => self.version.num_temporaries(),
+            (RegisterType::Varying, _) => self.version.num_varyings(),
+            (RegisterType::Sampler, ShaderType::Fragment) => MAX_TEXTURES,
+            (RegisterType::Output, ShaderType::Vertex) => 1,
+            (RegisterType::Output, ShaderType::Fragment) => self.version.num_color_outputs(),
+            (RegisterType::Depth, ShaderType::Fragment) if self.version != Version::Agal1 => 1,
+            _ => 0,
+        };
+        if index >= num_registers {
+            return Err(invalid(format!(
+                "Invalid register: {register_type:?} register {index} in {:?} {:?} program",
+                self.version, self.shader_type
+            )));
+        }
+        Ok(())
+    }
+
+    fn parse_dest(&self, value: u32) -> Result<Dest, Error> {
+        let field = DestField::parse(value).map_err(parse_error)?;
+        let dest = Dest {
+            register_type: field.register_type,
+            reg_num: field.reg_num as usize,
+            write_mask: field.write_mask.bits(),
+        };
+        match (dest.register_type, self.shader_type) {
+            (RegisterType::Temporary | RegisterType::Output | RegisterType::Depth, _)
+            | (RegisterType::Varying, ShaderType::Vertex) => {}
+            _ => {
+                return Err(invalid(format!(
+                    "Unimplemented dest register type {:?}",
+                    dest.register_type
+                )))
+            }
+        }
+        self.check_register(dest.register_type, dest.reg_num)?;
+        Ok(dest)
+    }
+
+    fn parse_source(&self, value: u64) -> Result<Source, Error> {
+        let field = SourceField::parse(value).map_err(parse_error)?;
+        let register_type = field.register_type;
+        let reg_num = field.reg_num as usize;
+
+        let readable = |register_type: RegisterType| {
+            matches!(
+                register_type,
+                RegisterType::Attribute
+                    | RegisterType::Constant
+                    | RegisterType::Temporary
+                    | RegisterType::Varying
+            )
+        };
+        if !readable(register_type) {
+            return Err(invalid(format!(
+                "Unimplemented source register type {register_type:?}"
+            )));
+        }
+
+        let indirect = match field.direct_mode {
+            DirectMode::Direct => {
+                self.check_register(register_type, reg_num)?;
+                None
+            }
+            DirectMode::Indirect => {
+                // Indirect loads are only supported from constants, e.g. `vc[va0.x + offset]`
+                if register_type != RegisterType::Constant || !readable(field.index_type) {
+                    return Err(invalid(format!(
+                        "Unimplemented register type in indirect mode {register_type:?}"
+                    )));
+                }
+                self.check_register(field.index_type, reg_num)?;
+                Some(Indirect {
+                    register_type: field.index_type,
+                    component: field.index_select as usize,
+                    offset: field.indirect_offset as usize,
+                })
+            }
+        };
+
+        Ok(Source {
+            register_type,
+            reg_num,
+            swizzle: std::array::from_fn(|i| ((field.swizzle >> (i * 2)) & 0b11) as usize),
+            indirect,
+        })
+    }
+
+    fn parse_sampler(&self, value: u64) -> Result<(Sampler, SamplerConfig), Error> {
+        let field = SamplerField::parse(value).map_err(parse_error)?;
+        let index = field.reg_num as usize;
+        if field.reg_type != RegisterType::Sampler {
+            return Err(invalid(format!(
+                "Invalid sampler: register type {:?}",
+                field.reg_type
+            )));
+        }
+        self.check_register(field.reg_type, index)?;
+
+        // Flash Player seems to ignore this flag, and `naga-agal` rejects it.
+        if field.special.ignore_sampler {
+            return Err(invalid(format!(
+                "Unimplemented: ignore_sampler in {value:#x}"
+            )));
+        }
+
+        Ok((
+            Sampler {
+                index,
+                dimension: field.dimension,
+                // The LOD bias is stored in eighths of a mip level.
+                lod_bias: field.texture_lod_bias as f32 / 8.0,
+            },
+            SamplerConfig {
+                wrapping: field.wrapping,
+                filter: field.filter,
+                mipmap: field.mipmap,
+            },
+        ))
+    }
+}
+
+impl Program {
+    pub fn parse(bytecode: &[u8]) -> Result<Self, Error> {
+        if bytecode.len() < 7 || bytecode[0] != 0xa0 || bytecode[5] != 0xa1 {
+            return Err(invalid("Invalid program header".to_string()));
+        }
+        let version = match read_u32(bytecode, 1) {
+            1 => Version::Agal1,
+            2 => Version::Agal2,
+            3 => Version::Agal3,
+            version => return Err(invalid(format!("Invalid version {version}"))),
+        };
+        let shader_type = match bytecode[6] {
+            0x00 => ShaderType::Vertex,
+            0x01 => ShaderType::Fragment,
+            ty => return Err(invalid(format!("Invalid shader type {ty}"))),
+        };
+
+        let tokens = &bytecode[7..];
+        if tokens.len() % 24 != 0 {
+            return Err(invalid("Program is truncated".to_string()));
+        }
+
+        let parser = Parser {
+            shader_type,
+            version,
+        };
+        let mut program = Program {
+            shader_type,
+            instructions: Vec::with_capacity(tokens.len() / 24),
+            sampler_configs: [None; MAX_TEXTURES],
+            attributes: [false; MAX_VERTEX_ATTRIBUTES],
+            color_outputs: [false; MAX_COLOR_OUTPUTS],
+            writes_depth: false,
+        };
+        // Whether each enclosing 'if' has already seen its 'els'
+        let mut blocks: Vec<bool> = Vec::new();
+
+        for token in tokens.chunks_exact(24) {
+            let raw_opcode = read_u32(token, 0);
+            let opcode = Opcode::from_u32(raw_opcode)
+                // `ted` is only available from AGAL version 2
+                .filter(|opcode| !(*opcode == Opcode::Ted && version == Version::Agal1))
+                .ok_or_else(|| invalid(format!("Invalid opcode {raw_opcode:#x}")))?;
+
+            let fragment_only = matches!(
+                opcode,
+                Opcode::Tex | Opcode::Ted | Opcode::Kil | Opcode::Ddx | Opcode::Ddy
+            );
+            if fragment_only && shader_type != ShaderType::Fragment {
+                return Err(invalid(format!("Opcode {opcode:?} in a vertex program")));
+            }
+
+            // The fields that an opcode doesn't use don't need to be valid.
+            let dest = if writes_dest(opcode) {
+                parser.parse_dest(read_u32(token, 4))?
+            } else {
+                Dest {
+                    register_type: RegisterType::Temporary,
+                    reg_num: 0,
+                    write_mask: 0,
+                }
+            };
+            let source1 = if let Opcode::Els | Opcode::Eif = opcode {
+                Source {
+                    register_type: RegisterType::Temporary,
+                    reg_num: 0,
+                    swizzle: [0, 1, 2, 3],
+                    indirect: None,
+                }
+            } else {
+                parser.parse_source(read_u64(token, 8))?
+            };
+            let source2 = if let Opcode::Tex | Opcode::Ted = opcode {
+                let (sampler, config) = parser.parse_sampler(read_u64(token, 16))?;
+                // There's only one sampler per texture, so if several `tex` instructions
+                // use different flags for the same texture, the first one wins.
+                let sampler_config = &mut program.sampler_configs[sampler.index];
+                if sampler_config.is_none() {
+                    *sampler_config = Some(config);
+                }
+                Source2::Sampler(sampler)
+            } else if let Opcode::M33 | Opcode::M34 | Opcode::M44 = opcode {
+                // The rows of a matrix are read from consecutive registers.
+                let source2 = parser.parse_source(read_u64(token, 16))?;
+                let num_rows = if opcode == Opcode::M44 { 4 } else { 3 };
+                if source2.indirect.is_none() {
+                    parser.check_register(source2.register_type, source2.reg_num + num_rows - 1)?;
+                }
+                Source2::Source(source2)
+            } else if uses_source2(opcode) {
+                Source2::Source(parser.parse_source(read_u64(token, 16))?)
+            } else {
+                Source2::Source(source1)
+            };
+
+            match opcode {
+                Opcode::Ife | Opcode::Ine | Opcode::Ifg | Opcode::Ifl => blocks.push(false),
+                Opcode::Els => match blocks.last_mut() {
+                    Some(seen_else) if !*seen_else => *seen_else = true,
+                    Some(_) => {
+                        return Err(invalid(
+                            "Invalid control flow: multiple 'els' opcodes for single 'if' opcode"
+                                .to_string(),
+                        ))
+                    }
+                    None => {
+                        return Err(invalid(
+                            "Invalid control flow: 'els' opcode without matching 'if'".to_string(),
+                        ))
+                    }
+                },
+                Opcode::Eif => {
+                    if blocks.pop().is_none() {
+                        return Err(invalid(
+                            "Invalid control flow: 'eif' opcode without matching 'if'".to_string(),
+                        ));
+                    }
+                }
+                Opcode::Kil => {
+                    if source1.swizzle.iter().any(|c| *c != source1.swizzle[0]) {
+                        return Err(invalid(
+                            "Unimplemented: kil with a source swizzle involving multiple components"
+                                .to_string(),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+
+            let instruction = Instruction {
+                opcode,
+                dest,
+                source1,
+                source2,
+            };
+            if writes_dest(opcode) {
+                match dest.register_type {
+                    RegisterType::Output if shader_type == ShaderType::Fragment => {
+                        program.color_outputs[dest.reg_num] = true
+                    }
+                    RegisterType::Depth => program.writes_depth = true,
+                    _ => {}
+                }
+            }
+            program.mark_attributes(&instruction);
+            program.instructions.push(instruction);
+        }
+
+        if !blocks.is_empty() {
+            return Err(invalid(
+                "Invalid control flow: 'if' opcode without matching 'eif'".to_string(),
+            ));
+        }
+        if shader_type == ShaderType::Fragment {
+            // The main color output always exists.
+            program.color_outputs[0] = true;
+        }
+
+        Ok(program)
+    }
+
+    pub fn shader_type(&self) -> ShaderType {
+        self.shader_type
+    }
+
+    fn mark_attributes(&mut self, instruction: &Instruction) {
+        let mut sources = vec![instruction.source1];
+        if let Source2::Source(source2) = instruction.source2 {
+            if uses_source2(instruction.opcode) {
+                sources.push(source2);
+            }
+        }
+        for source in sources {
+            match source.indirect {
+                Some(indirect) if indirect.register_type == RegisterType::Attribute => {
+                    self.attributes[source.reg_num] = true
+                }
+                None if source.register_type == RegisterType::Attribute => {
+                    self.attributes[source.reg_num] = true
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Runs the program for each lane of `registers`.
+    pub fn run(&self, registers: &mut Registers, constants: &[Vec4], textures: &dyn Textures) {
+        let mut active = [true; LANES];
+        // The active lanes before each enclosing 'if', along with its condition
+        let mut blocks: Vec<(Lanes<bool>, Lanes<bool>)> = Vec::new();
+
+        for instruction in &self.instructions {
+            let Instruction {
+                opcode,
+                dest,
+                source1,
+                source2,
+            } = *instruction;
+            let load = |source: &Source| registers.load(source, constants);
+            let load2 = || match &source2 {
+                Source2::Source(source) => load(source),
+                Source2::Sampler(_) => [[0.0; 4]; LANES],
+            };
+
+            let result = match opcode {
+                Opcode::Mov => load(&source1),
+                Opcode::Add => zip(load(&source1), load2(), |a, b| a + b),
+                Opcode::Sub => zip(load(&source1), load2(), |a, b| a - b),
+                Opcode::Mul => zip(load(&source1), load2(), |a, b| a * b),
+                Opcode::Div => zip(load(&source1), load2(), |a, b| a / b),
+                Opcode::Min => zip(load(&source1), load2(), f32::min),
+                Opcode::Max => zip(load(&source1), load2(), f32::max),
+                Opcode::Pow => zip(load(&source1), load2(), f32::powf),
+                Opcode::Sge => zip(load(&source1), load2(), |a, b| bool_to_f32(a >= b)),
+                Opcode::Slt => zip(load(&source1), load2(), |a, b| bool_to_f32(a < b)),
+                Opcode::Seq => zip(load(&source1), load2(), |a, b| bool_to_f32(a == b)),
+                Opcode::Sne => zip(load(&source1), load2(), |a, b| bool_to_f32(a != b)),
+                Opcode::Rcp => map(load(&source1), |a| 1.0 / a),
+                Opcode::Frc => map(load(&source1), |a| a - a.floor()),
+                Opcode::Sqt => map(load(&source1), f32::sqrt),
+                Opcode::Rsq => map(load(&source1), |a| 1.0 / a.sqrt()),
+                Opcode::Log => map(load(&source1), f32::log2),
+                Opcode::Exp => map(load(&source1), f32::exp2),
+                Opcode::Sin => map(load(&source1), f32::sin),
+                Opcode::Cos => map(load(&source1), f32::cos),
+                Opcode::Abs => map(load(&source1), f32::abs),
+                Opcode::Neg => map(load(&source1), |a| -a),
+                Opcode::Sat => map(load(&source1), |a| a.clamp(0.0, 1.0)),
+                Opcode::Nrm => load(&source1).map(|[x, y, z, _]| {
+                    let length = (x * x + y * y + z * z).sqrt();
+                    [x / length, y / length, z / length, 0.0]
+                }),
+                Opcode::Crs => {
+                    let a = load(&source1);
+                    let b = load2();
+                    std::array::from_fn(|lane| {
+                        let [ax, ay, az, _] = a[lane];
+                        let [bx, by, bz, _] = b[lane];
+                        [ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx, 1.0]
+                    })
+                }
+                Opcode::Dp3 => {
+                    let a = load(&source1);
+                    let b = load2();
+                    std::array::from_fn(|lane| [dot(&a[lane][..3], &b[lane][..3]); 4])
+                }
+                Opcode::Dp4 => {
+                    let a = load(&source1);
+                    let b = load2();
+                    std::array::from_fn(|lane| [dot(&a[lane], &b[lane]); 4])
+                }
+                Opcode::M33 | Opcode::M34 | Opcode::M44 => {
+                    let Source2::Source(matrix) = source2 else {
+                        continue;
+                    };
+                    let (num_rows, row_size) = match opcode {
+                        Opcode::M33 => (3, 3),
+                        Opcode::M34 => (3, 4),
+                        _ => (4, 4),
+                    };
+                    let vector = load(&source1);
+                    let rows: [Lanes<Vec4>; 4] = std::array::from_fn(|row| {
+                        let mut source = matrix;
+                        match &mut source.indirect {
+                            Some(indirect) => indirect.offset += row,
+                            None => source.reg_num += row,
+                        }
+                        if row < num_rows {
+                            load(&source)
+                        } else {
+                            [[0.0; 4]; LANES]
+                        }
+                    });
+                    std::array::from_fn(|lane| {
+                        let v = &vector[lane][..row_size];
+                        std::array::from_fn(|row| {
+                            if row < num_rows {
+                                dot(&rows[row][lane][..row_size], v)
+                            } else {
+                                1.0
+                            }
+                        })
+                    })
+                }
+                Opcode::Ddx => {
+                    let a = load(&source1);
+                    let dx = std::array::from_fn(|c| a[1][c] - a[0][c]);
+                    [dx; LANES]
+                }
+                Opcode::Ddy => {
+                    let a = load(&source1);
+                    let dy = std::array::from_fn(|c| a[2][c] - a[0][c]);
+                    [dy; LANES]
+                }
+                Opcode::Tex | Opcode::Ted => {
+                    let Source2::Sampler(sampler) = source2 else {
+                        continue;
+                    };
+                    let coords = load(&source1);
+                    let bias = std::array::from_fn(|lane| {
+                        if opcode == Opcode::Ted {
+                            // `ted` takes an additional LOD bias from the last component.
+                            sampler.lod_bias + coords[lane][3]
+                        } else {
+                            sampler.lod_bias
+                        }
+                    });
+                    textures.sample(sampler.index, sampler.dimension, &coords, &bias)
+                }
+                Opcode::Kil => {
+                    let a = load(&source1);
+                    for ((killed, active), value) in
+                        registers.killed.iter_mut().zip(&active).zip(&a)
+                    {
+                        if *active && value[0] < 0.0 {
+                            *killed = true;
+                        }
+                    }
+                    continue;
+                }
+                Opcode::Ife | Opcode::Ine | Opcode::Ifg | Opcode::Ifl => {
+                    let a = load(&source1);
+                    let b = load2();
+                    let condition = std::array::from_fn(|lane| {
+                        a[lane].iter().zip(&b[lane]).all(|(a, b)| match opcode {
+                            Opcode::Ife => a == b,
+                            Opcode::Ine => a != b,
+                            Opcode::Ifg => a > b,
+                            _ => a < b,
+                        })
+                    });
+                    blocks.push((active, condition));
+                    active = std::array::from_fn(|lane| active[lane] && condition[lane]);
+                    continue;
+                }
+                Opcode::Els => {
+                    if let Some((before, condition)) = blocks.last() {
+                        active = std::array::from_fn(|lane| before[lane] && !condition[lane]);
+                    }
+                    continue;
+                }
+                Opcode::Eif => {
+                    if let Some((before, _)) = blocks.pop() {
+                        active = before;
+                    }
+                    continue;
+                }
+            };
+
+            registers.store(&dest, &result, &active);
+        }
+    }
+}
+
+fn uses_source2(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Add
+            | Opcode::Sub
+            | Opcode::Mul
+            | Opcode::Div
+            | Opcode::Min
+            | Opcode::Max
+            | Opcode::Pow
+            | Opcode::Crs
+            | Opcode::Dp3
+            | Opcode::Dp4
+            | Opcode::Sge
+            | Opcode::Slt
+            | Opcode::Seq
+            | Opcode::Sne
+            | Opcode::Ife
+            | Opcode::Ine
+            | Opcode::Ifg
+            | Opcode::Ifl
+    )
+}
+
+fn writes_dest(opcode: Opcode) -> bool {
+    !matches!(
+        opcode,
+        Opcode::Kil
+            | Opcode::Ife
+            | Opcode::Ine
+            | Opcode::Ifg
+            | Opcode::Ifl
+            | Opcode::Els
+            | Opcode::Eif
+    )
+}
+
+fn bool_to_f32(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn map(a: Lanes<Vec4>, f: impl Fn(f32) -> f32) -> Lanes<Vec4> {
+    a.map(|a| a.map(&f))
+}
+
+fn zip(a: Lanes<Vec4>, b: Lanes<Vec4>, f: impl Fn(f32, f32) -> f32) -> Lanes<Vec4> {
+    std::array::from_fn(|lane| std::array::from_fn(|c| f(a[lane][c], b[lane][c])))
+}
+
+/// Samples the textures bound to a fragment program.
+pub trait Textures {
+    /// Samples texture `index` for each lane of a quad, using the coordinates (`xy`, or `xyz`
+    /// for a cube texture) of `coords`, and adding `bias` to the mip level they select.
+    fn sample(
+        &self,
+        index: usize,
+        dimension: Dimension,
+        coords: &Lanes<Vec4>,
+        bias: &Lanes<f32>,
+    ) -> Lanes<Vec4>;
+}
+
+/// The registers of `LANES` invocations of a program.
+pub struct Registers {
+    /// `va0` to `va7`, already extended to 4 components.
+    pub attributes: [Lanes<Vec4>; MAX_VERTEX_ATTRIBUTES],
+
+    /// `v0` to `v9`, written by a vertex program and read by a fragment program.
+    pub varyings: [Lanes<Vec4>; MAX_VARYINGS],
+
+    /// `op` in a vertex program, or `oc0` to `oc3` in a fragment program.
+    pub outputs: [Lanes<Vec4>; MAX_COLOR_OUTPUTS],
+
+    /// `fd` in a fragment program, of which only the first component is used.
+    pub depth: Lanes<Vec4>,
+
+    /// The fragments discarded by `kil`.
+    pub killed: Lanes<bool>,
+
+    temporaries: [Lanes<Vec4>; MAX_TEMPORARIES],
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self {
+            attributes: [[[0.0; 4]; LANES]; MAX_VERTEX_ATTRIBUTES],
+            varyings: [[[0.0; 4]; LANES]; MAX_VARYINGS],
+            outputs: [[[0.0; 4]; LANES]; MAX_COLOR_OUTPUTS],
+            depth: [[0.0; 4]; LANES],
+            killed: [false; LANES],
+            temporaries: [[[0.0; 4]; LANES]; MAX_TEMPORARIES],
+        }
+    }
+
+    fn register(&self, register_type: RegisterType, index: usize, lane: usize) -> Vec4 {
+        let registers: &[Lanes<Vec4>] = match register_type {
+            RegisterType::Attribute => &self.attributes,
+            RegisterType::Temporary => &self.temporaries,
+            RegisterType::Varying => &self.varyings,
+            _ => &[],
+        };
+        registers
+            .get(index)
+            .map_or([0.0; 4], |register| register[lane])
+    }
+
+    fn load(&self, source: &Source, constants: &[Vec4]) -> Lanes<Vec4> {
+        std::array::from_fn(|lane| {
+            let value = match source.indirect {
+                Some(indirect) => {
+                    let index = self.register(indirect.register_type, source.reg_num, lane)
+                        [indirect.component];
+                    // Out-of-bounds accesses are clamped, as is done by the GPU.
+                    let index =
+                        (index as usize + indirect.offset).min(constants.len().saturating_sub(1));
+                    constants.get(index).copied().unwrap_or([0.0; 4])
+                }
+                None if source.register_type == RegisterType::Constant => {
+                    constants.get(source.reg_num).copied().unwrap_or([0.0; 4])
+                }
+                None => self.register(source.register_type, source.reg_num, lane),
+            };
+            if source.swizzle == [0, 1, 2, 3] {
+                value
+            } else {
+                source.swizzle.map(|component| value[component])
+            }
+        })
+    }
+
+    fn store(&mut self, dest: &Dest, value: &Lanes<Vec4>, active: &Lanes<bool>) {
+        let register = match dest.register_type {
+            RegisterType::Output => &mut self.outputs[dest.reg_num],
+            RegisterType::Depth => &mut self.depth,
+            RegisterType::Varying => &mut self.varyings[dest.reg_num],
+            RegisterType::Temporary => &mut self.temporaries[dest.reg_num],
+            _ => return,
+        };
+        // A write to a single component always uses the first component of the value.
+        let scalar_write = dest.write_mask.count_ones() == 1;
+        for ((lane, value), active) in register.iter_mut().zip(value).zip(active) {
+            if !*active {
+                continue;
+            }
+            for (component, slot) in lane.iter_mut().enumerate() {
+                if dest.write_mask & (1 << component) != 0 {
+                    *slot = if scalar_write {
+                        value[0]
+                    } else {
+                        value[component]
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
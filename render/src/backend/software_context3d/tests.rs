@@ -0,0 +1,447 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::SoftwareContext3D;
+use crate::backend::null::NullRenderer;
+use crate::backend::{
+    BufferUsage, Context3D, Context3DBlendFactor, Context3DCommand, Context3DCompareMode,
+    Context3DMipFilter, Context3DTextureFilter, Context3DTextureFormat,
+    Context3DVertexBufferFormat, Context3DWrapMode, ShaderModule, ViewportDimensions,
+};
+use crate::bitmap::{Bitmap, BitmapFormat};
+
+const SIZE: u32 = 4;
+
+const ATTRIBUTE: u32 = 0;
+const TEMPORARY: u32 = 2;
+const OUTPUT: u32 = 3;
+const VARYING: u32 = 4;
+const SAMPLER: u32 = 5;
+
+const MOV: u32 = 0x00;
+const TEX: u32 = 0x28;
+
+// Sampler flags, from the top bytes of an AGAL sampler.
+const CLAMP: u64 = 0;
+const REPEAT: u64 = 1 << 52;
+const CLAMP_U_REPEAT_V: u64 = 2 << 52;
+const REPEAT_U_CLAMP_V: u64 = 3 << 52;
+const NEAREST: u64 = 0;
+const LINEAR: u64 = 1 << 60;
+
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+fn program_header(fragment: bool) -> Vec<u8> {
+    vec![0xa0, 1, 0, 0, 0, 0xa1, fragment as u8]
+}
+
+/// Appends `opcode dest.xyzw, source1.xyzw, source2` to the bytecode.
+fn push_instruction(
+    bytecode: &mut Vec<u8>,
+    opcode: u32,
+    (dest_type, dest_num): (u32, u32),
+    source1: u64,
+    source2: u64,
+) {
+    let dest = dest_num | (0xF << 16) | (dest_type << 24);
+    let source1 = source1 | (0xE4 << 24);
+    bytecode.extend_from_slice(&opcode.to_le_bytes());
+    bytecode.extend_from_slice(&dest.to_le_bytes());
+    bytecode.extend_from_slice(&source1.to_le_bytes());
+    bytecode.extend_from_slice(&source2.to_le_bytes());
+}
+
+/// An AGAL 1 program made of `mov` instructions, from each source to each destination.
+fn mov_program(fragment: bool, movs: &[(u32, u32, u64)]) -> Vec<u8> {
+    let mut bytecode = program_header(fragment);
+    for (dest_type, dest_num, source) in movs {
+        push_instruction(&mut bytecode, MOV, (*dest_type, *dest_num), *source, 0);
+    }
+    bytecode
+}
+
+fn source(register_type: u32, reg_num: u32) -> u64 {
+    reg_num as u64 | ((register_type as u64) << 32)
+}
+
+/// Passes `va0` through as the position, and `va1` as the color.
+fn color_program() -> (Vec<u8>, Vec<u8>) {
+    let vertex = mov_program(
+        false,
+        &[
+            (OUTPUT, 0, source(ATTRIBUTE, 0)),
+            (VARYING, 0, source(ATTRIBUTE, 1)),
+        ],
+    );
+    let fragment = mov_program(true, &[(OUTPUT, 0, source(VARYING, 0))]);
+    (vertex, fragment)
+}
+
+/// Passes `va1` through as the texture coordinates, and samples `fs0` at them with the given
+/// sampler flags.
+fn texture_program(wrapping: u64, filter: u64) -> (Vec<u8>, Vec<u8>) {
+    let (vertex, _) = color_program();
+    let mut fragment = program_header(true);
+    // `tex ft0, v0, fs0 <flags>`
+    push_instruction(
+        &mut fragment,
+        TEX,
+        (TEMPORARY, 0),
+        source(VARYING, 0),
+        source(SAMPLER, 0) | wrapping | filter,
+    );
+    // `mov oc, ft0`
+    push_instruction(&mut fragment, MOV, (OUTPUT, 0), source(TEMPORARY, 0), 0);
+    (vertex, fragment)
+}
+
+fn create_context(renderer: &mut NullRenderer) -> SoftwareContext3D {
+    let mut context = SoftwareContext3D::new(renderer).expect("Context should be created");
+    context.process_command(Context3DCommand::ConfigureBackBuffer {
+        width: SIZE,
+        height: SIZE,
+        anti_alias: 0,
+        depth_and_stencil: true,
+        wants_best_resolution: false,
+        wants_best_resolution_on_browser_zoom: false,
+    });
+
+    let (vertex, fragment) = color_program();
+    set_program(&mut context, vertex, fragment);
+    context
+}
+
+fn set_program(context: &mut SoftwareContext3D, vertex: Vec<u8>, fragment: Vec<u8>) {
+    let module: RefCell<Option<Rc<dyn ShaderModule>>> = RefCell::new(None);
+    let mut error = None;
+    context.process_command(Context3DCommand::UploadShaders {
+        module: &module,
+        vertex_shader_agal: vertex,
+        fragment_shader_agal: fragment,
+        error: &mut error,
+    });
+    assert!(error.is_none(), "Program should be valid: {error:?}");
+    context.process_command(Context3DCommand::SetShaders {
+        module: module.borrow().clone(),
+    });
+}
+
+/// Uploads a texture with the given rows of texels, and binds it to `fs0`.
+fn bind_texture(context: &mut SoftwareContext3D, rows: &[&[[u8; 4]]]) {
+    let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+    let texture = context
+        .create_texture(width, height, Context3DTextureFormat::Bgra, false, 0)
+        .expect("Texture should be created");
+    let data = rows.iter().flat_map(|row| row.concat()).collect();
+    context.process_command(Context3DCommand::CopyBitmapToTexture {
+        source: Bitmap::new(width, height, BitmapFormat::Rgba, data),
+        dest: texture.clone(),
+        layer: 0,
+        mip_level: 0,
+    });
+    context.process_command(Context3DCommand::SetTextureAt {
+        sampler: 0,
+        texture: Some(texture),
+        cube: false,
+    });
+}
+
+fn clear(context: &mut SoftwareContext3D, color: [f64; 4]) {
+    context.process_command(Context3DCommand::Clear {
+        red: color[0],
+        green: color[1],
+        blue: color[2],
+        alpha: color[3],
+        depth: 1.0,
+        stencil: 0,
+        mask: 0b111,
+    });
+}
+
+/// Draws a triangle covering the whole back buffer at the given depth.
+fn draw_fullscreen_triangle(context: &mut SoftwareContext3D, depth: f32, color: [f32; 4]) {
+    draw_fullscreen_triangle_with(context, depth, |_, _| color);
+}
+
+/// Draws a triangle covering the whole back buffer, with texture coordinates going from
+/// (0, 0) at the top left to (`scale`, `scale`) at the bottom right.
+fn draw_textured_triangle(context: &mut SoftwareContext3D, scale: f32) {
+    draw_fullscreen_triangle_with(context, 0.5, |x, y| {
+        [(x + 1.0) / 2.0 * scale, (1.0 - y) / 2.0 * scale, 0.0, 0.0]
+    });
+}
+
+/// Draws a triangle covering the whole back buffer, where `va1` of each vertex is computed from
+/// its position.
+fn draw_fullscreen_triangle_with(
+    context: &mut SoftwareContext3D,
+    depth: f32,
+    attribute: impl Fn(f32, f32) -> [f32; 4],
+) {
+    let positions = [[-1.0, -1.0], [-1.0, 3.0], [3.0, -1.0]];
+    let data: Vec<u8> = positions
+        .iter()
+        .flat_map(|[x, y]| [*x, *y, depth].into_iter().chain(attribute(*x, *y)))
+        .flat_map(f32::to_le_bytes)
+        .collect();
+
+    let vertex_buffer = context.create_vertex_buffer(BufferUsage::StaticDraw, 3, 7);
+    context.process_command(Context3DCommand::UploadToVertexBuffer {
+        buffer: vertex_buffer.clone(),
+        start_vertex: 0,
+        data32_per_vertex: 7,
+        data,
+    });
+    for (index, format, offset) in [
+        (0, Context3DVertexBufferFormat::Float3, 0),
+        (1, Context3DVertexBufferFormat::Float4, 3),
+    ] {
+        context.process_command(Context3DCommand::SetVertexBufferAt {
+            index,
+            buffer: Some((vertex_buffer.clone(), format)),
+            buffer_offset: offset,
+        });
+    }
+
+    let mut index_buffer = context.create_index_buffer(BufferUsage::StaticDraw, 3);
+    context.process_command(Context3DCommand::UploadToIndexBuffer {
+        buffer: index_buffer.as_mut(),
+        start_offset: 0,
+        data: [0u16, 1, 2].iter().flat_map(|i| i.to_le_bytes()).collect(),
+    });
+    context.process_command(Context3DCommand::DrawTriangles {
+        index_buffer: index_buffer.as_ref(),
+        first_index: 0,
+        num_triangles: 1,
+    });
+}
+
+fn presented_pixels(context: &mut SoftwareContext3D, renderer: &mut NullRenderer) -> Vec<[u8; 4]> {
+    context.present(renderer).expect("Present should succeed");
+    let bitmap = context.front_buffer().expect("Back buffer was configured");
+    bitmap
+        .data()
+        .chunks_exact(4)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+        .collect()
+}
+
+fn renderer() -> NullRenderer {
+    NullRenderer::with_software_context3d(ViewportDimensions {
+        width: SIZE,
+        height: SIZE,
+        scale_factor: 1.0,
+    })
+}
+
+#[test]
+fn clear_and_draw() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+
+    clear(&mut context, [1.0, 0.0, 0.0, 1.0]);
+    assert!(presented_pixels(&mut context, &mut renderer)
+        .iter()
+        .all(|pixel| *pixel == [255, 0, 0, 255]));
+
+    clear(&mut context, [1.0, 0.0, 0.0, 1.0]);
+    draw_fullscreen_triangle(&mut context, 0.5, [0.0, 1.0, 0.0, 1.0]);
+    assert!(presented_pixels(&mut context, &mut renderer)
+        .iter()
+        .all(|pixel| *pixel == [0, 255, 0, 255]));
+}
+
+#[test]
+fn depth_test() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    context.process_command(Context3DCommand::SetDepthTest {
+        depth_mask: true,
+        pass_compare_mode: Context3DCompareMode::Less,
+    });
+
+    clear(&mut context, [0.0, 0.0, 0.0, 1.0]);
+    draw_fullscreen_triangle(&mut context, 0.5, [0.0, 0.0, 1.0, 1.0]);
+    // This is behind the first triangle, so it isn't drawn.
+    draw_fullscreen_triangle(&mut context, 0.8, [1.0, 0.0, 0.0, 1.0]);
+    assert!(presented_pixels(&mut context, &mut renderer)
+        .iter()
+        .all(|pixel| *pixel == [0, 0, 255, 255]));
+}
+
+#[test]
+fn blending() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    context.process_command(Context3DCommand::SetBlendFactors {
+        source_factor: Context3DBlendFactor::SourceAlpha,
+        destination_factor: Context3DBlendFactor::OneMinusSourceAlpha,
+    });
+
+    clear(&mut context, [0.0, 0.0, 1.0, 1.0]);
+    draw_fullscreen_triangle(&mut context, 0.5, [1.0, 0.0, 0.0, 0.5]);
+    assert!(presented_pixels(&mut context, &mut renderer)
+        .iter()
+        .all(|pixel| pixel[..3] == [128, 0, 128]));
+}
+
+#[test]
+fn invalid_program() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    let (vertex, fragment) = color_program();
+
+    let module: RefCell<Option<Rc<dyn ShaderModule>>> = RefCell::new(None);
+    let mut error = None;
+    // The programs are the wrong way around.
+    context.process_command(Context3DCommand::UploadShaders {
+        module: &module,
+        vertex_shader_agal: fragment,
+        fragment_shader_agal: vertex,
+        error: &mut error,
+    });
+    assert!(error.is_some());
+    assert!(module.borrow().is_none());
+}
+
+/// Draws the texture over the whole back buffer, and returns the presented rows of pixels.
+fn draw_texture(
+    renderer: &mut NullRenderer,
+    context: &mut SoftwareContext3D,
+    scale: f32,
+) -> Vec<Vec<[u8; 4]>> {
+    clear(context, [0.0, 0.0, 0.0, 0.0]);
+    draw_textured_triangle(context, scale);
+    presented_pixels(context, renderer)
+        .chunks_exact(SIZE as usize)
+        .map(<[_]>::to_vec)
+        .collect()
+}
+
+#[test]
+fn texture_nearest() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    let (vertex, fragment) = texture_program(CLAMP, NEAREST);
+    set_program(&mut context, vertex, fragment);
+    bind_texture(&mut context, &[&[RED, GREEN], &[BLUE, WHITE]]);
+
+    // Each texel covers a 2x2 block of pixels.
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 1.0),
+        [
+            [RED, RED, GREEN, GREEN],
+            [RED, RED, GREEN, GREEN],
+            [BLUE, BLUE, WHITE, WHITE],
+            [BLUE, BLUE, WHITE, WHITE],
+        ]
+    );
+}
+
+#[test]
+fn texture_linear() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    let (vertex, fragment) = texture_program(CLAMP, LINEAR);
+    set_program(&mut context, vertex, fragment);
+    bind_texture(&mut context, &[&[BLACK, WHITE], &[BLACK, WHITE]]);
+
+    // The pixel centers are at texel coordinates -0.25, 0.25, 0.75 and 1.25, so the inner
+    // pixels are a quarter of the way between the texels, and the outer ones are clamped.
+    let gray = |value: u8| [value, value, value, 255];
+    let clamped = [gray(0), gray(64), gray(191), gray(255)];
+    assert!(draw_texture(&mut renderer, &mut context, 1.0)
+        .iter()
+        .all(|row| *row == clamped));
+
+    // When repeating, the outer pixels are blended with the texel on the other side.
+    context.process_command(Context3DCommand::SetSamplerStateAt {
+        sampler: 0,
+        wrap: Context3DWrapMode::Repeat,
+        filter: Context3DTextureFilter::Linear,
+        mip_filter: Context3DMipFilter::MipNone,
+    });
+    let repeated = [gray(64), gray(64), gray(191), gray(191)];
+    assert!(draw_texture(&mut renderer, &mut context, 1.0)
+        .iter()
+        .all(|row| *row == repeated));
+}
+
+#[test]
+fn texture_wrapping() {
+    let mut renderer = renderer();
+    let mut context = create_context(&mut renderer);
+    let texels: &[&[[u8; 4]]] = &[&[RED, GREEN], &[BLUE, WHITE]];
+
+    // The texture coordinates go up to 2, so each texel covers a single pixel, and the bottom
+    // right of the back buffer is outside of the texture.
+    let (vertex, fragment) = texture_program(REPEAT, NEAREST);
+    set_program(&mut context, vertex, fragment);
+    bind_texture(&mut context, texels);
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 2.0),
+        [
+            [RED, GREEN, RED, GREEN],
+            [BLUE, WHITE, BLUE, WHITE],
+            [RED, GREEN, RED, GREEN],
+            [BLUE, WHITE, BLUE, WHITE],
+        ]
+    );
+
+    let (vertex, fragment) = texture_program(CLAMP, NEAREST);
+    set_program(&mut context, vertex, fragment);
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 2.0),
+        [
+            [RED, GREEN, GREEN, GREEN],
+            [BLUE, WHITE, WHITE, WHITE],
+            [BLUE, WHITE, WHITE, WHITE],
+            [BLUE, WHITE, WHITE, WHITE],
+        ]
+    );
+
+    let (vertex, fragment) = texture_program(CLAMP_U_REPEAT_V, NEAREST);
+    set_program(&mut context, vertex, fragment);
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 2.0),
+        [
+            [RED, GREEN, GREEN, GREEN],
+            [BLUE, WHITE, WHITE, WHITE],
+            [RED, GREEN, GREEN, GREEN],
+            [BLUE, WHITE, WHITE, WHITE],
+        ]
+    );
+
+    let (vertex, fragment) = texture_program(REPEAT_U_CLAMP_V, NEAREST);
+    set_program(&mut context, vertex, fragment);
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 2.0),
+        [
+            [RED, GREEN, RED, GREEN],
+            [BLUE, WHITE, BLUE, WHITE],
+            [BLUE, WHITE, BLUE, WHITE],
+            [BLUE, WHITE, BLUE, WHITE],
+        ]
+    );
+
+    // A sampler state set on the context overrides the one in the program.
+    context.process_command(Context3DCommand::SetSamplerStateAt {
+        sampler: 0,
+        wrap: Context3DWrapMode::Repeat,
+        filter: Context3DTextureFilter::Nearest,
+        mip_filter: Context3DMipFilter::MipNone,
+    });
+    assert_eq!(
+        draw_texture(&mut renderer, &mut context, 2.0),
+        [
+            [RED, GREEN, RED, GREEN],
+            [BLUE, WHITE, BLUE, WHITE],
+            [RED, GREEN, RED, GREEN],
+            [BLUE, WHITE, BLUE, WHITE],
+        ]
+    );
+}
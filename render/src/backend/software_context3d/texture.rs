@@ -0,0 +1,230 @@
+use std::cell::{Ref, RefCell};
+
+use naga_agal::{Dimension, Filter, Mipmap, SamplerConfig, Wrapping};
+
+use super::agal::{Lanes, Vec4, LANES};
+use crate::backend::Texture;
+
+/// The RGBA pixels of a single mip level.
+pub type Pixels = Vec<u8>;
+
+pub struct SoftwareTexture {
+    width: u32,
+    height: u32,
+    /// Every mip level of every layer (one, or six for a cube texture).
+    /// Each level starts out transparent black, as with a freshly created GPU texture.
+    pub(super) layers: RefCell<Vec<Vec<Pixels>>>,
+}
+
+impl SoftwareTexture {
+    pub fn new(width: u32, height: u32, num_layers: usize) -> Self {
+        // Stage3D textures can have a mip level for every halving of their size, down to 1x1.
+        // Any of them can be uploaded at any time, so we always allocate all of them.
+        let num_levels = width.max(height).max(1).ilog2() + 1;
+        let levels: Vec<Pixels> = (0..num_levels)
+            .map(|level| {
+                let (width, height) = level_size(width, height, level as usize);
+                vec![0; width as usize * height as usize * 4]
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            layers: RefCell::new(vec![levels; num_layers]),
+        }
+    }
+
+    pub fn level_size(&self, level: usize) -> (u32, u32) {
+        level_size(self.width, self.height, level)
+    }
+}
+
+impl Texture for SoftwareTexture {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+fn level_size(width: u32, height: u32, level: usize) -> (u32, u32) {
+    let shift = level.min(31) as u32;
+    ((width >> shift).max(1), (height >> shift).max(1))
+}
+
+/// A texture bound to a sampler while drawing.
+pub struct BoundTexture<'a> {
+    pub texture: &'a SoftwareTexture,
+    pub layers: Ref<'a, Vec<Vec<Pixels>>>,
+    pub config: SamplerConfig,
+}
+
+impl BoundTexture<'_> {
+    pub fn sample(
+        &self,
+        dimension: Dimension,
+        coords: &Lanes<Vec4>,
+        bias: &Lanes<f32>,
+    ) -> Lanes<Vec4> {
+        // Each lane samples from a layer at some coordinates, which are used to find the
+        // rate of change of the coordinates across the quad, and so the mip level to use.
+        let (layers, uvs): (Lanes<usize>, Lanes<(f32, f32)>) = match dimension {
+            Dimension::TwoD => ([0; LANES], coords.map(|[u, v, _, _]| (u, v))),
+            Dimension::Cube => {
+                let faces = coords.map(|[x, y, z, _]| cube_face(x, y, z));
+                (faces.map(|(face, _)| face), faces.map(|(_, uv)| uv))
+            }
+        };
+
+        let (width, height) = self.texture.level_size(0);
+        let (width, height) = (width as f32, height as f32);
+        let derivative = |lane: usize| {
+            let du = (uvs[lane].0 - uvs[0].0) * width;
+            let dv = (uvs[lane].1 - uvs[0].1) * height;
+            (du * du + dv * dv).sqrt()
+        };
+        // The quad can straddle the faces of a cube texture, in which case the derivatives
+        // are meaningless.
+        let same_face = layers.iter().all(|layer| *layer == layers[0]);
+        let lod = if same_face {
+            derivative(1).max(derivative(2)).log2()
+        } else {
+            0.0
+        };
+
+        std::array::from_fn(|lane| {
+            let Some(levels) = self.layers.get(layers[lane]) else {
+                return [0.0; 4];
+            };
+            let (wrap_u, wrap_v) = match (dimension, self.config.wrapping) {
+                (Dimension::Cube, _) | (_, Wrapping::Clamp) => (false, false),
+                (_, Wrapping::Repeat) => (true, true),
+                (_, Wrapping::ClampURepeatV) => (false, true),
+                (_, Wrapping::RepeatUClampV) => (true, false),
+            };
+            let (u, v) = uvs[lane];
+            let sample_level = |level: usize| {
+                let (width, height) = self.texture.level_size(level);
+                let Some(pixels) = levels.get(level) else {
+                    return [0.0; 4];
+                };
+                let level = Level {
+                    pixels,
+                    width,
+                    height,
+                    wrap_u,
+                    wrap_v,
+                };
+                match self.config.filter {
+                    Filter::Nearest => level.nearest(u, v),
+                    // Anisotropic filtering is approximated by linear filtering.
+                    Filter::Linear
+                    | Filter::Anisotropic2X
+                    | Filter::Anisotropic4X
+                    | Filter::Anisotropic8X
+                    | Filter::Anisotropic16X => level.linear(u, v),
+                }
+            };
+
+            let max_level = (levels.len().max(1) - 1) as f32;
+            let lod = (lod + bias[lane]).clamp(0.0, max_level);
+            match self.config.mipmap {
+                // Without mipmapping, only the largest mip level is sampled.
+                Mipmap::Disable => sample_level(0),
+                Mipmap::Nearest => sample_level((lod + 0.5).floor() as usize),
+                Mipmap::Linear => {
+                    let level = lod.floor();
+                    let fraction = lod - level;
+                    let a = sample_level(level as usize);
+                    if fraction == 0.0 {
+                        a
+                    } else {
+                        let b = sample_level(level as usize + 1);
+                        std::array::from_fn(|c| a[c] + (b[c] - a[c]) * fraction)
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Picks the face of a cube texture that a direction points at, returning its layer
+/// and the coordinates on it.
+fn cube_face(x: f32, y: f32, z: f32) -> (usize, (f32, f32)) {
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    let (face, s, t, major) = if ax >= ay && ax >= az {
+        if x >= 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y >= 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z >= 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+    if major == 0.0 {
+        return (face, (0.5, 0.5));
+    }
+    (face, ((s / major + 1.0) * 0.5, (t / major + 1.0) * 0.5))
+}
+
+/// A single mip level being sampled.
+struct Level<'a> {
+    pixels: &'a [u8],
+    width: u32,
+    height: u32,
+    wrap_u: bool,
+    wrap_v: bool,
+}
+
+impl Level<'_> {
+    fn texel(&self, x: i64, y: i64) -> Vec4 {
+        let coordinate = |value: i64, size: u32, wrap: bool| {
+            if wrap {
+                value.rem_euclid(size as i64)
+            } else {
+                value.clamp(0, size as i64 - 1)
+            }
+        };
+        let x = coordinate(x, self.width, self.wrap_u) as usize;
+        let y = coordinate(y, self.height, self.wrap_v) as usize;
+        let index = (y * self.width as usize + x) * 4;
+        match self.pixels.get(index..index + 4) {
+            Some(texel) => std::array::from_fn(|c| texel[c] as f32 / 255.0),
+            None => [0.0; 4],
+        }
+    }
+
+    fn nearest(&self, u: f32, v: f32) -> Vec4 {
+        let x = (u * self.width as f32).floor() as i64;
+        let y = (v * self.height as f32).floor() as i64;
+        self.texel(x, y)
+    }
+
+    fn linear(&self, u: f32, v: f32) -> Vec4 {
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top_left = self.texel(x0, y0);
+        let top_right = self.texel(x0 + 1, y0);
+        let bottom_left = self.texel(x0, y0 + 1);
+        let bottom_right = self.texel(x0 + 1, y0 + 1);
+        std::array::from_fn(|c| {
+            let top = top_left[c] + (top_right[c] - top_left[c]) * fx;
+            let bottom = bottom_left[c] + (bottom_right[c] - bottom_left[c]) * fx;
+            top + (bottom - top) * fy
+        })
+    }
+}
@@ -24,6 +24,12 @@ pub enum Error {
     #[error("Invalid PNG")]
     InvalidPng(#[from] png::DecodingError),
 
+    #[error("Couldn't encode PNG")]
+    PngEncoding(#[from] png::EncodingError),
+
+    #[error("Couldn't encode JPEG")]
+    JpegEncoding(#[from] image::ImageError),
+
     #[error("Invalid GIF")]
     InvalidGif(#[from] gif::DecodingError),
 
@@ -42,4 +48,7 @@ pub enum Error {
 
     #[error("Not yet implemented: {0}")]
     Unimplemented(Cow<'static, str>),
+
+    #[error("Invalid shader: {0}")]
+    InvalidShader(String),
 }
@@ -4,7 +4,20 @@ use crate::{
 };
 use downcast_rs::{impl_downcast, Downcast};
 use std::fmt::Debug;
-use swf::Color;
+use swf::{Color, GradientRecord, Rectangle};
+
+pub mod software;
+
+/// How much each blur pass should multiply the requested blur size by - accumulative.
+/// These are very approximate to Flash, and not 100% exact.
+/// Pass 1 would be 100%, but pass 2 would be 110%.
+/// This is accumulative so you can calculate the size upfront for how many passes you'll need to perform.
+pub const BLUR_PASS_SCALES: [f32; 15] = [
+    1.0, 2.1, 2.7, 3.1, 3.5, 3.8, 4.0, 4.2, 4.4, 4.6, 5.0, 6.0, 6.0, 7.0, 7.0,
+];
+
+/// The number of entries in the color lookup table of a gradient filter.
+pub const GRADIENT_FILTER_LUT_SIZE: usize = 256;
 
 #[derive(Debug, Clone)]
 pub enum Filter {
@@ -58,6 +71,94 @@ impl Filter {
             _ => {}
         }
     }
+
+    /// Calculates the destination rect needed to hold this filter upon a source of the given area.
+    pub fn calculate_dest_rect(&self, source_rect: Rectangle<i32>) -> Rectangle<i32> {
+        match self {
+            Filter::BlurFilter(filter) => blur_dest_rect(filter, source_rect),
+            // TODO: Inner might not need this. Docs suggest it doesn't care about source rect, but rather source *size*?
+            Filter::GlowFilter(filter) => blur_dest_rect(&filter.inner_blur_filter(), source_rect),
+            Filter::DropShadowFilter(filter) => {
+                let result =
+                    blur_dest_rect(&filter.inner_glow_filter().inner_blur_filter(), source_rect);
+                // A drop shadow is only offset away from the light.
+                offset_dest_rect(
+                    result,
+                    filter.angle.to_f32(),
+                    filter.distance.to_f32(),
+                    true,
+                )
+            }
+            Filter::BevelFilter(filter) => {
+                let result = blur_dest_rect(&filter.inner_blur_filter(), source_rect);
+                offset_dest_rect(
+                    result,
+                    filter.angle.to_f32(),
+                    filter.distance.to_f32(),
+                    false,
+                )
+            }
+            Filter::GradientBevelFilter(filter) => {
+                let result = blur_dest_rect(&filter.inner_blur_filter(), source_rect);
+                offset_dest_rect(
+                    result,
+                    filter.angle.to_f32(),
+                    filter.distance.to_f32(),
+                    false,
+                )
+            }
+            Filter::GradientGlowFilter(filter) => {
+                let result = blur_dest_rect(&filter.inner_blur_filter(), source_rect);
+                // A glow is only offset away from the light, like a drop shadow.
+                offset_dest_rect(
+                    result,
+                    filter.angle.to_f32(),
+                    filter.distance.to_f32(),
+                    true,
+                )
+            }
+            _ => source_rect,
+        }
+    }
+}
+
+fn blur_dest_rect(filter: &swf::BlurFilter, source_rect: Rectangle<i32>) -> Rectangle<i32> {
+    let scale = BLUR_PASS_SCALES[filter.num_passes().clamp(1, 15) as usize - 1];
+    let x = (scale * filter.blur_x.to_f32()).ceil().max(0.0) as i32;
+    let y = (scale * filter.blur_y.to_f32()).ceil().max(0.0) as i32;
+    Rectangle {
+        x_min: source_rect.x_min.saturating_sub(x),
+        x_max: source_rect.x_max.saturating_add(x),
+        y_min: source_rect.y_min.saturating_sub(y),
+        y_max: source_rect.y_max.saturating_add(y),
+    }
+}
+
+/// Grows a rect to cover an offset copy of itself, at the given angle and distance.
+/// One-sided offsets only grow in the direction of the offset, others grow both ways.
+fn offset_dest_rect(
+    mut rect: Rectangle<i32>,
+    angle: f32,
+    distance: f32,
+    one_sided: bool,
+) -> Rectangle<i32> {
+    let offset_x = angle.cos() * distance;
+    let offset_y = angle.sin() * distance;
+    let x = offset_x.abs().ceil() as i32;
+    let y = offset_y.abs().ceil() as i32;
+    if !one_sided || offset_x < 0.0 {
+        rect.x_min = rect.x_min.saturating_sub(x);
+    }
+    if !one_sided || offset_x >= 0.0 {
+        rect.x_max = rect.x_max.saturating_add(x);
+    }
+    if !one_sided || offset_y < 0.0 {
+        rect.y_min = rect.y_min.saturating_sub(y);
+    }
+    if !one_sided || offset_y >= 0.0 {
+        rect.y_max = rect.y_max.saturating_add(y);
+    }
+    rect
 }
 
 impl From<&swf::Filter> for Filter {
@@ -85,6 +186,139 @@ impl From<&swf::Filter> for Filter {
     }
 }
 
+/// Everything that a Bevel, GradientBevel or GradientGlow filter needs to render.
+///
+/// A plain bevel is just a gradient bevel going from the highlight color,
+/// through transparent, to the shadow color.
+#[derive(Debug, Clone)]
+pub struct BevelArgs {
+    pub blur: swf::BlurFilter,
+    pub angle: f32,
+    pub distance: f32,
+    pub strength: f32,
+    pub inner: bool,
+    pub on_top: bool,
+    pub knockout: bool,
+    pub glow: bool,
+    pub colors: Vec<GradientRecord>,
+}
+
+impl BevelArgs {
+    pub fn from_bevel(filter: &swf::BevelFilter) -> Self {
+        Self {
+            blur: filter.inner_blur_filter(),
+            angle: filter.angle.to_f32(),
+            distance: filter.distance.to_f32(),
+            strength: filter.strength.to_f32(),
+            inner: filter.is_inner(),
+            on_top: filter.is_on_top(),
+            knockout: filter.is_knockout(),
+            glow: false,
+            colors: bevel_filter_gradient(filter),
+        }
+    }
+
+    pub fn from_gradient_bevel(filter: &swf::GradientFilter) -> Self {
+        Self::from_gradient(filter, false)
+    }
+
+    pub fn from_gradient_glow(filter: &swf::GradientFilter) -> Self {
+        Self::from_gradient(filter, true)
+    }
+
+    fn from_gradient(filter: &swf::GradientFilter, glow: bool) -> Self {
+        Self {
+            blur: filter.inner_blur_filter(),
+            angle: filter.angle.to_f32(),
+            distance: filter.distance.to_f32(),
+            strength: filter.strength.to_f32(),
+            inner: filter.is_inner(),
+            on_top: filter.is_on_top(),
+            knockout: filter.is_knockout(),
+            glow,
+            colors: filter.colors.clone(),
+        }
+    }
+
+    /// Whether this is drawn only outside of the source.
+    pub fn is_outer(&self) -> bool {
+        !self.inner && !self.on_top
+    }
+
+    /// The offset of the highlight from the source, in pixels.
+    /// The shadow is offset by the same amount in the other direction.
+    pub fn offset(&self) -> (f32, f32) {
+        (
+            self.angle.cos() * self.distance,
+            self.angle.sin() * self.distance,
+        )
+    }
+}
+
+/// The gradient that a plain BevelFilter is equivalent to: the highlight color at ratio 0,
+/// fading to transparent in the middle, then to the shadow color at ratio 255.
+fn bevel_filter_gradient(filter: &swf::BevelFilter) -> Vec<GradientRecord> {
+    let transparent = |color: Color| Color { a: 0, ..color };
+    vec![
+        GradientRecord {
+            ratio: 0,
+            color: filter.highlight_color,
+        },
+        GradientRecord {
+            ratio: 128,
+            color: transparent(filter.highlight_color),
+        },
+        GradientRecord {
+            ratio: 128,
+            color: transparent(filter.shadow_color),
+        },
+        GradientRecord {
+            ratio: 255,
+            color: filter.shadow_color,
+        },
+    ]
+}
+
+/// Builds the premultiplied RGBA color lookup table of a GradientBevel or GradientGlow filter,
+/// indexed by ratio.
+pub fn gradient_filter_lut(records: &[GradientRecord]) -> Vec<[u8; 4]> {
+    (0..GRADIENT_FILTER_LUT_SIZE)
+        .map(|t| {
+            let next = records
+                .iter()
+                .position(|record| usize::from(record.ratio) >= t);
+            let color = match next {
+                Some(0) => records[0].color,
+                Some(next) => {
+                    let last = &records[next - 1];
+                    let next = &records[next];
+                    let a = (t as f32 - f32::from(last.ratio))
+                        / (f32::from(next.ratio) - f32::from(last.ratio));
+                    let lerp = |from: u8, to: u8| {
+                        (f32::from(from) + (f32::from(to) - f32::from(from)) * a) as u8
+                    };
+                    Color {
+                        r: lerp(last.color.r, next.color.r),
+                        g: lerp(last.color.g, next.color.g),
+                        b: lerp(last.color.b, next.color.b),
+                        a: lerp(last.color.a, next.color.a),
+                    }
+                }
+                None => records
+                    .last()
+                    .map_or(Color::from_rgba(0), |record| record.color),
+            };
+            let premultiply = |value: u8| (u16::from(value) * u16::from(color.a) / 255) as u8;
+            [
+                premultiply(color.r),
+                premultiply(color.g),
+                premultiply(color.b),
+                color.a,
+            ]
+        })
+        .collect()
+}
+
 impl Default for Filter {
     fn default() -> Self {
         // A default colormatrix is a filter that essentially does nothing,
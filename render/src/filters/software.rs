@@ -0,0 +1,417 @@
+//! Software implementations of the filters, for backends that can't run them on the GPU.
+//!
+//! These follow the wgpu filters as closely as possible, so they also serve as a reference for them.
+
+#[cfg(test)]
+mod tests;
+
+use crate::filters::{
+    gradient_filter_lut, BevelArgs, DisplacementMapFilter, DisplacementMapFilterMode, Filter,
+    BLUR_PASS_SCALES,
+};
+use swf::{BlurFilter, Color, ColorMatrixFilter, ConvolutionFilter, GlowFilter};
+
+/// A premultiplied RGBA image, which the software filters read from and write to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterImage {
+    pub width: u32,
+    pub height: u32,
+    /// Premultiplied RGBA pixels, row by row.
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// Applies a filter to an image, returning a filtered image of the same size.
+///
+/// `map` is the map of a DisplacementMapFilter, which can't be read back from its `BitmapHandle`.
+/// Returns `None` if the filter can't be applied in software, or wouldn't change anything.
+pub fn apply_filter(
+    filter: &Filter,
+    source: &FilterImage,
+    map: Option<&FilterImage>,
+) -> Option<FilterImage> {
+    let source = Image::from_filter_image(source);
+    let result = match filter {
+        Filter::BlurFilter(filter) => blur(source, filter),
+        Filter::ColorMatrixFilter(filter) => color_matrix(&source, filter),
+        Filter::GlowFilter(filter) => glow(&source, filter, (0.0, 0.0)),
+        Filter::DropShadowFilter(filter) => {
+            let (x, y) = offset(filter.angle.to_f32(), filter.distance.to_f32());
+            glow(&source, &filter.inner_glow_filter(), (-x, -y))
+        }
+        Filter::BevelFilter(filter) => bevel(&source, &BevelArgs::from_bevel(filter)),
+        Filter::GradientBevelFilter(filter) => {
+            bevel(&source, &BevelArgs::from_gradient_bevel(filter))
+        }
+        Filter::GradientGlowFilter(filter) => {
+            bevel(&source, &BevelArgs::from_gradient_glow(filter))
+        }
+        Filter::ConvolutionFilter(filter) => convolution(&source, filter),
+        Filter::DisplacementMapFilter(filter) => {
+            displacement_map(&source, filter, &Image::from_filter_image(map?))
+        }
+        // TODO: This needs a Pixel Bender interpreter.
+        Filter::ShaderFilter(_) => return None,
+    };
+    Some(result.to_filter_image())
+}
+
+/// A premultiplied image with each channel in the 0-1 range, to work on in between passes.
+#[derive(Clone, Debug)]
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Image {
+    fn from_filter_image(image: &FilterImage) -> Self {
+        Self {
+            width: image.width as usize,
+            height: image.height as usize,
+            pixels: image
+                .pixels
+                .iter()
+                .map(|pixel| pixel.map(|channel| f32::from(channel) / 255.0))
+                .collect(),
+        }
+    }
+
+    fn to_filter_image(&self) -> FilterImage {
+        FilterImage {
+            width: self.width as u32,
+            height: self.height as u32,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|pixel| pixel.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8))
+                .collect(),
+        }
+    }
+
+    /// Returns the pixel at the given coordinates, or transparent black if it's outside the image.
+    fn get(&self, x: i32, y: i32) -> [f32; 4] {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return [0.0; 4];
+        }
+        self.pixels[y as usize * self.width + x as usize]
+    }
+
+    /// Samples the pixel at the given position, like a non-filtering GPU sampler would.
+    fn sample(&self, x: f32, y: f32) -> [f32; 4] {
+        self.get(x.floor() as i32, y.floor() as i32)
+    }
+
+    /// Creates a new image of the same size, from a function of each pixel's coordinates.
+    fn map(&self, mut f: impl FnMut(i32, i32, [f32; 4]) -> [f32; 4]) -> Self {
+        let pixels = self
+            .pixels
+            .iter()
+            .enumerate()
+            .map(|(index, pixel)| {
+                f(
+                    (index % self.width) as i32,
+                    (index / self.width) as i32,
+                    *pixel,
+                )
+            })
+            .collect();
+        Self {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+fn add(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + b[i])
+}
+
+fn sub(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] - b[i])
+}
+
+fn mul(a: [f32; 4], b: f32) -> [f32; 4] {
+    a.map(|channel| channel * b)
+}
+
+fn unmultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    if a > 0.0 {
+        [r / a, g / a, b / a, a]
+    } else {
+        [0.0; 4]
+    }
+}
+
+fn premultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [r * a, g * a, b * a, a]
+}
+
+fn color_to_f32(color: Color) -> [f32; 4] {
+    [color.r, color.g, color.b, color.a].map(|channel| f32::from(channel) / 255.0)
+}
+
+fn offset(angle: f32, distance: f32) -> (f32, f32) {
+    (angle.cos() * distance, angle.sin() * distance)
+}
+
+fn blur(mut image: Image, filter: &BlurFilter) -> Image {
+    let mut last_scale_total = 0.0;
+    for current_scale_total in BLUR_PASS_SCALES
+        .into_iter()
+        .take(filter.num_passes() as usize)
+    {
+        let pass_scale = current_scale_total - last_scale_total;
+        last_scale_total = current_scale_total;
+
+        let full_size_x = (filter.blur_x.to_f32().min(255.0) * pass_scale).round();
+        let full_size_y = (filter.blur_y.to_f32().min(255.0) * pass_scale).round();
+        image = blur_pass(image, full_size_x, true);
+        image = blur_pass(image, full_size_y, false);
+    }
+    image
+}
+
+/// A single pass of a box blur, `full_size` pixels wide, in one direction.
+///
+/// Like the GPU blur, an even size covers half a pixel on either end.
+fn blur_pass(image: Image, full_size: f32, horizontal: bool) -> Image {
+    if full_size <= 1.0 {
+        // A width of 1 or less is a noop (it'd just sample itself and nothing else)
+        return image;
+    }
+    let size = full_size as usize;
+    let radius = size / 2;
+    let even = size % 2 == 0;
+    let weight = 1.0 / full_size;
+
+    let width = image.width;
+    let (length, lines) = if horizontal {
+        (image.width, image.height)
+    } else {
+        (image.height, image.width)
+    };
+    let mut pixels = vec![[0.0; 4]; image.pixels.len()];
+    let mut prefix_sums = vec![[0.0; 4]; length + 1];
+    for line in 0..lines {
+        let index = |i: usize| {
+            if horizontal {
+                line * width + i
+            } else {
+                i * width + line
+            }
+        };
+        for i in 0..length {
+            prefix_sums[i + 1] = add(prefix_sums[i], image.pixels[index(i)]);
+        }
+        for i in 0..length {
+            // Anything past the edges is transparent, so it doesn't contribute to the sum
+            let start = i.saturating_sub(radius);
+            let end = (i + radius + 1).min(length);
+            let mut sum = sub(prefix_sums[end], prefix_sums[start]);
+            if even {
+                if i >= radius {
+                    sum = sub(sum, mul(image.pixels[index(i - radius)], 0.5));
+                }
+                if i + radius < length {
+                    sum = sub(sum, mul(image.pixels[index(i + radius)], 0.5));
+                }
+            }
+            pixels[index(i)] = mul(sum, weight);
+        }
+    }
+    Image { pixels, ..image }
+}
+
+fn color_matrix(source: &Image, filter: &ColorMatrixFilter) -> Image {
+    let matrix = &filter.matrix;
+    source.map(|_, _, pixel| {
+        let [r, g, b, _] = unmultiply(pixel);
+        let channel = |row: usize| {
+            let m = &matrix[row * 5..row * 5 + 5];
+            (m[0] * r + m[1] * g + m[2] * b + m[3] * pixel[3] + m[4] / 255.0).clamp(0.0, 1.0)
+        };
+        premultiply([channel(0), channel(1), channel(2), channel(3)])
+    })
+}
+
+fn glow(source: &Image, filter: &GlowFilter, blur_offset: (f32, f32)) -> Image {
+    let blurred = blur(source.clone(), &filter.inner_blur_filter());
+    let [r, g, b, color_alpha] = color_to_f32(filter.color);
+    let color = [r, g, b, 1.0];
+    let strength = filter.strength.to_f32();
+    let inner = filter.is_inner();
+    let knockout = filter.is_knockout();
+    let composite_source = filter.composite_source();
+
+    source.map(|x, y, dest| {
+        let blur = blurred.sample(
+            x as f32 + 0.5 + blur_offset.0,
+            y as f32 + 0.5 + blur_offset.1,
+        )[3];
+        if inner {
+            let alpha = color_alpha * ((1.0 - blur) * strength).clamp(0.0, 1.0);
+            let glow = mul(color, alpha * dest[3]);
+            if composite_source && !knockout {
+                add(glow, mul(dest, 1.0 - alpha))
+            } else {
+                glow
+            }
+        } else {
+            let alpha = color_alpha * (blur * strength).clamp(0.0, 1.0);
+            if knockout {
+                mul(color, alpha * (1.0 - dest[3]))
+            } else if composite_source {
+                add(mul(color, alpha * (1.0 - dest[3])), dest)
+            } else {
+                mul(color, alpha)
+            }
+        }
+    })
+}
+
+fn bevel(source: &Image, filter: &BevelArgs) -> Image {
+    let blurred = blur(source.clone(), &filter.blur);
+    let gradient = gradient_filter_lut(&filter.colors);
+    let outer = filter.is_outer();
+    let (offset_x, offset_y) = filter.offset();
+
+    source.map(|x, y, dest| {
+        let x = x as f32 + 0.5;
+        let y = y as f32 + 0.5;
+        let highlight = blurred.sample(x + offset_x, y + offset_y)[3];
+        let shadow = blurred.sample(x - offset_x, y - offset_y)[3];
+
+        // The gradient goes from the highlight (ratio 0) to the shadow (ratio 255) for bevels,
+        // and from the edge to the middle of the glow for glows.
+        let position = if filter.glow {
+            if filter.inner {
+                ((1.0 - shadow) * filter.strength).clamp(0.0, 1.0)
+            } else {
+                (shadow * filter.strength).clamp(0.0, 1.0)
+            }
+        } else {
+            0.5 - ((highlight - shadow) * filter.strength).clamp(-1.0, 1.0) * 0.5
+        };
+        let color = gradient[(position * 255.0).round() as usize].map(|c| f32::from(c) / 255.0);
+        let color = if filter.inner {
+            mul(color, dest[3])
+        } else if outer {
+            mul(color, 1.0 - dest[3])
+        } else {
+            color
+        };
+
+        if filter.knockout {
+            color
+        } else if outer {
+            // Outer bevels are drawn behind the source, which has already masked them out
+            add(color, dest)
+        } else {
+            add(color, mul(dest, 1.0 - color[3]))
+        }
+    })
+}
+
+fn convolution(source: &Image, filter: &ConvolutionFilter) -> Image {
+    let columns = usize::from(filter.num_matrix_cols);
+    let rows = usize::from(filter.num_matrix_rows);
+    let half_x = (columns / 2) as i32;
+    let half_y = (rows / 2) as i32;
+    let divisor = filter.divisor.to_f32();
+    // Flash treats a divisor of 0 as 1
+    let divisor = if divisor == 0.0 { 1.0 } else { divisor };
+    let bias = filter.bias.to_f32() / 255.0;
+    let default_color = color_to_f32(filter.default_color);
+    let max_x = source.width as i32 - 1;
+    let max_y = source.height as i32 - 1;
+
+    // Unmultiplied pixels, handling the edges of the source the way the filter asks us to.
+    let load_pixel = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x > max_x || y > max_y {
+            if !filter.is_clamped() {
+                return default_color;
+            }
+            return unmultiply(source.get(x.clamp(0, max_x), y.clamp(0, max_y)));
+        }
+        unmultiply(source.get(x, y))
+    };
+
+    source.map(|x, y, _| {
+        let mut sum = [0.0; 4];
+        for row in 0..rows {
+            for column in 0..columns {
+                let weight = filter
+                    .matrix
+                    .get(row * columns + column)
+                    .map_or(0.0, |value| value.to_f32());
+                let pixel = load_pixel(x + column as i32 - half_x, y + row as i32 - half_y);
+                sum = add(sum, mul(pixel, weight));
+            }
+        }
+
+        let mut color = sum.map(|channel| (channel / divisor + bias).clamp(0.0, 1.0));
+        if filter.is_preserve_alpha() {
+            color[3] = load_pixel(x, y)[3];
+        }
+        premultiply(color)
+    })
+}
+
+/// Picks the channel of an unmultiplied map pixel, as a `BitmapDataChannel` value.
+fn displacement_component(color: [f32; 4], channel: u8) -> f32 {
+    match channel {
+        1 => color[0],
+        2 => color[1],
+        4 => color[2],
+        8 => color[3],
+        // Right in the middle, so no displacement
+        _ => 128.0 / 255.0,
+    }
+}
+
+fn displacement_map(source: &Image, filter: &DisplacementMapFilter, map: &Image) -> Image {
+    let max_x = source.width as i32 - 1;
+    let max_y = source.height as i32 - 1;
+    let color = premultiply(color_to_f32(filter.color));
+
+    source.map(|x, y, _| {
+        // Pixels not covered by the map aren't displaced at all
+        let map_x = x - filter.map_point.0;
+        let map_y = y - filter.map_point.1;
+        let (offset_x, offset_y) = if map_x >= 0
+            && map_y >= 0
+            && (map_x as usize) < map.width
+            && (map_y as usize) < map.height
+        {
+            let map_color = unmultiply(map.get(map_x, map_y));
+            let displacement = |channel: u8, scale: f32| {
+                let value = displacement_component(map_color, channel) * 255.0 - 128.0;
+                (value * scale / 256.0).floor() as i32
+            };
+            (
+                displacement(filter.component_x, filter.scale_x),
+                displacement(filter.component_y, filter.scale_y),
+            )
+        } else {
+            (0, 0)
+        };
+
+        let source_x = x + offset_x;
+        let source_y = y + offset_y;
+        if source_x >= 0 && source_y >= 0 && source_x <= max_x && source_y <= max_y {
+            return source.get(source_x, source_y);
+        }
+        match filter.mode {
+            DisplacementMapFilterMode::Clamp => {
+                source.get(source_x.clamp(0, max_x), source_y.clamp(0, max_y))
+            }
+            DisplacementMapFilterMode::Color => color,
+            DisplacementMapFilterMode::Ignore => source.get(x, y),
+            DisplacementMapFilterMode::Wrap => source.get(
+                source_x.rem_euclid(max_x + 1),
+                source_y.rem_euclid(max_y + 1),
+            ),
+        }
+    })
+}
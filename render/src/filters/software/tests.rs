@@ -0,0 +1,103 @@
+use super::{apply_filter, FilterImage};
+use crate::filters::{DisplacementMapFilter, Filter};
+use swf::{
+    BlurFilter, BlurFilterFlags, Color, ColorMatrixFilter, ConvolutionFilter,
+    ConvolutionFilterFlags, DropShadowFilter, DropShadowFilterFlags, Fixed16, Fixed8,
+};
+
+const TRANSPARENT: [u8; 4] = [0, 0, 0, 0];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+fn image(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> FilterImage {
+    assert_eq!(pixels.len(), (width * height) as usize);
+    FilterImage {
+        width,
+        height,
+        pixels,
+    }
+}
+
+fn checkerboard() -> FilterImage {
+    image(
+        3,
+        3,
+        [
+            [WHITE, BLACK, WHITE],
+            [BLACK, [64, 32, 16, 128], BLACK],
+            [WHITE, BLACK, WHITE],
+        ]
+        .concat(),
+    )
+}
+
+#[test]
+fn identity_color_matrix() {
+    let source = checkerboard();
+    let result = apply_filter(
+        &Filter::ColorMatrixFilter(ColorMatrixFilter::default()),
+        &source,
+        None,
+    );
+    assert_eq!(result, Some(source));
+}
+
+#[test]
+fn blur_spreads_pixel() {
+    let source = image(
+        5,
+        1,
+        vec![TRANSPARENT, TRANSPARENT, WHITE, TRANSPARENT, TRANSPARENT],
+    );
+    let filter = Filter::BlurFilter(BlurFilter {
+        blur_x: Fixed16::from_f32(3.0),
+        blur_y: Fixed16::from_f32(1.0),
+        flags: BlurFilterFlags::from_passes(1),
+    });
+    let third = [85, 85, 85, 85];
+    assert_eq!(
+        apply_filter(&filter, &source, None).map(|result| result.pixels),
+        Some(vec![TRANSPARENT, third, third, third, TRANSPARENT])
+    );
+}
+
+#[test]
+fn identity_convolution() {
+    let source = checkerboard();
+    let mut matrix = vec![Fixed16::ZERO; 9];
+    matrix[4] = Fixed16::ONE;
+    let filter = Filter::ConvolutionFilter(ConvolutionFilter {
+        num_matrix_rows: 3,
+        num_matrix_cols: 3,
+        matrix,
+        divisor: Fixed16::ONE,
+        bias: Fixed16::ZERO,
+        default_color: Color::from_rgba(0),
+        flags: ConvolutionFilterFlags::CLAMP,
+    });
+    assert_eq!(apply_filter(&filter, &source, None), Some(source));
+}
+
+#[test]
+fn drop_shadow_is_offset() {
+    let source = image(3, 1, vec![BLACK, TRANSPARENT, TRANSPARENT]);
+    let filter = Filter::DropShadowFilter(DropShadowFilter {
+        color: Color::from_rgba(0xFFFF0000),
+        blur_x: Fixed16::ZERO,
+        blur_y: Fixed16::ZERO,
+        angle: Fixed16::ZERO,
+        distance: Fixed16::ONE,
+        strength: Fixed8::ONE,
+        flags: DropShadowFilterFlags::from_passes(1) | DropShadowFilterFlags::COMPOSITE_SOURCE,
+    });
+    assert_eq!(
+        apply_filter(&filter, &source, None).map(|result| result.pixels),
+        Some(vec![BLACK, [255, 0, 0, 255], TRANSPARENT])
+    );
+}
+
+#[test]
+fn displacement_map_needs_a_map() {
+    let filter = Filter::DisplacementMapFilter(DisplacementMapFilter::default());
+    assert_eq!(apply_filter(&filter, &checkerboard(), None), None);
+}
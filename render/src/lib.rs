@@ -1,10 +1,12 @@
 #![deny(clippy::unwrap_used)]
 
+pub mod atf;
 pub mod backend;
 pub mod bitmap;
 pub mod error;
 pub mod filters;
 pub mod matrix;
+pub mod matrix3d;
 pub mod pixel_bender;
 // The `renderdoc` crate doesn't compile on apple platforms
 #[cfg(all(feature = "renderdoc", not(target_vendor = "apple")))]
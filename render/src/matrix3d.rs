@@ -0,0 +1,373 @@
+use crate::matrix::Matrix;
+use swf::Twips;
+
+/// The 4x4 transformation matrix used by display objects with a 3D transform.
+///
+/// Like `flash.geom.Matrix3D`, the values are stored in column-major order,
+/// and translations are measured in pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix3D {
+    pub raw_data: [f64; 16],
+}
+
+impl Matrix3D {
+    pub const IDENTITY: Self = Self {
+        raw_data: [
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, //
+        ],
+    };
+
+    /// Returns the value at the given row and column.
+    #[inline]
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.raw_data[column * 4 + row]
+    }
+
+    pub fn translate(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[12] = x;
+        matrix.raw_data[13] = y;
+        matrix.raw_data[14] = z;
+        matrix
+    }
+
+    pub fn scale(x: f64, y: f64, z: f64) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = x;
+        matrix.raw_data[5] = y;
+        matrix.raw_data[10] = z;
+        matrix
+    }
+
+    pub fn rotate_x(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[5] = cos;
+        matrix.raw_data[6] = sin;
+        matrix.raw_data[9] = -sin;
+        matrix.raw_data[10] = cos;
+        matrix
+    }
+
+    pub fn rotate_y(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = cos;
+        matrix.raw_data[2] = -sin;
+        matrix.raw_data[8] = sin;
+        matrix.raw_data[10] = cos;
+        matrix
+    }
+
+    pub fn rotate_z(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[0] = cos;
+        matrix.raw_data[1] = sin;
+        matrix.raw_data[4] = -sin;
+        matrix.raw_data[5] = cos;
+        matrix
+    }
+
+    /// Builds a matrix from a translation, a rotation in radians and a scale.
+    ///
+    /// This matches `Matrix3D.recompose` with `Orientation3D.EULER_ANGLES`:
+    /// the object is scaled, rotated around the X, Y and then Z axes, and finally translated.
+    pub fn compose(translation: [f64; 3], rotation: [f64; 3], scale: [f64; 3]) -> Self {
+        Self::translate(translation[0], translation[1], translation[2])
+            * Self::rotate_z(rotation[2])
+            * Self::rotate_y(rotation[1])
+            * Self::rotate_x(rotation[0])
+            * Self::scale(scale[0], scale[1], scale[2])
+    }
+
+    /// Splits this matrix into a translation, a rotation in radians and a scale.
+    ///
+    /// This is the inverse of `compose`, and matches `Matrix3D.decompose` with
+    /// `Orientation3D.EULER_ANGLES`. Any projective part of the matrix is ignored.
+    pub fn decompose(&self) -> ([f64; 3], [f64; 3], [f64; 3]) {
+        let m = &self.raw_data;
+        let translation = [m[12], m[13], m[14]];
+
+        let mut scale = [
+            (m[0] * m[0] + m[1] * m[1] + m[2] * m[2]).sqrt(),
+            (m[4] * m[4] + m[5] * m[5] + m[6] * m[6]).sqrt(),
+            (m[8] * m[8] + m[9] * m[9] + m[10] * m[10]).sqrt(),
+        ];
+        let determinant = m[0] * (m[5] * m[10] - m[6] * m[9]) - m[1] * (m[4] * m[10] - m[6] * m[8])
+            + m[2] * (m[4] * m[9] - m[5] * m[8]);
+        if determinant < 0.0 {
+            scale[2] = -scale[2];
+        }
+
+        // The rotation part of the matrix, with the scale removed.
+        let mut columns = [[0.0; 3]; 3];
+        for (column, values) in columns.iter_mut().enumerate() {
+            if scale[column] != 0.0 {
+                for (row, value) in values.iter_mut().enumerate() {
+                    *value = m[column * 4 + row] / scale[column];
+                }
+            }
+        }
+        // A column scaled to zero has lost its direction, but it's still perpendicular
+        // to the other two, so it can be rebuilt from them if they're both there.
+        if let Some(zero) = scale.iter().position(|scale| *scale == 0.0) {
+            let [a, b] = [columns[(zero + 1) % 3], columns[(zero + 2) % 3]];
+            columns[zero] = [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ];
+        }
+        let r = |row: usize, column: usize| columns[column][row];
+        let rotation_y = (-r(2, 0)).clamp(-1.0, 1.0).asin();
+        let rotation = if r(2, 0).abs() < 1.0 {
+            [r(2, 1).atan2(r(2, 2)), rotation_y, r(1, 0).atan2(r(0, 0))]
+        } else {
+            // Gimbal lock: the X and Z rotations share an axis, so put it all into X.
+            [r(0, 1).atan2(r(1, 1)), rotation_y, 0.0]
+        };
+
+        (translation, rotation, scale)
+    }
+
+    /// A perspective projection onto the `z = 0` plane, as seen by a viewer
+    /// `focal_length` pixels in front of (`center_x`, `center_y`).
+    ///
+    /// Points with `z = 0` are left where they are, points with a positive `z`
+    /// move towards the center, and points with a negative `z` move away from it.
+    pub fn perspective(focal_length: f64, center_x: f64, center_y: f64) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.raw_data[8] = center_x / focal_length;
+        matrix.raw_data[9] = center_y / focal_length;
+        matrix.raw_data[11] = 1.0 / focal_length;
+        matrix
+    }
+
+    /// Transforms a point, returning its homogeneous coordinates.
+    pub fn transform_point(&self, x: f64, y: f64, z: f64) -> [f64; 4] {
+        let mut result = [0.0; 4];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = self.get(row, 0) * x
+                + self.get(row, 1) * y
+                + self.get(row, 2) * z
+                + self.get(row, 3);
+        }
+        result
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination on `[self | identity]`, with partial pivoting.
+        let mut m = [[0.0; 8]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values[..4].iter_mut().enumerate() {
+                *value = self.get(row, column);
+            }
+            values[4 + row] = 1.0;
+        }
+
+        for column in 0..4 {
+            let pivot =
+                (column..4).max_by(|&a, &b| m[a][column].abs().total_cmp(&m[b][column].abs()))?;
+            if m[pivot][column].abs() < 1e-11 {
+                return None;
+            }
+            m.swap(column, pivot);
+
+            let divisor = m[column][column];
+            for value in m[column].iter_mut() {
+                *value /= divisor;
+            }
+
+            let pivot_row = m[column];
+            for (row, values) in m.iter_mut().enumerate() {
+                let factor = values[column];
+                if row != column && factor != 0.0 {
+                    for (value, pivot_value) in values.iter_mut().zip(pivot_row) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+
+        let mut inverse = Self::IDENTITY;
+        for (row, values) in m.iter().enumerate() {
+            for (column, value) in values[4..].iter().enumerate() {
+                inverse.raw_data[column * 4 + row] = *value;
+            }
+        }
+        Some(inverse)
+    }
+
+    /// Returns the 2D matrix that best approximates this matrix around the local origin,
+    /// ignoring the resulting `z`.
+    ///
+    /// A projective matrix can't be represented exactly by a 2D `Matrix`; this approximation
+    /// is used for bounds and by renderers without 3D support.
+    pub fn to_affine(&self) -> Matrix {
+        let w = self.get(3, 3);
+        if w <= 0.0 || !w.is_finite() {
+            // The origin is behind the viewer.
+            return Matrix::ZERO;
+        }
+
+        let x = self.get(0, 3);
+        let y = self.get(1, 3);
+        let w_squared = w * w;
+        Matrix {
+            a: ((self.get(0, 0) * w - x * self.get(3, 0)) / w_squared) as f32,
+            b: ((self.get(1, 0) * w - y * self.get(3, 0)) / w_squared) as f32,
+            c: ((self.get(0, 1) * w - x * self.get(3, 1)) / w_squared) as f32,
+            d: ((self.get(1, 1) * w - y * self.get(3, 1)) / w_squared) as f32,
+            tx: Twips::from_pixels(x / w),
+            ty: Twips::from_pixels(y / w),
+        }
+    }
+}
+
+impl Default for Matrix3D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Matrix> for Matrix3D {
+    fn from(matrix: Matrix) -> Self {
+        Self {
+            raw_data: [
+                matrix.a.into(),
+                matrix.b.into(),
+                0.0,
+                0.0,
+                matrix.c.into(),
+                matrix.d.into(),
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                matrix.tx.to_pixels(),
+                matrix.ty.to_pixels(),
+                0.0,
+                1.0,
+            ],
+        }
+    }
+}
+
+impl std::ops::Mul for Matrix3D {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let mut raw_data = [0.0; 16];
+        for (i, value) in raw_data.iter_mut().enumerate() {
+            let (row, column) = (i % 4, i / 4);
+            *value = (0..4).map(|k| self.get(row, k) * rhs.get(k, column)).sum();
+        }
+        Self { raw_data }
+    }
+}
+
+impl std::ops::MulAssign for Matrix3D {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_matrix_eq(left: Matrix3D, right: Matrix3D) {
+        for (l, r) in left.raw_data.iter().zip(right.raw_data) {
+            assert!(
+                (l - r).abs() < 1e-9,
+                "Matrices differ: {left:?} != {right:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn compose_decompose() {
+        let matrix = Matrix3D::compose([10.0, -20.0, 30.0], [0.5, -0.25, 1.0], [2.0, 3.0, 0.5]);
+        let (translation, rotation, scale) = matrix.decompose();
+        assert_matrix_eq(Matrix3D::compose(translation, rotation, scale), matrix);
+        assert_eq!(translation, [10.0, -20.0, 30.0]);
+        for (value, expected) in rotation.iter().zip([0.5, -0.25, 1.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        for (value, expected) in scale.iter().zip([2.0, 3.0, 0.5]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn decompose_zero_scale() {
+        let matrix = Matrix3D::compose([5.0, 6.0, 0.0], [0.3, 0.2, 0.5], [0.0, 2.0, 1.0]);
+        let (translation, rotation, scale) = matrix.decompose();
+        assert_matrix_eq(Matrix3D::compose(translation, rotation, scale), matrix);
+        for (value, expected) in rotation.iter().zip([0.3, 0.2, 0.5]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        assert_eq!(scale[0], 0.0);
+
+        // Nothing is left of the rotation, but it shouldn't be NaN either.
+        let (_, rotation, scale) = Matrix3D::scale(0.0, 0.0, 1.0).decompose();
+        assert_eq!(rotation, [0.0, 0.0, 0.0]);
+        assert_eq!(scale, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rotate_y_quarter_turn() {
+        // Rotating around Y turns the X axis into the Z axis.
+        let point = Matrix3D::rotate_y(FRAC_PI_2).transform_point(1.0, 0.0, 0.0);
+        assert!(point[0].abs() < 1e-9);
+        assert!((point[2] + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn inverse() {
+        let matrix = Matrix3D::perspective(500.0, 100.0, 50.0)
+            * Matrix3D::compose([1.0, 2.0, 3.0], [0.1, 0.2, 0.3], [1.0, 2.0, 4.0]);
+        let inverse = matrix.inverse().expect("Matrix should be invertible");
+        assert_matrix_eq(matrix * inverse, Matrix3D::IDENTITY);
+        assert_eq!(Matrix3D::scale(1.0, 0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn perspective() {
+        let projection = Matrix3D::perspective(400.0, 100.0, 100.0);
+
+        // Points on the z = 0 plane stay where they are.
+        let [x, y, _, w] = projection.transform_point(300.0, 50.0, 0.0);
+        assert_eq!((x / w, y / w), (300.0, 50.0));
+
+        // Points further away move towards the center.
+        let [x, y, _, w] = projection.transform_point(300.0, 50.0, 400.0);
+        assert_eq!((x / w, y / w), (200.0, 75.0));
+    }
+
+    #[test]
+    fn to_affine() {
+        let matrix = Matrix {
+            a: 2.0,
+            b: 0.5,
+            c: -0.5,
+            d: 3.0,
+            tx: Twips::from_pixels(10.0),
+            ty: Twips::from_pixels(-20.0),
+        };
+        assert_eq!(Matrix3D::from(matrix).to_affine(), matrix);
+
+        // Halfway to the focal point, everything at the origin appears twice as large.
+        let projected =
+            Matrix3D::perspective(128.0, 0.0, 0.0) * Matrix3D::translate(0.0, 0.0, -64.0);
+        let affine = projected.to_affine();
+        assert_eq!((affine.a, affine.d), (2.0, 2.0));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::matrix::Matrix;
+use crate::matrix3d::Matrix3D;
 use swf::ColorTransform;
 
 /// Represents the transform for a DisplayObject.
@@ -7,6 +8,12 @@ use swf::ColorTransform;
 pub struct Transform {
     pub matrix: Matrix,
     pub color_transform: ColorTransform,
+
+    /// The 3D transformation matrix, if this transform has one.
+    ///
+    /// When present, this takes precedence over `matrix`, which then holds an
+    /// affine approximation for renderers that can't draw with a 3D transform.
+    pub matrix3d: Option<Matrix3D>,
 }
 
 pub struct TransformStack(Vec<Transform>);
@@ -18,11 +25,19 @@ impl TransformStack {
 
     pub fn push(&mut self, transform: &Transform) {
         let cur_transform = self.transform();
-        let matrix = cur_transform.matrix * transform.matrix;
         let color_transform = cur_transform.color_transform * transform.color_transform;
+        let (matrix, matrix3d) = match (cur_transform.matrix3d, transform.matrix3d) {
+            (None, None) => (cur_transform.matrix * transform.matrix, None),
+            (cur_matrix3d, matrix3d) => {
+                let matrix3d = cur_matrix3d.unwrap_or_else(|| cur_transform.matrix.into())
+                    * matrix3d.unwrap_or_else(|| transform.matrix.into());
+                (matrix3d.to_affine(), Some(matrix3d))
+            }
+        };
         self.0.push(Transform {
             matrix,
             color_transform,
+            matrix3d,
         });
     }
 
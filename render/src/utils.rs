@@ -380,6 +380,61 @@ fn decode_png(data: &[u8]) -> Result<Bitmap, Error> {
     Ok(Bitmap::new(info.width, info.height, format, data))
 }
 
+/// Encodes a bitmap as a PNG file, as `BitmapData.encode` does with `PNGEncoderOptions`.
+/// RGBA bitmaps keep their alpha channel, everything else is written out as RGB.
+pub fn encode_png(bitmap: Bitmap, fast_compression: bool) -> Result<Vec<u8>, Error> {
+    use png::{BitDepth, ColorType, Compression};
+
+    let (color_type, bitmap) = match bitmap.format() {
+        BitmapFormat::Rgba | BitmapFormat::Yuva420p => (ColorType::Rgba, bitmap.to_rgba()),
+        BitmapFormat::Rgb | BitmapFormat::Yuv420p => (ColorType::Rgb, bitmap.to_rgb()),
+    };
+    let mut data = bitmap.data().to_vec();
+    if color_type == ColorType::Rgba {
+        unmultiply_alpha_rgba(&mut data);
+    }
+
+    let mut output = Vec::new();
+    let mut encoder = png::Encoder::new(&mut output, bitmap.width(), bitmap.height());
+    encoder.set_color(color_type);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_compression(if fast_compression {
+        Compression::Fast
+    } else {
+        Compression::Default
+    });
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+    Ok(output)
+}
+
+/// Encodes a bitmap as a baseline JPEG file, as `BitmapData.encode` does with `JPEGEncoderOptions`.
+/// JPEG has no alpha channel, so the unmultiplied colors of transparent pixels are kept as they are.
+pub fn encode_jpeg(bitmap: Bitmap, quality: u8) -> Result<Vec<u8>, Error> {
+    use image::codecs::jpeg::JpegEncoder;
+
+    let (width, height) = (bitmap.width(), bitmap.height());
+    let rgb: Vec<u8> = match bitmap.format() {
+        BitmapFormat::Rgba | BitmapFormat::Yuva420p => {
+            let mut rgba = bitmap.to_rgba().data().to_vec();
+            unmultiply_alpha_rgba(&mut rgba);
+            rgba.chunks_exact(4)
+                .flat_map(|rgba| [rgba[0], rgba[1], rgba[2]])
+                .collect()
+        }
+        BitmapFormat::Rgb | BitmapFormat::Yuv420p => bitmap.to_rgb().data().to_vec(),
+    };
+    let mut output = Vec::new();
+    JpegEncoder::new_with_quality(&mut output, quality).encode(
+        &rgb,
+        width,
+        height,
+        image::ColorType::Rgb8,
+    )?;
+    Ok(output)
+}
+
 /// Decodes the bitmap data in DefineBitsLossless tag into RGBA.
 /// DefineBitsLossless is Zlib encoded pixel data (similar to PNG), possibly
 /// palletized.
@@ -0,0 +1,92 @@
+struct Filter {
+    strength: f32,
+    bevel_type: u32,
+    knockout: u32,
+    glow: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> filter_args: Filter;
+@group(0) @binding(3) var blurred: texture_2d<f32>;
+@group(0) @binding(4) var gradient: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) source_uv: vec2<f32>,
+    @location(1) highlight_uv: vec2<f32>,
+    @location(2) shadow_uv: vec2<f32>,
+};
+
+struct VertexInput {
+    /// The position of the vertex in texture space (topleft 0,0, bottomright 1,1)
+    @location(0) position: vec2<f32>,
+
+    /// The coordinate of the source texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(1) source_uv: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample for the highlight, shifted towards the light
+    @location(2) highlight_uv: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample for the shadow, shifted away from the light
+    @location(3) shadow_uv: vec2<f32>,
+};
+
+@vertex
+fn main_vertex(in: VertexInput) -> VertexOutput {
+    // Convert texture space (topleft 0,0 to bottomright 1,1) to render space (topleft -1,1 to bottomright 1,-1)
+    let pos = vec4<f32>((in.position.x * 2.0 - 1.0), (1.0 - in.position.y * 2.0), 0.0, 1.0);
+    return VertexOutput(pos, in.source_uv, in.highlight_uv, in.shadow_uv);
+}
+
+fn in_bounds(uv: vec2<f32>) -> bool {
+    return uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0;
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 0 is inner, 1 is outer, 2 is full
+    let inner = filter_args.bevel_type == 0u;
+    let outer = filter_args.bevel_type == 1u;
+    let knockout = filter_args.knockout > 0u;
+    let dest = textureSample(texture, texture_sampler, in.source_uv);
+    var highlight = textureSample(blurred, texture_sampler, in.highlight_uv).a;
+    var shadow = textureSample(blurred, texture_sampler, in.shadow_uv).a;
+
+    if (!in_bounds(in.highlight_uv)) {
+        highlight = 0.0;
+    }
+    if (!in_bounds(in.shadow_uv)) {
+        shadow = 0.0;
+    }
+
+    // The gradient goes from the highlight (ratio 0) to the shadow (ratio 255) for bevels,
+    // and from the edge to the middle of the glow for glows.
+    var position: f32;
+    if (filter_args.glow > 0u) {
+        if (inner) {
+            position = saturate((1.0 - shadow) * filter_args.strength);
+        } else {
+            position = saturate(shadow * filter_args.strength);
+        }
+    } else {
+        position = 0.5 - clamp((highlight - shadow) * filter_args.strength, -1.0, 1.0) * 0.5;
+    }
+    // The gradient is premultiplied
+    var color = textureLoad(gradient, vec2<i32>(i32(round(position * 255.0)), 0), 0);
+
+    if (inner) {
+        color = color * dest.a;
+    } else if (outer) {
+        color = color * (1.0 - dest.a);
+    }
+
+    if (knockout) {
+        return color;
+    } else if (outer) {
+        // Outer bevels are drawn behind the source, which has already masked them out
+        return color + dest;
+    } else {
+        return color + dest * (1.0 - color.a);
+    }
+}
@@ -0,0 +1,61 @@
+#import filter
+
+struct Filter {
+    matrix: array<vec4<f32>, 64>,
+    default_color: vec4<f32>,
+    bounds: vec4<i32>,
+    matrix_x: u32,
+    matrix_y: u32,
+    divisor: f32,
+    bias: f32,
+    clamp_edges: u32,
+    preserve_alpha: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> filter_args: Filter;
+
+@vertex
+fn main_vertex(in: filter::VertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+/// Loads an unmultiplied pixel, handling the edges of the source the way the filter asks us to.
+fn load_pixel(coord: vec2<i32>) -> vec4<f32> {
+    let min_coord = filter_args.bounds.xy;
+    let max_coord = filter_args.bounds.zw - vec2<i32>(1, 1);
+    var pixel_coord = coord;
+    if (any(coord < min_coord) || any(coord > max_coord)) {
+        if (filter_args.clamp_edges == 0u) {
+            return filter_args.default_color;
+        }
+        pixel_coord = clamp(coord, min_coord, max_coord);
+    }
+    let color = textureLoad(texture, pixel_coord, 0);
+    if (color.a == 0.0) {
+        return vec4<f32>(0.0);
+    }
+    return vec4<f32>(color.rgb / color.a, color.a);
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let center = vec2<i32>(floor(in.uv * vec2<f32>(textureDimensions(texture))));
+    let half_size = vec2<i32>(i32(filter_args.matrix_x / 2u), i32(filter_args.matrix_y / 2u));
+
+    var sum = vec4<f32>(0.0);
+    for (var y = 0u; y < filter_args.matrix_y; y += 1u) {
+        for (var x = 0u; x < filter_args.matrix_x; x += 1u) {
+            let index = y * filter_args.matrix_x + x;
+            let weight = filter_args.matrix[index / 4u][index % 4u];
+            sum += weight * load_pixel(center + vec2<i32>(i32(x), i32(y)) - half_size);
+        }
+    }
+
+    // The bias is in the 0-255 range, just like the colors Flash works with
+    var color = saturate(sum / filter_args.divisor + vec4<f32>(filter_args.bias / 255.0));
+    if (filter_args.preserve_alpha > 0u) {
+        color.a = load_pixel(center).a;
+    }
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
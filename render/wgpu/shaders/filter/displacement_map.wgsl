@@ -0,0 +1,90 @@
+#import filter
+
+struct Filter {
+    color: vec4<f32>,
+    /// The left, top, right and bottom of the source area, in pixels.
+    bounds: vec4<i32>,
+    map_point: vec2<i32>,
+    scale: vec2<f32>,
+    component_x: u32,
+    component_y: u32,
+    mode: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> filter_args: Filter;
+@group(0) @binding(2) var map_texture: texture_2d<f32>;
+
+@vertex
+fn main_vertex(in: filter::VertexInput) -> filter::VertexOutput {
+    return filter::main_vertex(in);
+}
+
+/// Picks the channel of an unmultiplied map pixel, as a `BitmapDataChannel` value.
+fn component(color: vec4<f32>, channel: u32) -> f32 {
+    switch (channel) {
+        case 1u: {
+            return color.r;
+        }
+        case 2u: {
+            return color.g;
+        }
+        case 4u: {
+            return color.b;
+        }
+        case 8u: {
+            return color.a;
+        }
+        default: {
+            // Right in the middle, so no displacement
+            return 128.0 / 255.0;
+        }
+    }
+}
+
+@fragment
+fn main_fragment(in: filter::VertexOutput) -> @location(0) vec4<f32> {
+    let min_coord = filter_args.bounds.xy;
+    let max_coord = filter_args.bounds.zw - vec2<i32>(1, 1);
+    let size = filter_args.bounds.zw - filter_args.bounds.xy;
+    let coord = vec2<i32>(floor(in.uv * vec2<f32>(textureDimensions(texture))));
+
+    // Pixels not covered by the map aren't displaced at all
+    var offset = vec2<i32>(0, 0);
+    let map_coord = coord - min_coord - filter_args.map_point;
+    let map_size = vec2<i32>(textureDimensions(map_texture));
+    if (all(map_coord >= vec2<i32>(0, 0)) && all(map_coord < map_size)) {
+        var map_color = textureLoad(map_texture, map_coord, 0);
+        if (map_color.a > 0.0) {
+            map_color = vec4<f32>(map_color.rgb / map_color.a, map_color.a);
+        }
+        let displacement = vec2<f32>(
+            component(map_color, filter_args.component_x),
+            component(map_color, filter_args.component_y)
+        ) * 255.0 - 128.0;
+        offset = vec2<i32>(floor(displacement * filter_args.scale / 256.0));
+    }
+
+    var source_coord = coord + offset;
+    if (any(source_coord < min_coord) || any(source_coord > max_coord)) {
+        switch (filter_args.mode) {
+            // Clamp
+            case 0u: {
+                source_coord = clamp(source_coord, min_coord, max_coord);
+            }
+            // Color
+            case 1u: {
+                return filter_args.color;
+            }
+            // Ignore
+            case 2u: {
+                source_coord = coord;
+            }
+            // Wrap
+            default: {
+                source_coord = ((source_coord - min_coord) % size + size) % size + min_coord;
+            }
+        }
+    }
+    return textureLoad(texture, source_coord, 0);
+}
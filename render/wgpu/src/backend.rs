@@ -400,7 +400,10 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     fn create_context3d(
         &mut self,
     ) -> Result<Box<dyn ruffle_render::backend::Context3D>, BitmapError> {
-        Ok(Box::new(WgpuContext3D::new(self.descriptors.clone())))
+        Ok(Box::new(WgpuContext3D::new(
+            self.descriptors.clone(),
+            self.offscreen_buffer_pool.clone(),
+        )))
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -803,14 +806,11 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn is_filter_supported(&self, filter: &Filter) -> bool {
-        matches!(
-            filter,
-            Filter::BlurFilter(_)
-                | Filter::GlowFilter(_)
-                | Filter::DropShadowFilter(_)
-                | Filter::ColorMatrixFilter(_)
-                | Filter::ShaderFilter(_)
-        )
+        match filter {
+            // Without a map, Flash leaves the source untouched
+            Filter::DisplacementMapFilter(filter) => filter.map_bitmap.is_some(),
+            _ => true,
+        }
     }
 
     fn is_offscreen_supported(&self) -> bool {
@@ -902,9 +902,7 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
     }
 
     fn calculate_dest_rect(&self, filter: &Filter, source_rect: Rectangle<i32>) -> Rectangle<i32> {
-        self.descriptors
-            .filters
-            .calculate_dest_rect(filter, source_rect)
+        filter.calculate_dest_rect(source_rect)
     }
 
     fn compile_pixelbender_shader(
@@ -1,8 +1,8 @@
 use naga::valid::{Capabilities, ValidationFlags, Validator};
-use naga_agal::{Filter, SamplerOverride, Wrapping};
+use naga_agal::{Filter, Mipmap, SamplerConfig, Wrapping};
 use ruffle_render::backend::{
-    Context3DTextureFilter, Context3DTriangleFace, Context3DVertexBufferFormat, Context3DWrapMode,
-    Texture,
+    Context3DMipFilter, Context3DTextureFilter, Context3DTriangleFace, Context3DVertexBufferFormat,
+    Context3DWrapMode, Texture,
 };
 
 use wgpu::{
@@ -11,7 +11,8 @@ use wgpu::{
 use wgpu::{Buffer, DepthStencilState, StencilFaceState};
 use wgpu::{ColorTargetState, RenderPipelineDescriptor, TextureFormat, VertexState};
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroU64;
 use std::rc::Rc;
@@ -22,8 +23,8 @@ use crate::descriptors::Descriptors;
 
 use super::{ShaderPairAgal, VertexAttributeInfo, MAX_VERTEX_ATTRIBUTES};
 
-const AGAL_NUM_VERTEX_CONSTANTS: u64 = 128;
-const AGAL_NUM_FRAGMENT_CONSTANTS: u64 = 28;
+const AGAL_NUM_VERTEX_CONSTANTS: u64 = naga_agal::MAX_VERTEX_CONSTANTS as u64;
+const AGAL_NUM_FRAGMENT_CONSTANTS: u64 = naga_agal::MAX_FRAGMENT_CONSTANTS as u64;
 pub(super) const AGAL_FLOATS_PER_REGISTER: u64 = 4;
 
 const VERTEX_SHADER_UNIFORMS_BUFFER_SIZE: u64 =
@@ -31,17 +32,19 @@ const VERTEX_SHADER_UNIFORMS_BUFFER_SIZE: u64 =
 const FRAGMENT_SHADER_UNIFORMS_BUFFER_SIZE: u64 =
     AGAL_NUM_FRAGMENT_CONSTANTS * AGAL_FLOATS_PER_REGISTER * std::mem::size_of::<f32>() as u64;
 
-pub(super) const SAMPLER_REPEAT_LINEAR: u32 = 2;
-pub(super) const SAMPLER_REPEAT_NEAREST: u32 = 3;
-pub(super) const SAMPLER_CLAMP_LINEAR: u32 = 4;
-pub(super) const SAMPLER_CLAMP_NEAREST: u32 = 5;
-pub(super) const SAMPLER_CLAMP_U_REPEAT_V_LINEAR: u32 = 6;
-pub(super) const SAMPLER_CLAMP_U_REPEAT_V_NEAREST: u32 = 7;
-pub(super) const SAMPLER_REPEAT_U_CLAMP_V_LINEAR: u32 = 8;
-pub(super) const SAMPLER_REPEAT_U_CLAMP_V_NEAREST: u32 = 9;
+pub(super) const SAMPLER_START_BIND_INDEX: u32 = 2;
+
+/// The sampler bound for texture slots that the shader doesn't sample from.
+const DEFAULT_SAMPLER_CONFIG: SamplerConfig = SamplerConfig {
+    wrapping: Wrapping::Clamp,
+    filter: Filter::Nearest,
+    mipmap: Mipmap::Disable,
+};
 
 pub(super) const TEXTURE_START_BIND_INDEX: u32 = 10;
 
+pub(super) const EXTRA_SAMPLER_START_BIND_INDEX: u32 = 18;
+
 // The flash Context3D API is similar to OpenGL - it has many methods
 // which modify the current state (`setVertexBufferAt`, `setCulling`, etc.)
 // These methods can be called at any time.
@@ -75,14 +78,25 @@ pub struct CurrentPipeline {
     depth_mask: bool,
     pass_compare_mode: wgpu::CompareFunction,
 
+    stencil_front: StencilFaceState,
+    stencil_back: StencilFaceState,
+    stencil_read_mask: u32,
+    stencil_write_mask: u32,
+
     color_component: wgpu::BlendComponent,
     alpha_component: wgpu::BlendComponent,
 
     sample_count: u32,
 
+    /// Which of the color outputs after the first one have a texture to render to.
+    extra_color_outputs: [bool; naga_agal::MAX_COLOR_OUTPUTS - 1],
+
     dirty: Cell<bool>,
 
-    sampler_override: [Option<SamplerOverride>; 8],
+    sampler_override: [Option<SamplerConfig>; 8],
+
+    /// Every sampler created so far, as there's only a handful of configurations in use.
+    samplers: RefCell<HashMap<SamplerConfig, Rc<wgpu::Sampler>>>,
 }
 
 #[derive(Clone)]
@@ -151,11 +165,17 @@ impl CurrentPipeline {
 
             depth_mask: true,
             pass_compare_mode: wgpu::CompareFunction::LessEqual,
+            stencil_front: StencilFaceState::IGNORE,
+            stencil_back: StencilFaceState::IGNORE,
+            stencil_read_mask: 0xFF,
+            stencil_write_mask: 0xFF,
             color_component: wgpu::BlendComponent::REPLACE,
             alpha_component: wgpu::BlendComponent::REPLACE,
             sample_count: 1,
+            extra_color_outputs: [false; naga_agal::MAX_COLOR_OUTPUTS - 1],
 
             sampler_override: [None; 8],
+            samplers: RefCell::new(HashMap::new()),
         }
     }
     pub fn set_shaders(&mut self, shaders: Option<Rc<ShaderPairAgal>>) {
@@ -203,6 +223,35 @@ impl CurrentPipeline {
         self.pass_compare_mode = pass_compare_mode;
     }
 
+    pub fn update_stencil_actions(
+        &mut self,
+        triangle_face: Context3DTriangleFace,
+        stencil_face: StencilFaceState,
+    ) {
+        let (front, back) = match triangle_face {
+            Context3DTriangleFace::None => (false, false),
+            Context3DTriangleFace::Front => (true, false),
+            Context3DTriangleFace::Back => (false, true),
+            Context3DTriangleFace::FrontAndBack => (true, true),
+        };
+        if front && self.stencil_front != stencil_face {
+            self.dirty.set(true);
+            self.stencil_front = stencil_face;
+        }
+        if back && self.stencil_back != stencil_face {
+            self.dirty.set(true);
+            self.stencil_back = stencil_face;
+        }
+    }
+
+    pub fn update_stencil_masks(&mut self, read_mask: u32, write_mask: u32) {
+        if self.stencil_read_mask != read_mask || self.stencil_write_mask != write_mask {
+            self.dirty.set(true);
+        }
+        self.stencil_read_mask = read_mask;
+        self.stencil_write_mask = write_mask;
+    }
+
     pub fn update_has_depth_texture(&mut self, has_depth_texture: bool) {
         if self.has_depth_texture != has_depth_texture {
             self.dirty.set(true);
@@ -217,20 +266,61 @@ impl CurrentPipeline {
         }
     }
 
+    pub fn update_extra_color_outputs(
+        &mut self,
+        extra_color_outputs: [bool; naga_agal::MAX_COLOR_OUTPUTS - 1],
+    ) {
+        if self.extra_color_outputs != extra_color_outputs {
+            self.dirty.set(true);
+            self.extra_color_outputs = extra_color_outputs;
+        }
+    }
+
     /// If the pipeline is dirty, recompiles it and returns `Some(freshly_compiled_pipeline`)
     /// Otherwise, returns `None`.
+    ///
+    /// The pipeline stays dirty if the shaders can't be compiled with the current vertex
+    /// attribute formats.
     pub fn rebuild_pipeline(
         &self,
         descriptors: &Descriptors,
         vertex_attributes: &[Option<VertexAttributeInfo>; MAX_VERTEX_ATTRIBUTES],
-    ) -> Option<(wgpu::RenderPipeline, wgpu::BindGroup)> {
+    ) -> Result<Option<(wgpu::RenderPipeline, wgpu::BindGroup)>, naga_agal::Error> {
         if !self.dirty.get() {
-            return None;
+            return Ok(None);
         }
 
+        let bind_group_label = create_debug_label!("Bind group");
+
+        let agal_attributes = vertex_attributes.clone().map(|attr| {
+            attr.map(|attr| match attr.format {
+                Context3DVertexBufferFormat::Float4 => naga_agal::VertexAttributeFormat::Float4,
+                Context3DVertexBufferFormat::Float3 => naga_agal::VertexAttributeFormat::Float3,
+                Context3DVertexBufferFormat::Float2 => naga_agal::VertexAttributeFormat::Float2,
+                Context3DVertexBufferFormat::Float1 => naga_agal::VertexAttributeFormat::Float1,
+                Context3DVertexBufferFormat::Bytes4 => naga_agal::VertexAttributeFormat::Bytes4,
+            })
+        });
+
+        let compiled_shaders = self.shaders.as_ref().expect("Missing shaders!").compile(
+            descriptors,
+            ShaderCompileData {
+                vertex_attributes: agal_attributes,
+                sampler_overrides: self.sampler_override,
+                bound_textures: self.bound_textures.clone(),
+            },
+        )?;
         self.dirty.set(false);
 
-        let bind_group_label = create_debug_label!("Bind group");
+        // Every texture slot gets a sampler, even if the shader doesn't use it.
+        let samplers = compiled_shaders
+            .sampler_configs
+            .map(|config| self.get_sampler(descriptors, config.unwrap_or(DEFAULT_SAMPLER_CONFIG)));
+        let extra_samplers: Vec<_> = compiled_shaders
+            .extra_sampler_configs
+            .iter()
+            .map(|config| self.get_sampler(descriptors, *config))
+            .collect();
 
         let mut bind_group_entries = vec![
             BindGroupEntry {
@@ -249,48 +339,22 @@ impl CurrentPipeline {
                     size: Some(NonZeroU64::new(FRAGMENT_SHADER_UNIFORMS_BUFFER_SIZE).unwrap()),
                 }),
             },
-            BindGroupEntry {
-                binding: SAMPLER_REPEAT_LINEAR,
-                resource: BindingResource::Sampler(&descriptors.bitmap_samplers.repeat_linear),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_REPEAT_NEAREST,
-                resource: BindingResource::Sampler(&descriptors.bitmap_samplers.repeat_nearest),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_CLAMP_LINEAR,
-                resource: BindingResource::Sampler(&descriptors.bitmap_samplers.clamp_linear),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_CLAMP_NEAREST,
-                resource: BindingResource::Sampler(&descriptors.bitmap_samplers.clamp_nearest),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_CLAMP_U_REPEAT_V_LINEAR,
-                resource: BindingResource::Sampler(
-                    &descriptors.bitmap_samplers.clamp_u_repeat_v_linear,
-                ),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_CLAMP_U_REPEAT_V_NEAREST,
-                resource: BindingResource::Sampler(
-                    &descriptors.bitmap_samplers.clamp_u_repeat_v_nearest,
-                ),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_REPEAT_U_CLAMP_V_LINEAR,
-                resource: BindingResource::Sampler(
-                    &descriptors.bitmap_samplers.repeat_u_clamp_v_linear,
-                ),
-            },
-            BindGroupEntry {
-                binding: SAMPLER_REPEAT_U_CLAMP_V_NEAREST,
-                resource: BindingResource::Sampler(
-                    &descriptors.bitmap_samplers.repeat_u_clamp_v_nearest,
-                ),
-            },
         ];
 
+        for (i, sampler) in samplers.iter().enumerate() {
+            bind_group_entries.push(BindGroupEntry {
+                binding: SAMPLER_START_BIND_INDEX + i as u32,
+                resource: BindingResource::Sampler(sampler),
+            });
+        }
+
+        for (i, sampler) in extra_samplers.iter().enumerate() {
+            bind_group_entries.push(BindGroupEntry {
+                binding: EXTRA_SAMPLER_START_BIND_INDEX + i as u32,
+                resource: BindingResource::Sampler(sampler),
+            });
+        }
+
         for (i, bound_texture) in self.bound_textures.iter().enumerate() {
             if let Some(bound_texture) = bound_texture {
                 bind_group_entries.push(BindGroupEntry {
@@ -300,25 +364,6 @@ impl CurrentPipeline {
             }
         }
 
-        let agal_attributes = vertex_attributes.clone().map(|attr| {
-            attr.map(|attr| match attr.format {
-                Context3DVertexBufferFormat::Float4 => naga_agal::VertexAttributeFormat::Float4,
-                Context3DVertexBufferFormat::Float3 => naga_agal::VertexAttributeFormat::Float3,
-                Context3DVertexBufferFormat::Float2 => naga_agal::VertexAttributeFormat::Float2,
-                Context3DVertexBufferFormat::Float1 => naga_agal::VertexAttributeFormat::Float1,
-                Context3DVertexBufferFormat::Bytes4 => naga_agal::VertexAttributeFormat::Bytes4,
-            })
-        });
-
-        let compiled_shaders = self.shaders.as_ref().expect("Missing shaders!").compile(
-            descriptors,
-            ShaderCompileData {
-                vertex_attributes: agal_attributes,
-                sampler_overrides: self.sampler_override,
-                bound_textures: self.bound_textures.clone(),
-            },
-        );
-
         let pipeline_layout_label = create_debug_label!("Pipeline layout");
         let pipeline_layout =
             descriptors
@@ -413,12 +458,11 @@ impl CurrentPipeline {
                 format: TextureFormat::Depth24PlusStencil8,
                 depth_write_enabled: self.depth_mask,
                 depth_compare: self.pass_compare_mode,
-                // FIXME - implement this
                 stencil: wgpu::StencilState {
-                    front: StencilFaceState::IGNORE,
-                    back: StencilFaceState::IGNORE,
-                    read_mask: !0,
-                    write_mask: !0,
+                    front: self.stencil_front,
+                    back: self.stencil_back,
+                    read_mask: self.stencil_read_mask,
+                    write_mask: self.stencil_write_mask,
                 },
                 bias: Default::default(),
             })
@@ -450,6 +494,33 @@ impl CurrentPipeline {
             })
             .collect::<Vec<_>>();
 
+        // There's one target for each bound texture, up to the last one. Color outputs
+        // that the shader doesn't write to leave their texture untouched.
+        let color_target = |written: bool| ColorTargetState {
+            format: TextureFormat::Rgba8Unorm,
+            blend: Some(wgpu::BlendState {
+                color: self.color_component,
+                alpha: self.alpha_component,
+            }),
+            write_mask: if written {
+                self.color_mask
+            } else {
+                wgpu::ColorWrites::empty()
+            },
+        };
+        let num_extra_targets = self
+            .extra_color_outputs
+            .iter()
+            .rposition(|bound| *bound)
+            .map_or(0, |last| last + 1);
+        let mut targets = vec![Some(color_target(true))];
+        targets.extend(
+            self.extra_color_outputs[..num_extra_targets]
+                .iter()
+                .zip(&compiled_shaders.color_outputs[1..])
+                .map(|(bound, written)| bound.then(|| color_target(*written))),
+        );
+
         let compiled = descriptors
             .device
             .create_render_pipeline(&RenderPipelineDescriptor {
@@ -463,14 +534,7 @@ impl CurrentPipeline {
                 fragment: Some(wgpu::FragmentState {
                     module: &compiled_shaders.fragment_module,
                     entry_point: naga_agal::SHADER_ENTRY_POINT,
-                    targets: &[Some(ColorTargetState {
-                        format: TextureFormat::Rgba8Unorm,
-                        blend: Some(wgpu::BlendState {
-                            color: self.color_component,
-                            alpha: self.alpha_component,
-                        }),
-                        write_mask: self.color_mask,
-                    })],
+                    targets: &targets,
                 }),
                 primitive: wgpu::PrimitiveState {
                     // Stage3d appears to use clockwise winding:
@@ -487,7 +551,7 @@ impl CurrentPipeline {
                 },
                 multiview: Default::default(),
             });
-        Some((compiled, bind_group))
+        Ok(Some((compiled, bind_group)))
     }
 
     pub fn set_culling(&mut self, face: Context3DTriangleFace) {
@@ -512,8 +576,9 @@ impl CurrentPipeline {
         sampler: usize,
         wrap: ruffle_render::backend::Context3DWrapMode,
         filter: ruffle_render::backend::Context3DTextureFilter,
+        mip_filter: ruffle_render::backend::Context3DMipFilter,
     ) {
-        let sampler_override = SamplerOverride {
+        let sampler_override = SamplerConfig {
             wrapping: match wrap {
                 Context3DWrapMode::Clamp => Wrapping::Clamp,
                 Context3DWrapMode::Repeat => Wrapping::Repeat,
@@ -523,16 +588,76 @@ impl CurrentPipeline {
             filter: match filter {
                 Context3DTextureFilter::Linear => Filter::Linear,
                 Context3DTextureFilter::Nearest => Filter::Nearest,
-                _ => unimplemented!(),
+                Context3DTextureFilter::Anisotropic2X => Filter::Anisotropic2X,
+                Context3DTextureFilter::Anisotropic4X => Filter::Anisotropic4X,
+                Context3DTextureFilter::Anisotropic8X => Filter::Anisotropic8X,
+                Context3DTextureFilter::Anisotropic16X => Filter::Anisotropic16X,
+            },
+            mipmap: match mip_filter {
+                Context3DMipFilter::MipNone => Mipmap::Disable,
+                Context3DMipFilter::MipNearest => Mipmap::Nearest,
+                Context3DMipFilter::MipLinear => Mipmap::Linear,
             },
-            // FIXME - implement this
-            mipmap: naga_agal::Mipmap::Disable,
         };
         if self.sampler_override[sampler] != Some(sampler_override) {
             self.dirty.set(true);
             self.sampler_override[sampler] = Some(sampler_override);
         }
     }
+
+    fn get_sampler(&self, descriptors: &Descriptors, config: SamplerConfig) -> Rc<wgpu::Sampler> {
+        self.samplers
+            .borrow_mut()
+            .entry(config)
+            .or_insert_with(|| Rc::new(create_sampler(&descriptors.device, config)))
+            .clone()
+    }
+}
+
+fn create_sampler(device: &wgpu::Device, config: SamplerConfig) -> wgpu::Sampler {
+    let (address_mode_u, address_mode_v) = match config.wrapping {
+        Wrapping::Clamp => (
+            wgpu::AddressMode::ClampToEdge,
+            wgpu::AddressMode::ClampToEdge,
+        ),
+        Wrapping::Repeat => (wgpu::AddressMode::Repeat, wgpu::AddressMode::Repeat),
+        Wrapping::ClampURepeatV => (wgpu::AddressMode::ClampToEdge, wgpu::AddressMode::Repeat),
+        Wrapping::RepeatUClampV => (wgpu::AddressMode::Repeat, wgpu::AddressMode::ClampToEdge),
+    };
+    let (filter, anisotropy_clamp) = match config.filter {
+        Filter::Nearest => (wgpu::FilterMode::Nearest, 1),
+        Filter::Linear => (wgpu::FilterMode::Linear, 1),
+        Filter::Anisotropic2X => (wgpu::FilterMode::Linear, 2),
+        Filter::Anisotropic4X => (wgpu::FilterMode::Linear, 4),
+        Filter::Anisotropic8X => (wgpu::FilterMode::Linear, 8),
+        Filter::Anisotropic16X => (wgpu::FilterMode::Linear, 16),
+    };
+    // wgpu only allows anisotropic filtering when every filter is linear.
+    let mipmap_filter = match config.mipmap {
+        Mipmap::Nearest if anisotropy_clamp == 1 => wgpu::FilterMode::Nearest,
+        _ => wgpu::FilterMode::Linear,
+    };
+    // Without mipmapping, only the largest mip level is sampled.
+    let lod_max_clamp = match config.mipmap {
+        Mipmap::Disable => 0.0,
+        Mipmap::Nearest | Mipmap::Linear => 32.0,
+    };
+
+    let label = create_debug_label!("Context3D sampler {:?}", config);
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        label: label.as_deref(),
+        address_mode_u,
+        address_mode_v,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter,
+        lod_min_clamp: 0.0,
+        lod_max_clamp,
+        compare: None,
+        anisotropy_clamp,
+        border_color: None,
+    })
 }
 
 // This is useful for debugging shader issues
@@ -1,8 +1,10 @@
+use naga_agal::MAX_COLOR_OUTPUTS;
 use ruffle_render::backend::{
     Context3D, Context3DBlendFactor, Context3DCommand, Context3DCompareMode,
-    Context3DTextureFormat, Context3DVertexBufferFormat, IndexBuffer, ProgramType, VertexBuffer,
+    Context3DStencilAction, Context3DTextureFormat, Context3DVertexBufferFormat, IndexBuffer,
+    ProgramType, VertexBuffer,
 };
-use ruffle_render::bitmap::{BitmapFormat, BitmapHandle};
+use ruffle_render::bitmap::{BitmapFormat, BitmapHandle, PixelRegion};
 use ruffle_render::error::Error;
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -15,9 +17,11 @@ use wgpu::{
 };
 use wgpu::{CommandEncoder, Extent3d, RenderPass};
 
+use crate::buffer_pool::BufferPool;
 use crate::context3d::current_pipeline::{BoundTextureData, AGAL_FLOATS_PER_REGISTER};
 use crate::descriptors::Descriptors;
-use crate::Texture;
+use crate::utils::BufferDimensions;
+use crate::{as_texture, QueueSyncHandle, Texture};
 
 use std::num::NonZeroU64;
 use std::rc::Rc;
@@ -34,6 +38,18 @@ const COLOR_MASK: u32 = 1 << 0;
 const DEPTH_MASK: u32 = 1 << 1;
 const STENCIL_MASK: u32 = 1 << 2;
 
+/// A view of only the largest mip level of a texture, as needed for render attachments.
+const TOP_MIP_LEVEL_VIEW: wgpu::TextureViewDescriptor<'static> = wgpu::TextureViewDescriptor {
+    label: None,
+    format: None,
+    dimension: None,
+    aspect: wgpu::TextureAspect::All,
+    base_mip_level: 0,
+    mip_level_count: Some(1),
+    base_array_layer: 0,
+    array_layer_count: None,
+};
+
 /// A wgpu-based implemented of `Context3D`.
 /// Many of the WGPU methods have very strict lifetime requirements
 /// (e.g. taking in a reference that lives as long as the `RenderPass`).
@@ -53,12 +69,21 @@ pub struct WgpuContext3D {
     // we need in individual `Arc`s.
     descriptors: Arc<Descriptors>,
 
+    // Used to read back the BitmapData pixels written by `drawToBitmapData`.
+    offscreen_buffer_pool: Arc<BufferPool<wgpu::Buffer, BufferDimensions>>,
+
     buffer_staging_belt: StagingBelt,
 
     current_texture_view: Option<Rc<wgpu::TextureView>>,
     current_texture_size: Option<Extent3d>,
     current_depth_texture_view: Option<Rc<wgpu::TextureView>>,
     current_texture_resolve_view: Option<Rc<wgpu::TextureView>>,
+    // The textures that the additional color outputs of an AGAL 2 fragment shader
+    // are rendered to. These can only be used while rendering to a texture.
+    extra_color_targets: [Option<ColorTarget>; MAX_COLOR_OUTPUTS - 1],
+    // The textures being rendered to, for each color output, so that we can stop
+    // rendering to them when they're disposed.
+    render_target_textures: [Option<Rc<dyn ruffle_render::backend::Texture>>; MAX_COLOR_OUTPUTS],
 
     back_buffer_sample_count: u32,
     back_buffer_size: Option<Extent3d>,
@@ -95,10 +120,17 @@ pub struct WgpuContext3D {
     seen_clear_command: bool,
 
     scissor_rectangle: Option<Rectangle<Twips>>,
+
+    // The reference value isn't part of a `wgpu::RenderPipeline`, so it's set
+    // on each render pass instead.
+    stencil_reference: u32,
 }
 
 impl WgpuContext3D {
-    pub fn new(descriptors: Arc<Descriptors>) -> Self {
+    pub fn new(
+        descriptors: Arc<Descriptors>,
+        offscreen_buffer_pool: Arc<BufferPool<wgpu::Buffer, BufferDimensions>>,
+    ) -> Self {
         let make_dummy_handle = || {
             let texture_label = create_debug_label!("Render target texture");
             let format = wgpu::TextureFormat::Rgba8Unorm;
@@ -141,6 +173,7 @@ impl WgpuContext3D {
 
         Self {
             descriptors,
+            offscreen_buffer_pool,
             buffer_staging_belt,
             back_buffer_raw_texture_handle,
             front_buffer_raw_texture_handle,
@@ -153,6 +186,8 @@ impl WgpuContext3D {
             current_texture_size: None,
             current_depth_texture_view: None,
             current_texture_resolve_view: None,
+            extra_color_targets: std::array::from_fn(|_| None),
+            render_target_textures: std::array::from_fn(|_| None),
 
             back_buffer_sample_count: 1,
             back_buffer_size: None,
@@ -168,6 +203,7 @@ impl WgpuContext3D {
             clear_color: None,
             seen_clear_command: false,
             scissor_rectangle: None,
+            stencil_reference: 0,
         }
     }
 
@@ -206,12 +242,42 @@ impl WgpuContext3D {
         self.current_texture_view = self.back_buffer_texture_view.clone();
         self.current_texture_resolve_view = self.back_buffer_resolve_texture_view.clone();
         self.current_depth_texture_view = self.back_buffer_depth_texture_view.clone();
+        self.extra_color_targets = std::array::from_fn(|_| None);
+        self.render_target_textures = std::array::from_fn(|_| None);
+        self.current_pipeline
+            .update_extra_color_outputs(self.extra_color_outputs());
         self.current_pipeline
             .update_has_depth_texture(self.current_depth_texture_view.is_some());
         self.current_pipeline
             .update_sample_count(self.back_buffer_sample_count);
     }
 
+    fn extra_color_outputs(&self) -> [bool; MAX_COLOR_OUTPUTS - 1] {
+        std::array::from_fn(|i| self.extra_color_targets[i].is_some())
+    }
+
+    /// Takes the buffer and texture uploads recorded so far, to be submitted before
+    /// anything that depends on them. `buffer_staging_belt.recall()` has to be called
+    /// once they have been submitted.
+    fn take_buffer_commands(&mut self) -> wgpu::CommandBuffer {
+        self.buffer_staging_belt.finish();
+        let new_encoder =
+            self.descriptors
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: create_debug_label!("Buffer command encoder").as_deref(),
+                });
+        std::mem::replace(&mut self.buffer_command_encoder, new_encoder).finish()
+    }
+
+    /// Submits the pending uploads, which have to happen before their destination
+    /// is destroyed.
+    fn submit_buffer_commands(&mut self) {
+        let buffer_commands = self.take_buffer_commands();
+        self.descriptors.queue.submit([buffer_commands]);
+        self.buffer_staging_belt.recall();
+    }
+
     pub(crate) fn present(&mut self) {
         std::mem::swap(
             &mut self.back_buffer_raw_texture_handle,
@@ -243,55 +309,24 @@ impl WgpuContext3D {
         // will use a clear color of None. This ensures that by itself,
         // re-creating the render pass has no effect on the output
         let clear_color = self.clear_color.take();
-        let color_load = match clear_color {
-            Some(clear) if clear.mask & COLOR_MASK != 0 => wgpu::LoadOp::Clear(clear.rgb),
-            _ => wgpu::LoadOp::Load,
-        };
-
-        let depth_load = match clear_color {
-            Some(clear) if clear.mask & DEPTH_MASK != 0 => wgpu::LoadOp::Clear(clear.depth),
-            _ => wgpu::LoadOp::Load,
-        };
-
-        let stencil_load = match clear_color {
-            Some(clear) if clear.mask & STENCIL_MASK != 0 => wgpu::LoadOp::Clear(clear.stencil),
-            _ => wgpu::LoadOp::Load,
-        };
-
-        let depth_stencil_attachment = if let Some(depth_view) = &self.current_depth_texture_view {
-            Some(wgpu::RenderPassDepthStencilAttachment {
-                view: depth_view,
-                depth_ops: Some(wgpu::Operations {
-                    load: depth_load,
-                    store: true,
-                }),
-                stencil_ops: Some(wgpu::Operations {
-                    load: stencil_load,
-                    store: true,
-                }),
-            })
-        } else {
-            None
-        };
-
-        let mut pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Context3D render pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: self.current_texture_view.as_ref().unwrap(),
-                resolve_target: self.current_texture_resolve_view.as_deref(),
-                ops: wgpu::Operations {
-                    load: color_load,
-                    store: true,
-                },
-            })],
-            depth_stencil_attachment,
-        });
+        let has_depth_stencil = self.current_depth_texture_view.is_some();
+        let mut pass = begin_render_pass(
+            command_encoder,
+            clear_color,
+            self.current_texture_view.as_ref().unwrap(),
+            self.current_texture_resolve_view.as_deref(),
+            &self.extra_color_targets,
+            self.current_depth_texture_view.as_deref(),
+        );
         pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
         pass.set_pipeline(
             self.compiled_pipeline
                 .as_ref()
                 .expect("Missing compiled pipeline"),
         );
+        if has_depth_stencil {
+            pass.set_stencil_reference(self.stencil_reference);
+        }
         if let Some(rect) = &self.scissor_rectangle {
             let current_size = self.current_texture_size.unwrap();
             if rect.x_min.to_pixels() < 0.0
@@ -351,7 +386,15 @@ pub struct TextureWrapper {
 
 impl IndexBuffer for IndexBufferWrapper {}
 impl VertexBuffer for VertexBufferWrapper {}
-impl ruffle_render::backend::Texture for TextureWrapper {}
+impl ruffle_render::backend::Texture for TextureWrapper {
+    fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.texture.height()
+    }
+}
 
 // Context3D.setVertexBufferAt supports up to 8 vertex buffer attributes
 const MAX_VERTEX_ATTRIBUTES: usize = 8;
@@ -410,14 +453,6 @@ impl Context3D for WgpuContext3D {
         })
     }
 
-    fn disposed_index_buffer_handle(&self) -> Rc<dyn IndexBuffer> {
-        todo!()
-    }
-
-    fn disposed_vertex_buffer_handle(&self) -> Rc<dyn VertexBuffer> {
-        todo!()
-    }
-
     fn create_texture(
         &mut self,
         width: u32,
@@ -441,7 +476,7 @@ impl Context3D for WgpuContext3D {
                 height,
                 depth_or_array_layers: 1,
             },
-            mip_level_count: 1,
+            mip_level_count: full_mip_level_count(width, height),
             sample_count: 1,
             dimension: TextureDimension::D2,
             format,
@@ -476,7 +511,7 @@ impl Context3D for WgpuContext3D {
                 height: size,
                 depth_or_array_layers: 6,
             },
-            mip_level_count: 1,
+            mip_level_count: full_mip_level_count(size, size),
             sample_count: 1,
             dimension: TextureDimension::D2,
             format,
@@ -739,11 +774,40 @@ impl Context3D for WgpuContext3D {
                     .copy_from_slice(&data);
             }
 
+            Context3DCommand::DisposeIndexBuffer { buffer } => {
+                let buffer: &mut IndexBufferWrapper = buffer
+                    .as_any_mut()
+                    .downcast_mut::<IndexBufferWrapper>()
+                    .unwrap();
+
+                self.submit_buffer_commands();
+                buffer.buffer.destroy();
+                buffer.data = Vec::new();
+            }
+
+            Context3DCommand::DisposeVertexBuffer { buffer } => {
+                let buffer: Rc<VertexBufferWrapper> = buffer
+                    .into_any_rc()
+                    .downcast::<VertexBufferWrapper>()
+                    .unwrap();
+
+                for (index, attribute) in self.vertex_attributes.iter_mut().enumerate() {
+                    if matches!(attribute, Some(info) if Rc::ptr_eq(&info.buffer, &buffer)) {
+                        *attribute = None;
+                        self.current_pipeline.update_vertex_buffer_at(index);
+                    }
+                }
+
+                self.submit_buffer_commands();
+                buffer.buffer.destroy();
+            }
+
             Context3DCommand::SetRenderToTexture {
                 texture,
                 enable_depth_and_stencil,
                 anti_alias,
                 surface_selector: _,
+                color_output_index,
             } => {
                 let mut sample_count = anti_alias;
                 if sample_count == 0 {
@@ -751,13 +815,8 @@ impl Context3D for WgpuContext3D {
                 }
 
                 let texture_wrapper = texture.as_any().downcast_ref::<TextureWrapper>().unwrap();
-                self.current_texture_size = Some(Extent3d {
-                    width: texture_wrapper.texture.width(),
-                    height: texture_wrapper.texture.height(),
-                    depth_or_array_layers: 1,
-                });
 
-                if sample_count != 1 {
+                let (texture_view, resolve_view) = if sample_count != 1 {
                     let texture_label = create_debug_label!("Render target texture MSAA");
 
                     let msaa_texture =
@@ -780,18 +839,49 @@ impl Context3D for WgpuContext3D {
                                     | wgpu::TextureUsages::TEXTURE_BINDING,
                             });
 
-                    self.current_texture_resolve_view = Some(Rc::new(
-                        texture_wrapper.texture.create_view(&Default::default()),
-                    ));
-                    self.current_texture_view =
-                        Some(Rc::new(msaa_texture.create_view(&Default::default())));
+                    (
+                        Rc::new(msaa_texture.create_view(&Default::default())),
+                        Some(Rc::new(
+                            texture_wrapper.texture.create_view(&TOP_MIP_LEVEL_VIEW),
+                        )),
+                    )
                 } else {
-                    self.current_texture_resolve_view = None;
-                    self.current_texture_view = Some(Rc::new(
-                        texture_wrapper.texture.create_view(&Default::default()),
-                    ));
+                    (
+                        Rc::new(texture_wrapper.texture.create_view(&TOP_MIP_LEVEL_VIEW)),
+                        None,
+                    )
+                };
+
+                self.current_pipeline.remove_texture(&texture);
+
+                // The additional color outputs of the fragment shader share the size, depth
+                // buffer and sample count of the first one, which has to be a texture too.
+                // `Context3D.setRenderToTexture` checks this before we get here.
+                if color_output_index > 0 {
+                    if let Some(target) = self
+                        .extra_color_targets
+                        .get_mut(color_output_index as usize - 1)
+                    {
+                        *target = Some(ColorTarget {
+                            view: texture_view,
+                            resolve_view,
+                        });
+                        self.render_target_textures[color_output_index as usize] =
+                            Some(texture.clone());
+                        self.current_pipeline
+                            .update_extra_color_outputs(self.extra_color_outputs());
+                    }
+                    return;
                 }
 
+                self.current_texture_size = Some(Extent3d {
+                    width: texture_wrapper.texture.width(),
+                    height: texture_wrapper.texture.height(),
+                    depth_or_array_layers: 1,
+                });
+                self.current_texture_view = Some(texture_view);
+                self.current_texture_resolve_view = resolve_view;
+
                 if enable_depth_and_stencil {
                     self.current_depth_texture_view = Some(self.create_depth_texture(
                         texture_wrapper.texture.width(),
@@ -804,8 +894,8 @@ impl Context3D for WgpuContext3D {
 
                 self.current_pipeline
                     .update_has_depth_texture(enable_depth_and_stencil);
-                self.current_pipeline.remove_texture(&texture);
                 self.current_pipeline.update_sample_count(sample_count);
+                self.render_target_textures[0] = Some(texture);
             }
 
             Context3DCommand::SetRenderToBackBuffer => {
@@ -825,9 +915,16 @@ impl Context3D for WgpuContext3D {
                 let indices =
                     (first_index as u32)..((first_index as u32) + (num_triangles as u32 * 3));
 
-                let new_pipeline = self
+                let new_pipeline = match self
                     .current_pipeline
-                    .rebuild_pipeline(&self.descriptors, &self.vertex_attributes);
+                    .rebuild_pipeline(&self.descriptors, &self.vertex_attributes)
+                {
+                    Ok(new_pipeline) => new_pipeline,
+                    Err(e) => {
+                        tracing::error!("Couldn't compile Context3D program: {}", e);
+                        return;
+                    }
+                };
 
                 if !self.seen_clear_command {
                     tracing::warn!(
@@ -835,14 +932,7 @@ impl Context3D for WgpuContext3D {
                     );
                 }
 
-                self.buffer_staging_belt.finish();
-                let new_encoder = self.descriptors.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor {
-                        label: create_debug_label!("Buffer command encoder").as_deref(),
-                    },
-                );
-                let finished_buffer_command_encoder =
-                    std::mem::replace(&mut self.buffer_command_encoder, new_encoder);
+                let buffer_commands = self.take_buffer_commands();
 
                 // Note - we need to unconditionally re-create the render pass, since we had to submit the
                 // buffer command encoder above.
@@ -868,10 +958,9 @@ impl Context3D for WgpuContext3D {
                 // easily re-use it across multiple `DrawTriangles` calls.
                 drop(render_pass);
 
-                self.descriptors.queue.submit([
-                    finished_buffer_command_encoder.finish(),
-                    render_command_encoder.finish(),
-                ]);
+                self.descriptors
+                    .queue
+                    .submit([buffer_commands, render_command_encoder.finish()]);
                 self.buffer_staging_belt.recall();
             }
 
@@ -905,12 +994,11 @@ impl Context3D for WgpuContext3D {
                 module,
                 vertex_shader_agal,
                 fragment_shader_agal,
-            } => {
-                *module.borrow_mut() = Some(Rc::new(ShaderPairAgal::new(
-                    vertex_shader_agal,
-                    fragment_shader_agal,
-                )));
-            }
+                error,
+            } => match ShaderPairAgal::new(vertex_shader_agal, fragment_shader_agal) {
+                Ok(shaders) => *module.borrow_mut() = Some(Rc::new(shaders)),
+                Err(e) => *error = Some(e),
+            },
 
             Context3DCommand::SetShaders { module } => {
                 let shaders =
@@ -957,6 +1045,7 @@ impl Context3D for WgpuContext3D {
                 source,
                 dest,
                 layer,
+                mip_level,
             } => {
                 let dest = dest.as_any().downcast_ref::<TextureWrapper>().unwrap();
 
@@ -1013,7 +1102,7 @@ impl Context3D for WgpuContext3D {
                     },
                     wgpu::ImageCopyTexture {
                         texture: &dest.texture,
-                        mip_level: 0,
+                        mip_level,
                         origin: wgpu::Origin3d {
                             x: 0,
                             y: 0,
@@ -1038,7 +1127,7 @@ impl Context3D for WgpuContext3D {
                     let texture_wrapper =
                         texture.as_any().downcast_ref::<TextureWrapper>().unwrap();
 
-                    let mut view: wgpu::TextureViewDescriptor = Default::default();
+                    let mut view = wgpu::TextureViewDescriptor::default();
                     if cube {
                         view.dimension = Some(wgpu::TextureViewDimension::Cube);
                         view.array_layer_count = Some(6);
@@ -1056,6 +1145,24 @@ impl Context3D for WgpuContext3D {
                 self.current_pipeline
                     .update_texture_at(sampler as usize, bound_texture);
             }
+            Context3DCommand::DisposeTexture { texture } => {
+                self.current_pipeline.remove_texture(&texture);
+
+                // Ignore the vtable pointer
+                let is_render_target = self.render_target_textures.iter().flatten().any(|t| {
+                    std::ptr::eq(
+                        Rc::as_ptr(t) as *const (),
+                        Rc::as_ptr(&texture) as *const (),
+                    )
+                });
+                if is_render_target {
+                    self.set_render_to_back_buffer();
+                }
+
+                let texture_wrapper = texture.as_any().downcast_ref::<TextureWrapper>().unwrap();
+                self.submit_buffer_commands();
+                texture_wrapper.texture.destroy();
+            }
             Context3DCommand::SetColorMask {
                 red,
                 green,
@@ -1073,17 +1180,33 @@ impl Context3D for WgpuContext3D {
                 depth_mask,
                 pass_compare_mode,
             } => {
-                let function = match pass_compare_mode {
-                    Context3DCompareMode::Always => wgpu::CompareFunction::Always,
-                    Context3DCompareMode::Equal => wgpu::CompareFunction::Equal,
-                    Context3DCompareMode::Greater => wgpu::CompareFunction::Greater,
-                    Context3DCompareMode::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
-                    Context3DCompareMode::Less => wgpu::CompareFunction::Less,
-                    Context3DCompareMode::LessEqual => wgpu::CompareFunction::LessEqual,
-                    Context3DCompareMode::Never => wgpu::CompareFunction::Never,
-                    Context3DCompareMode::NotEqual => wgpu::CompareFunction::NotEqual,
+                self.current_pipeline
+                    .update_depth(depth_mask, convert_compare_mode(pass_compare_mode));
+            }
+            Context3DCommand::SetStencilActions {
+                triangle_face,
+                compare_mode,
+                action_on_both_pass,
+                action_on_depth_fail,
+                action_on_depth_pass_stencil_fail,
+            } => {
+                let stencil_face = wgpu::StencilFaceState {
+                    compare: convert_compare_mode(compare_mode),
+                    fail_op: convert_stencil_action(action_on_depth_pass_stencil_fail),
+                    depth_fail_op: convert_stencil_action(action_on_depth_fail),
+                    pass_op: convert_stencil_action(action_on_both_pass),
                 };
-                self.current_pipeline.update_depth(depth_mask, function);
+                self.current_pipeline
+                    .update_stencil_actions(triangle_face, stencil_face);
+            }
+            Context3DCommand::SetStencilReferenceValue {
+                reference_value,
+                read_mask,
+                write_mask,
+            } => {
+                self.stencil_reference = reference_value;
+                self.current_pipeline
+                    .update_stencil_masks(read_mask, write_mask);
             }
             Context3DCommand::SetBlendFactors {
                 source_factor,
@@ -1152,9 +1275,87 @@ impl Context3D for WgpuContext3D {
                 sampler,
                 wrap,
                 filter,
+                mip_filter,
             } => {
-                self.current_pipeline
-                    .update_sampler_state_at(sampler as usize, wrap, filter);
+                self.current_pipeline.update_sampler_state_at(
+                    sampler as usize,
+                    wrap,
+                    filter,
+                    mip_filter,
+                );
+            }
+            Context3DCommand::DrawToBitmapData {
+                destination,
+                source_region,
+                dest_point,
+                sync_handle,
+            } => {
+                if self.back_buffer_size.is_none() {
+                    return;
+                }
+
+                let mut encoder = self.descriptors.device.create_command_encoder(
+                    &wgpu::CommandEncoderDescriptor {
+                        label: create_debug_label!("Context3D drawToBitmapData encoder").as_deref(),
+                    },
+                );
+
+                // A `clear()` is only applied when the next render pass starts, so make
+                // sure that it shows up in the copy even if nothing was drawn after it.
+                if let Some(clear_color) = self.clear_color.take() {
+                    drop(begin_render_pass(
+                        &mut encoder,
+                        Some(clear_color),
+                        self.current_texture_view.as_ref().unwrap(),
+                        self.current_texture_resolve_view.as_deref(),
+                        &self.extra_color_targets,
+                        self.current_depth_texture_view.as_deref(),
+                    ));
+                }
+
+                // The raw texture handle is never multisampled, so it can always be copied from.
+                let back_buffer = as_texture(&self.back_buffer_raw_texture_handle);
+                let dest_texture = as_texture(&destination);
+                encoder.copy_texture_to_texture(
+                    wgpu::ImageCopyTexture {
+                        texture: &back_buffer.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d {
+                            x: source_region.x_min,
+                            y: source_region.y_min,
+                            z: 0,
+                        },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    wgpu::ImageCopyTexture {
+                        texture: &dest_texture.texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d {
+                            x: dest_point.0,
+                            y: dest_point.1,
+                            z: 0,
+                        },
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    Extent3d {
+                        width: source_region.width(),
+                        height: source_region.height(),
+                        depth_or_array_layers: 1,
+                    },
+                );
+                self.descriptors.queue.submit(Some(encoder.finish()));
+
+                *sync_handle = Some(Box::new(QueueSyncHandle::NotCopied {
+                    handle: destination,
+                    copy_area: PixelRegion::for_region(
+                        dest_point.0,
+                        dest_point.1,
+                        source_region.width(),
+                        source_region.height(),
+                    ),
+                    descriptors: self.descriptors.clone(),
+                    pool: self.offscreen_buffer_pool.clone(),
+                }));
             }
             Context3DCommand::SetScissorRectangle { rect } => {
                 self.scissor_rectangle = rect;
@@ -1171,6 +1372,103 @@ pub struct ClearColor {
     mask: u32,
 }
 
+/// A texture that one of the additional color outputs is rendered to.
+struct ColorTarget {
+    view: Rc<TextureView>,
+    resolve_view: Option<Rc<TextureView>>,
+}
+
+/// Begins a render pass on the given attachments, applying `clear_color` if there is one.
+///
+/// The color attachments have to match the targets of `CurrentPipeline::rebuild_pipeline`.
+fn begin_render_pass<'a>(
+    command_encoder: &'a mut CommandEncoder,
+    clear_color: Option<ClearColor>,
+    texture_view: &'a TextureView,
+    resolve_view: Option<&'a TextureView>,
+    extra_targets: &'a [Option<ColorTarget>],
+    depth_view: Option<&'a TextureView>,
+) -> RenderPass<'a> {
+    let color_load = match clear_color {
+        Some(clear) if clear.mask & COLOR_MASK != 0 => wgpu::LoadOp::Clear(clear.rgb),
+        _ => wgpu::LoadOp::Load,
+    };
+
+    let depth_load = match clear_color {
+        Some(clear) if clear.mask & DEPTH_MASK != 0 => wgpu::LoadOp::Clear(clear.depth),
+        _ => wgpu::LoadOp::Load,
+    };
+
+    let stencil_load = match clear_color {
+        Some(clear) if clear.mask & STENCIL_MASK != 0 => wgpu::LoadOp::Clear(clear.stencil),
+        _ => wgpu::LoadOp::Load,
+    };
+
+    let depth_stencil_attachment =
+        depth_view.map(|depth_view| wgpu::RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: depth_load,
+                store: true,
+            }),
+            stencil_ops: Some(wgpu::Operations {
+                load: stencil_load,
+                store: true,
+            }),
+        });
+
+    let color_attachment = |view, resolve_target| wgpu::RenderPassColorAttachment {
+        view,
+        resolve_target,
+        ops: wgpu::Operations {
+            load: color_load,
+            store: true,
+        },
+    };
+    let num_extra_targets = extra_targets
+        .iter()
+        .rposition(Option::is_some)
+        .map_or(0, |last| last + 1);
+    let mut color_attachments = vec![Some(color_attachment(texture_view, resolve_view))];
+    color_attachments.extend(extra_targets[..num_extra_targets].iter().map(|target| {
+        target
+            .as_ref()
+            .map(|target| color_attachment(&*target.view, target.resolve_view.as_deref()))
+    }));
+
+    command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Context3D render pass"),
+        color_attachments: &color_attachments,
+        depth_stencil_attachment,
+    })
+}
+
+fn convert_compare_mode(mode: Context3DCompareMode) -> wgpu::CompareFunction {
+    match mode {
+        Context3DCompareMode::Always => wgpu::CompareFunction::Always,
+        Context3DCompareMode::Equal => wgpu::CompareFunction::Equal,
+        Context3DCompareMode::Greater => wgpu::CompareFunction::Greater,
+        Context3DCompareMode::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+        Context3DCompareMode::Less => wgpu::CompareFunction::Less,
+        Context3DCompareMode::LessEqual => wgpu::CompareFunction::LessEqual,
+        Context3DCompareMode::Never => wgpu::CompareFunction::Never,
+        Context3DCompareMode::NotEqual => wgpu::CompareFunction::NotEqual,
+    }
+}
+
+fn convert_stencil_action(action: Context3DStencilAction) -> wgpu::StencilOperation {
+    match action {
+        Context3DStencilAction::DecrementSaturate => wgpu::StencilOperation::DecrementClamp,
+        Context3DStencilAction::DecrementWrap => wgpu::StencilOperation::DecrementWrap,
+        Context3DStencilAction::IncrementSaturate => wgpu::StencilOperation::IncrementClamp,
+        Context3DStencilAction::IncrementWrap => wgpu::StencilOperation::IncrementWrap,
+        Context3DStencilAction::Invert => wgpu::StencilOperation::Invert,
+        Context3DStencilAction::Keep => wgpu::StencilOperation::Keep,
+        Context3DStencilAction::Set => wgpu::StencilOperation::Replace,
+        Context3DStencilAction::Zero => wgpu::StencilOperation::Zero,
+    }
+}
+
 fn convert_texture_format(input: Context3DTextureFormat) -> Result<wgpu::TextureFormat, Error> {
     match input {
         // All of these formats are unsupported by wgpu to various degrees:
@@ -1203,6 +1501,12 @@ fn convert_texture_format(input: Context3DTextureFormat) -> Result<wgpu::Texture
     }
 }
 
+// Stage3D textures can have a mip level for every halving of their size, down to 1x1.
+// Any of them can be uploaded at any time, so we always allocate all of them.
+fn full_mip_level_count(width: u32, height: u32) -> u32 {
+    width.max(height).max(1).ilog2() + 1
+}
+
 // Rounds up 'len' to the nearest multiple of COPY_BUFFER_ALIGNMENT
 fn align_copy_buffer_size(len: usize) -> usize {
     let align = COPY_BUFFER_ALIGNMENT as usize;
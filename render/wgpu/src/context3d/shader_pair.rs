@@ -1,6 +1,7 @@
 use lru::LruCache;
-use naga_agal::{SamplerOverride, VertexAttributeFormat};
+use naga_agal::{SamplerConfig, VertexAttributeFormat};
 use ruffle_render::backend::ShaderModule;
+use ruffle_render::error::Error;
 use std::{
     borrow::Cow,
     cell::{RefCell, RefMut},
@@ -10,9 +11,7 @@ use wgpu::SamplerBindingType;
 
 use super::{
     current_pipeline::{
-        BoundTextureData, SAMPLER_CLAMP_LINEAR, SAMPLER_CLAMP_NEAREST,
-        SAMPLER_CLAMP_U_REPEAT_V_LINEAR, SAMPLER_CLAMP_U_REPEAT_V_NEAREST, SAMPLER_REPEAT_LINEAR,
-        SAMPLER_REPEAT_NEAREST, SAMPLER_REPEAT_U_CLAMP_V_LINEAR, SAMPLER_REPEAT_U_CLAMP_V_NEAREST,
+        BoundTextureData, EXTRA_SAMPLER_START_BIND_INDEX, SAMPLER_START_BIND_INDEX,
         TEXTURE_START_BIND_INDEX,
     },
     MAX_VERTEX_ATTRIBUTES,
@@ -34,173 +33,173 @@ pub struct CompiledShaderProgram {
     pub vertex_module: wgpu::ShaderModule,
     pub fragment_module: wgpu::ShaderModule,
     pub bind_group_layout: wgpu::BindGroupLayout,
+    pub sampler_configs: [Option<SamplerConfig>; naga_agal::MAX_TEXTURES],
+    pub extra_sampler_configs: Vec<SamplerConfig>,
+    pub color_outputs: [bool; naga_agal::MAX_COLOR_OUTPUTS],
 }
 
 impl ShaderPairAgal {
-    pub fn new(vertex_bytecode: Vec<u8>, fragment_bytecode: Vec<u8>) -> Self {
-        Self {
+    /// Checks that both programs can be compiled, so that a malformed program is reported
+    /// by `Program3D.upload` rather than when drawing.
+    pub fn new(vertex_bytecode: Vec<u8>, fragment_bytecode: Vec<u8>) -> Result<Self, Error> {
+        // The actual vertex attribute formats are only known when drawing.
+        let vertex_attributes = [Some(VertexAttributeFormat::Float4); MAX_VERTEX_ATTRIBUTES];
+        for (bytecode, stage) in [
+            (&vertex_bytecode, naga::ShaderStage::Vertex),
+            (&fragment_bytecode, naga::ShaderStage::Fragment),
+        ] {
+            let shader = naga_agal::agal_to_naga(bytecode, &vertex_attributes, &[None; 8])
+                .map_err(|e| Error::InvalidShader(e.to_string()))?;
+            if shader.module.entry_points[0].stage != stage {
+                return Err(Error::InvalidShader(format!(
+                    "Expected a {stage:?} program"
+                )));
+            }
+        }
+
+        Ok(Self {
             vertex_bytecode,
             fragment_bytecode,
             // TODO - figure out a good size for this cache.
             compiled: RefCell::new(LruCache::new(NonZeroUsize::new(2).unwrap())),
-        }
+        })
     }
 
     pub fn compile(
         &self,
         descriptors: &Descriptors,
         data: ShaderCompileData,
-    ) -> RefMut<'_, CompiledShaderProgram> {
-        let compiled = self.compiled.borrow_mut();
-        RefMut::map(compiled, |compiled| {
-            // TODO: Figure out a way to avoid the clone when we have a cache hit
-            compiled.get_or_insert_mut(data.clone(), || {
-                let vertex_naga_module = naga_agal::agal_to_naga(
-                    &self.vertex_bytecode,
-                    &data.vertex_attributes,
-                    &data.sampler_overrides,
-                )
-                .unwrap();
-                let vertex_module =
-                    descriptors
-                        .device
-                        .create_shader_module(wgpu::ShaderModuleDescriptor {
-                            label: Some("AGAL vertex shader"),
-                            source: wgpu::ShaderSource::Naga(Cow::Owned(vertex_naga_module)),
-                        });
-
-                let fragment_naga_module = naga_agal::agal_to_naga(
-                    &self.fragment_bytecode,
-                    &data.vertex_attributes,
-                    &data.sampler_overrides,
-                )
-                .unwrap();
-                let fragment_module =
-                    descriptors
-                        .device
-                        .create_shader_module(wgpu::ShaderModuleDescriptor {
-                            label: Some("AGAL fragment shader"),
-                            source: wgpu::ShaderSource::Naga(Cow::Owned(fragment_naga_module)),
-                        });
-
-                let mut layout_entries = vec![
-                    // Vertex shader program constants
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    },
-                    // Fragment shader program constants
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    },
-                    // One sampler per filter/wrapping combination - see BitmapFilters
-                    // An AGAL shader can use any of these samplers, so
-                    // we need to bind them all.
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_REPEAT_LINEAR,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_REPEAT_NEAREST,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_CLAMP_LINEAR,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_CLAMP_NEAREST,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_CLAMP_U_REPEAT_V_LINEAR,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_CLAMP_U_REPEAT_V_NEAREST,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
+    ) -> Result<RefMut<'_, CompiledShaderProgram>, naga_agal::Error> {
+        let mut compiled = self.compiled.borrow_mut();
+        if !compiled.contains(&data) {
+            let vertex_naga_module = naga_agal::agal_to_naga(
+                &self.vertex_bytecode,
+                &data.vertex_attributes,
+                &data.sampler_overrides,
+            )?
+            .module;
+            let vertex_module =
+                descriptors
+                    .device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some("AGAL vertex shader"),
+                        source: wgpu::ShaderSource::Naga(Cow::Owned(vertex_naga_module)),
+                    });
+
+            let fragment_naga_shader = naga_agal::agal_to_naga(
+                &self.fragment_bytecode,
+                &data.vertex_attributes,
+                &data.sampler_overrides,
+            )?;
+            let fragment_naga_module = fragment_naga_shader.module;
+            let fragment_module =
+                descriptors
+                    .device
+                    .create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some("AGAL fragment shader"),
+                        source: wgpu::ShaderSource::Naga(Cow::Owned(fragment_naga_module)),
+                    });
+
+            let mut layout_entries = vec![
+                // Vertex shader program constants
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
                     },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_REPEAT_U_CLAMP_V_LINEAR,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
-                        count: None,
+                    count: None,
+                },
+                // Fragment shader program constants
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
                     },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: SAMPLER_REPEAT_U_CLAMP_V_NEAREST,
+                    count: None,
+                },
+            ];
+
+            // One sampler per texture, configured as the fragment shader expects.
+            // See `CurrentPipeline::rebuild_pipeline`.
+            for i in 0..naga_agal::MAX_TEXTURES as u32 {
+                layout_entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: SAMPLER_START_BIND_INDEX + i,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                });
+            }
+
+            for (i, bound_texture) in data.bound_textures.iter().enumerate() {
+                if let Some(bound_texture) = bound_texture {
+                    let dimension = if bound_texture.cube {
+                        wgpu::TextureViewDimension::Cube
+                    } else {
+                        wgpu::TextureViewDimension::D2
+                    };
+                    layout_entries.push(wgpu::BindGroupLayoutEntry {
+                        binding: TEXTURE_START_BIND_INDEX + i as u32,
                         visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: dimension,
+                            multisampled: false,
+                        },
                         count: None,
-                    },
-                ];
-
-                for (i, bound_texture) in data.bound_textures.iter().enumerate() {
-                    if let Some(bound_texture) = bound_texture {
-                        let dimension = if bound_texture.cube {
-                            wgpu::TextureViewDimension::Cube
-                        } else {
-                            wgpu::TextureViewDimension::D2
-                        };
-                        layout_entries.push(wgpu::BindGroupLayoutEntry {
-                            binding: TEXTURE_START_BIND_INDEX + i as u32,
-                            visibility: wgpu::ShaderStages::FRAGMENT,
-                            ty: wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                                view_dimension: dimension,
-                                multisampled: false,
-                            },
-                            count: None,
-                        });
-                    }
+                    });
                 }
+            }
 
-                let globals_layout_label = create_debug_label!("Globals bind group layout");
-                let bind_group_layout =
-                    descriptors
-                        .device
-                        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                            label: globals_layout_label.as_deref(),
-                            entries: &layout_entries,
-                        });
+            // The samplers of `tex` instructions that sample a texture differently
+            // from the first one using it.
+            for i in 0..fragment_naga_shader.extra_sampler_configs.len() as u32 {
+                layout_entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: EXTRA_SAMPLER_START_BIND_INDEX + i,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                });
+            }
 
+            let globals_layout_label = create_debug_label!("Globals bind group layout");
+            let bind_group_layout =
+                descriptors
+                    .device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: globals_layout_label.as_deref(),
+                        entries: &layout_entries,
+                    });
+
+            compiled.put(
+                data.clone(),
                 CompiledShaderProgram {
                     vertex_module,
                     fragment_module,
                     bind_group_layout,
-                }
-            })
-        })
+                    sampler_configs: fragment_naga_shader.sampler_configs,
+                    extra_sampler_configs: fragment_naga_shader.extra_sampler_configs,
+                    color_outputs: fragment_naga_shader.color_outputs,
+                },
+            );
+        }
+
+        Ok(RefMut::map(compiled, |compiled| {
+            compiled
+                .get_mut(&data)
+                .expect("Compiled program should be cached")
+        }))
     }
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
 pub struct ShaderCompileData {
-    pub sampler_overrides: [Option<SamplerOverride>; 8],
+    pub sampler_overrides: [Option<SamplerConfig>; 8],
     pub vertex_attributes: [Option<VertexAttributeFormat>; MAX_VERTEX_ATTRIBUTES],
     pub bound_textures: [Option<BoundTextureData>; 8],
 }
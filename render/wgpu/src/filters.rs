@@ -1,23 +1,25 @@
+mod bevel;
 mod blur;
 mod color_matrix;
+mod convolution;
+mod displacement_map;
 mod drop_shadow;
 mod glow;
 mod shader;
 
-use std::collections::HashSet;
-use std::sync::{Mutex, OnceLock};
-
 use crate::buffer_pool::TexturePool;
 use crate::descriptors::Descriptors;
+use crate::filters::bevel::BevelFilter;
 use crate::filters::blur::BlurFilter;
 use crate::filters::color_matrix::ColorMatrixFilter;
+use crate::filters::convolution::ConvolutionFilter;
+use crate::filters::displacement_map::DisplacementMapFilter;
 use crate::filters::drop_shadow::DropShadowFilter;
 use crate::filters::glow::GlowFilter;
 use crate::filters::shader::ShaderFilter;
 use crate::surface::target::CommandTarget;
 use bytemuck::{Pod, Zeroable};
-use ruffle_render::filters::Filter;
-use swf::Rectangle;
+use ruffle_render::filters::{BevelArgs, Filter};
 use wgpu::util::DeviceExt;
 use wgpu::vertex_attr_array;
 
@@ -130,6 +132,44 @@ impl<'a> FilterSource<'a> {
             usage: wgpu::BufferUsages::VERTEX,
         })
     }
+
+    /// Like `vertices_with_blur_offset`, but samples the blurred texture twice:
+    /// once shifted towards the light for the highlight, and once away from it for the shadow.
+    pub fn vertices_with_highlight_and_shadow(
+        &self,
+        device: &wgpu::Device,
+        offset: (f32, f32),
+    ) -> wgpu::Buffer {
+        let source_width = self.texture.width() as f32;
+        let source_height = self.texture.height() as f32;
+        let left = self.point.0 as f32;
+        let top = self.point.1 as f32;
+        let right = left + self.size.0 as f32;
+        let bottom = top + self.size.1 as f32;
+        let vertices = [
+            ([0.0, 0.0], left, top),
+            ([1.0, 0.0], right, top),
+            ([1.0, 1.0], right, bottom),
+            ([0.0, 1.0], left, bottom),
+        ]
+        .map(|(position, x, y)| FilterVertexWithDoubleBlur {
+            position,
+            source_uv: [x / source_width, y / source_height],
+            highlight_uv: [
+                (x + offset.0) / source_width,
+                (y + offset.1) / source_height,
+            ],
+            shadow_uv: [
+                (x - offset.0) / source_width,
+                (y - offset.1) / source_height,
+            ],
+        });
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: create_debug_label!("Filter vertices").as_deref(),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        })
+    }
 }
 
 pub struct Filters {
@@ -137,6 +177,9 @@ pub struct Filters {
     pub color_matrix: ColorMatrixFilter,
     pub shader: ShaderFilter,
     pub glow: GlowFilter,
+    pub bevel: BevelFilter,
+    pub convolution: ConvolutionFilter,
+    pub displacement_map: DisplacementMapFilter,
 }
 
 impl Filters {
@@ -146,24 +189,9 @@ impl Filters {
             color_matrix: ColorMatrixFilter::new(device),
             shader: ShaderFilter::new(),
             glow: GlowFilter::new(device),
-        }
-    }
-
-    pub fn calculate_dest_rect(
-        &self,
-        filter: &Filter,
-        source_rect: Rectangle<i32>,
-    ) -> Rectangle<i32> {
-        match filter {
-            Filter::BlurFilter(filter) => self.blur.calculate_dest_rect(filter, source_rect),
-            Filter::GlowFilter(filter) => {
-                self.glow
-                    .calculate_dest_rect(filter, source_rect, &self.blur)
-            }
-            Filter::DropShadowFilter(filter) => {
-                DropShadowFilter::calculate_dest_rect(filter, source_rect, &self.blur, &self.glow)
-            }
-            _ => source_rect,
+            bevel: BevelFilter::new(device),
+            convolution: ConvolutionFilter::new(device),
+            displacement_map: DisplacementMapFilter::new(device),
         }
     }
 
@@ -175,72 +203,83 @@ impl Filters {
         source: FilterSource,
         filter: Filter,
     ) -> CommandTarget {
-        let target = match filter {
-            Filter::ColorMatrixFilter(filter) => Some(descriptors.filters.color_matrix.apply(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                &source,
-                &filter,
-            )),
-            Filter::BlurFilter(filter) => descriptors.filters.blur.apply(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                &source,
-                &filter,
-            ),
-            Filter::ShaderFilter(shader) => Some(descriptors.filters.shader.apply(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                &source,
-                shader,
-            )),
-            Filter::GlowFilter(filter) => Some(descriptors.filters.glow.apply(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                &source,
-                &filter,
-                &self.blur,
-                (0.0, 0.0),
-            )),
-            Filter::DropShadowFilter(filter) => Some(DropShadowFilter::apply(
-                descriptors,
-                texture_pool,
-                draw_encoder,
-                &source,
-                &filter,
-                &self.blur,
-                &self.glow,
-            )),
-            filter => {
-                static WARNED_FILTERS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
-                let name = match filter {
-                    Filter::BevelFilter(_) => "BevelFilter",
-                    Filter::GradientGlowFilter(_) => "GradientGlowFilter",
-                    Filter::GradientBevelFilter(_) => "GradientBevelFilter",
-                    Filter::ConvolutionFilter(_) => "ConvolutionFilter",
-                    Filter::DisplacementMapFilter(_) => "DisplacementMapFilter",
-                    Filter::ColorMatrixFilter(_)
-                    | Filter::BlurFilter(_)
-                    | Filter::GlowFilter(_)
-                    | Filter::DropShadowFilter(_)
-                    | Filter::ShaderFilter(_) => unreachable!(),
-                };
-                // Only warn once per filter type
-                if WARNED_FILTERS
-                    .get_or_init(Default::default)
-                    .lock()
-                    .unwrap()
-                    .insert(name)
-                {
-                    tracing::warn!("Unsupported filter {filter:?}");
-                }
-                None
-            }
-        };
+        let target =
+            match filter {
+                Filter::ColorMatrixFilter(filter) => Some(descriptors.filters.color_matrix.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &filter,
+                )),
+                Filter::BlurFilter(filter) => descriptors.filters.blur.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &filter,
+                ),
+                Filter::ShaderFilter(shader) => Some(descriptors.filters.shader.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    shader,
+                )),
+                Filter::GlowFilter(filter) => Some(descriptors.filters.glow.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &filter,
+                    &self.blur,
+                    (0.0, 0.0),
+                )),
+                Filter::DropShadowFilter(filter) => Some(DropShadowFilter::apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &filter,
+                    &self.blur,
+                    &self.glow,
+                )),
+                Filter::BevelFilter(filter) => Some(descriptors.filters.bevel.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &BevelArgs::from_bevel(&filter),
+                    &self.blur,
+                )),
+                Filter::GradientBevelFilter(filter) => Some(descriptors.filters.bevel.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &BevelArgs::from_gradient_bevel(&filter),
+                    &self.blur,
+                )),
+                Filter::GradientGlowFilter(filter) => Some(descriptors.filters.bevel.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &BevelArgs::from_gradient_glow(&filter),
+                    &self.blur,
+                )),
+                Filter::ConvolutionFilter(filter) => Some(descriptors.filters.convolution.apply(
+                    descriptors,
+                    texture_pool,
+                    draw_encoder,
+                    &source,
+                    &filter,
+                )),
+                Filter::DisplacementMapFilter(filter) => descriptors
+                    .filters
+                    .displacement_map
+                    .apply(descriptors, texture_pool, draw_encoder, &source, &filter),
+            };
 
         let target = target.unwrap_or_else(|| {
             // Apply a default color matrix - it's essentially a blit
@@ -297,3 +336,24 @@ pub const VERTEX_BUFFERS_DESCRIPTION_FILTERS_WITH_BLUR: [wgpu::VertexBufferLayou
             2 => Float32x2,
         ],
     }];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct FilterVertexWithDoubleBlur {
+    pub position: [f32; 2],
+    pub source_uv: [f32; 2],
+    pub highlight_uv: [f32; 2],
+    pub shadow_uv: [f32; 2],
+}
+
+pub const VERTEX_BUFFERS_DESCRIPTION_FILTERS_WITH_DOUBLE_BLUR: [wgpu::VertexBufferLayout; 1] =
+    [wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<FilterVertexWithDoubleBlur>() as u64,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32x2,
+            3 => Float32x2,
+        ],
+    }];
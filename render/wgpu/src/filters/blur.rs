@@ -5,18 +5,11 @@ use crate::filters::{FilterSource, VERTEX_BUFFERS_DESCRIPTION_FILTERS};
 use crate::surface::target::CommandTarget;
 use crate::utils::SampleCountMap;
 use bytemuck::{Pod, Zeroable};
+use ruffle_render::filters::BLUR_PASS_SCALES;
 use std::sync::OnceLock;
-use swf::{BlurFilter as BlurFilterArgs, Rectangle};
+use swf::BlurFilter as BlurFilterArgs;
 use wgpu::util::DeviceExt;
 
-/// How much each pass should multiply the requested blur size by - accumulative.
-/// These are very approximate to Flash, and not 100% exact.
-/// Pass 1 would be 100%, but pass 2 would be 110%.
-/// This is accumulative so you can calculate the size upfront for how many passes you'll need to perform.
-const PASS_SCALES: [f32; 15] = [
-    1.0, 2.1, 2.7, 3.1, 3.5, 3.8, 4.0, 4.2, 4.4, 4.6, 5.0, 6.0, 6.0, 7.0, 7.0,
-];
-
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable, PartialEq)]
 struct BlurUniform {
@@ -118,22 +111,6 @@ impl BlurFilter {
         })
     }
 
-    pub fn calculate_dest_rect(
-        &self,
-        filter: &BlurFilterArgs,
-        source_rect: Rectangle<i32>,
-    ) -> Rectangle<i32> {
-        let scale = PASS_SCALES[filter.num_passes().clamp(1, 15) as usize - 1];
-        let x = (scale * filter.blur_x.to_f32()).ceil().max(0.0) as i32;
-        let y = (scale * filter.blur_y.to_f32()).ceil().max(0.0) as i32;
-        Rectangle {
-            x_min: source_rect.x_min - x,
-            x_max: source_rect.x_max + x,
-            y_min: source_rect.y_min - y,
-            y_max: source_rect.y_max + y,
-        }
-    }
-
     pub fn apply(
         &self,
         descriptors: &Descriptors,
@@ -178,7 +155,10 @@ impl BlurFilter {
         let source_view = source.texture.create_view(&Default::default());
         let mut first = true;
         let mut last_scale_total = 0.0;
-        for current_scale_total in PASS_SCALES.into_iter().take(filter.num_passes() as usize) {
+        for current_scale_total in BLUR_PASS_SCALES
+            .into_iter()
+            .take(filter.num_passes() as usize)
+        {
             let pass_scale = current_scale_total - last_scale_total;
             last_scale_total = current_scale_total;
 
@@ -24,6 +24,9 @@ pub struct Shaders {
     pub color_matrix_filter: wgpu::ShaderModule,
     pub blur_filter: wgpu::ShaderModule,
     pub glow_filter: wgpu::ShaderModule,
+    pub bevel_filter: wgpu::ShaderModule,
+    pub convolution_filter: wgpu::ShaderModule,
+    pub displacement_map_filter: wgpu::ShaderModule,
 }
 
 impl Shaders {
@@ -95,6 +98,27 @@ impl Shaders {
             "filter/glow.wgsl",
             include_str!("../shaders/filter/glow.wgsl"),
         );
+        let bevel_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/bevel.wgsl",
+            include_str!("../shaders/filter/bevel.wgsl"),
+        );
+        let convolution_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/convolution.wgsl",
+            include_str!("../shaders/filter/convolution.wgsl"),
+        );
+        let displacement_map_filter = make_shader(
+            device,
+            &mut composer,
+            &shader_defs,
+            "filter/displacement_map.wgsl",
+            include_str!("../shaders/filter/displacement_map.wgsl"),
+        );
         let gradient_shader = make_shader(
             device,
             &mut composer,
@@ -126,6 +150,9 @@ impl Shaders {
             color_matrix_filter,
             blur_filter,
             glow_filter,
+            bevel_filter,
+            convolution_filter,
+            displacement_map_filter,
         }
     }
 }
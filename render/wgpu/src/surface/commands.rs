@@ -13,6 +13,7 @@ use ruffle_render::backend::ShapeHandle;
 use ruffle_render::bitmap::BitmapHandle;
 use ruffle_render::commands::Command;
 use ruffle_render::matrix::Matrix;
+use ruffle_render::matrix3d::Matrix3D;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::transform::Transform;
 use swf::{BlendMode, Color, ColorTransform, Fixed8};
@@ -174,18 +175,36 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         self.render_pass.draw_indexed(0..num_indices, 0, 0..1);
     }
 
-    pub fn apply_transform(&mut self, matrix: &Matrix, color_adjustments: &ColorTransform) {
-        let world_matrix = [
-            [matrix.a, matrix.b, 0.0, 0.0],
-            [matrix.c, matrix.d, 0.0, 0.0],
-            [0.0, 0.0, 1.0, 0.0],
+    pub fn apply_transform(
+        &mut self,
+        matrix: &Matrix,
+        matrix3d: Option<Matrix3D>,
+        color_adjustments: &ColorTransform,
+    ) {
+        let world_matrix = if let Some(matrix3d) = matrix3d {
+            // The perspective divide is done for us after the vertex shader.
+            // The resulting `z` is flattened, as we draw in display list order
+            // rather than testing depth.
+            let m = matrix3d.raw_data.map(|value| value as f32);
+            [
+                [m[0], m[1], 0.0, m[3]],
+                [m[4], m[5], 0.0, m[7]],
+                [m[8], m[9], 0.0, m[11]],
+                [m[12], m[13], 0.0, m[15]],
+            ]
+        } else {
             [
-                matrix.tx.to_pixels() as f32,
-                matrix.ty.to_pixels() as f32,
-                0.0,
-                1.0,
-            ],
-        ];
+                [matrix.a, matrix.b, 0.0, 0.0],
+                [matrix.c, matrix.d, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [
+                    matrix.tx.to_pixels() as f32,
+                    matrix.ty.to_pixels() as f32,
+                    0.0,
+                    1.0,
+                ],
+            ]
+        };
 
         if self.descriptors.limits.max_push_constant_size > 0 {
             self.render_pass.set_push_constants(
@@ -249,12 +268,15 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             &descriptors.bitmap_samplers,
         );
         self.prep_bitmap(&bind.bind_group, blend_mode, render_stage3d);
+        let scale = Matrix::scale(
+            texture.texture.width() as f32,
+            texture.texture.height() as f32,
+        );
         self.apply_transform(
-            &(transform.matrix
-                * Matrix::scale(
-                    texture.texture.width() as f32,
-                    texture.texture.height() as f32,
-                )),
+            &(transform.matrix * scale),
+            transform
+                .matrix3d
+                .map(|matrix3d| matrix3d * Matrix3D::from(scale)),
             &transform.color_transform,
         );
 
@@ -278,7 +300,11 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
             self.render_pass.push_debug_group("render_texture");
         }
         self.prep_bitmap(bind_group, blend_mode, false);
-        self.apply_transform(&transform.matrix, &transform.color_transform);
+        self.apply_transform(
+            &transform.matrix,
+            transform.matrix3d,
+            &transform.color_transform,
+        );
 
         self.draw(
             self.descriptors.quad.vertices_pos.slice(..),
@@ -320,7 +346,11 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
                     self.prep_bitmap(&binds.bind_group, TrivialBlend::Normal, false);
                 }
             }
-            self.apply_transform(&transform.matrix, &transform.color_transform);
+            self.apply_transform(
+                &transform.matrix,
+                transform.matrix3d,
+                &transform.color_transform,
+            );
 
             self.draw(
                 mesh.vertex_buffer.slice(draw.vertices.clone()),
@@ -340,10 +370,11 @@ impl<'pass, 'frame: 'pass, 'global: 'frame> CommandRenderer<'pass, 'frame, 'glob
         self.prep_color();
 
         if color == &Color::WHITE {
-            self.apply_transform(matrix, &ColorTransform::IDENTITY);
+            self.apply_transform(matrix, None, &ColorTransform::IDENTITY);
         } else {
             self.apply_transform(
                 matrix,
+                None,
                 &ColorTransform {
                     r_multiply: Fixed8::from_f32(f32::from(color.r) / 255.0),
                     g_multiply: Fixed8::from_f32(f32::from(color.g) / 255.0),
@@ -502,6 +533,7 @@ pub fn chunk_blends<'a>(
                         let transform = Transform {
                             matrix: Matrix::scale(target.width() as f32, target.height() as f32),
                             color_transform: Default::default(),
+                            matrix3d: None,
                         };
                         let texture = target.take_color_texture();
                         let bind_group =
@@ -0,0 +1,46 @@
+package {
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.geom.Rectangle;
+	import flash.utils.ByteArray;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var bmd: BitmapData = new BitmapData(2, 2, true, 0xFF112233);
+			bmd.setPixel32(1, 1, 0x00000000);
+
+			var bytes: ByteArray = new ByteArray();
+			bytes.writeByte(9);
+
+			trace("/// copyPixelsToByteArray(new Rectangle(0, 0, 2, 2), bytes)");
+			bmd.copyPixelsToByteArray(new Rectangle(0, 0, 2, 2), bytes);
+			printBytes(bytes);
+
+			trace("/// copyPixelsToByteArray(new Rectangle(1, 0, 1, 1), bytes)");
+			bmd.copyPixelsToByteArray(new Rectangle(1, 0, 1, 1), bytes);
+			printBytes(bytes);
+
+			trace("/// bytes.position = 1; copyPixelsToByteArray(new Rectangle(1, 1, 1, 1), bytes)");
+			bytes.position = 1;
+			bmd.copyPixelsToByteArray(new Rectangle(1, 1, 1, 1), bytes);
+			printBytes(bytes);
+		}
+
+		static function printBytes(bytes: ByteArray): void {
+			trace("// bytes.length");
+			trace(bytes.length);
+			trace("// bytes.position");
+			trace(bytes.position);
+			var position: uint = bytes.position;
+			var result: Array = [];
+			bytes.position = 0;
+			while (bytes.bytesAvailable > 0) {
+				result.push(bytes.readUnsignedByte());
+			}
+			bytes.position = position;
+			trace("// bytes");
+			trace(result);
+			trace("");
+		}
+	}
+}
@@ -0,0 +1,81 @@
+package {
+	import flash.display.BitmapData;
+	import flash.display.JPEGEncoderOptions;
+	import flash.display.PNGEncoderOptions;
+	import flash.display.Sprite;
+	import flash.geom.Rectangle;
+	import flash.utils.ByteArray;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var bmd: BitmapData = new BitmapData(4, 4, true, 0xFF336699);
+			bmd.setPixel32(1, 1, 0x80FF0000);
+
+			trace("/// encode(bmd.rect, new PNGEncoderOptions())");
+			var png: ByteArray = bmd.encode(bmd.rect, new PNGEncoderOptions());
+			traceBytes(png, 0, 8);
+			trace("// IHDR width and height");
+			traceSize(png);
+			trace("// position == length");
+			trace(png.position == png.length);
+
+			trace("/// encode(new Rectangle(1, 1, 2, 3), new PNGEncoderOptions(true))");
+			png = bmd.encode(new Rectangle(1, 1, 2, 3), new PNGEncoderOptions(true));
+			traceBytes(png, 0, 8);
+			trace("// IHDR width and height");
+			traceSize(png);
+
+			trace("/// encode(bmd.rect, new JPEGEncoderOptions(50), byteArray)");
+			var byteArray: ByteArray = new ByteArray();
+			byteArray.writeUTFBytes("abc");
+			var result: ByteArray = bmd.encode(bmd.rect, new JPEGEncoderOptions(50), byteArray);
+			trace("// result === byteArray");
+			trace(result === byteArray);
+			trace("// the existing bytes are kept");
+			traceBytes(byteArray, 0, 3);
+			trace("// followed by the start of image");
+			traceBytes(byteArray, 3, 3);
+			trace("// and ending with the end of image");
+			traceBytes(byteArray, byteArray.length - 2, 2);
+			trace("// position == length");
+			trace(byteArray.position == byteArray.length);
+
+			trace("/// encode(new Rectangle(0, 0, 0, 0), new PNGEncoderOptions(), byteArray)");
+			byteArray = new ByteArray();
+			byteArray.writeByte(1);
+			result = bmd.encode(new Rectangle(0, 0, 0, 0), new PNGEncoderOptions(), byteArray);
+			trace("// result === byteArray");
+			trace(result === byteArray);
+			trace("// length, position");
+			trace(byteArray.length + ", " + byteArray.position);
+
+			trace("/// encode(new Rectangle(10, 10, 2, 2), new PNGEncoderOptions())");
+			result = bmd.encode(new Rectangle(10, 10, 2, 2), new PNGEncoderOptions());
+			trace("// length");
+			trace(result.length);
+
+			trace("/// encode(bmd.rect, {})");
+			try {
+				bmd.encode(bmd.rect, {});
+			} catch (e: Error) {
+				trace(e);
+			}
+		}
+
+		static function traceBytes(bytes: ByteArray, start: uint, length: uint): void {
+			var hex: Array = [];
+			for (var i: uint = start; i < start + length; i++) {
+				var byte: String = bytes[i].toString(16);
+				hex.push(byte.length == 1 ? "0" + byte : byte);
+			}
+			trace(hex.join(" "));
+		}
+
+		static function traceSize(png: ByteArray): void {
+			var position: uint = png.position;
+			png.position = 16;
+			trace(png.readUnsignedInt() + "x" + png.readUnsignedInt());
+			png.position = position;
+		}
+	}
+}
@@ -0,0 +1,51 @@
+package {
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.filters.BevelFilter;
+	import flash.filters.BlurFilter;
+	import flash.filters.ColorMatrixFilter;
+	import flash.filters.ConvolutionFilter;
+	import flash.filters.DropShadowFilter;
+	import flash.filters.GlowFilter;
+	import flash.geom.Rectangle;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var bmd: BitmapData = new BitmapData(100, 100, true, 0);
+			var rect: Rectangle = new Rectangle(10, 10, 20, 20);
+
+			trace("/// generateFilterRect(rect, new ColorMatrixFilter())");
+			var result: Rectangle = bmd.generateFilterRect(rect, new ColorMatrixFilter());
+			trace(result);
+			trace("// result == rect");
+			trace(result == rect);
+
+			trace("/// generateFilterRect(rect, new ConvolutionFilter(3, 3, [0, 0, 0, 0, 1, 0, 0, 0, 0]))");
+			trace(bmd.generateFilterRect(rect, new ConvolutionFilter(3, 3, [0, 0, 0, 0, 1, 0, 0, 0, 0])));
+
+			trace("/// generateFilterRect(new Rectangle(-5, 90, 20, 20), new ColorMatrixFilter())");
+			trace(bmd.generateFilterRect(new Rectangle(-5, 90, 20, 20), new ColorMatrixFilter()));
+
+			trace("/// generateFilterRect(rect, new BlurFilter())");
+			trace(bmd.generateFilterRect(rect, new BlurFilter()));
+
+			trace("/// generateFilterRect(rect, new BlurFilter(10, 5, 3))");
+			trace(bmd.generateFilterRect(rect, new BlurFilter(10, 5, 3)));
+
+			trace("/// generateFilterRect(rect, new BlurFilter(0, 0))");
+			trace(bmd.generateFilterRect(rect, new BlurFilter(0, 0)));
+
+			trace("/// generateFilterRect(rect, new GlowFilter())");
+			trace(bmd.generateFilterRect(rect, new GlowFilter()));
+
+			trace("/// generateFilterRect(rect, new DropShadowFilter())");
+			trace(bmd.generateFilterRect(rect, new DropShadowFilter()));
+
+			trace("/// generateFilterRect(rect, new DropShadowFilter(4, 225))");
+			trace(bmd.generateFilterRect(rect, new DropShadowFilter(4, 225)));
+
+			trace("/// generateFilterRect(rect, new BevelFilter())");
+			trace(bmd.generateFilterRect(rect, new BevelFilter()));
+		}
+	}
+}
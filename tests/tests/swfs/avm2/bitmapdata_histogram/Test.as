@@ -0,0 +1,39 @@
+package {
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.geom.Rectangle;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var bmd: BitmapData = new BitmapData(4, 2, true, 0xFF102030);
+			bmd.setPixel32(0, 0, 0xFFFF0000);
+			bmd.setPixel32(1, 0, 0x00000000);
+
+			trace("/// histogram()");
+			printHistogram(bmd.histogram());
+
+			trace("/// histogram(new Rectangle(0, 0, 2, 1))");
+			printHistogram(bmd.histogram(new Rectangle(0, 0, 2, 1)));
+
+			trace("/// histogram(new Rectangle(2, 1, 2, 1))");
+			printHistogram(bmd.histogram(new Rectangle(2, 1, 2, 1)));
+		}
+
+		static function printHistogram(histogram: Vector.<Vector.<Number>>): void {
+			trace("// histogram.length");
+			trace(histogram.length);
+			var names: Array = ["red", "green", "blue", "alpha"];
+			for (var channel: int = 0; channel < histogram.length; channel++) {
+				var counts: Array = [];
+				for (var value: int = 0; value < histogram[channel].length; value++) {
+					if (histogram[channel][value] != 0) {
+						counts.push(value + "=" + histogram[channel][value]);
+					}
+				}
+				trace("// " + names[channel] + " (" + histogram[channel].length + " entries)");
+				trace(counts.join(" "));
+			}
+			trace("");
+		}
+	}
+}
@@ -0,0 +1,34 @@
+package {
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.geom.Point;
+	import flash.geom.Rectangle;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var dest: BitmapData = new BitmapData(2, 1, false, 0x000000);
+			var source: BitmapData = new BitmapData(2, 1, false, 0xFFFFFF);
+
+			trace("/// merge(source, new Rectangle(0, 0, 2, 1), new Point(0, 0), 128, 0, 256, 256)");
+			dest.merge(source, new Rectangle(0, 0, 2, 1), new Point(0, 0), 128, 0, 256, 256);
+			printPixels(dest);
+
+			trace("/// merge(source, new Rectangle(0, 0, 1, 1), new Point(1, 0), 64, 64, 64, 64)");
+			dest.merge(source, new Rectangle(0, 0, 1, 1), new Point(1, 0), 64, 64, 64, 64);
+			printPixels(dest);
+
+			trace("/// merge(dest, new Rectangle(1, 0, 1, 1), new Point(0, 0), 256, 256, 256, 256)");
+			dest.merge(dest, new Rectangle(1, 0, 1, 1), new Point(0, 0), 256, 256, 256, 256);
+			printPixels(dest);
+		}
+
+		static function printPixels(bmd: BitmapData): void {
+			var pixels: Array = [];
+			for (var x: int = 0; x < bmd.width; x++) {
+				pixels.push(bmd.getPixel32(x, 0).toString(16));
+			}
+			trace(pixels.join(","));
+			trace("");
+		}
+	}
+}
@@ -0,0 +1,93 @@
+package {
+	import flash.display.Sprite;
+	import flash.geom.Matrix3D;
+	import flash.geom.PerspectiveProjection;
+	import flash.geom.Point;
+	import flash.geom.Vector3D;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var s:Sprite = new Sprite();
+			trace("// 2D object");
+			trace(s.z, s.rotationX, s.rotationY, s.rotationZ, s.scaleZ);
+			trace(s.transform.matrix3D);
+			trace(s.transform.matrix);
+			trace(s.transform.perspectiveProjection);
+
+			trace("// Setting z");
+			s.x = 10;
+			s.y = 20;
+			s.z = 30;
+			trace(s.x, s.y, s.z);
+			trace(s.transform.matrix);
+			trace(s.transform.matrix3D.rawData);
+
+			trace("// Setting rotationY");
+			s.rotationY = 90;
+			trace(s.rotationY);
+			trace(round(s.transform.matrix3D.rawData));
+
+			trace("// Setting matrix3D");
+			var m:Matrix3D = new Matrix3D();
+			m.appendScale(2, 3, 4);
+			m.appendRotation(30, Vector3D.X_AXIS);
+			m.appendTranslation(5, 6, 7);
+			s.transform.matrix3D = m;
+			trace(s.x, s.y, s.z);
+			trace(s.scaleX, roundNumber(s.scaleY), roundNumber(s.scaleZ));
+			trace(roundNumber(s.rotationX), roundNumber(s.rotationY), roundNumber(s.rotationZ));
+
+			trace("// Removing matrix3D");
+			s.transform.matrix3D = null;
+			trace(s.transform.matrix3D, s.z);
+			trace(s.transform.matrix != null);
+
+			trace("// Setting rotationZ");
+			var r:Sprite = new Sprite();
+			r.rotationZ = 45;
+			trace(r.rotation, r.rotationZ, r.transform.matrix);
+
+			trace("// new PerspectiveProjection()");
+			var pp:PerspectiveProjection = new PerspectiveProjection();
+			trace(pp.fieldOfView, roundNumber(pp.focalLength), pp.projectionCenter);
+			pp.focalLength = 250;
+			trace(roundNumber(pp.fieldOfView));
+			trace(round(pp.toMatrix3D().rawData));
+
+			trace("// Root perspectiveProjection");
+			var rootProjection:PerspectiveProjection = this.transform.perspectiveProjection;
+			trace(rootProjection.fieldOfView, roundNumber(rootProjection.focalLength), rootProjection.projectionCenter);
+			rootProjection.focalLength = 550;
+			this.transform.perspectiveProjection = rootProjection;
+			trace(roundNumber(this.transform.perspectiveProjection.focalLength));
+
+			trace("// local3DToGlobal");
+			var c:Sprite = new Sprite();
+			c.x = 100;
+			c.y = 50;
+			c.z = 550;
+			addChild(c);
+			trace(c.local3DToGlobal(new Vector3D(0, 0, 0)));
+			trace(c.local3DToGlobal(new Vector3D(20, 0, 0)));
+
+			trace("// globalToLocal3D");
+			var local:Vector3D = c.globalToLocal3D(new Point(197.5, 125));
+			trace(roundNumber(local.x), roundNumber(local.y), local.z);
+
+			trace("// getRelativeMatrix3D");
+			trace(round(c.transform.getRelativeMatrix3D(this).rawData));
+		}
+
+		private static function roundNumber(n:Number):Number {
+			return Math.round(n * 1000) / 1000;
+		}
+
+		private static function round(v:Vector.<Number>):String {
+			var values:Array = [];
+			for each (var n:Number in v) {
+				values.push(roundNumber(n));
+			}
+			return values.join(",");
+		}
+	}
+}
@@ -0,0 +1,42 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.ProgressEvent;
+	import flash.net.SecureSocket;
+	import flash.utils.ByteArray;
+
+	public class Test extends Sprite {
+		public function Test() {
+			trace("isSupported", SecureSocket.isSupported);
+
+			var socket:SecureSocket = new SecureSocket();
+			trace("status before connect", socket.serverCertificateStatus);
+
+			var root:ByteArray = new ByteArray();
+			root.writeUTFBytes("root");
+			socket.addBinaryChainBuildingCertificate(root, true);
+
+			var intermediate:ByteArray = new ByteArray();
+			intermediate.writeUTFBytes("intermediate");
+			socket.addBinaryChainBuildingCertificate(intermediate, false);
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect", socket.connected, socket.serverCertificateStatus);
+				socket.writeUTFBytes("hello");
+				socket.flush();
+			});
+			socket.addEventListener(ProgressEvent.SOCKET_DATA, function(e:ProgressEvent):void {
+				trace("socketData", socket.readUTFBytes(socket.bytesAvailable));
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close", socket.connected, socket.serverCertificateStatus);
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError", e.text);
+			});
+
+			socket.connect("localhost", 8000);
+		}
+	}
+}
@@ -0,0 +1,45 @@
+package {
+	import flash.display.Sprite;
+	import flash.errors.IOError;
+	import flash.events.Event;
+	import flash.events.ProgressEvent;
+	import flash.net.Socket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect", socket.connected);
+			});
+			socket.addEventListener(ProgressEvent.SOCKET_DATA, function(e:ProgressEvent):void {
+				trace("socketData", socket.bytesAvailable);
+				trace(socket.readUTFBytes(1));
+
+				socket.close();
+				trace("after close", socket.connected, socket.bytesAvailable);
+
+				try {
+					socket.readByte();
+				} catch (err:IOError) {
+					trace("readByte", err.errorID);
+				}
+				try {
+					socket.writeByte(1);
+				} catch (err:IOError) {
+					trace("writeByte", err.errorID);
+				}
+				try {
+					socket.close();
+				} catch (err:IOError) {
+					trace("close", err.errorID);
+				}
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close event");
+			});
+
+			socket.connect("localhost", 8000);
+		}
+	}
+}
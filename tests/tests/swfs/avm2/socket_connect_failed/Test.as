@@ -0,0 +1,30 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.SecurityErrorEvent;
+	import flash.net.Socket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect");
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close");
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError", e.errorID, e.text);
+				trace("connected", socket.connected);
+			});
+			socket.addEventListener(SecurityErrorEvent.SECURITY_ERROR, function(e:SecurityErrorEvent):void {
+				trace("securityError", e.errorID);
+			});
+
+			// Nothing listens on this port, so the connection is refused.
+			socket.connect("localhost", 1);
+			trace("connecting", socket.connected);
+		}
+	}
+}
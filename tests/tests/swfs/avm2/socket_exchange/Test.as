@@ -0,0 +1,42 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.ProgressEvent;
+	import flash.events.SecurityErrorEvent;
+	import flash.net.Socket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+			trace("before connect", socket.connected, socket.bytesAvailable);
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect", socket.connected);
+				socket.writeUTFBytes("hello");
+				trace("bytesPending", socket.bytesPending);
+				socket.flush();
+				trace("bytesPending after flush", socket.bytesPending);
+			});
+			socket.addEventListener(ProgressEvent.SOCKET_DATA, function(e:ProgressEvent):void {
+				trace("socketData", e.bytesLoaded, socket.bytesAvailable);
+				if (socket.bytesAvailable == 4) {
+					trace(socket.readInt());
+				} else {
+					trace(socket.readUTFBytes(socket.bytesAvailable));
+				}
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close", socket.connected, socket.bytesAvailable);
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError", e.text);
+			});
+			socket.addEventListener(SecurityErrorEvent.SECURITY_ERROR, function(e:SecurityErrorEvent):void {
+				trace("securityError", e.text);
+			});
+
+			socket.connect("localhost", 8000);
+		}
+	}
+}
@@ -0,0 +1,35 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.SecurityErrorEvent;
+	import flash.net.Socket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+			trace("default timeout", socket.timeout);
+			socket.timeout = 100;
+			trace("timeout", socket.timeout);
+			socket.timeout = 300;
+			trace("timeout", socket.timeout);
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect");
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close");
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError", e.errorID, e.text);
+				trace("connected", socket.connected);
+			});
+			socket.addEventListener(SecurityErrorEvent.SECURITY_ERROR, function(e:SecurityErrorEvent):void {
+				trace("securityError", e.errorID);
+			});
+
+			// The server never answers, so the connection times out.
+			socket.connect("localhost", 8000);
+		}
+	}
+}
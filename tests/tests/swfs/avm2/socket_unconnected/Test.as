@@ -0,0 +1,42 @@
+package {
+	import flash.display.Sprite;
+	import flash.net.Socket;
+	import flash.utils.ByteArray;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+			trace("connected", socket.connected);
+			trace("bytesAvailable", socket.bytesAvailable);
+			trace("bytesPending", socket.bytesPending);
+			trace("endian", socket.endian);
+			trace("objectEncoding", socket.objectEncoding);
+
+			socket.endian = "littleEndian";
+			trace("endian", socket.endian);
+			socket.objectEncoding = 0;
+			trace("objectEncoding", socket.objectEncoding);
+
+			attempt("readInt", function():void { socket.readInt(); });
+			attempt("readUTF", function():void { socket.readUTF(); });
+			attempt("readObject", function():void { socket.readObject(); });
+			attempt("readBytes", function():void { socket.readBytes(new ByteArray()); });
+			attempt("writeInt", function():void { socket.writeInt(1); });
+			attempt("writeUTF", function():void { socket.writeUTF("hello"); });
+			attempt("writeObject", function():void { socket.writeObject({a: 1}); });
+			attempt("writeBytes", function():void { socket.writeBytes(new ByteArray()); });
+			attempt("flush", function():void { socket.flush(); });
+			attempt("close", function():void { socket.close(); });
+			trace("bytesPending", socket.bytesPending);
+		}
+
+		private function attempt(name:String, f:Function):void {
+			try {
+				f();
+				trace(name, "succeeded");
+			} catch (e:Error) {
+				trace(name, e);
+			}
+		}
+	}
+}
@@ -0,0 +1,100 @@
+package {
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DTextureFormat;
+	import flash.display3D.textures.Texture;
+	import flash.events.Event;
+	import flash.utils.ByteArray;
+
+	public class Test extends Sprite {
+		// 16 zero bytes (the indices of four DXT1 blocks), compressed with LZMA.
+		// The properties aren't followed by the unpacked size, as in ATF files.
+		private static const PACKED_INDICES:Array = [
+			0x5d, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x6d, 0x5f, 0x4a, 0x7b, 0xff, 0xff, 0xf0, 0x00, 0x40, 0x00
+		];
+
+		public function Test() {
+			var stage3d:Stage3D = stage.stage3Ds[0];
+			stage3d.addEventListener(Event.CONTEXT3D_CREATE, function(e:Event):void {
+				run(stage3d.context3D);
+			});
+			stage3d.requestContext3D();
+		}
+
+		private function run(context:Context3D):void {
+			var dxt1:ByteArray = bytes([0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0]);
+			var dxt5:ByteArray = bytes([0x80, 0x80, 0, 0, 0, 0, 0, 0, 0x1F, 0x00, 0x1F, 0x00, 0, 0, 0, 0]);
+
+			var texture:Texture = context.createTexture(4, 4, Context3DTextureFormat.COMPRESSED, false);
+			upload("raw compressed", texture, atf(0x3, 2, [dxt1, empty(), empty(), empty()]));
+
+			texture = context.createTexture(4, 4, Context3DTextureFormat.COMPRESSED_ALPHA, false);
+			upload("raw compressed alpha", texture, atf(0x5, 2, [dxt5, empty(), empty(), empty()]));
+
+			texture = context.createTexture(4, 4, Context3DTextureFormat.COMPRESSED, false);
+			texture.addEventListener(Event.TEXTURE_READY, function(e:Event):void {
+				trace("raw compressed async: " + e.type);
+			});
+			upload("raw compressed async", texture, atf(0x3, 2, [dxt1, empty(), empty(), empty()]), true);
+
+			// The indices decompress fine, but the endpoint images aren't valid JPEG-XR.
+			var packed:Array = [bytes(PACKED_INDICES), bytes([1, 2, 3]), bytes([4, 5, 6])];
+			for (var i:int = 0; i < 9; i++) {
+				packed.push(empty());
+			}
+			texture = context.createTexture(8, 8, Context3DTextureFormat.COMPRESSED, false);
+			upload("packed compressed with invalid endpoint images", texture, atf(0x2, 3, packed));
+
+			texture = context.createTexture(8, 8, Context3DTextureFormat.COMPRESSED_ALPHA, false);
+			upload("packed compressed into alpha texture", texture, atf(0x2, 3, packed));
+
+			texture = context.createTexture(4, 4, Context3DTextureFormat.COMPRESSED, false);
+			upload("packed compressed of the wrong size", texture, atf(0x2, 3, packed));
+
+			trace("Done");
+		}
+
+		private function upload(name:String, texture:Texture, data:ByteArray, async:Boolean = false):void {
+			try {
+				texture.uploadCompressedTextureFromByteArray(data, 0, async);
+				trace(name + ": uploaded");
+			} catch (e:Error) {
+				trace(name + ": " + e);
+			}
+		}
+
+		// A version 3 ATF file with a single mip level.
+		private function atf(format:int, log2Size:int, blocks:Array):ByteArray {
+			var data:ByteArray = new ByteArray();
+			data.writeUTFBytes("ATF");
+			data.writeByte(0);
+			data.writeByte(0);
+			data.writeByte(0);
+			data.writeByte(0xFF);
+			data.writeByte(3);
+			data.writeUnsignedInt(0);
+			data.writeByte(format);
+			data.writeByte(log2Size);
+			data.writeByte(log2Size);
+			data.writeByte(1);
+			for each (var block:ByteArray in blocks) {
+				data.writeUnsignedInt(block.length);
+				data.writeBytes(block);
+			}
+			return data;
+		}
+
+		private function bytes(values:Array):ByteArray {
+			var data:ByteArray = new ByteArray();
+			for each (var value:int in values) {
+				data.writeByte(value);
+			}
+			return data;
+		}
+
+		private function empty():ByteArray {
+			return new ByteArray();
+		}
+	}
+}
@@ -0,0 +1,65 @@
+package {
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.VertexBuffer3D;
+	import flash.events.Event;
+
+	public class Test extends Sprite {
+		private var stage3d:Stage3D;
+		private var creates:int = 0;
+		private var step:int = 0;
+		private var first:Context3D;
+		private var second:Context3D;
+
+		public function Test() {
+			stage3d = stage.stage3Ds[0];
+			stage3d.addEventListener(Event.CONTEXT3D_CREATE, function(e:Event):void {
+				creates++;
+			});
+			stage3d.requestContext3D();
+			addEventListener(Event.ENTER_FRAME, onEnterFrame);
+		}
+
+		private function onEnterFrame(e:Event):void {
+			if (creates == 0) {
+				return;
+			}
+			step++;
+			if (step == 1) {
+				trace("context3DCreate events: " + creates);
+				first = stage3d.context3D;
+				var buffer:VertexBuffer3D = first.createVertexBuffer(3, 2);
+				trace("dispose(false)");
+				first.dispose(false);
+				trace("  driverInfo: " + first.driverInfo);
+				trace("  same context: " + (stage3d.context3D == first));
+				try {
+					first.clear();
+				} catch (error:Error) {
+					trace("  clear: " + error.errorID);
+				}
+				try {
+					buffer.uploadFromVector(Vector.<Number>([0, 0, 0, 0, 0, 0]), 0, 3);
+				} catch (error:Error) {
+					trace("  uploadFromVector: " + error.errorID);
+				}
+				trace("requestContext3D");
+				stage3d.requestContext3D();
+			} else if (step == 2) {
+				second = stage3d.context3D;
+				trace("  context3DCreate events: " + creates);
+				trace("  new context: " + (second != first));
+				trace("  driverInfo is Disposed: " + (second.driverInfo == "Disposed"));
+				trace("dispose()");
+				second.dispose();
+				trace("  driverInfo: " + second.driverInfo);
+			} else if (step == 3) {
+				trace("  context3DCreate events: " + creates);
+				trace("  new context: " + (stage3d.context3D != second));
+				trace("  driverInfo is Disposed: " + (stage3d.context3D.driverInfo == "Disposed"));
+				removeEventListener(Event.ENTER_FRAME, onEnterFrame);
+			}
+		}
+	}
+}
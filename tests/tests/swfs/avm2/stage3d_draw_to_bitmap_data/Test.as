@@ -0,0 +1,107 @@
+package {
+	import com.adobe.utils.AGALMiniAssembler;
+	import flash.display.Bitmap;
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DProgramType;
+	import flash.display3D.Context3DVertexBufferFormat;
+	import flash.display3D.IndexBuffer3D;
+	import flash.display3D.Program3D;
+	import flash.display3D.VertexBuffer3D;
+	import flash.events.Event;
+	import flash.geom.Point;
+	import flash.geom.Rectangle;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var stage3d:Stage3D = stage.stage3Ds[0];
+			stage3d.addEventListener(Event.CONTEXT3D_CREATE, function(e:Event):void {
+				run(stage3d.context3D);
+			});
+			stage3d.requestContext3D();
+		}
+
+		private function run(context:Context3D):void {
+			context.configureBackBuffer(64, 32, 0, false);
+
+			var vertexAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			vertexAssembly.assemble(Context3DProgramType.VERTEX, "mov op, va0\nmov v0, va1");
+			var fragmentAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			fragmentAssembly.assemble(Context3DProgramType.FRAGMENT, "mov oc, v0");
+			var program:Program3D = context.createProgram();
+			program.upload(vertexAssembly.agalcode, fragmentAssembly.agalcode);
+			context.setProgram(program);
+
+			// A green quad over the left half of the back buffer.
+			var vertices:VertexBuffer3D = context.createVertexBuffer(4, 8);
+			vertices.uploadFromVector(Vector.<Number>([
+				-1, -1, 0, 1, 0, 1, 0, 1,
+				0, -1, 0, 1, 0, 1, 0, 1,
+				0, 1, 0, 1, 0, 1, 0, 1,
+				-1, 1, 0, 1, 0, 1, 0, 1
+			]), 0, 4);
+			context.setVertexBufferAt(0, vertices, 0, Context3DVertexBufferFormat.FLOAT_4);
+			context.setVertexBufferAt(1, vertices, 4, Context3DVertexBufferFormat.FLOAT_4);
+			var indices:IndexBuffer3D = context.createIndexBuffer(6);
+			indices.uploadFromVector(Vector.<uint>([0, 1, 2, 0, 2, 3]), 0, 6);
+
+			context.clear(1, 0, 0, 1);
+			context.drawTriangles(indices);
+
+			var whole:BitmapData = new BitmapData(64, 32, true, 0);
+			context.drawToBitmapData(whole);
+			trace("whole back buffer");
+			tracePixels(whole, [[0, 0], [16, 16], [31, 31], [32, 0], [48, 16], [63, 31]]);
+
+			var part:BitmapData = new BitmapData(16, 16, true, 0xff223344);
+			context.drawToBitmapData(part, new Rectangle(24, 8, 16, 16), new Point(4, 4));
+			trace("part of the back buffer, at (4, 4)");
+			tracePixels(part, [[0, 0], [3, 3], [4, 4], [11, 11], [12, 12], [15, 15]]);
+
+			var clipped:BitmapData = new BitmapData(16, 16, true, 0);
+			context.drawToBitmapData(clipped, null, new Point(-24, -8));
+			trace("back buffer at (-24, -8)");
+			tracePixels(clipped, [[0, 0], [15, 15]]);
+
+			var outside:BitmapData = new BitmapData(16, 16, true, 0xff223344);
+			context.drawToBitmapData(outside, new Rectangle(100, 100, 16, 16));
+			trace("outside of the back buffer");
+			tracePixels(outside, [[0, 0]]);
+
+			// Neither later draws nor presenting affect what was copied.
+			context.clear(0, 0, 1, 1);
+			context.drawTriangles(indices);
+			context.present();
+			trace("after drawing again");
+			tracePixels(whole, [[16, 16], [48, 16]]);
+
+			var again:BitmapData = new BitmapData(64, 32, true, 0);
+			context.clear(0, 0, 1, 1);
+			context.drawToBitmapData(again);
+			trace("after clearing to blue");
+			tracePixels(again, [[16, 16], [48, 16]]);
+
+			// The presented back buffer stays at the top left, with the copies next to it.
+			show(whole, 64, 0);
+			show(again, 0, 32);
+			show(part, 64, 32);
+			show(clipped, 80, 32);
+			show(outside, 96, 32);
+		}
+
+		private function show(bitmapData:BitmapData, x:Number, y:Number):void {
+			var bitmap:Bitmap = new Bitmap(bitmapData);
+			bitmap.x = x;
+			bitmap.y = y;
+			addChild(bitmap);
+		}
+
+		private function tracePixels(bitmapData:BitmapData, points:Array):void {
+			for each (var point:Array in points) {
+				trace("  (" + point[0] + ", " + point[1] + "): " + bitmapData.getPixel32(point[0], point[1]).toString(16));
+			}
+		}
+	}
+}
@@ -0,0 +1,60 @@
+package {
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DProfile;
+	import flash.display3D.Context3DTextureFormat;
+	import flash.display3D.textures.Texture;
+	import flash.events.Event;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var stage3d:Stage3D = stage.stage3Ds[0];
+			stage3d.addEventListener(Event.CONTEXT3D_CREATE, function(e:Event):void {
+				run(stage3d.context3D);
+			});
+
+			try {
+				stage3d.requestContext3D("auto", "bogus");
+				trace("requested a bogus profile");
+			} catch (e:Error) {
+				trace("bogus profile: " + e);
+			}
+
+			stage3d.requestContext3D("auto", Context3DProfile.ENHANCED);
+			trace("requested " + Context3DProfile.ENHANCED);
+		}
+
+		private function run(context:Context3D):void {
+			trace("profile: " + context.profile);
+
+			var first:Texture = context.createTexture(64, 64, Context3DTextureFormat.BGRA, true);
+			var second:Texture = context.createTexture(64, 64, Context3DTextureFormat.BGRA, true);
+			var small:Texture = context.createTexture(32, 32, Context3DTextureFormat.BGRA, true);
+
+			target("second output along with the back buffer", context, second, 0, 1);
+			target("first output", context, first, 0, 0);
+			target("second output of another size", context, small, 0, 1);
+			target("second output with anti-aliasing", context, second, 4, 1);
+			target("second output", context, second, 0, 1);
+			target("third output", context, small, 0, 2);
+			target("first output of another size", context, small, 0, 0);
+			target("fifth output", context, second, 0, 4);
+
+			context.setRenderToBackBuffer();
+			target("first output of another size, after the back buffer", context, small, 0, 0);
+			target("second output of another size, after the back buffer", context, small, 0, 1);
+
+			trace("Done");
+		}
+
+		private function target(name:String, context:Context3D, texture:Texture, antiAlias:int, colorOutputIndex:int):void {
+			try {
+				context.setRenderToTexture(texture, false, antiAlias, 0, colorOutputIndex);
+				trace(name + ": ok");
+			} catch (e:Error) {
+				trace(name + ": " + e);
+			}
+		}
+	}
+}
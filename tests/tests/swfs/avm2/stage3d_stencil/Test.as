@@ -0,0 +1,88 @@
+package {
+	import com.adobe.utils.AGALMiniAssembler;
+
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DCompareMode;
+	import flash.display3D.Context3DProgramType;
+	import flash.display3D.Context3DRenderMode;
+	import flash.display3D.Context3DStencilAction;
+	import flash.display3D.Context3DTriangleFace;
+	import flash.display3D.Context3DVertexBufferFormat;
+	import flash.display3D.IndexBuffer3D;
+	import flash.display3D.Program3D;
+	import flash.display3D.VertexBuffer3D;
+	import flash.events.Event;
+
+	// Marks the back buffer's stencil with quads that don't write any color,
+	// then fills each quarter with a color chosen by its stencil value:
+	// top left red (1), top right blue (0), bottom left green (2), bottom right red (1).
+	public class Test extends Sprite {
+		private var context:Context3D;
+		private var indices:IndexBuffer3D;
+
+		public function Test() {
+			var stage3D:Stage3D = stage.stage3Ds[0];
+			stage3D.addEventListener(Event.CONTEXT3D_CREATE, contextCreated);
+			stage3D.requestContext3D(Context3DRenderMode.AUTO);
+		}
+
+		private function contextCreated(event:Event):void {
+			context = Stage3D(event.target).context3D;
+			context.configureBackBuffer(100, 100, 0, true);
+			context.setDepthTest(false, Context3DCompareMode.ALWAYS);
+
+			var vertexAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			vertexAssembly.assemble(Context3DProgramType.VERTEX, "mov op, va0");
+			var fragmentAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			fragmentAssembly.assemble(Context3DProgramType.FRAGMENT, "mov oc, fc0");
+			var program:Program3D = context.createProgram();
+			program.upload(vertexAssembly.agalcode, fragmentAssembly.agalcode);
+			context.setProgram(program);
+
+			indices = context.createIndexBuffer(6);
+			indices.uploadFromVector(Vector.<uint>([0, 1, 2, 0, 2, 3]), 0, 6);
+
+			context.clear(0, 0, 0, 1);
+
+			// Left half: stencil set to 1.
+			context.setColorMask(false, false, false, false);
+			context.setStencilReferenceValue(1);
+			context.setStencilActions(Context3DTriangleFace.FRONT_AND_BACK, Context3DCompareMode.ALWAYS, Context3DStencilAction.SET);
+			drawQuad(-1, -1, 0, 1, [1, 1, 1, 1]);
+			trace("set left half");
+
+			// Bottom half: stencil incremented, to 2 on the left and 1 on the right.
+			context.setStencilActions(Context3DTriangleFace.FRONT_AND_BACK, Context3DCompareMode.ALWAYS, Context3DStencilAction.INCREMENT_SATURATE);
+			drawQuad(-1, -1, 1, 0, [1, 1, 1, 1]);
+			trace("incremented bottom half");
+
+			context.setColorMask(true, true, true, true);
+			context.setStencilActions(Context3DTriangleFace.FRONT_AND_BACK, Context3DCompareMode.EQUAL, Context3DStencilAction.KEEP);
+
+			context.setStencilReferenceValue(1);
+			drawQuad(-1, -1, 1, 1, [1, 0, 0, 1]);
+			context.setStencilReferenceValue(2);
+			drawQuad(-1, -1, 1, 1, [0, 1, 0, 1]);
+			context.setStencilReferenceValue(0);
+			drawQuad(-1, -1, 1, 1, [0, 0, 1, 1]);
+			trace("filled by stencil value");
+
+			context.present();
+		}
+
+		private function drawQuad(left:Number, bottom:Number, right:Number, top:Number, color:Array):void {
+			var vertices:VertexBuffer3D = context.createVertexBuffer(4, 3);
+			vertices.uploadFromVector(Vector.<Number>([
+				left, bottom, 0,
+				right, bottom, 0,
+				right, top, 0,
+				left, top, 0
+			]), 0, 4);
+			context.setVertexBufferAt(0, vertices, 0, Context3DVertexBufferFormat.FLOAT_3);
+			context.setProgramConstantsFromVector(Context3DProgramType.FRAGMENT, 0, Vector.<Number>(color));
+			context.drawTriangles(indices);
+		}
+	}
+}
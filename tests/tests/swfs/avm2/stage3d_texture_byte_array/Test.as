@@ -0,0 +1,112 @@
+package {
+	import com.adobe.utils.AGALMiniAssembler;
+
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DProgramType;
+	import flash.display3D.Context3DRenderMode;
+	import flash.display3D.Context3DTextureFormat;
+	import flash.display3D.Context3DVertexBufferFormat;
+	import flash.display3D.IndexBuffer3D;
+	import flash.display3D.Program3D;
+	import flash.display3D.VertexBuffer3D;
+	import flash.display3D.textures.Texture;
+	import flash.events.Event;
+	import flash.utils.ByteArray;
+	import flash.utils.Endian;
+
+	// Uploads a 2x2 texture, a quad and its indices from ByteArrays, and draws the
+	// texture over the whole back buffer: red and green on top, blue and white below.
+	public class Test extends Sprite {
+		public function Test() {
+			var stage3D:Stage3D = stage.stage3Ds[0];
+			stage3D.addEventListener(Event.CONTEXT3D_CREATE, contextCreated);
+			stage3D.requestContext3D(Context3DRenderMode.AUTO);
+		}
+
+		private function contextCreated(event:Event):void {
+			var context:Context3D = Stage3D(event.target).context3D;
+			context.configureBackBuffer(100, 100, 0, false);
+
+			var vertexAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			vertexAssembly.assemble(Context3DProgramType.VERTEX, "mov op, va0\nmov v0, va1");
+			var fragmentAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			fragmentAssembly.assemble(Context3DProgramType.FRAGMENT, "tex oc, v0, fs0 <2d,nearest,mipnone,clamp>");
+			var program:Program3D = context.createProgram();
+			program.upload(vertexAssembly.agalcode, fragmentAssembly.agalcode);
+			context.setProgram(program);
+
+			// Four bytes of padding before the pixels, which are stored as B, G, R, A.
+			var pixels:ByteArray = new ByteArray();
+			pixels.endian = Endian.BIG_ENDIAN;
+			pixels.writeUnsignedInt(0xDEADBEEF);
+			for each (var color:uint in [0xFFFF0000, 0xFF00FF00, 0xFF0000FF, 0xFFFFFFFF]) {
+				pixels.writeByte(color & 0xFF);
+				pixels.writeByte((color >> 8) & 0xFF);
+				pixels.writeByte((color >> 16) & 0xFF);
+				pixels.writeByte((color >> 24) & 0xFF);
+			}
+
+			var texture:Texture = context.createTexture(2, 2, Context3DTextureFormat.BGRA, false);
+			texture.uploadFromByteArray(pixels, 4);
+			trace("uploaded texture");
+			texture.uploadFromByteArray(pixels, 16, 1);
+			trace("uploaded mip level 1");
+			attempt("texture data too short", function():void {
+				texture.uploadFromByteArray(pixels, 8);
+			});
+			attempt("texture mip level out of range", function():void {
+				texture.uploadFromByteArray(pixels, 4, 2);
+			});
+
+			var vertexData:ByteArray = new ByteArray();
+			vertexData.endian = Endian.LITTLE_ENDIAN;
+			for each (var value:Number in [
+				-1, 1, 0, 0,
+				1, 1, 1, 0,
+				1, -1, 1, 1,
+				-1, -1, 0, 1
+			]) {
+				vertexData.writeFloat(value);
+			}
+			var vertices:VertexBuffer3D = context.createVertexBuffer(4, 4);
+			vertices.uploadFromByteArray(vertexData, 0, 0, 4);
+			trace("uploaded vertices");
+			attempt("vertices past the end of the buffer", function():void {
+				vertices.uploadFromByteArray(vertexData, 0, 2, 3);
+			});
+			attempt("vertex data too short", function():void {
+				vertices.uploadFromByteArray(vertexData, 16, 0, 4);
+			});
+
+			var indexData:ByteArray = new ByteArray();
+			indexData.endian = Endian.LITTLE_ENDIAN;
+			for each (var index:uint in [0, 1, 2, 0, 2, 3]) {
+				indexData.writeShort(index);
+			}
+			var indices:IndexBuffer3D = context.createIndexBuffer(6);
+			indices.uploadFromByteArray(indexData, 0, 0, 6);
+			trace("uploaded indices");
+			attempt("indices past the end of the buffer", function():void {
+				indices.uploadFromByteArray(indexData, 0, 4, 3);
+			});
+
+			context.setVertexBufferAt(0, vertices, 0, Context3DVertexBufferFormat.FLOAT_2);
+			context.setVertexBufferAt(1, vertices, 2, Context3DVertexBufferFormat.FLOAT_2);
+			context.setTextureAt(0, texture);
+			context.clear(0, 0, 0, 1);
+			context.drawTriangles(indices);
+			context.present();
+		}
+
+		private function attempt(name:String, f:Function):void {
+			try {
+				f();
+				trace(name + ": no error");
+			} catch (e:Error) {
+				trace(name + ": " + e);
+			}
+		}
+	}
+}
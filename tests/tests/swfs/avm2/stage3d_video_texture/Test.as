@@ -0,0 +1,78 @@
+package {
+	import com.adobe.utils.AGALMiniAssembler;
+	import flash.display.BitmapData;
+	import flash.display.Sprite;
+	import flash.display.Stage3D;
+	import flash.display3D.Context3D;
+	import flash.display3D.Context3DProgramType;
+	import flash.display3D.Context3DVertexBufferFormat;
+	import flash.display3D.IndexBuffer3D;
+	import flash.display3D.Program3D;
+	import flash.display3D.VertexBuffer3D;
+	import flash.display3D.textures.VideoTexture;
+	import flash.events.Event;
+	import flash.net.NetConnection;
+	import flash.net.NetStream;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var stage3d:Stage3D = stage.stage3Ds[0];
+			stage3d.addEventListener(Event.CONTEXT3D_CREATE, function(e:Event):void {
+				run(stage3d.context3D);
+			});
+			stage3d.requestContext3D();
+		}
+
+		private function run(context:Context3D):void {
+			trace("supportsVideoTexture: " + Context3D.supportsVideoTexture);
+			context.configureBackBuffer(32, 32, 0, false);
+
+			var vertexAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			vertexAssembly.assemble(Context3DProgramType.VERTEX, "mov op, va0\nmov v0, va1");
+			var fragmentAssembly:AGALMiniAssembler = new AGALMiniAssembler();
+			fragmentAssembly.assemble(Context3DProgramType.FRAGMENT, "tex oc, v0, fs0 <2d, clamp, linear>");
+			var program:Program3D = context.createProgram();
+			program.upload(vertexAssembly.agalcode, fragmentAssembly.agalcode);
+			context.setProgram(program);
+
+			// A quad over the whole back buffer, showing the whole texture.
+			var vertices:VertexBuffer3D = context.createVertexBuffer(4, 4);
+			vertices.uploadFromVector(Vector.<Number>([
+				-1, -1, 0, 1,
+				1, -1, 1, 1,
+				1, 1, 1, 0,
+				-1, 1, 0, 0
+			]), 0, 4);
+			context.setVertexBufferAt(0, vertices, 0, Context3DVertexBufferFormat.FLOAT_2);
+			context.setVertexBufferAt(1, vertices, 2, Context3DVertexBufferFormat.FLOAT_2);
+			var indices:IndexBuffer3D = context.createIndexBuffer(6);
+			indices.uploadFromVector(Vector.<uint>([0, 1, 2, 0, 2, 3]), 0, 6);
+
+			var texture:VideoTexture = context.createVideoTexture();
+			trace("before playing: " + texture.videoWidth + "x" + texture.videoHeight);
+			// The texture is bound before the size of the video is known.
+			context.setTextureAt(0, texture);
+
+			var connection:NetConnection = new NetConnection();
+			connection.connect(null);
+			var stream:NetStream = new NetStream(connection);
+			stream.client = {onMetaData: function(data:Object):void {}};
+			texture.attachNetStream(stream);
+			texture.addEventListener("textureReady", function(e:Event):void {
+				texture.removeEventListener("textureReady", arguments.callee);
+				trace("textureReady, target is the texture: " + (e.target === texture));
+				trace("video size: " + texture.videoWidth + "x" + texture.videoHeight);
+
+				context.clear(0, 0, 0, 0);
+				context.drawTriangles(indices);
+				var pixels:BitmapData = new BitmapData(32, 32, true, 0);
+				context.drawToBitmapData(pixels);
+				// Video frames are opaque, while nothing was ever uploaded to the
+				// texture as it was first bound.
+				trace("alpha of the drawn frame: " + (pixels.getPixel32(16, 16) >>> 24).toString(16));
+				context.present();
+			});
+			stream.play("test_video.flv");
+		}
+	}
+}
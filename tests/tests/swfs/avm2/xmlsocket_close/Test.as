@@ -0,0 +1,35 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.DataEvent;
+	import flash.events.Event;
+	import flash.net.XMLSocket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:XMLSocket = new XMLSocket();
+			trace("before connect", socket.connected);
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect", socket.connected);
+				socket.send(new XML("<ping/>"));
+			});
+			socket.addEventListener(DataEvent.DATA, function(e:DataEvent):void {
+				trace("data", e.data);
+				if (e.data == "<pong/>") {
+					socket.close();
+					trace("after close", socket.connected);
+					try {
+						socket.send("<late/>");
+					} catch (err:Error) {
+						trace(err.errorID);
+					}
+				}
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close event");
+			});
+
+			socket.connect("localhost", 8000);
+		}
+	}
+}
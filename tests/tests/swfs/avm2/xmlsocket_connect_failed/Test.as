@@ -0,0 +1,42 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.DataEvent;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.SecurityErrorEvent;
+	import flash.net.XMLSocket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:XMLSocket = new XMLSocket();
+			trace("before connect", socket.connected);
+
+			try {
+				socket.send("<early/>");
+			} catch (err:Error) {
+				trace("send before connect", err.errorID);
+			}
+
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect");
+			});
+			socket.addEventListener(DataEvent.DATA, function(e:DataEvent):void {
+				trace("data", e.data);
+			});
+			socket.addEventListener(Event.CLOSE, function(e:Event):void {
+				trace("close");
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError", e.errorID, e.text);
+				trace("connected", socket.connected);
+			});
+			socket.addEventListener(SecurityErrorEvent.SECURITY_ERROR, function(e:SecurityErrorEvent):void {
+				trace("securityError", e.errorID);
+			});
+
+			// The test runner doesn't let this connection through.
+			socket.connect("localhost", 8000);
+			trace("connecting", socket.connected);
+		}
+	}
+}
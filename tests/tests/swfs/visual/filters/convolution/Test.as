@@ -0,0 +1,24 @@
+package {
+	import flash.display.Shape;
+	import flash.display.Sprite;
+	import flash.filters.ConvolutionFilter;
+
+	public class Test extends Sprite {
+		public function Test() {
+			graphics.beginFill(0xFFFFFF);
+			graphics.drawRect(0, 0, 100, 100);
+			graphics.endFill();
+
+			var shape:Shape = new Shape();
+			shape.graphics.beginFill(0x0000FF);
+			shape.graphics.drawRect(30, 30, 20, 40);
+			shape.graphics.endFill();
+			shape.graphics.beginFill(0xFFFF00);
+			shape.graphics.drawRect(50, 30, 20, 40);
+			shape.graphics.endFill();
+			// Sharpens the edge between the two halves, clamping at the edges of the shape.
+			shape.filters = [new ConvolutionFilter(3, 3, [0, -1, 0, -1, 5, -1, 0, -1, 0], 1, 0, true, true)];
+			addChild(shape);
+		}
+	}
+}
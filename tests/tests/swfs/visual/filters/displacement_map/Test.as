@@ -0,0 +1,35 @@
+package {
+	import flash.display.Shape;
+	import flash.display.Sprite;
+	import flash.display.BitmapData;
+	import flash.display.BitmapDataChannel;
+	import flash.filters.DisplacementMapFilter;
+	import flash.geom.Point;
+
+	public class Test extends Sprite {
+		public function Test() {
+			graphics.beginFill(0xFFFFFF);
+			graphics.drawRect(0, 0, 100, 100);
+			graphics.endFill();
+
+			var shape:Shape = new Shape();
+			var colors:Array = [0xFF0000, 0x00FF00, 0x0000FF, 0x000000];
+			for (var i:int = 0; i < colors.length; i++) {
+				shape.graphics.beginFill(colors[i]);
+				shape.graphics.drawRect(30 + i * 10, 30, 10, 40);
+				shape.graphics.endFill();
+			}
+
+			// Red goes up from the left, displacing less towards the right. Green is
+			// right in the middle, so nothing moves vertically.
+			var map:BitmapData = new BitmapData(40, 40, false);
+			for (var x:int = 0; x < 40; x++) {
+				for (var y:int = 0; y < 40; y++) {
+					map.setPixel(x, y, (x * 6) << 16 | 0x80 << 8);
+				}
+			}
+			shape.filters = [new DisplacementMapFilter(map, new Point(0, 0), BitmapDataChannel.RED, BitmapDataChannel.GREEN, 10, 10, "clamp")];
+			addChild(shape);
+		}
+	}
+}
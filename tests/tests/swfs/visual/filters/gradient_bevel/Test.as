@@ -0,0 +1,20 @@
+package {
+	import flash.display.Shape;
+	import flash.display.Sprite;
+	import flash.filters.GradientBevelFilter;
+
+	public class Test extends Sprite {
+		public function Test() {
+			graphics.beginFill(0xFFFFFF);
+			graphics.drawRect(0, 0, 100, 100);
+			graphics.endFill();
+
+			var shape:Shape = new Shape();
+			shape.graphics.beginFill(0x0000FF);
+			shape.graphics.drawRect(30, 30, 40, 40);
+			shape.graphics.endFill();
+			shape.filters = [new GradientBevelFilter(4, 45, [0xFFFFFF, 0xFF0000, 0x000000], [1, 0, 1], [0, 128, 255], 4, 4, 1, 1, "inner")];
+			addChild(shape);
+		}
+	}
+}
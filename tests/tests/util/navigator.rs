@@ -1,14 +1,17 @@
+use crate::util::options::SocketScript;
 use crate::util::runner::TestLogBackend;
 use ruffle_core::backend::log::LogBackend;
 use ruffle_core::backend::navigator::{
-    fetch_path, resolve_url_with_relative_base_path, ErrorResponse, NavigationMethod,
-    NavigatorBackend, NullExecutor, NullSpawner, OwnedFuture, Request, SuccessResponse,
+    fetch_path, resolve_url_with_relative_base_path, ChainBuildingCertificate, ErrorResponse,
+    NavigationMethod, NavigatorBackend, NullExecutor, NullSpawner, OutgoingSocketAction,
+    OwnedFuture, Request, SocketEvent, SuccessResponse,
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
-use std::future::Future;
+use std::cell::{Cell, RefCell};
 use std::path::{Path, PathBuf};
-use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Duration;
 use url::{ParseError, Url};
 
 /// A `NavigatorBackend` used by tests that supports logging fetch requests.
@@ -18,6 +21,7 @@ pub struct TestNavigatorBackend {
     spawner: NullSpawner,
     relative_base_path: PathBuf,
     log: Option<TestLogBackend>,
+    socket_endpoint: Option<TestSocketEndpoint>,
 }
 
 impl TestNavigatorBackend {
@@ -25,11 +29,136 @@ impl TestNavigatorBackend {
         path: &Path,
         executor: &NullExecutor,
         log: Option<TestLogBackend>,
+        socket_endpoint: Option<TestSocketEndpoint>,
     ) -> Result<Self, std::io::Error> {
         Ok(Self {
             spawner: executor.spawner(),
             relative_base_path: path.canonicalize()?,
             log,
+            socket_endpoint,
+        })
+    }
+}
+
+/// A fake socket server, which follows a script instead of touching the network.
+///
+/// Everything exchanged with it is recorded, so that tests can compare it
+/// against the expected traffic.
+pub struct TestSocketEndpoint {
+    host: String,
+    port: u16,
+    script: SocketScript,
+    traffic: Rc<RefCell<String>>,
+    connections: Cell<u32>,
+}
+
+impl TestSocketEndpoint {
+    pub fn new(host: String, port: u16, script: SocketScript) -> Self {
+        Self {
+            host,
+            port,
+            script,
+            traffic: Default::default(),
+            connections: Cell::new(0),
+        }
+    }
+
+    /// The recorded traffic, shared with every connection to this endpoint.
+    pub fn traffic(&self) -> Rc<RefCell<String>> {
+        self.traffic.clone()
+    }
+
+    /// Connect to this endpoint, over TLS if `certificates` is given.
+    fn connect(
+        &self,
+        timeout: Duration,
+        certificates: Option<Vec<ChainBuildingCertificate>>,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) -> OwnedFuture<(), Error> {
+        let id = self.connections.get() + 1;
+        self.connections.set(id);
+
+        let script = self.script.clone();
+        let traffic = self.traffic.clone();
+        let record = move |line: &str| {
+            let mut traffic = traffic.borrow_mut();
+            traffic.push_str(&format!("[{id}] {line}\n"));
+        };
+        if let Some(certificates) = certificates {
+            // The handshake always succeeds, as if the certificate was trusted.
+            record(&format!("connect {}:{} (secure)", self.host, self.port));
+            for certificate in certificates {
+                let kind = if certificate.trusted {
+                    "trusted"
+                } else {
+                    "intermediate"
+                };
+                record(&format!(
+                    "{kind} certificate: {}",
+                    certificate.der.escape_ascii()
+                ));
+            }
+        } else {
+            record(&format!("connect {}:{}", self.host, self.port));
+        }
+
+        if script.silent {
+            record(&format!("timed out after {}ms", timeout.as_millis()));
+            let _ = events.send(SocketEvent::ConnectTimedOut);
+            return Box::pin(async { Ok(()) });
+        }
+
+        Box::pin(async move {
+            let send = |data: Vec<u8>| {
+                if !data.is_empty() {
+                    record(&format!("server: {}", data.escape_ascii()));
+                    let _ = events.send(SocketEvent::Data(data));
+                }
+            };
+
+            let _ = events.send(SocketEvent::Connected);
+            send(script.on_connect.to_vec());
+
+            let mut exchanges = script.exchange.iter();
+            let mut exchange = exchanges.next();
+            let mut written = Vec::new();
+            let mut received = Vec::new();
+            loop {
+                if exchange.is_none() && script.close {
+                    record("server closed");
+                    let _ = events.send(SocketEvent::Closed);
+                    return Ok(());
+                }
+
+                match actions.recv_async().await {
+                    Ok(OutgoingSocketAction::Write(data)) => written.extend(data),
+                    Ok(OutgoingSocketAction::Flush) => {
+                        if written.is_empty() {
+                            continue;
+                        }
+                        record(&format!("client: {}", written.escape_ascii()));
+                        received.append(&mut written);
+
+                        while let Some(current) = exchange {
+                            let expected = current.expect.to_vec();
+                            if received.len() < expected.len() {
+                                break;
+                            }
+                            if !received.starts_with(&expected) {
+                                record(&format!("expected: {}", expected.escape_ascii()));
+                            }
+                            received.drain(..expected.len());
+                            send(current.respond.to_vec());
+                            exchange = exchanges.next();
+                        }
+                    }
+                    Ok(OutgoingSocketAction::Close) | Err(_) => {
+                        record("client closed");
+                        return Ok(());
+                    }
+                }
+            }
         })
     }
 }
@@ -93,11 +222,65 @@ impl NavigatorBackend for TestNavigatorBackend {
         self.spawner.spawn_local(future);
     }
 
-    fn spawn_io_future(&mut self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
-        self.spawner.spawn_local(Box::pin(async move {
-            future.await;
-            Ok(())
-        }));
+    fn sleep(&self, _duration: Duration) -> OwnedFuture<(), Error> {
+        // Tests have no wall clock, so nothing ever times out.
+        Box::pin(std::future::pending())
+    }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        // Log request.
+        if let Some(log) = &self.log {
+            log.avm_trace("Navigator::connect_socket:");
+            log.avm_trace(&format!("  Host: {}:{}", host, port));
+        }
+
+        // Tests never touch the network, only the fake endpoint.
+        match &self.socket_endpoint {
+            Some(endpoint) if endpoint.host == host && endpoint.port == port => {
+                let future = endpoint.connect(timeout, None, actions, events);
+                self.spawner.spawn_local(future);
+            }
+            _ => {
+                let _ = events.send(SocketEvent::ConnectFailed);
+            }
+        }
+    }
+
+    fn supports_secure_sockets(&self) -> bool {
+        true
+    }
+
+    fn connect_secure_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        certificates: Vec<ChainBuildingCertificate>,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        // Log request.
+        if let Some(log) = &self.log {
+            log.avm_trace("Navigator::connect_secure_socket:");
+            log.avm_trace(&format!("  Host: {}:{}", host, port));
+        }
+
+        match &self.socket_endpoint {
+            Some(endpoint) if endpoint.host == host && endpoint.port == port => {
+                let future = endpoint.connect(timeout, Some(certificates), actions, events);
+                self.spawner.spawn_local(future);
+            }
+            _ => {
+                let _ = events.send(SocketEvent::ConnectFailed);
+            }
+        }
     }
 
     fn pre_process_url(&self, url: Url) -> Url {
@@ -25,6 +25,7 @@ pub struct TestOptions {
     pub approximations: Option<Approximations>,
     pub player_options: PlayerOptions,
     pub log_fetch: bool,
+    pub socket: Option<SocketOptions>,
 }
 
 impl Default for TestOptions {
@@ -40,6 +41,7 @@ impl Default for TestOptions {
             approximations: None,
             player_options: PlayerOptions::default(),
             log_fetch: false,
+            socket: None,
         }
     }
 }
@@ -54,6 +56,80 @@ impl TestOptions {
     }
 }
 
+/// A fake socket endpoint, which answers connections according to a script
+/// instead of touching the network.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SocketOptions {
+    pub host: String,
+    pub port: u16,
+    #[serde(default = "SocketOptions::default_script_path")]
+    pub script_path: PathBuf,
+    #[serde(default = "SocketOptions::default_output_path")]
+    pub output_path: PathBuf,
+}
+
+impl SocketOptions {
+    fn default_script_path() -> PathBuf {
+        PathBuf::from("socket.toml")
+    }
+
+    fn default_output_path() -> PathBuf {
+        PathBuf::from("socket_output.txt")
+    }
+}
+
+/// What a fake socket endpoint sends, and what it expects the movie to send.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SocketScript {
+    /// Sent as soon as a connection is established.
+    pub on_connect: SocketBytes,
+    /// Each exchange waits for the movie to send `expect`, then sends `respond`.
+    pub exchange: Vec<SocketExchange>,
+    /// Whether to close the connection once every exchange is done.
+    pub close: bool,
+    /// Whether to never answer connections, so that they time out.
+    pub silent: bool,
+}
+
+impl SocketScript {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SocketExchange {
+    pub expect: SocketBytes,
+    #[serde(default)]
+    pub respond: SocketBytes,
+}
+
+/// Bytes in a socket script, given either as a string or as a list of bytes.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SocketBytes {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Default for SocketBytes {
+    fn default() -> Self {
+        Self::Bytes(vec![])
+    }
+}
+
+impl SocketBytes {
+    pub fn to_vec(&self) -> Vec<u8> {
+        match self {
+            Self::Text(text) => text.as_bytes().to_vec(),
+            Self::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
 #[derive(Deserialize, Default)]
 #[serde(default, deny_unknown_fields)]
 pub struct Approximations {
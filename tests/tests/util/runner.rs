@@ -1,4 +1,5 @@
-use crate::util::navigator::TestNavigatorBackend;
+use crate::util::navigator::{TestNavigatorBackend, TestSocketEndpoint};
+use crate::util::options::SocketScript;
 use crate::util::test::Test;
 use anyhow::{anyhow, Result};
 use ruffle_core::backend::audio::{
@@ -81,13 +82,13 @@ impl LogBackend for TestLogBackend {
 }
 
 /// Loads an SWF and runs it through the Ruffle core for a number of frames.
-/// Tests that the trace output matches the given expected output.
+/// Returns the trace output, and the traffic of the fake socket endpoint if the test declares one.
 pub fn run_swf(
     test: &Test,
     mut injector: InputInjector,
     before_start: impl FnOnce(Arc<Mutex<Player>>) -> Result<()>,
     before_end: impl FnOnce(Arc<Mutex<Player>>) -> Result<()>,
-) -> Result<String> {
+) -> Result<(String, Option<String>)> {
     let base_path = Path::new(&test.output_path).parent().unwrap();
     let mut executor = NullExecutor::new();
     let movie = SwfMovie::from_path(&test.swf_path, None).map_err(|e| anyhow!(e.to_string()))?;
@@ -98,11 +99,34 @@ pub fn run_swf(
 
     let frame_time_duration = Duration::from_millis(frame_time as u64);
 
+    let socket_endpoint = match &test.options.socket {
+        Some(socket) => Some(TestSocketEndpoint::new(
+            socket.host.clone(),
+            socket.port,
+            SocketScript::read(base_path.join(&socket.script_path))?,
+        )),
+        None => None,
+    };
+    let socket_traffic = socket_endpoint.as_ref().map(TestSocketEndpoint::traffic);
+
+    // A fake socket connection waits for the movie until it's closed, so running every
+    // task to completion would never return. Tests without one still run everything,
+    // so that they can't accidentally leave a task unfinished.
+    let has_socket = socket_endpoint.is_some();
+    let run_executor = |executor: &mut NullExecutor| {
+        if has_socket {
+            executor.run_until_stalled();
+        } else {
+            executor.run();
+        }
+    };
+
     let log = TestLogBackend::new();
     let navigator = TestNavigatorBackend::new(
         base_path,
         &executor,
         test.options.log_fetch.then(|| log.clone()),
+        socket_endpoint,
     )?;
 
     let builder = PlayerBuilder::new()
@@ -170,7 +194,7 @@ pub fn run_swf(
             player.lock().unwrap().update_timers(frame_time);
             player.lock().unwrap().audio_mut().tick();
         }
-        executor.run();
+        run_executor(&mut executor);
 
         injector.next(|evt, _btns_down| {
             player.lock().unwrap().handle_event(match evt {
@@ -260,12 +284,13 @@ pub fn run_swf(
 
     before_end(player)?;
 
-    executor.run();
+    run_executor(&mut executor);
 
     let trace = log.trace_output();
     // Null bytes are invisible, and interfere with constructing
     // the expected output.txt file. Any tests dealing with null
     // bytes should explicitly test for them in ActionScript.
     let normalized_trace = trace.replace('\0', "");
-    Ok(normalized_trace)
+    let socket_traffic = socket_traffic.map(|traffic| traffic.take());
+    Ok((normalized_trace, socket_traffic))
 }
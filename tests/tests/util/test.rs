@@ -13,6 +13,7 @@ pub struct Test {
     pub swf_path: PathBuf,
     pub input_path: PathBuf,
     pub output_path: PathBuf,
+    pub socket_output_path: Option<PathBuf>,
     pub name: String,
 }
 
@@ -21,11 +22,16 @@ impl Test {
         let swf_path = test_dir.join("test.swf");
         let input_path = test_dir.join("input.json");
         let output_path = options.output_path(test_dir);
+        let socket_output_path = options
+            .socket
+            .as_ref()
+            .map(|socket| test_dir.join(&socket.output_path));
         Ok(Self {
             options,
             swf_path,
             input_path,
             output_path,
+            socket_output_path,
             name,
         })
     }
@@ -51,8 +57,11 @@ impl Test {
         } else {
             InputInjector::empty()
         };
-        let output = run_swf(&self, injector, before_start, before_end)?;
+        let (output, socket_traffic) = run_swf(&self, injector, before_start, before_end)?;
         self.compare_output(&output)?;
+        if let Some(socket_traffic) = socket_traffic {
+            self.compare_socket_traffic(&socket_traffic)?;
+        }
         Ok(())
     }
 
@@ -63,6 +72,20 @@ impl Test {
         self.options.player_options.can_run(check_renderer)
     }
 
+    pub fn compare_socket_traffic(&self, actual_traffic: &str) -> Result<()> {
+        let Some(socket_output_path) = &self.socket_output_path else {
+            return Ok(());
+        };
+        let expected_traffic = std::fs::read_to_string(socket_output_path)?.replace("\r\n", "\n");
+
+        assert_eq!(
+            actual_traffic, expected_traffic,
+            "ruffle socket traffic != expected socket traffic"
+        );
+
+        Ok(())
+    }
+
     pub fn compare_output(&self, actual_output: &str) -> Result<()> {
         let expected_output = std::fs::read_to_string(&self.output_path)?.replace("\r\n", "\n");
 
@@ -1,7 +1,7 @@
 use crate::decoder::VideoDecoder;
 use generational_arena::Arena;
 use ruffle_render::backend::RenderBackend;
-use ruffle_render::bitmap::{BitmapHandle, BitmapInfo, PixelRegion};
+use ruffle_render::bitmap::{Bitmap, BitmapHandle, BitmapInfo, PixelRegion};
 use ruffle_video::backend::VideoBackend;
 use ruffle_video::error::Error;
 use ruffle_video::frame::{EncodedFrame, FrameDependency};
@@ -81,6 +81,7 @@ impl VideoBackend for SoftwareVideoBackend {
 
         let w = frame.width();
         let h = frame.height();
+        let last_frame = stream.keep_last_frame.then(|| frame.clone());
 
         let handle = if let Some(bitmap) = stream.bitmap.clone() {
             renderer.update_texture(&bitmap, frame, PixelRegion::for_whole_size(w, h))?;
@@ -89,6 +90,7 @@ impl VideoBackend for SoftwareVideoBackend {
             renderer.register_bitmap(frame)?
         };
         stream.bitmap = Some(handle.clone());
+        stream.last_frame = last_frame;
 
         Ok(BitmapInfo {
             handle,
@@ -96,12 +98,31 @@ impl VideoBackend for SoftwareVideoBackend {
             height: h as u16,
         })
     }
+
+    fn keep_decoded_frames(&mut self, stream: VideoStreamHandle, keep: bool) {
+        if let Some(stream) = self.streams.get_mut(stream) {
+            stream.keep_last_frame = keep;
+            if !keep {
+                stream.last_frame = None;
+            }
+        }
+    }
+
+    fn last_decoded_frame(&self, stream: VideoStreamHandle) -> Option<&Bitmap> {
+        self.streams.get(stream)?.last_frame.as_ref()
+    }
 }
 
 /// A single preloaded video stream.
 pub struct VideoStream {
     bitmap: Option<BitmapHandle>,
     decoder: Box<dyn VideoDecoder>,
+
+    /// Whether the pixels of decoded frames are kept in `last_frame`.
+    keep_last_frame: bool,
+
+    /// The pixels of the last decoded frame, which are also in `bitmap`.
+    last_frame: Option<Bitmap>,
 }
 
 impl VideoStream {
@@ -109,6 +130,8 @@ impl VideoStream {
         Self {
             decoder,
             bitmap: None,
+            keep_last_frame: false,
+            last_frame: None,
         }
     }
 }
@@ -2,7 +2,7 @@ use crate::error::Error;
 use crate::frame::{EncodedFrame, FrameDependency};
 use crate::VideoStreamHandle;
 use ruffle_render::backend::RenderBackend;
-use ruffle_render::bitmap::BitmapInfo;
+use ruffle_render::bitmap::{Bitmap, BitmapInfo};
 use swf::{VideoCodec, VideoDeblocking};
 
 /// A backend that provides access to some number of video decoders.
@@ -65,4 +65,18 @@ pub trait VideoBackend {
         encoded_frame: EncodedFrame<'_>,
         renderer: &mut dyn RenderBackend,
     ) -> Result<BitmapInfo, Error>;
+
+    /// Sets whether the pixels of the frames decoded from a stream are kept for
+    /// `last_decoded_frame`, which is off by default.
+    fn keep_decoded_frames(&mut self, _stream: VideoStreamHandle, _keep: bool) {}
+
+    /// The pixels of the frame last decoded by `decode_video_stream_frame`,
+    /// if `keep_decoded_frames` was turned on for the stream.
+    ///
+    /// This is used for frames that are needed outside of the renderer, such
+    /// as when uploading them to a Stage3D `VideoTexture`. Implementations
+    /// that don't decode frames into memory may return `None`.
+    fn last_decoded_frame(&self, _stream: VideoStreamHandle) -> Option<&Bitmap> {
+        None
+    }
 }
@@ -513,6 +513,37 @@ export interface BaseLoadOptions {
      * @default NetworkingAccessMode.All
      */
     allowNetworking?: NetworkingAccessMode;
+
+    /**
+     * WebSocket proxies that `flash.net.Socket` connections are tunnelled through.
+     *
+     * Browsers can't open raw TCP connections, so every host and port a movie
+     * connects to must be mapped to a WebSocket URL that forwards the traffic.
+     * Connections to unmapped hosts fail.
+     *
+     * @default []
+     */
+    socketProxy?: Array<SocketProxy>;
+}
+
+/**
+ * Maps a host and port a movie connects to onto a WebSocket proxy.
+ */
+export interface SocketProxy {
+    /**
+     * The host the movie connects to.
+     */
+    host: string;
+
+    /**
+     * The port the movie connects to.
+     */
+    port: number;
+
+    /**
+     * The WebSocket URL that forwards traffic to that host and port.
+     */
+    proxyUrl: string;
 }
 
 /**
@@ -0,0 +1,25 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.IOErrorEvent;
+	import flash.events.SecurityErrorEvent;
+	import flash.net.Socket;
+
+	public class Test extends Sprite {
+		public function Test() {
+			var socket:Socket = new Socket();
+			socket.addEventListener(Event.CONNECT, function(e:Event):void {
+				trace("connect");
+			});
+			socket.addEventListener(IOErrorEvent.IO_ERROR, function(e:IOErrorEvent):void {
+				trace("ioError " + e.errorID);
+			});
+			socket.addEventListener(SecurityErrorEvent.SECURITY_ERROR, function(e:SecurityErrorEvent):void {
+				trace("securityError " + e.errorID);
+			});
+
+			// A proxy is configured for this port, but allowNetworking forbids using it.
+			socket.connect("localhost", 8001);
+		}
+	}
+}
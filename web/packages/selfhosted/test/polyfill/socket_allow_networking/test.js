@@ -0,0 +1,25 @@
+const {
+    injectRuffleAndWait,
+    openTest,
+    playAndMonitor,
+} = require("../../utils");
+const { expect } = require("chai");
+
+describe("Socket with allowNetworking", () => {
+    it("loads the test", async () => {
+        await openTest(browser, __dirname);
+    });
+
+    it("refuses to connect when networking is not allowed", async () => {
+        await injectRuffleAndWait(browser);
+        await playAndMonitor(
+            browser,
+            await browser.$("#test-container").$("<ruffle-embed />"),
+            "ioError 2031\nsecurityError 2048\n"
+        );
+        const openedWebSockets = await browser.execute(
+            () => window.openedWebSockets
+        );
+        expect(openedWebSockets).to.eql([]);
+    });
+});
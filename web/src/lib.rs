@@ -289,6 +289,8 @@ struct Config {
     open_url_mode: OpenURLMode,
 
     allow_networking: NetworkingAccessMode,
+
+    socket_proxy: Vec<navigator::SocketProxy>,
 }
 
 /// Metadata about the playing SWF file to be passed back to JavaScript.
@@ -542,6 +544,7 @@ impl Ruffle {
             config.base_url,
             log_subscriber.clone(),
             config.open_url_mode,
+            config.socket_proxy,
         ));
 
         match window.local_storage() {
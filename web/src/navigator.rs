@@ -1,27 +1,44 @@
 //! Navigator backend for web
-use js_sys::{Array, ArrayBuffer, Uint8Array};
+use futures::future::{select, Either};
+use js_sys::{Array, ArrayBuffer, Promise, Uint8Array};
 use ruffle_core::backend::navigator::{
-    async_return, create_fetch_error, create_specific_fetch_error, ErrorResponse, NavigationMethod,
-    NavigatorBackend, OpenURLMode, OwnedFuture, Request, SuccessResponse,
+    async_return, create_fetch_error, create_specific_fetch_error, ChainBuildingCertificate,
+    ErrorResponse, NavigationMethod, NavigatorBackend, OpenURLMode, OutgoingSocketAction,
+    OwnedFuture, Request, SocketEvent, SuccessResponse,
 };
 use ruffle_core::config::NetworkingAccessMode;
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
+use serde::Deserialize;
 use std::borrow::Cow;
-use std::future::Future;
-use std::pin::Pin;
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::layer::Layered;
 use tracing_subscriber::Registry;
 use tracing_wasm::WASMLayer;
 use url::{ParseError, Url};
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::{
-    window, Blob, BlobPropertyBag, HtmlFormElement, HtmlInputElement, Request as WebRequest,
-    RequestInit, Response as WebResponse,
+    window, BinaryType, Blob, BlobPropertyBag, HtmlFormElement, HtmlInputElement, MessageEvent,
+    Request as WebRequest, RequestInit, Response as WebResponse, WebSocket,
 };
 
+/// Maps a host and port a movie connects to onto a WebSocket proxy.
+///
+/// Browsers can't open raw TCP connections, so Socket traffic is tunnelled
+/// through a WebSocket that forwards it to the real server.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketProxy {
+    host: String,
+    port: u16,
+    proxy_url: String,
+}
+
 pub struct WebNavigatorBackend {
     log_subscriber: Arc<Layered<WASMLayer, Registry>>,
     allow_script_access: bool,
@@ -29,6 +46,7 @@ pub struct WebNavigatorBackend {
     upgrade_to_https: bool,
     base_url: Option<Url>,
     open_url_mode: OpenURLMode,
+    socket_proxies: Vec<SocketProxy>,
 }
 
 impl WebNavigatorBackend {
@@ -39,6 +57,7 @@ impl WebNavigatorBackend {
         base_url: Option<String>,
         log_subscriber: Arc<Layered<WASMLayer, Registry>>,
         open_url_mode: OpenURLMode,
+        socket_proxies: Vec<SocketProxy>,
     ) -> Self {
         let window = web_sys::window().expect("window()");
 
@@ -80,6 +99,7 @@ impl WebNavigatorBackend {
             base_url,
             log_subscriber,
             open_url_mode,
+            socket_proxies,
         }
     }
 }
@@ -352,8 +372,82 @@ impl NavigatorBackend for WebNavigatorBackend {
         })
     }
 
-    fn spawn_io_future(&mut self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
-        unimplemented!();
+    fn sleep(&self, duration: Duration) -> OwnedFuture<(), Error> {
+        let timeout = duration.as_millis().try_into().unwrap_or(i32::MAX);
+        let promise = Promise::new(&mut |resolve, _reject| {
+            if let Some(window) = window() {
+                let _ =
+                    window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, timeout);
+            }
+        });
+        Box::pin(async move {
+            let _ = JsFuture::from(promise).await;
+            Ok(())
+        })
+    }
+
+    fn connect_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        timeout: Duration,
+        actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        // Sockets are a networking API too, so `allowNetworking` blocks them unless set to `all`.
+        if self.allow_networking != NetworkingAccessMode::All {
+            tracing::warn!("SWF tried to open a socket, but networking is not allowed");
+            let _ = events.send(SocketEvent::ConnectFailed);
+            return;
+        }
+
+        let Some(proxy) = self
+            .socket_proxies
+            .iter()
+            .find(|proxy| proxy.host == host && proxy.port == port)
+        else {
+            tracing::warn!("No socket proxy configured for {}:{}", host, port);
+            let _ = events.send(SocketEvent::ConnectFailed);
+            return;
+        };
+
+        let ws = match WebSocket::new(&proxy.proxy_url) {
+            Ok(ws) => ws,
+            Err(e) => {
+                tracing::error!("Could not open WebSocket to {}: {:?}", proxy.proxy_url, e);
+                let _ = events.send(SocketEvent::ConnectFailed);
+                return;
+            }
+        };
+        ws.set_binary_type(BinaryType::Arraybuffer);
+
+        let subscriber = self.log_subscriber.clone();
+        spawn_local(async move {
+            let _subscriber = tracing::subscriber::set_default(subscriber);
+            run_socket(ws, timeout, actions, events).await;
+        });
+    }
+
+    fn supports_secure_sockets(&self) -> bool {
+        false
+    }
+
+    fn connect_secure_socket(
+        &mut self,
+        host: String,
+        port: u16,
+        _timeout: Duration,
+        _certificates: Vec<ChainBuildingCertificate>,
+        _actions: flume::Receiver<OutgoingSocketAction>,
+        events: flume::Sender<SocketEvent>,
+    ) {
+        // The browser only lets us validate the certificate of the proxy, not of the server behind it.
+        tracing::warn!(
+            "Secure sockets are not supported, can't connect to {}:{}",
+            host,
+            port
+        );
+        let _ = events.send(SocketEvent::ConnectFailed);
     }
 
     fn pre_process_url(&self, mut url: Url) -> Url {
@@ -363,3 +457,130 @@ impl NavigatorBackend for WebNavigatorBackend {
         url
     }
 }
+
+/// Drive a Socket tunnelled over `ws` until either end closes it.
+async fn run_socket(
+    ws: WebSocket,
+    timeout: Duration,
+    actions: flume::Receiver<OutgoingSocketAction>,
+    events: flume::Sender<SocketEvent>,
+) {
+    let connected = Rc::new(Cell::new(false));
+    // Set once an event that ends the connection has been reported.
+    let finished = Rc::new(Cell::new(false));
+
+    let onopen = Closure::<dyn FnMut()>::new({
+        let events = events.clone();
+        let connected = connected.clone();
+        let finished = finished.clone();
+        move || {
+            if !finished.get() {
+                connected.set(true);
+                let _ = events.send(SocketEvent::Connected);
+            }
+        }
+    });
+    ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+
+    let onmessage = Closure::<dyn FnMut(MessageEvent)>::new({
+        let events = events.clone();
+        move |event: MessageEvent| {
+            if let Ok(buffer) = event.data().dyn_into::<ArrayBuffer>() {
+                let _ = events.send(SocketEvent::Data(Uint8Array::new(&buffer).to_vec()));
+            }
+        }
+    });
+    ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+    let onerror = Closure::<dyn FnMut()>::new({
+        let events = events.clone();
+        let connected = connected.clone();
+        move || {
+            if connected.get() {
+                let _ = events.send(SocketEvent::IoError);
+            }
+        }
+    });
+    ws.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+
+    let (closed_tx, closed_rx) = flume::bounded::<()>(1);
+    let onclose = Closure::<dyn FnMut()>::new({
+        let events = events.clone();
+        let connected = connected.clone();
+        let finished = finished.clone();
+        move || {
+            let _ = closed_tx.try_send(());
+            if finished.replace(true) {
+                return;
+            }
+            let _ = events.send(if connected.get() {
+                SocketEvent::Closed
+            } else {
+                SocketEvent::ConnectFailed
+            });
+        }
+    });
+    ws.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+
+    let ontimeout = Closure::<dyn FnMut()>::new({
+        let ws = ws.clone();
+        let events = events.clone();
+        let connected = connected.clone();
+        let finished = finished.clone();
+        move || {
+            if !connected.get() && !finished.replace(true) {
+                let _ = ws.close();
+                let _ = events.send(SocketEvent::ConnectTimedOut);
+            }
+        }
+    });
+    let timeout_handle = window().and_then(|window| {
+        window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                ontimeout.as_ref().unchecked_ref(),
+                timeout.as_millis().try_into().unwrap_or(i32::MAX),
+            )
+            .ok()
+    });
+
+    let mut send_buffer = Vec::new();
+    loop {
+        let action = match select(
+            Box::pin(actions.recv_async()),
+            Box::pin(closed_rx.recv_async()),
+        )
+        .await
+        {
+            Either::Left((Ok(action), _)) => action,
+            // Either the movie dropped the socket, or the WebSocket was closed.
+            _ => break,
+        };
+        match action {
+            OutgoingSocketAction::Write(data) => send_buffer.extend(data),
+            OutgoingSocketAction::Flush => {
+                if let Err(e) = ws.send_with_u8_array(&send_buffer) {
+                    tracing::warn!("Socket write failed: {:?}", e);
+                    if !finished.replace(true) {
+                        let _ = events.send(SocketEvent::IoError);
+                        let _ = events.send(SocketEvent::Closed);
+                    }
+                }
+                send_buffer.clear();
+            }
+            OutgoingSocketAction::Close => break,
+        }
+    }
+
+    // Flash doesn't dispatch `close` when the movie closes the socket itself.
+    finished.set(true);
+    let _ = ws.close();
+    ws.set_onopen(None);
+    ws.set_onmessage(None);
+    ws.set_onerror(None);
+    ws.set_onclose(None);
+    // The timer must not fire once its closure has been freed.
+    if let (Some(window), Some(handle)) = (window(), timeout_handle) {
+        window.clear_timeout_with_handle(handle);
+    }
+    drop((onopen, onmessage, onerror, onclose, ontimeout));
+}